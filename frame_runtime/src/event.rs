@@ -204,27 +204,22 @@ mod tests {
 
     mod info {
         use crate::info::*;
-        use once_cell::sync::OnceCell;
 
-        pub fn machine() -> &'static MachineInfo {
-            if MACHINE_CELL.get().is_none() {
-                let _ = MACHINE_CELL.set(MACHINE);
-            }
-            MACHINE
+        pub const fn machine() -> &'static MachineInfo {
+            &MACHINE
         }
 
-        static MACHINE: &MachineInfo = &MachineInfo {
+        static MACHINE: MachineInfo = MachineInfo {
             name: "Dummy",
             variables: &[],
-            states: &[STATE_A, STATE_B],
+            states: &[&STATE_A, &STATE_B],
             interface: &[EVENTS[0]],
             actions: ACTIONS,
             events: EVENTS,
             transitions: TRANSITIONS,
         };
-        static MACHINE_CELL: OnceCell<&MachineInfo> = OnceCell::new();
-        static STATE_A: &StateInfo = &StateInfo {
-            machine_cell: &MACHINE_CELL,
+        static STATE_A: StateInfo = StateInfo {
+            machine: &MACHINE,
             name: "A",
             parent: None,
             parameters: &[],
@@ -232,8 +227,8 @@ mod tests {
             handlers: &[EVENTS[0]],
             is_stack_pop: false,
         };
-        static STATE_B: &StateInfo = &StateInfo {
-            machine_cell: &MACHINE_CELL,
+        static STATE_B: StateInfo = StateInfo {
+            machine: &MACHINE,
             name: "B",
             parent: None,
             parameters: &[],
@@ -275,16 +270,18 @@ mod tests {
                 kind: TransitionKind::Transition,
                 event: EVENTS[0],
                 label: "",
-                source: STATE_A,
-                target: STATE_B,
+                guard: None,
+                source: &STATE_A,
+                target: &STATE_B,
             },
             &TransitionInfo {
                 id: 1,
                 kind: TransitionKind::ChangeState,
                 event: EVENTS[0],
                 label: "",
-                source: STATE_B,
-                target: STATE_A,
+                guard: None,
+                source: &STATE_B,
+                target: &STATE_A,
             },
         ];
     }
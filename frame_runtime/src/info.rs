@@ -2,9 +2,15 @@
 //! Static information is shared among all running instances of a state machine, and includes
 //! things like the names and types of declared states, variables, events, and actions, as well as
 //! structural information such as possible transitions and hierarchy relationships among states.
+//!
+//! All of the structs in this module can be constructed as `static` data, and the lookup methods
+//! that do not allocate are `const fn`, so a machine's structure can be inspected at compile time.
+//! This module is available without the `std` feature; methods that return a `Vec` require it.
 
-use once_cell::sync::OnceCell;
-use std::fmt;
+use core::fmt;
+use core::ptr;
+#[cfg(feature = "std")]
+use std::vec::Vec;
 
 /// Information about a simple name declaration. Names in Frame include domain, state, and local
 /// variables, as well as method parameters.
@@ -60,7 +66,7 @@ pub struct MachineInfo {
 impl MachineInfo {
     /// The initial state of the machine, which is is the first state listed in the `machine` block.
     /// Returns `None` if the machine has no states.
-    pub fn initial_state(&self) -> Option<&'static StateInfo> {
+    pub const fn initial_state(&self) -> Option<&'static StateInfo> {
         if self.states.is_empty() {
             None
         } else {
@@ -69,6 +75,7 @@ impl MachineInfo {
     }
 
     /// The top-level states are those which are not children of another state.
+    #[cfg(feature = "std")]
     pub fn top_level_states(&self) -> Vec<&'static StateInfo> {
         self.states
             .iter()
@@ -78,35 +85,40 @@ impl MachineInfo {
     }
 
     /// Get a domain variable declaration by name.
-    pub fn get_variable(&self, name: &str) -> Option<&'static NameInfo> {
-        self.variables.iter().find(|n| name == n.name)
+    pub const fn get_variable(&self, name: &str) -> Option<&'static NameInfo> {
+        find_name(self.variables, name)
     }
 
     /// Get a state within this machine by name.
-    pub fn get_state(&self, name: &str) -> Option<&'static StateInfo> {
-        self.states.iter().cloned().find(|s| name == s.name)
+    pub const fn get_state(&self, name: &str) -> Option<&'static StateInfo> {
+        let mut i = 0;
+        while i < self.states.len() {
+            if str_eq(self.states[i].name, name) {
+                return Some(self.states[i]);
+            }
+            i += 1;
+        }
+        None
     }
 
     /// Get the signature corresponding to the named event. You can use this method to get
     /// the signatures of both interface events and enter/exit events.
-    pub fn get_event(&self, name: &str) -> Option<&'static MethodInfo> {
-        self.events.iter().cloned().find(|m| name == m.name)
+    pub const fn get_event(&self, name: &str) -> Option<&'static MethodInfo> {
+        find_method(self.events, name)
     }
 
     /// Get the signature corresponding to the named action.
-    pub fn get_action(&self, name: &str) -> Option<&'static MethodInfo> {
-        self.actions.iter().cloned().find(|m| name == m.name)
+    pub const fn get_action(&self, name: &str) -> Option<&'static MethodInfo> {
+        find_method(self.actions, name)
     }
 }
 
 /// Static information about a single state.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone)]
 pub struct StateInfo {
-    /// A reference to the machine this state is contained in. This field should not be accessed
-    /// directly; instead use the `machine()` method. This reference is wrapped in a `OnceCell` to
-    /// avoid a cycle in the generated struct values. Framec should generate the code to initialize
-    /// everything correctly the first time the machine info is accessed.
-    pub machine_cell: &'static OnceCell<&'static MachineInfo>,
+    /// The machine this state is contained in. Since the machine also refers to its states, the
+    /// generated machine and state infos are named `static` items that refer to each other.
+    pub machine: &'static MachineInfo,
 
     /// The unique name of this state.
     pub name: &'static str,
@@ -134,14 +146,13 @@ pub struct StateInfo {
 
 impl StateInfo {
     /// The machine this state is contained in.
-    pub fn machine(&self) -> &'static MachineInfo {
-        self.machine_cell
-            .get()
-            .expect("Machine info cell not initialized")
+    pub const fn machine(&self) -> &'static MachineInfo {
+        self.machine
     }
 
     /// The sequence of ancestors for this state. The first element in the returned vector will be
     /// the immediate parent of this state, the next will be the parent's parent, and so on.
+    #[cfg(feature = "std")]
     pub fn ancestors(&self) -> Vec<&'static StateInfo> {
         let mut result = Vec::new();
         let mut parent_opt = self.parent;
@@ -153,8 +164,9 @@ impl StateInfo {
     }
 
     /// The children of this state, if any.
+    #[cfg(feature = "std")]
     pub fn children(&self) -> Vec<&'static StateInfo> {
-        self.machine()
+        self.machine
             .states
             .iter()
             .cloned()
@@ -166,23 +178,24 @@ impl StateInfo {
     }
 
     /// Get a state parameter declaration by name.
-    pub fn get_parameter(&self, name: &str) -> Option<&'static NameInfo> {
-        self.parameters.iter().find(|n| name == n.name)
+    pub const fn get_parameter(&self, name: &str) -> Option<&'static NameInfo> {
+        find_name(self.parameters, name)
     }
 
     /// Get a state variable declaration by name.
-    pub fn get_variable(&self, name: &str) -> Option<&'static NameInfo> {
-        self.variables.iter().find(|n| name == n.name)
+    pub const fn get_variable(&self, name: &str) -> Option<&'static NameInfo> {
+        find_name(self.variables, name)
     }
 
     /// Get the signature of an event handler associated with this state by name.
-    pub fn get_handler(&self, name: &str) -> Option<&'static MethodInfo> {
-        self.handlers.iter().cloned().find(|m| name == m.name)
+    pub const fn get_handler(&self, name: &str) -> Option<&'static MethodInfo> {
+        find_method(self.handlers, name)
     }
 
     /// All transitions in the machine with this state as the `target`.
+    #[cfg(feature = "std")]
     pub fn incoming_transitions(&self) -> Vec<&'static TransitionInfo> {
-        self.machine()
+        self.machine
             .transitions
             .iter()
             .cloned()
@@ -191,8 +204,9 @@ impl StateInfo {
     }
 
     /// All transitions in the machine with this state as the `source`.
+    #[cfg(feature = "std")]
     pub fn outgoing_transitions(&self) -> Vec<&'static TransitionInfo> {
-        self.machine()
+        self.machine
             .transitions
             .iter()
             .cloned()
@@ -201,6 +215,35 @@ impl StateInfo {
     }
 }
 
+// The machine is compared by address and shown by name, since it refers back to this state.
+impl PartialEq for StateInfo {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.machine, other.machine)
+            && self.name == other.name
+            && self.parent == other.parent
+            && self.parameters == other.parameters
+            && self.variables == other.variables
+            && self.handlers == other.handlers
+            && self.is_stack_pop == other.is_stack_pop
+    }
+}
+
+impl Eq for StateInfo {}
+
+impl fmt::Debug for StateInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StateInfo")
+            .field("machine", &self.machine.name)
+            .field("name", &self.name)
+            .field("parent", &self.parent)
+            .field("parameters", &self.parameters)
+            .field("variables", &self.variables)
+            .field("handlers", &self.handlers)
+            .field("is_stack_pop", &self.is_stack_pop)
+            .finish()
+    }
+}
+
 /// Is this a standard transition or a change-state transition (which bypasses enter/exit events)?
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub enum TransitionKind {
@@ -236,6 +279,11 @@ pub struct TransitionInfo {
     /// The label associated with this transition.
     pub label: &'static str,
 
+    /// The source text of the guard expression, if this transition is guarded, e.g. `x > 3` for
+    /// the transition `|e| [x > 3] -> $A`. An unguarded transition is taken whenever its statement
+    /// is reached.
+    pub guard: Option<&'static str>,
+
     /// The source state of this transition.
     pub source: &'static StateInfo,
 
@@ -245,18 +293,23 @@ pub struct TransitionInfo {
 
 impl TransitionInfo {
     /// The machine this transition occurs in.
-    pub fn machine(&self) -> &'static MachineInfo {
-        self.source.machine()
+    pub const fn machine(&self) -> &'static MachineInfo {
+        self.source.machine
     }
 
     /// Is this a change-state transition?
-    pub fn is_change_state(&self) -> bool {
-        self.kind == TransitionKind::ChangeState
+    pub const fn is_change_state(&self) -> bool {
+        matches!(self.kind, TransitionKind::ChangeState)
     }
 
     /// Is this a standard transition (as opposed to a change-state)?
-    pub fn is_transition(&self) -> bool {
-        self.kind == TransitionKind::Transition
+    pub const fn is_transition(&self) -> bool {
+        matches!(self.kind, TransitionKind::Transition)
+    }
+
+    /// Is this transition conditional on a guard?
+    pub const fn is_guarded(&self) -> bool {
+        self.guard.is_some()
     }
 }
 
@@ -265,3 +318,46 @@ impl fmt::Display for TransitionInfo {
         write!(f, "{}{}{}", self.source.name, self.kind, self.target.name)
     }
 }
+
+/// Compare two strings in a `const` context, where `==` on `str` is not available.
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Find a name declaration by name.
+const fn find_name(names: &'static [NameInfo], name: &str) -> Option<&'static NameInfo> {
+    let mut i = 0;
+    while i < names.len() {
+        if str_eq(names[i].name, name) {
+            return Some(&names[i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Find a method signature by name.
+const fn find_method(
+    methods: &'static [&'static MethodInfo],
+    name: &str,
+) -> Option<&'static MethodInfo> {
+    let mut i = 0;
+    while i < methods.len() {
+        if str_eq(methods[i].name, name) {
+            return Some(methods[i]);
+        }
+        i += 1;
+    }
+    None
+}
@@ -49,6 +49,20 @@
 //! state `A` may have the same name as a variable in state `B`, or have the same name as a
 //! parameter in state `A`.
 //!
+//! # Using the static info without `std`
+//!
+//! The `*Info` structs are plain `static` data with no lazy initialization, so they can be
+//! inspected in a `const fn` and used on embedded targets. The [info] module is the only part of
+//! this crate available when it is built without its default `std` feature, for example:
+//!
+//! ```text
+//! frame_runtime = { version = "0.8", default-features = false }
+//! ```
+//!
+//! Since the `no_std` feature of the Rust backend cannot be combined with `runtime_support`, specs
+//! compiled for `no_std` should enable the `runtime_info` feature instead, which generates only the
+//! static info and a `const fn machine_info()` on the state machine type.
+//!
 //! # Associated types and type bounds
 //!
 //! The [Machine] trait contains several associated types that enable the runtime interface to
@@ -86,20 +100,36 @@
 //! [bounds-rfc]: https://github.com/rust-lang/rust/issues/44491
 //! [smcat]: https://github.com/sverweij/state-machine-cat
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
 pub mod callback;
+#[cfg(feature = "std")]
 pub mod env;
+#[cfg(feature = "std")]
 pub mod event;
+#[cfg(feature = "std")]
 pub mod history;
 pub mod info;
+#[cfg(feature = "std")]
 pub mod machine;
+#[cfg(feature = "std")]
 pub mod smcat;
+#[cfg(feature = "std")]
 pub mod transition;
 
+#[cfg(feature = "std")]
 pub use crate::callback::*;
+#[cfg(feature = "std")]
 pub use crate::env::*;
+#[cfg(feature = "std")]
 pub use crate::event::*;
+#[cfg(feature = "std")]
 pub use crate::history::*;
 pub use crate::info::*;
+#[cfg(feature = "std")]
 pub use crate::machine::*;
+#[cfg(feature = "std")]
 pub use crate::smcat::*;
+#[cfg(feature = "std")]
 pub use crate::transition::*;
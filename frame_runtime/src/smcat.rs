@@ -270,13 +270,22 @@ impl Renderer {
         output: &mut String,
     ) {
         let style = self.style.edge(transition, active == Some(transition.id));
+        let guard = match transition.guard {
+            Some(guard) => format!(" [{}]", guard),
+            None => String::new(),
+        };
         let mut label = transition.label.to_string();
         if !label.is_empty() {
             label = format!("/ {}", label);
         }
         output.push_str(&format!(
-            "{} -> {}{} : \"  {}{}  \";\n",
-            transition.source.name, transition.target.name, style, transition.event.name, label
+            "{} -> {}{} : \"  {}{}{}  \";\n",
+            transition.source.name,
+            transition.target.name,
+            style,
+            transition.event.name,
+            guard,
+            label
         ));
     }
 }
@@ -54,21 +54,19 @@
 //! ##
 //! ```
 
+#![cfg(feature = "std")]
+
 use frame_runtime::env::Environment;
 use std::any::Any;
 
 mod info {
     use frame_runtime::info::*;
-    use once_cell::sync::OnceCell;
 
-    pub fn machine() -> &'static MachineInfo {
-        if MACHINE_CELL.get().is_none() {
-            let _ = MACHINE_CELL.set(MACHINE);
-        }
-        MACHINE
+    pub const fn machine() -> &'static MachineInfo {
+        &MACHINE
     }
 
-    static MACHINE: &MachineInfo = &MachineInfo {
+    static MACHINE: MachineInfo = MachineInfo {
         name: "Demo",
         variables: &[
             NameInfo {
@@ -80,15 +78,14 @@ mod info {
                 vtype: "i32",
             },
         ],
-        states: &[STATE_INIT, STATE_FOO, STATE_BAR],
+        states: &[&STATE_INIT, &STATE_FOO, &STATE_BAR],
         interface: &[EVENTS[0], EVENTS[1]],
         actions: ACTIONS,
         events: EVENTS,
         transitions: TRANSITIONS,
     };
-    static MACHINE_CELL: OnceCell<&MachineInfo> = OnceCell::new();
-    static STATE_INIT: &StateInfo = &StateInfo {
-        machine_cell: &MACHINE_CELL,
+    static STATE_INIT: StateInfo = StateInfo {
+        machine: &MACHINE,
         name: "Init",
         parent: None,
         parameters: &[],
@@ -96,8 +93,8 @@ mod info {
         handlers: &[EVENTS[4]],
         is_stack_pop: false,
     };
-    static STATE_FOO: &StateInfo = &StateInfo {
-        machine_cell: &MACHINE_CELL,
+    static STATE_FOO: StateInfo = StateInfo {
+        machine: &MACHINE,
         name: "Foo",
         parent: None,
         parameters: &[],
@@ -108,8 +105,8 @@ mod info {
         handlers: &[EVENTS[4], EVENTS[5], EVENTS[0], EVENTS[1]],
         is_stack_pop: false,
     };
-    static STATE_BAR: &StateInfo = &StateInfo {
-        machine_cell: &MACHINE_CELL,
+    static STATE_BAR: StateInfo = StateInfo {
+        machine: &MACHINE,
         name: "Bar",
         parent: None,
         parameters: &[NameInfo {
@@ -187,24 +184,27 @@ mod info {
             kind: TransitionKind::Transition,
             event: EVENTS[4],
             label: "",
-            source: STATE_INIT,
-            target: STATE_FOO,
+            guard: None,
+            source: &STATE_INIT,
+            target: &STATE_FOO,
         },
         &TransitionInfo {
             id: 1,
             kind: TransitionKind::Transition,
             event: EVENTS[1],
             label: "",
-            source: STATE_FOO,
-            target: STATE_BAR,
+            guard: None,
+            source: &STATE_FOO,
+            target: &STATE_BAR,
         },
         &TransitionInfo {
             id: 2,
             kind: TransitionKind::ChangeState,
             event: EVENTS[1],
             label: "",
-            source: STATE_BAR,
-            target: STATE_FOO,
+            guard: None,
+            source: &STATE_BAR,
+            target: &STATE_FOO,
         },
     ];
 }
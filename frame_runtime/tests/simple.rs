@@ -24,6 +24,8 @@
 //!     -domain-
 //! ```
 
+#![cfg(feature = "std")]
+
 use frame_runtime as runtime;
 use frame_runtime::Machine;
 use std::any::Any;
@@ -31,27 +33,22 @@ use std::sync::Arc;
 
 mod info {
     use frame_runtime::info::*;
-    use once_cell::sync::OnceCell;
 
-    pub fn machine() -> &'static MachineInfo {
-        if MACHINE_CELL.get().is_none() {
-            let _ = MACHINE_CELL.set(MACHINE);
-        }
-        MACHINE
+    pub const fn machine() -> &'static MachineInfo {
+        &MACHINE
     }
 
-    static MACHINE: &MachineInfo = &MachineInfo {
+    static MACHINE: MachineInfo = MachineInfo {
         name: "Simple",
         variables: &[],
-        states: &[STATE_A, STATE_B],
+        states: &[&STATE_A, &STATE_B],
         interface: &[EVENTS[0]],
         actions: ACTIONS,
         events: EVENTS,
         transitions: TRANSITIONS,
     };
-    static MACHINE_CELL: OnceCell<&MachineInfo> = OnceCell::new();
-    static STATE_A: &StateInfo = &StateInfo {
-        machine_cell: &MACHINE_CELL,
+    static STATE_A: StateInfo = StateInfo {
+        machine: &MACHINE,
         name: "A",
         parent: None,
         parameters: &[],
@@ -59,8 +56,8 @@ mod info {
         handlers: &[EVENTS[0]],
         is_stack_pop: false,
     };
-    static STATE_B: &StateInfo = &StateInfo {
-        machine_cell: &MACHINE_CELL,
+    static STATE_B: StateInfo = StateInfo {
+        machine: &MACHINE,
         name: "B",
         parent: None,
         parameters: &[],
@@ -102,16 +99,18 @@ mod info {
             kind: TransitionKind::Transition,
             event: EVENTS[0],
             label: "",
-            source: STATE_A,
-            target: STATE_B,
+            guard: None,
+            source: &STATE_A,
+            target: &STATE_B,
         },
         &TransitionInfo {
             id: 1,
             kind: TransitionKind::ChangeState,
             event: EVENTS[0],
             label: "",
-            source: STATE_B,
-            target: STATE_A,
+            guard: None,
+            source: &STATE_B,
+            target: &STATE_A,
         },
     ];
 }
@@ -298,6 +297,21 @@ mod tests {
         assert_eq!(2, sm.info().transitions.len());
     }
 
+    #[test]
+    fn const_static_info() {
+        const NUM_STATES: usize = super::info::machine().states.len();
+        const STATE_B: Option<&StateInfo> = super::info::machine().get_state("B");
+        const NEXT_IN_A: bool = match super::info::machine().initial_state() {
+            Some(state) => state.get_handler("next").is_some(),
+            None => false,
+        };
+        assert_eq!(2, NUM_STATES);
+        assert_eq!("B", STATE_B.unwrap().name);
+        assert_eq!("Simple", STATE_B.unwrap().machine().name);
+        assert!(NEXT_IN_A);
+        assert!(super::info::machine().transitions[1].is_change_state());
+    }
+
     #[test]
     fn current_state() {
         let mut sm = Simple::new();
@@ -141,6 +141,17 @@ impl SystemNode {
             None => None,
         }
     }
+
+    /// Returns true if any state in the machine defers events.
+    pub fn has_deferred_events(&self) -> bool {
+        match &self.machine_block_node_opt {
+            Some(mb) => mb
+                .states
+                .iter()
+                .any(|state| state.borrow().deferred_events_opt.is_some()),
+            None => false,
+        }
+    }
 }
 
 impl NodeElement for SystemNode {
@@ -241,6 +252,7 @@ pub struct ActionNode {
     pub name: String,
     pub params: Option<Vec<ParameterNode>>,
     pub type_opt: Option<TypeNode>,
+    pub is_fallible: bool,
     pub code_opt: Option<String>,
 }
 
@@ -249,12 +261,14 @@ impl ActionNode {
         name: String,
         params: Option<Vec<ParameterNode>>,
         type_opt: Option<TypeNode>,
+        is_fallible: bool,
         code_opt: Option<String>,
     ) -> ActionNode {
         ActionNode {
             name,
             params,
             type_opt,
+            is_fallible,
             code_opt,
         }
     }
@@ -390,11 +404,18 @@ impl NodeElement for ActionsBlockNode {
 
 pub struct DomainBlockNode {
     pub member_variables: Vec<Rc<RefCell<VariableDeclNode>>>,
+    pub enums: Vec<Rc<RefCell<EnumDeclNode>>>,
 }
 
 impl DomainBlockNode {
-    pub fn new(member_variables: Vec<Rc<RefCell<VariableDeclNode>>>) -> DomainBlockNode {
-        DomainBlockNode { member_variables }
+    pub fn new(
+        member_variables: Vec<Rc<RefCell<VariableDeclNode>>>,
+        enums: Vec<Rc<RefCell<EnumDeclNode>>>,
+    ) -> DomainBlockNode {
+        DomainBlockNode {
+            member_variables,
+            enums,
+        }
     }
 }
 
@@ -406,11 +427,31 @@ impl NodeElement for DomainBlockNode {
 
 //-----------------------------------------------------//
 
+pub struct EnumDeclNode {
+    pub name: String,
+    pub enumerators: Vec<String>,
+}
+
+impl EnumDeclNode {
+    pub fn new(name: String, enumerators: Vec<String>) -> EnumDeclNode {
+        EnumDeclNode { name, enumerators }
+    }
+}
+
+impl NodeElement for EnumDeclNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_enum_decl_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
 pub struct StateNode {
     pub name: String,
     pub params_opt: Option<Vec<ParameterNode>>,
     pub vars_opt: Option<Vec<Rc<RefCell<VariableDeclNode>>>>,
     pub calls_opt: Option<Vec<CallChainLiteralExprNode>>,
+    pub deferred_events_opt: Option<Vec<String>>,
     pub evt_handlers_rcref: Vec<Rc<RefCell<EventHandlerNode>>>,
     pub enter_event_handler_opt: Option<Rc<RefCell<EventHandlerNode>>>,
     pub exit_event_handler_opt: Option<Rc<RefCell<EventHandlerNode>>>,
@@ -425,6 +466,7 @@ impl StateNode {
         params: Option<Vec<ParameterNode>>,
         vars: Option<Vec<Rc<RefCell<VariableDeclNode>>>>,
         calls: Option<Vec<CallChainLiteralExprNode>>,
+        deferred_events: Option<Vec<String>>,
         evt_handlers_rcref: Vec<Rc<RefCell<EventHandlerNode>>>,
         enter_event_handler_opt: Option<Rc<RefCell<EventHandlerNode>>>,
         exit_event_handler_opt: Option<Rc<RefCell<EventHandlerNode>>>,
@@ -436,6 +478,7 @@ impl StateNode {
             params_opt: params,
             vars_opt: vars,
             calls_opt: calls,
+            deferred_events_opt: deferred_events,
             evt_handlers_rcref,
             enter_event_handler_opt,
             exit_event_handler_opt,
@@ -444,6 +487,14 @@ impl StateNode {
             line,
         }
     }
+
+    /// Returns true if this state defers the given message.
+    pub fn defers_event(&self, msg: &str) -> bool {
+        match &self.deferred_events_opt {
+            Some(deferred_events) => deferred_events.iter().any(|e| e == msg),
+            None => false,
+        }
+    }
 }
 
 impl NodeElement for StateNode {
@@ -719,6 +770,9 @@ pub enum ExprType {
     BinaryExprT {
         binary_expr_node: BinaryExprNode,
     },
+    EnumeratorExprT {
+        enumerator_expr_node: EnumeratorExprNode,
+    },
 }
 
 impl ExprType {
@@ -737,6 +791,7 @@ impl ExprType {
             ExprType::FrameEventExprT { .. } => "FrameEventExprT",
             ExprType::UnaryExprT { .. } => "UnaryExprT",
             ExprType::BinaryExprT { .. } => "BinaryExprT",
+            ExprType::EnumeratorExprT { .. } => "EnumeratorExprT",
         }
     }
 }
@@ -790,6 +845,11 @@ impl NodeElement for ExprType {
             ExprType::BinaryExprT { binary_expr_node } => {
                 ast_visitor.visit_binary_expr_node(binary_expr_node);
             }
+            ExprType::EnumeratorExprT {
+                enumerator_expr_node,
+            } => {
+                ast_visitor.visit_enumerator_expr_node(enumerator_expr_node);
+            }
         }
     }
 
@@ -844,6 +904,11 @@ impl NodeElement for ExprType {
             ExprType::UnaryExprT { unary_expr_node } => {
                 ast_visitor.visit_unary_expr_node_to_string(unary_expr_node, output);
             }
+            ExprType::EnumeratorExprT {
+                enumerator_expr_node,
+            } => {
+                ast_visitor.visit_enumerator_expr_node_to_string(enumerator_expr_node, output);
+            }
         }
     }
 }
@@ -886,6 +951,12 @@ pub enum StatementType {
     StateStackStmt {
         state_stack_operation_statement_node: StateStackOperationStatementNode,
     },
+    LoopStmt {
+        loop_stmt_node: LoopStmtNode,
+    },
+    LoopControlStmt {
+        loop_control_stmt_node: LoopControlStmtNode,
+    },
     #[allow(dead_code)] // is used, don't know why I need this
     NoStmt,
 }
@@ -1038,6 +1109,8 @@ pub struct TransitionStatementNode {
     pub target_state_context_t: StateContextType,
     pub exit_args_opt: Option<ExprListNode>,
     pub label_opt: Option<String>,
+    // Source text of the `[guard]` clause when the transition is guarded.
+    pub guard_opt: Option<String>,
 }
 
 impl TransitionStatementNode {
@@ -1064,6 +1137,8 @@ impl NodeElement for TransitionStatementNode {
 pub struct ChangeStateStatementNode {
     pub state_context_t: StateContextType,
     pub label_opt: Option<String>,
+    // Source text of the `[guard]` clause when the change state is guarded.
+    pub guard_opt: Option<String>,
 }
 
 impl ChangeStateStatementNode {
@@ -1125,6 +1200,59 @@ impl NodeElement for StateStackOperationStatementNode {
 
 //-----------------------------------------------------//
 
+// A `loop { ... }` has no condition and runs until a break,
+// return or transition. A `while <expr> { ... }` tests its
+// condition before each iteration.
+
+pub struct LoopStmtNode {
+    pub condition_expr_t_opt: Option<ExprType>,
+    pub statements: Vec<DeclOrStmtType>,
+}
+
+impl LoopStmtNode {
+    pub fn new(
+        condition_expr_t_opt: Option<ExprType>,
+        statements: Vec<DeclOrStmtType>,
+    ) -> LoopStmtNode {
+        LoopStmtNode {
+            condition_expr_t_opt,
+            statements,
+        }
+    }
+}
+
+impl NodeElement for LoopStmtNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_loop_stmt_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
+#[derive(Clone)]
+pub enum LoopControlType {
+    Break,
+    Continue,
+}
+
+pub struct LoopControlStmtNode {
+    pub loop_control_t: LoopControlType,
+}
+
+impl LoopControlStmtNode {
+    pub fn new(loop_control_t: LoopControlType) -> LoopControlStmtNode {
+        LoopControlStmtNode { loop_control_t }
+    }
+}
+
+impl NodeElement for LoopControlStmtNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_loop_control_stmt_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
 pub struct InterfaceMethodCallExprNode {
     pub identifier: IdentifierNode,
     pub call_expr_list: CallExprListNode,
@@ -1327,6 +1455,35 @@ impl NodeElement for BinaryExprNode {
 
 //-----------------------------------------------------//
 
+// A reference to an enumerator of an enum declared in the domain,
+// written `Type.Enumerator`.
+
+pub struct EnumeratorExprNode {
+    pub enum_type: String,
+    pub enumerator: String,
+}
+
+impl EnumeratorExprNode {
+    pub fn new(enum_type: String, enumerator: String) -> EnumeratorExprNode {
+        EnumeratorExprNode {
+            enum_type,
+            enumerator,
+        }
+    }
+}
+
+impl NodeElement for EnumeratorExprNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_enumerator_expr_node(self);
+    }
+
+    fn accept_to_string(&self, ast_visitor: &mut dyn AstVisitor, output: &mut String) {
+        ast_visitor.visit_enumerator_expr_node_to_string(self, output);
+    }
+}
+
+//-----------------------------------------------------//
+
 pub struct CallExprNode {
     pub identifier: IdentifierNode,
     pub call_expr_list: CallExprListNode,
@@ -1562,6 +1719,12 @@ pub enum TestType {
     NumberMatchTest {
         number_match_test_node: NumberMatchTestNode,
     },
+    RegexMatchTest {
+        regex_match_test_node: RegexMatchTestNode,
+    },
+    EnumMatchTest {
+        enum_match_test_node: EnumMatchTestNode,
+    },
 }
 
 pub struct BoolTestNode {
@@ -1746,6 +1909,248 @@ impl NodeElement for StringMatchTestPatternNode {
 
 //-----------------------------------------------------//
 
+pub struct RegexMatchTestNode {
+    pub expr_t: ExprType,
+    pub match_branch_nodes: Vec<RegexMatchTestMatchBranchNode>,
+    pub else_branch_node_opt: Option<RegexMatchTestElseBranchNode>,
+}
+
+impl RegexMatchTestNode {
+    pub fn new(
+        expr_t: ExprType,
+        match_branch_nodes: Vec<RegexMatchTestMatchBranchNode>,
+        else_branch_node_opt: Option<RegexMatchTestElseBranchNode>,
+    ) -> RegexMatchTestNode {
+        RegexMatchTestNode {
+            expr_t,
+            match_branch_nodes,
+            else_branch_node_opt,
+        }
+    }
+}
+
+impl NodeElement for RegexMatchTestNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_regex_match_test_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
+pub struct RegexMatchTestMatchBranchNode {
+    pub regex_match_pattern_node: RegexMatchTestPatternNode,
+    pub statements: Vec<DeclOrStmtType>,
+    pub branch_terminator_expr_opt: Option<TerminatorExpr>,
+}
+
+impl RegexMatchTestMatchBranchNode {
+    pub fn new(
+        regex_match_pattern_node: RegexMatchTestPatternNode,
+        statements: Vec<DeclOrStmtType>,
+        branch_terminator_t_opt: Option<TerminatorExpr>,
+    ) -> RegexMatchTestMatchBranchNode {
+        RegexMatchTestMatchBranchNode {
+            regex_match_pattern_node,
+            statements,
+            branch_terminator_expr_opt: branch_terminator_t_opt,
+        }
+    }
+}
+
+impl NodeElement for RegexMatchTestMatchBranchNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_regex_match_test_match_branch_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
+pub struct RegexMatchTestElseBranchNode {
+    pub statements: Vec<DeclOrStmtType>,
+    pub branch_terminator_expr_opt: Option<TerminatorExpr>,
+}
+
+impl RegexMatchTestElseBranchNode {
+    pub fn new(
+        statements: Vec<DeclOrStmtType>,
+        branch_terminator_t_opt: Option<TerminatorExpr>,
+    ) -> RegexMatchTestElseBranchNode {
+        RegexMatchTestElseBranchNode {
+            statements,
+            branch_terminator_expr_opt: branch_terminator_t_opt,
+        }
+    }
+}
+
+impl NodeElement for RegexMatchTestElseBranchNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_regex_match_test_else_branch_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
+// Named capture groups, written either (?P<name>...) or (?<name>...),
+// are bound to local variables in the match branch.
+
+pub struct RegexMatchTestPatternNode {
+    pub pattern: String,
+    pub capture_names: Vec<String>,
+    pub line: usize,
+}
+
+impl RegexMatchTestPatternNode {
+    pub fn new(pattern: String, line: usize) -> RegexMatchTestPatternNode {
+        let mut capture_names = Vec::new();
+        RegexMatchTestPatternNode::scan_named_groups(&pattern, |_, name| {
+            capture_names.push(name.to_string())
+        });
+        RegexMatchTestPatternNode {
+            pattern,
+            capture_names,
+            line,
+        }
+    }
+
+    /// Rewrite the named group syntax for a target regex dialect, e.g.
+    /// "?P<" for Rust and Python or "?<" for JavaScript.
+    pub fn pattern_with_group_prefix(&self, group_prefix: &str) -> String {
+        let mut pattern = String::new();
+        let mut last = 0;
+        RegexMatchTestPatternNode::scan_named_groups(&self.pattern, |range, name| {
+            pattern.push_str(&self.pattern[last..range.0]);
+            pattern.push_str(&format!("({}{}>", group_prefix, name));
+            last = range.1;
+        });
+        pattern.push_str(&self.pattern[last..]);
+        pattern
+    }
+
+    // Calls f with the byte range of each named group opener and the group name.
+    fn scan_named_groups<F: FnMut((usize, usize), &str)>(pattern: &str, mut f: F) {
+        let bytes = pattern.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'\\' {
+                i += 2;
+                continue;
+            }
+            let rest = &pattern[i..];
+            let name_start = if rest.starts_with("(?P<") {
+                Some(i + 4)
+            } else if rest.starts_with("(?<")
+                && !rest.starts_with("(?<=")
+                && !rest.starts_with("(?<!")
+            {
+                Some(i + 3)
+            } else {
+                None
+            };
+            if let Some(name_start) = name_start {
+                if let Some(len) = pattern[name_start..].find('>') {
+                    let name_end = name_start + len;
+                    f((i, name_end + 1), &pattern[name_start..name_end]);
+                    i = name_end + 1;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+    }
+}
+
+impl NodeElement for RegexMatchTestPatternNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_regex_match_test_pattern_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
+pub struct EnumMatchTestNode {
+    pub enum_type_name: String,
+    pub expr_t: ExprType,
+    pub match_branch_nodes: Vec<EnumMatchTestMatchBranchNode>,
+    pub else_branch_node_opt: Option<EnumMatchTestElseBranchNode>,
+}
+
+impl EnumMatchTestNode {
+    pub fn new(
+        enum_type_name: String,
+        expr_t: ExprType,
+        match_branch_nodes: Vec<EnumMatchTestMatchBranchNode>,
+        else_branch_node_opt: Option<EnumMatchTestElseBranchNode>,
+    ) -> EnumMatchTestNode {
+        EnumMatchTestNode {
+            enum_type_name,
+            expr_t,
+            match_branch_nodes,
+            else_branch_node_opt,
+        }
+    }
+}
+
+impl NodeElement for EnumMatchTestNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_enum_match_test_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
+pub struct EnumMatchTestMatchBranchNode {
+    pub enumerators: Vec<String>,
+    pub statements: Vec<DeclOrStmtType>,
+    pub branch_terminator_expr_opt: Option<TerminatorExpr>,
+}
+
+impl EnumMatchTestMatchBranchNode {
+    pub fn new(
+        enumerators: Vec<String>,
+        statements: Vec<DeclOrStmtType>,
+        branch_terminator_t_opt: Option<TerminatorExpr>,
+    ) -> EnumMatchTestMatchBranchNode {
+        EnumMatchTestMatchBranchNode {
+            enumerators,
+            statements,
+            branch_terminator_expr_opt: branch_terminator_t_opt,
+        }
+    }
+}
+
+impl NodeElement for EnumMatchTestMatchBranchNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_enum_match_test_match_branch_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
+pub struct EnumMatchTestElseBranchNode {
+    pub statements: Vec<DeclOrStmtType>,
+    pub branch_terminator_expr_opt: Option<TerminatorExpr>,
+}
+
+impl EnumMatchTestElseBranchNode {
+    pub fn new(
+        statements: Vec<DeclOrStmtType>,
+        branch_terminator_t_opt: Option<TerminatorExpr>,
+    ) -> EnumMatchTestElseBranchNode {
+        EnumMatchTestElseBranchNode {
+            statements,
+            branch_terminator_expr_opt: branch_terminator_t_opt,
+        }
+    }
+}
+
+impl NodeElement for EnumMatchTestElseBranchNode {
+    fn accept(&self, ast_visitor: &mut dyn AstVisitor) {
+        ast_visitor.visit_enum_match_test_else_branch_node(self);
+    }
+}
+
+//-----------------------------------------------------//
+
 pub struct NumberMatchTestNode {
     pub expr_t: ExprType,
     pub match_branch_nodes: Vec<NumberMatchTestMatchBranchNode>,
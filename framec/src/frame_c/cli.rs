@@ -1,6 +1,9 @@
-use crate::frame_c::compiler::Exe;
+use crate::frame_c::compiler::{Exe, GeneratedCode};
 use crate::frame_c::config::FrameConfig;
-use std::path::PathBuf;
+use crate::frame_c::targets;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 /// Command line arguments to the `framec` executable.
@@ -14,12 +17,28 @@ pub struct Cli {
     #[structopt(short, long)]
     generate_config: bool,
 
+    /// List the supported target languages and exit.
+    #[structopt(long)]
+    list_targets: bool,
+
+    /// Convert an SCXML file into a Frame specification skeleton and exit.
+    #[structopt(long, parse(from_os_str), value_name = "scxml-path")]
+    import_scxml: Option<PathBuf>,
+
+    /// Write the generated code to this file instead of stdout. If the code is a module tree,
+    /// this is the directory its files are written to.
+    #[structopt(short, long, parse(from_os_str), value_name = "output-path")]
+    output: Option<PathBuf>,
+
     /// Path to frame specification file.
-    #[structopt(parse(from_os_str), required_unless = "generate-config")]
+    #[structopt(
+        parse(from_os_str),
+        required_unless_one = &["generate-config", "list-targets", "import-scxml"]
+    )]
     path: Option<PathBuf>,
 
-    /// Target language.
-    #[structopt(required_unless = "generate-config")]
+    /// Target language. Run with `--list-targets` to see the supported names.
+    #[structopt(required_unless_one = &["generate-config", "list-targets", "import-scxml"])]
     language: Option<String>,
 }
 
@@ -28,6 +47,9 @@ impl Cli {
         Cli {
             config,
             generate_config: false,
+            list_targets: false,
+            import_scxml: None,
+            output: None,
             path: Some(path),
             language: Some(language),
         }
@@ -55,14 +77,60 @@ pub fn run_with(args: Cli) {
         return;
     }
 
-    // run the compiler and print output to stdout
-    match exe.run_file(&args.config, &args.path.unwrap(), args.language.unwrap()) {
-        Ok(code) => {
-            println!("{}", code);
+    // list target languages, if requested, then exit
+    if args.list_targets {
+        print!("{}", targets::list_targets());
+        return;
+    }
+
+    // convert an SCXML file, if requested, then exit
+    if let Some(scxml_path) = &args.import_scxml {
+        match exe.import_scxml_file(scxml_path) {
+            Ok(code) => print!("{}", code),
+            Err(err) => {
+                eprintln!("Framec failed with an error:\n{}", err.error);
+                std::process::exit(err.code);
+            }
         }
+        return;
+    }
+
+    // run the compiler and print output to stdout, or write it to the output path
+    let result = exe.generate_file(&args.config, &args.path.unwrap(), args.language.unwrap());
+    for warning in exe.take_warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+    match result {
+        Ok(generated) => match &args.output {
+            Some(output_path) => {
+                if let Err(err) = write_output(output_path, generated) {
+                    eprintln!(
+                        "Error writing output to {}:\n{}",
+                        output_path.display(),
+                        err
+                    );
+                    std::process::exit(exitcode::IOERR);
+                }
+            }
+            None => println!("{}", generated.into_code()),
+        },
         Err(err) => {
             eprintln!("Framec failed with an error:\n{}", err.error);
             std::process::exit(err.code);
         }
     }
 }
+
+/// Write generated code to a file, or the files of a module tree to a directory.
+fn write_output(output_path: &Path, generated: GeneratedCode) -> io::Result<()> {
+    match generated {
+        GeneratedCode::File(code) => fs::write(output_path, code),
+        GeneratedCode::ModuleTree(files) => {
+            fs::create_dir_all(output_path)?;
+            for (name, code) in files {
+                fs::write(output_path.join(name), code)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -1,19 +1,31 @@
 use crate::frame_c::config::FrameConfig;
 use crate::frame_c::parser::*;
 use crate::frame_c::scanner::*;
+use crate::frame_c::scxml_import;
 use crate::frame_c::symbol_table::*;
+use crate::frame_c::targets::{self, TargetLanguage};
 use crate::frame_c::utils::{frame_exitcode, RunError};
+use crate::frame_c::visitors::c99_visitor::C99Visitor;
 use crate::frame_c::visitors::cpp_visitor::CppVisitor;
 use crate::frame_c::visitors::cs_visitor::CsVisitor;
 use crate::frame_c::visitors::cs_visitor_for_bob::CsVisitorForBob;
 use crate::frame_c::visitors::gdscript_3_2_visitor::GdScript32Visitor;
+use crate::frame_c::visitors::go_visitor::GoVisitor;
+use crate::frame_c::visitors::graphviz_visitor::GraphVizVisitor;
 use crate::frame_c::visitors::java_8_visitor::Java8Visitor;
 use crate::frame_c::visitors::javascript_visitor::JavaScriptVisitor;
+use crate::frame_c::visitors::kotlin_visitor::KotlinVisitor;
+use crate::frame_c::visitors::mermaid_visitor::MermaidVisitor;
 use crate::frame_c::visitors::plantuml_visitor::PlantUmlVisitor;
 use crate::frame_c::visitors::python_visitor::PythonVisitor;
 use crate::frame_c::visitors::rust_visitor::RustVisitor;
+use crate::frame_c::visitors::scxml_visitor::ScxmlVisitor;
 use crate::frame_c::visitors::smcat_visitor::SmcatVisitor;
+use crate::frame_c::visitors::swift_visitor::SwiftVisitor;
+use crate::frame_c::visitors::typescript_visitor::TypeScriptVisitor;
+use crate::frame_c::visitors::xtate_visitor::XStateVisitor;
 use exitcode::USAGE;
+use std::cell::RefCell;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -24,13 +36,49 @@ static FRAMEC_VERSION: &str = "emitted from framec_v0.7.3";
 
 /* --------------------------------------------------------------------- */
 
-pub struct Exe {}
+/// Code generated from a Frame spec.
+pub enum GeneratedCode {
+    /// A single file of code.
+    File(String),
+    /// The files of a module directory, as pairs of a file name, relative to the directory, and
+    /// the file's code. This is generated by the Rust `generate_module_tree` feature.
+    ModuleTree(Vec<(String, String)>),
+}
+
+impl GeneratedCode {
+    /// Get all of the generated code as a single string. The files of a module tree are
+    /// concatenated, each preceded by a comment naming the file.
+    pub fn into_code(self) -> String {
+        match self {
+            GeneratedCode::File(code) => code,
+            GeneratedCode::ModuleTree(files) => files
+                .into_iter()
+                .map(|(name, code)| format!("// ---- {} ----\n{}", name, code))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        }
+    }
+}
+
+/* --------------------------------------------------------------------- */
+
+pub struct Exe {
+    warnings: RefCell<Vec<String>>,
+}
 
 impl Exe {
     /* --------------------------------------------------------------------- */
 
     pub fn new() -> Exe {
-        Exe {}
+        Exe {
+            warnings: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Take the warnings reported while generating code, leaving none behind. Warnings do not
+    /// stop code generation, so the caller decides how to show them.
+    pub fn take_warnings(&self) -> Vec<String> {
+        self.warnings.take()
     }
 
     pub fn debug_print(msg: &str) {
@@ -49,10 +97,21 @@ impl Exe {
         input_path: &Path,
         output_format: String,
     ) -> Result<String, RunError> {
+        self.generate_file(config_path, input_path, output_format)
+            .map(GeneratedCode::into_code)
+    }
+
+    /// Like `run_file()`, but keeps the files of a module tree apart.
+    pub fn generate_file(
+        &self,
+        config_path: &Option<PathBuf>,
+        input_path: &Path,
+        output_format: String,
+    ) -> Result<GeneratedCode, RunError> {
         match fs::read_to_string(input_path) {
             Ok(content) => {
                 Exe::debug_print(&(&content).to_string());
-                self.run(config_path, content, output_format)
+                self.generate(config_path, content, output_format)
             }
             Err(err) => {
                 let error_msg = format!("Error reading input file: {}", err);
@@ -62,23 +121,40 @@ impl Exe {
         }
     }
 
+    /// Convert an SCXML file into the source of a Frame system.
+    pub fn import_scxml_file(&self, input_path: &Path) -> Result<String, RunError> {
+        match fs::read_to_string(input_path) {
+            Ok(content) => scxml_import::import_scxml(&content),
+            Err(err) => {
+                let error_msg = format!("Error reading input file: {}", err);
+                Err(RunError::new(exitcode::NOINPUT, &error_msg))
+            }
+        }
+    }
+
     pub fn run(
         &self,
         config_path: &Option<PathBuf>,
         content: String,
-        mut output_format: String,
+        output_format: String,
     ) -> Result<String, RunError> {
-        // NOTE!!! There is a bug w/ the CLion debugger when a variable (maybe just String type)
-        // isn't initialized under some circumstances. Basically the debugger
-        // stops debugging or doesn't step and it looks like it hangs. To avoid
-        // this you have to initialize the variable, but the compiler then complains
-        // about the unused assignment. This can be squelched with `#[allow(unused_assignments)]`
-        // but I've reported it to JetBrains and want it fixed. So when you are
-        // debugging here, just uncomment the next line and then comment it back
-        // when checking in.
-        // let mut output= String::new();
+        self.generate(config_path, content, output_format)
+            .map(GeneratedCode::into_code)
+    }
+
+    /// Like `run()`, but keeps the files of a module tree apart.
+    pub fn generate(
+        &self,
+        config_path: &Option<PathBuf>,
+        content: String,
+        output_format: String,
+    ) -> Result<GeneratedCode, RunError> {
+        // fail fast on a misspelled target before doing any parsing
+        let cli_target = match TargetLanguage::from_name(&output_format) {
+            Some(target) => target,
+            None => return Err(Exe::unknown_target_error(&output_format, "")),
+        };
 
-        let output;
         let scanner = Scanner::new(content);
 
         let (has_errors, errors, tokens) = scanner.scan_tokens();
@@ -122,6 +198,7 @@ impl Exe {
         let generate_state_stack = semantic_parser.generate_state_stack;
         let generate_change_state = semantic_parser.generate_change_state;
         let generate_transition_state = semantic_parser.generate_transition_state;
+        let generate_regex_match = semantic_parser.generate_regex_match;
 
         // check for local config.yaml if no path specified
         let mut local_config_path = config_path;
@@ -140,165 +217,269 @@ impl Exe {
             }
         };
 
-        match &system_node.attributes_opt {
-            Some(attributes) => {
-                if let Some(language) = attributes.get("language") {
-                    output_format = language.value.clone();
+        // the #[language] attribute takes precedence over the command line
+        let mut target = cli_target;
+        if let Some(attributes) = &system_node.attributes_opt {
+            if let Some(language) = attributes.get("language") {
+                match TargetLanguage::from_name(&language.value) {
+                    Some(attribute_target) => {
+                        if attribute_target != cli_target {
+                            self.warnings.borrow_mut().push(format!(
+                                "#[language=\"{}\"] attribute overrides the '{}' target given on the command line.",
+                                language.value, cli_target
+                            ));
+                        }
+                        target = attribute_target;
+                    }
+                    None => {
+                        return Err(Exe::unknown_target_error(
+                            &language.value,
+                            " in #[language] attribute",
+                        ))
+                    }
                 }
             }
-            None => {}
-        }
-        if output_format == "javascript" {
-            let mut visitor = JavaScriptVisitor::new(
-                semantic_parser.get_arcanum(),
-                generate_exit_args,
-                generate_enter_args || generate_state_context,
-                generate_state_stack,
-                generate_change_state,
-                generate_transition_state,
-                FRAMEC_VERSION,
-                comments,
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        } else if output_format == "cpp" {
-            let mut visitor = CppVisitor::new(
-                semantic_parser.get_arcanum(),
-                config,
-                generate_exit_args,
-                generate_enter_args || generate_state_context,
-                generate_state_stack,
-                generate_change_state,
-                generate_transition_state,
-                FRAMEC_VERSION,
-                comments,
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        } else if output_format == "c_sharp_bob" {
-            let mut visitor = CsVisitorForBob::new(
-                semantic_parser.get_arcanum(),
-                generate_exit_args,
-                generate_enter_args || generate_state_context,
-                generate_state_stack,
-                generate_change_state,
-                generate_transition_state,
-                FRAMEC_VERSION,
-                comments,
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        } else if output_format == "c_sharp" {
-            let mut visitor = CsVisitor::new(
-                semantic_parser.get_arcanum(),
-                generate_exit_args,
-                generate_enter_args || generate_state_context,
-                generate_state_stack,
-                generate_change_state,
-                generate_transition_state,
-                FRAMEC_VERSION,
-                comments,
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        } else if output_format == "gdscript" {
-            let mut visitor = GdScript32Visitor::new(
-                semantic_parser.get_arcanum(),
-                generate_exit_args,
-                generate_enter_args || generate_state_context,
-                generate_state_stack,
-                generate_change_state,
-                generate_transition_state,
-                FRAMEC_VERSION,
-                comments,
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        } else if output_format == "java_8" {
-            let mut visitor = Java8Visitor::new(
-                semantic_parser.get_arcanum(),
-                generate_exit_args,
-                generate_enter_args || generate_state_context,
-                generate_state_stack,
-                generate_change_state,
-                generate_transition_state,
-                FRAMEC_VERSION,
-                comments,
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        } else if output_format == "python_3" {
-            let mut visitor = PythonVisitor::new(
-                semantic_parser.get_arcanum(),
-                generate_exit_args,
-                generate_enter_args || generate_state_context,
-                generate_state_stack,
-                generate_change_state,
-                generate_transition_state,
-                FRAMEC_VERSION,
-                comments,
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        } else if output_format == "plantuml" {
-            let (arcanum, system_hierarchy) = semantic_parser.get_all();
-            let mut visitor = PlantUmlVisitor::new(
-                arcanum,
-                system_hierarchy,
-                generate_state_context,
-                generate_state_stack,
-                generate_change_state,
-                generate_transition_state,
-                FRAMEC_VERSION,
-                comments,
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        } else if output_format == "rust" {
-            let mut visitor = RustVisitor::new(
-                FRAMEC_VERSION,
-                config,
-                semantic_parser.get_arcanum(),
-                generate_enter_args,
-                generate_exit_args,
-                generate_state_context,
-                generate_state_stack,
-                generate_change_state,
-                generate_transition_state,
-                comments,
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        } else if output_format == "smcat" {
-            let mut visitor = SmcatVisitor::new(
-                FRAMEC_VERSION,
-                config,
-                semantic_parser.get_system_hierarchy(),
-            );
-            visitor.run(&system_node);
-            output = visitor.get_code();
-        // } else if output_format == "xstate" {
-        //     let mut visitor = XStateVisitor::new(semantic_parser.get_arcanum()
-        //                                        , generate_exit_args
-        //                                        , generate_state_context
-        //                                        , generate_state_stack
-        //                                        , generate_change_state
-        //                                        , generate_transition_state
-        //                                        , FRAMEC_VERSION
-        //                                        , comments);
-        //     visitor.run(&system_node);
-        //     return visitor.get_code();
-        } else {
-            let error_msg = &format!("Error - unrecognized output format {}.", output_format);
-            let run_error = RunError::new(USAGE, error_msg);
-            return Err(run_error);
         }
 
-        Ok(output)
+        let output = match target {
+            TargetLanguage::JavaScript => {
+                let mut visitor = JavaScriptVisitor::new(
+                    semantic_parser.get_arcanum(),
+                    generate_exit_args,
+                    generate_enter_args || generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::C99 => {
+                let mut visitor = C99Visitor::new(
+                    semantic_parser.get_arcanum(),
+                    config,
+                    generate_state_stack,
+                    generate_transition_state,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Cpp => {
+                let mut visitor = CppVisitor::new(
+                    semantic_parser.get_arcanum(),
+                    config,
+                    generate_exit_args,
+                    generate_enter_args || generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::CSharpForBob => {
+                let mut visitor = CsVisitorForBob::new(
+                    semantic_parser.get_arcanum(),
+                    generate_exit_args,
+                    generate_enter_args || generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::CSharp => {
+                let mut visitor = CsVisitor::new(
+                    semantic_parser.get_arcanum(),
+                    generate_exit_args,
+                    generate_enter_args || generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::GdScript => {
+                let mut visitor = GdScript32Visitor::new(
+                    semantic_parser.get_arcanum(),
+                    generate_exit_args,
+                    generate_enter_args || generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Go => {
+                let mut visitor = GoVisitor::new(
+                    semantic_parser.get_arcanum(),
+                    config,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Graphviz => {
+                let mut visitor = GraphVizVisitor::new(
+                    FRAMEC_VERSION,
+                    config,
+                    semantic_parser.get_system_hierarchy(),
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Java8 => {
+                let mut visitor = Java8Visitor::new(
+                    semantic_parser.get_arcanum(),
+                    generate_exit_args,
+                    generate_enter_args || generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Kotlin => {
+                let mut visitor =
+                    KotlinVisitor::new(semantic_parser.get_arcanum(), FRAMEC_VERSION, comments);
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Python3 => {
+                let mut visitor = PythonVisitor::new(
+                    semantic_parser.get_arcanum(),
+                    generate_exit_args,
+                    generate_enter_args || generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    generate_regex_match,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Mermaid => {
+                let mut visitor =
+                    MermaidVisitor::new(FRAMEC_VERSION, semantic_parser.get_system_hierarchy());
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::PlantUml => {
+                let (arcanum, system_hierarchy) = semantic_parser.get_all();
+                let mut visitor = PlantUmlVisitor::new(
+                    arcanum,
+                    system_hierarchy,
+                    generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Rust => {
+                let mut visitor = RustVisitor::new(
+                    FRAMEC_VERSION,
+                    config,
+                    semantic_parser.get_arcanum(),
+                    generate_enter_args,
+                    generate_exit_args,
+                    generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    comments,
+                );
+                visitor.run(&system_node);
+                if let Some(files) = visitor.get_module_files() {
+                    return Ok(GeneratedCode::ModuleTree(files));
+                }
+                visitor.get_code()
+            }
+            TargetLanguage::Scxml => {
+                let mut visitor =
+                    ScxmlVisitor::new(FRAMEC_VERSION, semantic_parser.get_system_hierarchy());
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Smcat => {
+                let mut visitor = SmcatVisitor::new(
+                    FRAMEC_VERSION,
+                    config,
+                    semantic_parser.get_system_hierarchy(),
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::Swift => {
+                let mut visitor =
+                    SwiftVisitor::new(semantic_parser.get_arcanum(), FRAMEC_VERSION, comments);
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::TypeScript => {
+                let mut visitor = TypeScriptVisitor::new(
+                    semantic_parser.get_arcanum(),
+                    generate_exit_args,
+                    generate_enter_args || generate_state_context,
+                    generate_state_stack,
+                    generate_change_state,
+                    generate_transition_state,
+                    FRAMEC_VERSION,
+                    comments,
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+            TargetLanguage::XState => {
+                let mut visitor = XStateVisitor::new(
+                    FRAMEC_VERSION,
+                    config,
+                    semantic_parser.get_system_hierarchy(),
+                );
+                visitor.run(&system_node);
+                visitor.get_code()
+            }
+        };
+
+        Ok(GeneratedCode::File(output))
+    }
+}
 
-        // let mut graphviz_visitor = GraphVizVisitor::new(semantic_parser.get_arcanum(), comments);
-        // graphviz_visitor.run(&system_node);
-        // println!("{}", graphviz_visitor.code);
+impl Exe {
+    fn unknown_target_error(name: &str, context: &str) -> RunError {
+        let mut error_msg = format!(
+            "Error - unrecognized target language '{}'{}.",
+            name, context
+        );
+        if let Some(suggestion) = targets::suggest(name) {
+            error_msg.push_str(&format!(" Did you mean '{}'?", suggestion));
+        }
+        error_msg.push_str("\nRun 'framec --list-targets' to see the supported targets.");
+        RunError::new(USAGE, &error_msg)
     }
 }
 
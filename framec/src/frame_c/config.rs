@@ -53,6 +53,10 @@ pub struct CodeGenConfig {
     pub common: CommonConfig,
     pub rust: RustConfig,
     pub smcat: SmcatConfig,
+    pub graphviz: GraphvizConfig,
+    pub c99: C99Config,
+    pub go: GoConfig,
+    pub xstate: XStateConfig,
 }
 
 /// Code generation options shared among all backends.
@@ -81,6 +85,39 @@ pub struct RustConfig {
 /// Code generation features specific to the Rust backend.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustFeatures {
+    /// When enabled, interface methods, actions, and the internal event handling machinery are
+    /// generated as `async fn`, and calls to actions are awaited within event handlers. Exit
+    /// events, state updates, and enter events are still processed strictly in order, with each
+    /// step awaiting the previous one. The generated code does not depend on any particular
+    /// executor.
+    ///
+    /// Since the constructor sends an enter event to the start state, `new` is also `async`, and
+    /// no `Default` implementation is generated. If the `thread_safe` feature is also enabled,
+    /// the returned futures are `Send`.
+    ///
+    /// Default is `false`.
+    pub async_support: bool,
+
+    /// When enabled, actions declared with a trailing `!`, e.g. `read_temp : i32 !`, return
+    /// `Result<T, E>`, where `E` is set by `RustCode.error_type`. If a fallible action fails, the
+    /// handler calling it returns immediately, and if it was an exit handler, the transition is
+    /// abandoned and the machine stays in its current state. Once the event has been handled,
+    /// the first error raised while handling it is sent to the current state as an error event,
+    /// which a `|!|` handler may use to log the error or transition to a recovery state. The
+    /// handler may declare a single parameter of the error type to access the error, which it
+    /// should only use by reference.
+    ///
+    /// Every interface method returns `Result<T, E>`, and returns the error after the error
+    /// event has been handled. If the `|!|` handler fails itself, its error is returned instead.
+    /// An error raised while the constructor enters the start state is returned by the first
+    /// interface method called.
+    ///
+    /// This feature cannot be combined with `typed_events`. When combined with `no_std`,
+    /// `RustCode.error_type` must be set to a type that does not depend on `std`.
+    ///
+    /// Default is `false`.
+    pub fallible_actions: bool,
+
     /// When enabled, generated code will attempt to conform to standard Rust naming conventions.
     /// However, options in `RustCode` are in general not overridden by this feature.
     ///
@@ -100,6 +137,75 @@ pub struct RustFeatures {
     /// Default is `false`.
     pub generate_hook_methods: bool,
 
+    /// When enabled, the generated code is split into a module directory rather than a single
+    /// file: `mod.rs` holds the header and imports, and declares the submodules `events.rs`,
+    /// `states.rs`, `machine.rs`, and, if the `runtime_info` or `runtime_support` feature is
+    /// also enabled, `info.rs`. The module names can be changed in `RustCode`. Definitions shared between
+    /// these files are given the visibility set by `RustCode.internal_visibility`, and the state
+    /// machine type is re-exported from `mod.rs`.
+    ///
+    /// Since the output is several files, `framec` must be run with `--output`, which names the
+    /// module directory to write. Without it, the files are printed one after another.
+    ///
+    /// Default is `false`.
+    pub generate_module_tree: bool,
+
+    /// When enabled, generates a `#[cfg(test)]` module that uses the `proptest` crate to send
+    /// random sequences of interface calls, with random arguments, to a new state machine. The
+    /// generated test fails if any call panics, which includes popping an empty state stack, or
+    /// if an invariant is violated after a call. If the `runtime_support` feature is also
+    /// enabled, the current state must be one of the states listed in the machine's
+    /// `MachineInfo`. Further invariants can be added with `RustCode.test_invariants`.
+    ///
+    /// The crate including the generated code needs `proptest` as a dev-dependency, and every
+    /// interface parameter type must implement `proptest::arbitrary::Arbitrary`. This feature
+    /// cannot be combined with `async_support` or `generic_actions`.
+    ///
+    /// Default is `false`.
+    pub generate_test_harness: bool,
+
+    /// When enabled, the state machine is generated as a generic struct, e.g.
+    /// `pub struct Sys<A: SysActions>`, that owns an implementation of the public `Action` trait
+    /// passed to its constructor, `new(actions: A)`. Actions and hook methods are invoked on this
+    /// object and take `&mut self`, so test doubles and hardware drivers can be injected without
+    /// editing generated code. Further type parameters usable in the domain can be declared with
+    /// `RustCode.system_type_params`.
+    ///
+    /// In this mode, the `generate_action_impl` feature is ignored, and `Default` is implemented
+    /// only when the actions type implements `Default`. This feature cannot be combined with
+    /// `runtime_support`.
+    ///
+    /// Default is `false`.
+    pub generic_actions: bool,
+
+    /// When enabled, generates code that depends only on `core` and never allocates, so that it
+    /// can be used from `#![no_std]` crates such as embedded firmware. State contexts are stored
+    /// inline in the state machine, events are passed by reference, string literals and event
+    /// names are `&'static str`, and the state stack is a fixed-size array whose capacity is set
+    /// by `RustCode.state_stack_capacity`. Pushing onto a full state stack panics.
+    ///
+    /// This feature cannot be combined with `async_support`, `runtime_support`, `serde`, or
+    /// `thread_safe`, and specs using deferred events or regex matches are rejected. It can be
+    /// combined with `fallible_actions` only if `RustCode.error_type` is overridden with a type
+    /// that does not depend on `std`, since the default error type is boxed. Static runtime info
+    /// is still available through the `runtime_info` feature.
+    ///
+    /// Default is `false`.
+    pub no_std: bool,
+
+    /// When enabled, generates only the static info used by the runtime interface: a module
+    /// describing the machine's states, events, actions, and transitions, along with a
+    /// `const fn machine_info()` on the state machine type that returns its `MachineInfo`. The
+    /// rest of the runtime interface, such as the event monitor, is not generated. This feature
+    /// is implied by `runtime_support`.
+    ///
+    /// The info only depends on the `info` module of the `frame_runtime` crate, so it can be
+    /// combined with the `no_std` feature if `frame_runtime` is included with
+    /// `default-features = false`.
+    ///
+    /// Default is `false`.
+    pub runtime_info: bool,
+
     /// When enabled, generates code that links into the Frame runtime system. See the
     /// `frame_runtime` crate. This crate provides reflection and monitoring capabilities to
     /// running state machines.
@@ -113,11 +219,47 @@ pub struct RustFeatures {
     /// By default, the `runtime_support` feature is `false`.
     pub runtime_support: bool,
 
+    /// When enabled, derives `serde::Serialize` and `serde::Deserialize` for the state enum,
+    /// state contexts, and domain enums, and generates a snapshot type along with `snapshot` and
+    /// `restore` methods on the state machine. A snapshot captures the current state and its
+    /// context, the state stack, and all domain variables. Restoring a snapshot does not send
+    /// enter events, so it resumes a machine exactly where the snapshot left it.
+    ///
+    /// The generated code requires the `serde` crate with the `derive` and `rc` features enabled.
+    /// Domain variable types must implement `Clone`, `Serialize`, and `Deserialize`.
+    ///
+    /// Default is `false`.
+    pub serde: bool,
+
     /// When enabled, generates a state machine that implements the `Send` trait, and so can be
     /// safely passed acrosss thread boundries.
     ///
     /// Default is `false`.
     pub thread_safe: bool,
+
+    /// When enabled, generated code reports its activity through the `tracing` crate, which must
+    /// be a dependency of the crate including the generated code. Each call to `handle_event`
+    /// runs in a debug span recording the system name, the current state, and the event message.
+    /// Exit, enter, and state stack push and pop operations are reported as debug events, while
+    /// transitions and change-states are reported as info events that include the transition's
+    /// ID and label, matching `TransitionInfo` in the runtime interface.
+    ///
+    /// This feature is independent of `runtime_support`.
+    ///
+    /// Default is `false`.
+    pub tracing: bool,
+
+    /// When enabled, events are generated as a single enum with one variant per event, whose
+    /// fields are the event's arguments, e.g. `FrameEvent::Mult { a: i32, b: i32 }`. Events are
+    /// passed to state handlers by value rather than through a reference-counted pointer, and
+    /// each handler returns the event's return value directly, so sending an event does not
+    /// allocate.
+    ///
+    /// This feature cannot be combined with `async_support` or `runtime_support`, and specs using
+    /// deferred events are rejected.
+    ///
+    /// Default is `false`.
+    pub typed_events: bool,
 }
 
 /// Naming options for generated code specific to the Rust backend. These options can be used to
@@ -132,11 +274,21 @@ pub struct RustCode {
     pub action_suffix: String,
     pub actions_prefix: String,
     pub actions_suffix: String,
+    pub actions_var_name: String,
+    pub actions_type_param: String,
+    /// Type parameters, with their bounds, added to the state machine struct after the actions
+    /// type when the `generic_actions` feature is enabled, e.g. `T: Copy + Default`. Domain
+    /// variables may use these types. This option cannot be combined with the `serde` feature.
+    ///
+    /// Default is empty.
+    pub system_type_params: String,
 
     pub enter_token: String,
     pub exit_token: String,
+    pub error_token: String,
     pub enter_msg: String,
     pub exit_msg: String,
+    pub error_msg: String,
     pub event_args_suffix: String,
     pub event_args_method_suffix: String,
     pub enter_args_member_name: String,
@@ -181,6 +333,33 @@ pub struct RustCode {
     pub state_stack_var_name: String,
     pub state_stack_push_method_name: String,
     pub state_stack_pop_method_name: String,
+    pub state_stack_depth_var_name: String,
+    /// Number of state contexts the state stack can hold when the `no_std` feature is enabled.
+    /// Other configurations use a growable stack and ignore this option.
+    ///
+    /// Default is `8`.
+    pub state_stack_capacity: u32,
+
+    pub snapshot_suffix: String,
+    pub snapshot_method_name: String,
+    pub restore_method_name: String,
+
+    pub deferred_events_var_name: String,
+    pub is_deferred_method_name: String,
+    pub replay_deferred_events_method_name: String,
+
+    /// Type of the errors returned by fallible actions and interface methods when the
+    /// `fallible_actions` feature is enabled. The type must implement `Send` if the
+    /// `thread_safe` feature is also enabled, and `Clone` if a `|!|` handler takes a parameter
+    /// in that mode.
+    ///
+    /// Default is `Box<dyn std::error::Error>`.
+    pub error_type: String,
+    pub pending_error_var_name: String,
+    pub take_error_method_name: String,
+
+    pub regex_static_name_prefix: String,
+    pub regex_captures_var_name: String,
 
     pub runtime_info_module_name: String,
     pub runtime_module_use_as_name: String,
@@ -188,6 +367,39 @@ pub struct RustCode {
     pub pop_state_info_name: String,
     pub event_monitor_var_name: String,
     pub transition_info_arg_name: String,
+    pub transition_id_arg_name: String,
+    pub transition_label_arg_name: String,
+
+    pub test_harness_module_suffix: String,
+    /// Comma-separated paths of functions that check user-defined invariants of the state
+    /// machine, e.g. `check_budget, crate::checks::no_overflow`. Each function takes a reference
+    /// to the state machine and should panic if its invariant does not hold. The test harness
+    /// generated by the `generate_test_harness` feature calls these functions after creating the
+    /// state machine and after every interface call.
+    ///
+    /// Default is empty.
+    pub test_invariants: String,
+    /// Maximum number of interface calls in each sequence sent by the generated test harness.
+    ///
+    /// Default is `32`.
+    pub test_sequence_length: u32,
+
+    /// Visibility of the state machine type, its constructor, its interface and snapshot methods,
+    /// and the state enum, e.g. `pub(crate)` for a state machine used only within its crate.
+    ///
+    /// Default is `pub`.
+    pub system_visibility: String,
+    /// Visibility of the definitions shared between the files generated by the
+    /// `generate_module_tree` feature, namely the event types, state contexts, action trait, and
+    /// domain variables. These are private when the code is generated as a single file. Domain
+    /// variables need to be visible wherever actions are implemented.
+    ///
+    /// Default is `pub(crate)`.
+    pub internal_visibility: String,
+    pub events_module_name: String,
+    pub states_module_name: String,
+    pub info_module_name: String,
+    pub machine_module_name: String,
 }
 
 /// Initial settings for the Rust runtime system. These options are only relevant if
@@ -248,6 +460,99 @@ pub struct SmcatCode {
     pub transition_edge_style: String,
 }
 
+/// Code generation options specific to the Graphviz backend.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphvizConfig {
+    pub features: GraphvizFeatures,
+    pub code: GraphvizCode,
+}
+
+/// Code generation features specific to the Graphviz backend.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphvizFeatures {}
+
+/// Style options for generated code specific to the Graphviz backend.
+///
+/// Each option is a comma-separated DOT attribute list, without the enclosing brackets. See
+/// <https://graphviz.org/doc/info/attrs.html>
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphvizCode {
+    /// Style settings for nodes that do not have any children.
+    pub simple_state_node_style: String,
+    /// Style settings for the clusters drawn around states that have sub-states as children.
+    pub parent_state_cluster_style: String,
+    /// Style settings for the node inside a parent state's cluster that its edges attach to.
+    pub parent_state_anchor_style: String,
+    /// Style settings for "change-state" transitions.
+    pub change_state_edge_style: String,
+    /// Style settings for standard transitions.
+    pub transition_edge_style: String,
+    /// Style settings for the state stack push and pop pseudo-nodes.
+    pub state_stack_node_style: String,
+    /// Style settings for edges to the state stack push pseudo-node.
+    pub state_stack_push_edge_style: String,
+}
+
+/// Code generation options specific to the C99 backend.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct C99Config {
+    pub features: C99Features,
+    pub code: C99Code,
+}
+
+/// Code generation features specific to the C99 backend.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct C99Features {}
+
+/// Options for generated code specific to the C99 backend.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct C99Code {
+    /// Number of state contexts the fixed-size state stack can hold. The
+    /// generated code only uses this as the default for a macro, so it can
+    /// also be overridden when compiling.
+    ///
+    /// Default is `8`.
+    pub state_stack_capacity: u32,
+}
+
+/// Code generation options specific to the Go backend.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoConfig {
+    pub features: GoFeatures,
+    pub code: GoCode,
+}
+
+/// Code generation features specific to the Go backend.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoFeatures {}
+
+/// Options for generated code specific to the Go backend.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoCode {
+    /// Name of the generated package. When empty, the lower-cased system
+    /// name is used.
+    pub package_name: String,
+}
+
+/// Code generation options specific to the XState backend.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XStateConfig {
+    pub features: XStateFeatures,
+    pub code: XStateCode,
+}
+
+/// Code generation features specific to the XState backend.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XStateFeatures {}
+
+/// Options for generated code specific to the XState backend.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XStateCode {
+    /// Major version of XState the machine config targets. Version 4 names
+    /// transition guards `cond`, version 5 names them `guard`.
+    pub xstate_version: u32,
+}
+
 impl FrameConfig {
     /// Generate a configuration from any `Provider`.
     pub fn from<T: Provider>(provider: T) -> Result<FrameConfig, Error> {
@@ -368,11 +673,21 @@ impl Provider for SystemNode {
 impl Default for RustFeatures {
     fn default() -> Self {
         RustFeatures {
+            async_support: false,
+            fallible_actions: false,
             follow_rust_naming: true,
             generate_action_impl: true,
             generate_hook_methods: false,
+            generate_module_tree: false,
+            generate_test_harness: false,
+            generic_actions: false,
+            no_std: false,
+            runtime_info: false,
             runtime_support: false,
+            serde: false,
             thread_safe: false,
+            tracing: false,
+            typed_events: false,
         }
     }
 }
@@ -384,11 +699,16 @@ impl Default for RustCode {
             action_suffix: String::from(""),
             actions_prefix: String::from(""),
             actions_suffix: String::from("Actions"),
+            actions_var_name: String::from("actions"),
+            actions_type_param: String::from("A"),
+            system_type_params: String::new(),
 
             enter_token: String::from(">"),
             exit_token: String::from("<"),
+            error_token: String::from("!"),
             enter_msg: String::from("Enter"),
             exit_msg: String::from("Exit"),
+            error_msg: String::from("Error"),
             event_args_suffix: String::from("Args"),
             event_args_method_suffix: String::from("_args"),
             enter_args_member_name: String::from("enter_args"),
@@ -435,6 +755,23 @@ impl Default for RustCode {
             state_stack_var_name: String::from("state_stack"),
             state_stack_push_method_name: String::from("state_stack_push"),
             state_stack_pop_method_name: String::from("state_stack_pop"),
+            state_stack_depth_var_name: String::from("state_stack_depth"),
+            state_stack_capacity: 8,
+
+            snapshot_suffix: String::from("Snapshot"),
+            snapshot_method_name: String::from("snapshot"),
+            restore_method_name: String::from("restore"),
+
+            deferred_events_var_name: String::from("deferred_events"),
+            is_deferred_method_name: String::from("is_deferred"),
+            replay_deferred_events_method_name: String::from("replay_deferred_events"),
+
+            error_type: String::from("Box<dyn std::error::Error>"),
+            pending_error_var_name: String::from("pending_error"),
+            take_error_method_name: String::from("take_error"),
+
+            regex_static_name_prefix: String::from("REGEX_"),
+            regex_captures_var_name: String::from("regex_captures"),
 
             runtime_info_module_name: String::from("runtime_info"),
             runtime_module_use_as_name: String::from("runtime"),
@@ -442,6 +779,19 @@ impl Default for RustCode {
             pop_state_info_name: String::from("$$[-]"),
             event_monitor_var_name: String::from("event_monitor"),
             transition_info_arg_name: String::from("transition_info"),
+            transition_id_arg_name: String::from("transition_id"),
+            transition_label_arg_name: String::from("transition_label"),
+
+            test_harness_module_suffix: String::from("_harness"),
+            test_invariants: String::new(),
+            test_sequence_length: 32,
+
+            system_visibility: String::from("pub"),
+            internal_visibility: String::from("pub(crate)"),
+            events_module_name: String::from("events"),
+            states_module_name: String::from("states"),
+            info_module_name: String::from("info"),
+            machine_module_name: String::from("machine"),
         }
     }
 }
@@ -465,3 +815,31 @@ impl Default for SmcatCode {
         }
     }
 }
+
+impl Default for GraphvizCode {
+    fn default() -> Self {
+        GraphvizCode {
+            simple_state_node_style: String::from("shape=box, style=rounded"),
+            parent_state_cluster_style: String::from("style=rounded"),
+            parent_state_anchor_style: String::from("shape=point, style=invis"),
+            change_state_edge_style: String::from("style=dashed"),
+            transition_edge_style: String::new(),
+            state_stack_node_style: String::from("shape=circle, fontsize=10"),
+            state_stack_push_edge_style: String::from("style=dotted, arrowhead=empty"),
+        }
+    }
+}
+
+impl Default for C99Code {
+    fn default() -> Self {
+        C99Code {
+            state_stack_capacity: 8,
+        }
+    }
+}
+
+impl Default for XStateCode {
+    fn default() -> Self {
+        XStateCode { xstate_version: 5 }
+    }
+}
@@ -4,6 +4,8 @@ pub mod compiler;
 pub mod config;
 mod parser;
 mod scanner;
+pub mod scxml_import;
 mod symbol_table;
+pub mod targets;
 pub mod utils;
 mod visitors;
@@ -99,12 +99,15 @@ pub struct Parser<'a> {
     system_hierarchy_opt: Option<SystemHierarchy>,
     is_parsing_rhs: bool,
     event_handler_has_transition: bool,
+    loop_depth: usize,
+    statements_end_in_transition: bool,
     pub generate_enter_args: bool,
     pub generate_exit_args: bool,
     pub generate_state_context: bool,
     pub generate_state_stack: bool,
     pub generate_change_state: bool,
     pub generate_transition_state: bool,
+    pub generate_regex_match: bool,
 }
 
 impl<'a> Parser<'a> {
@@ -131,12 +134,15 @@ impl<'a> Parser<'a> {
             system_hierarchy_opt: None,
             is_parsing_rhs: false,
             event_handler_has_transition: false,
+            loop_depth: 0,
+            statements_end_in_transition: false,
             generate_enter_args: false,
             generate_exit_args: false,
             generate_state_context: false,
             generate_state_stack: false,
             generate_change_state: false,
             generate_transition_state: false,
+            generate_regex_match: false,
         }
     }
 
@@ -758,7 +764,7 @@ impl<'a> Parser<'a> {
 
     /* --------------------------------------------------------------------- */
 
-    // message => '|' ( identifier | string | '>' | '>>' | '>>>' | '<' | '<<' | '<<<' ) '|'
+    // message => '|' ( identifier | string | '>' | '>>' | '>>>' | '<' | '<<' | '<<<' | '!' ) '|'
 
     fn message(&mut self) -> Result<MessageType, ParseError> {
         let message_node;
@@ -787,7 +793,8 @@ impl<'a> Parser<'a> {
             | TokenType::GTx3
             | TokenType::LT
             | TokenType::LTx2
-            | TokenType::LTx3 => message_node = self.create_message_node(tt),
+            | TokenType::LTx3
+            | TokenType::Bang => message_node = self.create_message_node(tt),
             _ => {
                 self.error_at_current("Expected '|'");
                 return Err(ParseError::new("TODO"));
@@ -983,6 +990,9 @@ impl<'a> Parser<'a> {
             }
         }
 
+        // a trailing '!' declares an action that can fail
+        let is_fallible = self.match_token(&[TokenType::Bang]);
+
         let mut code_opt: Option<String> = None;
 
         if self.match_token(&[TokenType::OpenBrace]) {
@@ -996,7 +1006,8 @@ impl<'a> Parser<'a> {
             }
         }
 
-        let action_decl_node = ActionNode::new(action_name.clone(), params, type_opt, code_opt);
+        let action_decl_node =
+            ActionNode::new(action_name.clone(), params, type_opt, is_fallible, code_opt);
         let action_decl_rcref = Rc::new(RefCell::new(action_decl_node));
 
         if self.is_building_symbol_table {
@@ -1039,14 +1050,24 @@ impl<'a> Parser<'a> {
         }
 
         let mut domain_variables = Vec::new();
+        let mut enums = Vec::new();
 
-        while self.match_token(&[TokenType::Var, TokenType::Const]) {
-            match self.variable_decl(IdentifierDeclScope::DomainBlock) {
-                Ok(domain_variable_node) => domain_variables.push(domain_variable_node),
-                Err(_parse_err) => {
-                    let sync_tokens = &vec![TokenType::Var, TokenType::Const, TokenType::SystemEnd];
-                    self.synchronize(sync_tokens);
-                }
+        while self.match_token(&[TokenType::Var, TokenType::Const, TokenType::Enum]) {
+            let result = if self.previous().token_type == TokenType::Enum {
+                self.enum_decl()
+                    .map(|enum_decl_node| enums.push(enum_decl_node))
+            } else {
+                self.variable_decl(IdentifierDeclScope::DomainBlock)
+                    .map(|domain_variable_node| domain_variables.push(domain_variable_node))
+            };
+            if result.is_err() {
+                let sync_tokens = &[
+                    TokenType::Var,
+                    TokenType::Const,
+                    TokenType::Enum,
+                    TokenType::SystemEnd,
+                ];
+                self.synchronize(sync_tokens);
             }
         }
 
@@ -1054,7 +1075,60 @@ impl<'a> Parser<'a> {
             .debug_print_current_symbols(self.arcanum.get_current_symtab());
         self.arcanum.exit_parse_scope();
 
-        DomainBlockNode::new(domain_variables)
+        DomainBlockNode::new(domain_variables, enums)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // enum_decl -> 'enum' identifier '{' (identifier ','?)* '}'
+
+    fn enum_decl(&mut self) -> Result<Rc<RefCell<EnumDeclNode>>, ParseError> {
+        if !self.match_token(&[TokenType::Identifier]) {
+            self.error_at_current("Expected enum name.");
+            return Err(ParseError::new("TODO"));
+        }
+        let name = self.previous().lexeme.clone();
+
+        if self.consume(TokenType::OpenBrace, "Expected '{'.").is_err() {
+            return Err(ParseError::new("TODO"));
+        }
+
+        let mut enumerators: Vec<String> = Vec::new();
+        while self.match_token(&[TokenType::Identifier]) {
+            let enumerator = self.previous().lexeme.clone();
+            if enumerators.contains(&enumerator) {
+                self.error_at_previous(&format!("Duplicate enumerator '{}'.", enumerator));
+            } else {
+                enumerators.push(enumerator);
+            }
+            self.match_token(&[TokenType::Comma]);
+        }
+
+        if self
+            .consume(TokenType::CloseBrace, "Expected '}'.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+
+        if enumerators.is_empty() {
+            self.error_at_previous(&format!("Enum '{}' has no enumerators.", name));
+        }
+
+        let enum_decl_rcref = Rc::new(RefCell::new(EnumDeclNode::new(name.clone(), enumerators)));
+
+        if self.is_building_symbol_table {
+            let enum_decl_symbol = EnumDeclSymbol::new(name, Rc::clone(&enum_decl_rcref));
+            let enum_decl_symbol_t = SymbolType::EnumDecl {
+                enum_decl_symbol_rcref: Rc::new(RefCell::new(enum_decl_symbol)),
+            };
+            self.arcanum
+                .current_symtab
+                .borrow_mut()
+                .insert_symbol(&enum_decl_symbol_t);
+        }
+
+        Ok(enum_decl_rcref)
     }
 
     //* --------------------------------------------------------------------- *//
@@ -1107,6 +1181,8 @@ impl<'a> Parser<'a> {
                     => initializer_expr_t_opt = Some(BinaryExprT {binary_expr_node}),
                 Ok(Some(FrameEventExprT { frame_event_part }))
                     => initializer_expr_t_opt = Some(FrameEventExprT {frame_event_part}),
+                Ok(Some(EnumeratorExprT { enumerator_expr_node }))
+                    => initializer_expr_t_opt = Some(EnumeratorExprT {enumerator_expr_node}),
                 _ => {
                     self.error_at_current("Unexpected assignment expression value.");
                     return Err(ParseError::new("TODO"))
@@ -1216,6 +1292,7 @@ impl<'a> Parser<'a> {
                 None,
                 None,
                 Option::None,
+                Option::None,
                 Vec::new(),
                 Option::None,
                 Option::None,
@@ -1417,6 +1494,31 @@ impl<'a> Parser<'a> {
             calls_opt = Some(calls);
         }
 
+        // Deferred events
+        // '|' 'defer' '|' message (',' message)*
+        let mut deferred_events_opt = None;
+        let mut deferred_events = Vec::new();
+
+        while self.is_defer_clause() {
+            match self.deferred_events() {
+                Ok(mut msgs) => deferred_events.append(&mut msgs),
+                Err(_) => {
+                    let sync_tokens = &[
+                        TokenType::Pipe,
+                        TokenType::State,
+                        TokenType::ActionsBlock,
+                        TokenType::DomainBlock,
+                        TokenType::SystemEnd,
+                    ];
+                    self.synchronize(sync_tokens);
+                }
+            }
+        }
+
+        if !deferred_events.is_empty() {
+            deferred_events_opt = Some(deferred_events);
+        }
+
         // Parse any event handlers.
 
         // TODO: make this Option?
@@ -1529,6 +1631,7 @@ impl<'a> Parser<'a> {
             params_opt,
             vars_opt,
             calls_opt,
+            deferred_events_opt,
             evt_handlers,
             enter_event_handler,
             exit_event_handler,
@@ -1560,6 +1663,87 @@ impl<'a> Parser<'a> {
 
     /* --------------------------------------------------------------------- */
 
+    // Looks ahead for the '|' 'defer' '|' sequence that starts a deferred
+    // events clause. 'defer' is not a keyword so this has to be checked
+    // before the state's event handlers are parsed.
+
+    fn is_defer_clause(&self) -> bool {
+        let mut idx = self.current;
+        while self.tokens[idx].token_type == TokenType::SingleLineComment
+            || self.tokens[idx].token_type == TokenType::MultiLineComment
+        {
+            idx += 1;
+        }
+        if idx + 2 >= self.tokens.len() {
+            return false;
+        }
+        self.tokens[idx].token_type == TokenType::Pipe
+            && self.tokens[idx + 1].token_type == TokenType::Identifier
+            && self.tokens[idx + 1].lexeme == "defer"
+            && self.tokens[idx + 2].token_type == TokenType::Pipe
+    }
+
+    /* --------------------------------------------------------------------- */
+
+    // deferred_events -> '|' 'defer' '|' Identifier (',' Identifier)*
+
+    fn deferred_events(&mut self) -> Result<Vec<String>, ParseError> {
+        let mut msgs = Vec::new();
+
+        // '|' 'defer' '|' has already been verified by is_defer_clause()
+        self.match_token(&[TokenType::Pipe]);
+        self.match_token(&[TokenType::Identifier]);
+        self.match_token(&[TokenType::Pipe]);
+
+        loop {
+            if !self.match_token(&[TokenType::Identifier]) {
+                self.error_at_current("Expected deferred event message.");
+                return Err(ParseError::new("TODO"));
+            }
+            let msg = self.previous().lexeme.clone();
+
+            // Events are only fully known after the first pass.
+            if !self.is_building_symbol_table {
+                match self.arcanum.get_event(&msg, &None) {
+                    Some(event_symbol_rcref) => {
+                        let event_symbol = event_symbol_rcref.borrow();
+                        if event_symbol.interface_name_opt.is_none() {
+                            self.error_at_previous(&format!(
+                                "Deferred event |{}| is not an interface message.",
+                                msg
+                            ));
+                        } else if event_symbol.ret_type_opt.is_some() {
+                            self.error_at_previous(&format!(
+                                "Deferred event |{}| has a return type and cannot be deferred.",
+                                msg
+                            ));
+                        }
+                    }
+                    None => {
+                        self.error_at_previous(&format!(
+                            "Deferred event |{}| is not an interface message.",
+                            msg
+                        ));
+                    }
+                }
+            }
+
+            if msgs.contains(&msg) {
+                self.error_at_previous(&format!("Duplicate deferred event |{}|.", msg));
+            } else {
+                msgs.push(msg);
+            }
+
+            if !self.match_token(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        Ok(msgs)
+    }
+
+    /* --------------------------------------------------------------------- */
+
     // event_handler -> '|' Identifier '|' event_handler_terminator
 
     fn event_handler(&mut self) -> Result<Option<EventHandlerNode>, ParseError> {
@@ -1631,8 +1815,9 @@ impl<'a> Parser<'a> {
         // Remember to pop param scope at end if it is entered.
         let mut pop_params_scope = false;
 
-        // Parse event handler parameters
-        if self.match_token(&[TokenType::LBracket]) {
+        // Parse event handler parameters. A bracket that holds a guard
+        // expression is left for the statement parser.
+        if !self.is_guard_clause() && self.match_token(&[TokenType::LBracket]) {
             if msg == self.arcanum.symbol_config.enter_msg_symbol {
                 self.generate_state_context = true;
             }
@@ -1944,10 +2129,12 @@ impl<'a> Parser<'a> {
                                 match stmt_t {
                                     StatementType::TransitionStmt { .. } => {
                                         statements.push(statement);
+                                        self.statements_end_in_transition = true;
                                         return statements;
                                     }
                                     StatementType::ChangeStateStmt { .. } => {
                                         statements.push(statement);
+                                        self.statements_end_in_transition = true;
                                         return statements;
                                     }
                                     _ => {
@@ -1961,6 +2148,7 @@ impl<'a> Parser<'a> {
                         }
                     }
                     None => {
+                        self.statements_end_in_transition = false;
                         return statements;
                     }
                 },
@@ -2016,6 +2204,18 @@ impl<'a> Parser<'a> {
     // statement ->
 
     fn statement(&mut self) -> Result<Option<StatementType>, ParseError> {
+        if self.match_token(&[TokenType::LBracket]) {
+            return self.guarded_transition();
+        }
+
+        if self.match_token(&[TokenType::Loop, TokenType::While]) {
+            return self.loop_statement();
+        }
+
+        if self.match_token(&[TokenType::Break, TokenType::Continue]) {
+            return self.loop_control_statement();
+        }
+
         let mut expr_t_opt: Option<ExprType> = None;
         match self.expression() {
             Ok(et_opt) => expr_t_opt = et_opt,
@@ -2092,6 +2292,40 @@ impl<'a> Parser<'a> {
                             Err(parse_error)
                         }
                     };
+                } else if self.is_regex_match_test() {
+                    if !self.is_testable_expression(&expr_t) {
+                        self.error_at_current("Not a testable expression.");
+                        return Err(ParseError::new("TODO"));
+                    }
+                    let result = self.regex_match_test(expr_t);
+                    return match result {
+                        Ok(regex_match_test_node) => {
+                            let match_test_t = TestType::RegexMatchTest {
+                                regex_match_test_node,
+                            };
+                            let test_stmt_node = TestStatementNode::new(match_test_t);
+                            let test_stmt_t = StatementType::TestStmt { test_stmt_node };
+                            Ok(Some(test_stmt_t))
+                        }
+                        Err(parse_error) => Err(parse_error),
+                    };
+                } else if self.is_enum_match_test() {
+                    if !self.is_testable_expression(&expr_t) {
+                        self.error_at_current("Not a testable expression.");
+                        return Err(ParseError::new("TODO"));
+                    }
+                    let result = self.enum_match_test(expr_t);
+                    return match result {
+                        Ok(enum_match_test_node) => {
+                            let match_test_t = TestType::EnumMatchTest {
+                                enum_match_test_node,
+                            };
+                            let test_stmt_node = TestStatementNode::new(match_test_t);
+                            let test_stmt_t = StatementType::TestStmt { test_stmt_node };
+                            Ok(Some(test_stmt_t))
+                        }
+                        Err(parse_error) => Err(parse_error),
+                    };
                 }
 
                 match expr_t {
@@ -2181,6 +2415,10 @@ impl<'a> Parser<'a> {
                         self.error_at_previous("Binary expression statements not allowed.");
                         return Err(ParseError::new("TODO"));
                     }
+                    EnumeratorExprT { .. } => {
+                        self.error_at_previous("Enumerator statements not allowed.");
+                        return Err(ParseError::new("TODO"));
+                    }
                 }
             }
             None => {
@@ -2251,12 +2489,72 @@ impl<'a> Parser<'a> {
 
     /* --------------------------------------------------------------------- */
 
-    // TODO
-    // fn is_regex_test(&self) -> bool {
-    //
-    //     //panic!("not implemented")
-    //     false
-    // }
+    // Event handler parameters and a handler guard both start with '['.
+    // The bracket is a guard if it holds anything other than parameter
+    // tokens: |e| [x > 3] -> $A. A lone name followed by a transition could
+    // be either, so it is reported rather than decided by the event's
+    // declaration. Such a guard is written as an expression, e.g.
+    // [armed == true], and such a parameter with its type.
+
+    fn is_guard_clause(&mut self) -> bool {
+        let mut idx = self.skip_comments(self.current);
+        if self.tokens[idx].token_type != TokenType::LBracket {
+            return false;
+        }
+
+        idx += 1;
+        let mut names = Vec::new();
+        let mut is_lone_name = true;
+        while idx < self.tokens.len() && self.tokens[idx].token_type != TokenType::RBracket {
+            match self.tokens[idx].token_type {
+                TokenType::Identifier => names.push(idx),
+                TokenType::Colon | TokenType::SuperString | TokenType::And => is_lone_name = false,
+                TokenType::SingleLineComment | TokenType::MultiLineComment => {}
+                _ => return true,
+            }
+            idx += 1;
+        }
+
+        if is_lone_name && names.len() == 1 && idx < self.tokens.len() {
+            let next_idx = self.skip_comments(idx + 1);
+            if matches!(
+                self.tokens[next_idx].token_type,
+                TokenType::Transition | TokenType::ChangeState
+            ) {
+                let name_token = self.tokens[names[0]].clone();
+                let message = format!(
+                    "Ambiguous '[{0}]': write a guard as an expression, e.g. '[{0} == true]', or a parameter with its type, e.g. '[{0}:bool]'.",
+                    name_token.lexeme
+                );
+                self.error_at(&name_token, &message);
+            }
+        }
+        false
+    }
+
+    /* --------------------------------------------------------------------- */
+
+    fn skip_comments(&self, mut idx: usize) -> usize {
+        while idx < self.tokens.len() - 1
+            && (self.tokens[idx].token_type == TokenType::SingleLineComment
+                || self.tokens[idx].token_type == TokenType::MultiLineComment)
+        {
+            idx += 1;
+        }
+        idx
+    }
+
+    /* --------------------------------------------------------------------- */
+
+    fn is_regex_match_test(&self) -> bool {
+        self.peek().token_type == TokenType::RegexTest
+    }
+
+    /* --------------------------------------------------------------------- */
+
+    fn is_enum_match_test(&self) -> bool {
+        self.peek().token_type == TokenType::EnumTest
+    }
 
     /* --------------------------------------------------------------------- */
 
@@ -2429,6 +2727,14 @@ impl<'a> Parser<'a> {
                 None,
                 self.previous().line,
             )));
+        } else if self.loop_depth > 0 && self.statements_end_in_transition {
+            // A branch that transitions from inside a loop must leave the
+            // handler rather than go around the loop again.
+            Ok(Some(TerminatorExpr::new(
+                Return,
+                None,
+                self.previous().line,
+            )))
         } else {
             Ok(None)
         }
@@ -2436,6 +2742,77 @@ impl<'a> Parser<'a> {
 
     /* --------------------------------------------------------------------- */
 
+    // loop_statement -> 'loop' '{' statements* '}'
+    //                 | 'while' expr '{' statements* '}'
+
+    fn loop_statement(&mut self) -> Result<Option<StatementType>, ParseError> {
+        let mut condition_expr_t_opt = None;
+        if self.previous().token_type == TokenType::While {
+            let expr_t = match self.expression() {
+                Ok(Some(expr_t)) => expr_t,
+                Ok(None) => {
+                    self.error_at_current("Expected loop condition.");
+                    return Err(ParseError::new("TODO"));
+                }
+                Err(parse_error) => return Err(parse_error),
+            };
+            if !self.is_testable_expression(&expr_t) {
+                self.error_at_current("Not a testable expression.");
+                return Err(ParseError::new("TODO"));
+            }
+            condition_expr_t_opt = Some(expr_t);
+        }
+
+        if self.consume(TokenType::OpenBrace, "Expected '{'.").is_err() {
+            return Err(ParseError::new("TODO"));
+        }
+
+        self.loop_depth += 1;
+        let statements = self.statements();
+        self.loop_depth -= 1;
+
+        // Without a test around it the transition would end the loop on
+        // its first pass.
+        if self.statements_end_in_transition {
+            self.error_at_previous("Transitions in a loop body must be inside a test.");
+            return Err(ParseError::new("TODO"));
+        }
+
+        if self
+            .consume(TokenType::CloseBrace, "Expected '}'.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+
+        let loop_stmt_node = LoopStmtNode::new(condition_expr_t_opt, statements);
+        Ok(Some(StatementType::LoopStmt { loop_stmt_node }))
+    }
+
+    /* --------------------------------------------------------------------- */
+
+    // loop_control_statement -> 'break' | 'continue'
+
+    fn loop_control_statement(&mut self) -> Result<Option<StatementType>, ParseError> {
+        let loop_control_t = match self.previous().token_type {
+            TokenType::Break => LoopControlType::Break,
+            _ => LoopControlType::Continue,
+        };
+
+        if self.loop_depth == 0 {
+            let keyword = self.previous().lexeme.clone();
+            self.error_at_previous(&format!("'{}' outside of a loop.", keyword));
+            return Err(ParseError::new("TODO"));
+        }
+
+        let loop_control_stmt_node = LoopControlStmtNode::new(loop_control_t);
+        Ok(Some(StatementType::LoopControlStmt {
+            loop_control_stmt_node,
+        }))
+    }
+
+    /* --------------------------------------------------------------------- */
+
     // '^' '('
     //           return_expr -> expression ')'
 
@@ -2564,6 +2941,267 @@ impl<'a> Parser<'a> {
 
     /* --------------------------------------------------------------------- */
 
+    // regex_match_test -> '?*' ('/' regex_pattern '/' (statement* branch_terminator?) ':>')+ (':' (statement* branch_terminator?))? '::'
+
+    fn regex_match_test(&mut self, expr_t: ExprType) -> Result<RegexMatchTestNode, ParseError> {
+        self.generate_regex_match = true;
+
+        if self
+            .consume(TokenType::RegexTest, "Expected '?*'.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+
+        let mut conditional_branches: Vec<RegexMatchTestMatchBranchNode> = Vec::new();
+
+        loop {
+            match self.regex_match_test_match_branch() {
+                Ok(branch_node) => {
+                    conditional_branches.push(branch_node);
+                }
+                Err(parse_error) => return Err(parse_error),
+            }
+            if !self.match_token(&[TokenType::ElseContinue]) {
+                break;
+            }
+        }
+
+        // (':' match_test_else_branch)?
+        let mut else_branch_opt: Option<RegexMatchTestElseBranchNode> = None;
+        if self.match_token(&[TokenType::Colon]) {
+            let statements = self.statements();
+            else_branch_opt = match self.branch_terminator() {
+                Ok(branch_terminator_opt) => Some(RegexMatchTestElseBranchNode::new(
+                    statements,
+                    branch_terminator_opt,
+                )),
+                Err(parse_error) => return Err(parse_error),
+            };
+        }
+
+        // '::'
+        if self
+            .consume(TokenType::TestTerminator, "Expected TestTerminator.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+
+        Ok(RegexMatchTestNode::new(
+            expr_t,
+            conditional_branches,
+            else_branch_opt,
+        ))
+    }
+
+    /* --------------------------------------------------------------------- */
+
+    // regex_match_test_match_branch -> '/' regex_pattern '/' statement* branch_terminator?
+
+    fn regex_match_test_match_branch(
+        &mut self,
+    ) -> Result<RegexMatchTestMatchBranchNode, ParseError> {
+        if self
+            .consume(TokenType::ForwardSlash, "Expected '/'.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+
+        if !self.match_token(&[TokenType::MatchRegex]) {
+            self.error_at_current("Expected regular expression.");
+            return Err(ParseError::new("TODO"));
+        }
+
+        // '/' is escaped in Frame source only to end the pattern.
+        let pattern = self.previous().lexeme.replace("\\/", "/");
+        let regex_match_pattern_node =
+            RegexMatchTestPatternNode::new(pattern, self.previous().line);
+
+        if self
+            .consume(TokenType::ForwardSlash, "Expected '/'.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+
+        let statements = self.statements();
+        let result = self.branch_terminator();
+        match result {
+            Ok(branch_terminator_t_opt) => Ok(RegexMatchTestMatchBranchNode::new(
+                regex_match_pattern_node,
+                statements,
+                branch_terminator_t_opt,
+            )),
+            Err(parse_error) => Err(parse_error),
+        }
+    }
+
+    /* --------------------------------------------------------------------- */
+
+    // enum_match_test -> '?:' '(' enum_type ')' ('/' enumerator ('|' enumerator)* '/' (statement* branch_terminator?) ':>')+ (':' (statement* branch_terminator?))? '::'
+
+    fn enum_match_test(&mut self, expr_t: ExprType) -> Result<EnumMatchTestNode, ParseError> {
+        if self.consume(TokenType::EnumTest, "Expected '?:'.").is_err() {
+            return Err(ParseError::new("TODO"));
+        }
+
+        if self.consume(TokenType::LParen, "Expected '('.").is_err() {
+            return Err(ParseError::new("TODO"));
+        }
+        if !self.match_token(&[TokenType::Identifier]) {
+            self.error_at_current("Expected enum type.");
+            return Err(ParseError::new("TODO"));
+        }
+        let enum_type_name = self.previous().lexeme.clone();
+        if self.consume(TokenType::RParen, "Expected ')'.").is_err() {
+            return Err(ParseError::new("TODO"));
+        }
+
+        // The domain is parsed after the machine, so enums are only
+        // known in the semantic pass.
+        let mut enum_decl_symbol_rcref_opt = None;
+        if !self.is_building_symbol_table {
+            match self.arcanum.lookup_enum(&enum_type_name) {
+                Some(enum_decl_symbol_rcref) => {
+                    enum_decl_symbol_rcref_opt = Some(enum_decl_symbol_rcref)
+                }
+                None => {
+                    self.error_at_previous(&format!("Unknown enum type '{}'.", enum_type_name));
+                    return Err(ParseError::new("TODO"));
+                }
+            }
+        }
+
+        let mut conditional_branches: Vec<EnumMatchTestMatchBranchNode> = Vec::new();
+
+        loop {
+            match self.enum_match_test_match_branch(&enum_decl_symbol_rcref_opt) {
+                Ok(branch_node) => {
+                    conditional_branches.push(branch_node);
+                }
+                Err(parse_error) => return Err(parse_error),
+            }
+            if !self.match_token(&[TokenType::ElseContinue]) {
+                break;
+            }
+        }
+
+        // (':' match_test_else_branch)?
+        let mut else_branch_opt: Option<EnumMatchTestElseBranchNode> = None;
+        if self.match_token(&[TokenType::Colon]) {
+            let statements = self.statements();
+            else_branch_opt = match self.branch_terminator() {
+                Ok(branch_terminator_opt) => Some(EnumMatchTestElseBranchNode::new(
+                    statements,
+                    branch_terminator_opt,
+                )),
+                Err(parse_error) => return Err(parse_error),
+            };
+        }
+
+        // '::'
+        if self
+            .consume(TokenType::TestTerminator, "Expected TestTerminator.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+
+        // Without an else branch every enumerator must be matched.
+        if let Some(enum_decl_symbol_rcref) = &enum_decl_symbol_rcref_opt {
+            if else_branch_opt.is_none() {
+                let enum_decl_symbol = enum_decl_symbol_rcref.borrow();
+                let enum_decl_node = enum_decl_symbol.ast_node.borrow();
+                let missing: Vec<&str> = enum_decl_node
+                    .enumerators
+                    .iter()
+                    .filter(|enumerator| {
+                        !conditional_branches
+                            .iter()
+                            .any(|branch| branch.enumerators.contains(enumerator))
+                    })
+                    .map(|enumerator| enumerator.as_str())
+                    .collect();
+                if !missing.is_empty() {
+                    let msg = format!(
+                        "Match on enum '{}' is not exhaustive. Missing: {}.",
+                        enum_type_name,
+                        missing.join(", ")
+                    );
+                    self.error_at_previous(&msg);
+                }
+            }
+        }
+
+        Ok(EnumMatchTestNode::new(
+            enum_type_name,
+            expr_t,
+            conditional_branches,
+            else_branch_opt,
+        ))
+    }
+
+    /* --------------------------------------------------------------------- */
+
+    // enum_match_test_match_branch -> '/' enumerator ('|' enumerator)* '/' statement* branch_terminator?
+
+    fn enum_match_test_match_branch(
+        &mut self,
+        enum_decl_symbol_rcref_opt: &Option<Rc<RefCell<EnumDeclSymbol>>>,
+    ) -> Result<EnumMatchTestMatchBranchNode, ParseError> {
+        if self
+            .consume(TokenType::ForwardSlash, "Expected '/'.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+
+        let mut enumerators: Vec<String> = Vec::new();
+        loop {
+            if !self.match_token(&[TokenType::MatchString]) {
+                self.error_at_current("Expected enumerator.");
+                return Err(ParseError::new("TODO"));
+            }
+            let enumerator = self.previous().lexeme.trim().to_string();
+            if let Some(enum_decl_symbol_rcref) = enum_decl_symbol_rcref_opt {
+                let enum_decl_symbol = enum_decl_symbol_rcref.borrow();
+                if !enum_decl_symbol.has_enumerator(&enumerator) {
+                    let msg = format!(
+                        "'{}' is not an enumerator of '{}'.",
+                        enumerator, enum_decl_symbol.name
+                    );
+                    self.error_at_previous(&msg);
+                }
+            }
+            enumerators.push(enumerator);
+            if !self.match_token(&[TokenType::Pipe]) {
+                break;
+            }
+        }
+
+        if self
+            .consume(TokenType::ForwardSlash, "Expected '/'.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+
+        let statements = self.statements();
+        let result = self.branch_terminator();
+        match result {
+            Ok(branch_terminator_t_opt) => Ok(EnumMatchTestMatchBranchNode::new(
+                enumerators,
+                statements,
+                branch_terminator_t_opt,
+            )),
+            Err(parse_error) => Err(parse_error),
+        }
+    }
+
+    /* --------------------------------------------------------------------- */
+
     // expression -> TODO
 
     fn expression(&mut self) -> Result<Option<ExprType>, ParseError> {
@@ -2945,6 +3583,17 @@ impl<'a> Parser<'a> {
             is_reference = true;
         }
 
+        // enum_type '.' enumerator
+        match self.enumerator_expr() {
+            Ok(Some(enumerator_expr_node)) => {
+                return Ok(Some(EnumeratorExprT {
+                    enumerator_expr_node,
+                }))
+            }
+            Err(parse_error) => return Err(parse_error),
+            Ok(None) => {} // continue
+        }
+
         // TODO: I think only identifier is allowed?
         if self.match_token(&[TokenType::Identifier]) {
             match self.variable_or_call_expr(scope) {
@@ -3018,6 +3667,49 @@ impl<'a> Parser<'a> {
 
     /* --------------------------------------------------------------------- */
 
+    // enumerator_expr -> enum_type '.' enumerator
+
+    fn enumerator_expr(&mut self) -> Result<Option<EnumeratorExprNode>, ParseError> {
+        if self.peek().token_type != TokenType::Identifier {
+            return Ok(None);
+        }
+        match self.tokens.get(self.current + 1) {
+            Some(token) if token.token_type == TokenType::Dot => {}
+            _ => return Ok(None),
+        }
+        let enum_decl_symbol_rcref = match self.arcanum.lookup_enum(&self.peek().lexeme) {
+            Some(enum_decl_symbol_rcref) => enum_decl_symbol_rcref,
+            None => return Ok(None),
+        };
+
+        // enum_type '.'
+        self.advance();
+        self.advance();
+
+        if !self.match_token(&[TokenType::Identifier]) {
+            self.error_at_current("Expected enumerator.");
+            return Err(ParseError::new("TODO"));
+        }
+        let enumerator = self.previous().lexeme.clone();
+
+        let enum_decl_symbol = enum_decl_symbol_rcref.borrow();
+        if !enum_decl_symbol.has_enumerator(&enumerator) {
+            let msg = format!(
+                "'{}' is not an enumerator of '{}'.",
+                enumerator, enum_decl_symbol.name
+            );
+            self.error_at_previous(&msg);
+            return Err(ParseError::new("TODO"));
+        }
+
+        Ok(Some(EnumeratorExprNode::new(
+            enum_decl_symbol.name.clone(),
+            enumerator,
+        )))
+    }
+
+    /* --------------------------------------------------------------------- */
+
     //
 
     fn stack_operation(&mut self) -> Result<Option<StateStackOperationNode>, ParseError> {
@@ -3445,6 +4137,7 @@ impl<'a> Parser<'a> {
                 target_state_context_t: state_context_t,
                 exit_args_opt,
                 label_opt: transition_label,
+                guard_opt: None,
             },
         }))
     }
@@ -3474,12 +4167,140 @@ impl<'a> Parser<'a> {
             change_state_stmt: ChangeStateStatementNode {
                 state_context_t,
                 label_opt,
+                guard_opt: None,
             },
         }))
     }
 
     /* --------------------------------------------------------------------- */
 
+    // guarded_transition : '[' expr ']' ( transition | change_state )
+    //
+    // A guarded transition is sugar for a bool test with a single branch
+    // holding the transition. The guard text is kept on the transition
+    // so it can be reported at runtime and drawn on diagrams.
+
+    fn guarded_transition(&mut self) -> Result<Option<StatementType>, ParseError> {
+        let guard_start = self.current;
+
+        let expr_t = match self.expression() {
+            Ok(Some(expr_t)) => expr_t,
+            Ok(None) => {
+                self.error_at_current("Expected guard expression.");
+                return Err(ParseError::new("TODO"));
+            }
+            Err(parse_error) => return Err(parse_error),
+        };
+
+        if !self.is_testable_expression(&expr_t) {
+            self.error_at_current("Not a testable expression.");
+            return Err(ParseError::new("TODO"));
+        }
+
+        let guard_end = self.current;
+        if self
+            .consume(TokenType::RBracket, "Expected ']' after guard expression.")
+            .is_err()
+        {
+            return Err(ParseError::new("TODO"));
+        }
+        let guard = self.guard_text(guard_start, guard_end);
+
+        let mut stmt_t = if self.match_token(&[TokenType::Transition]) {
+            match self.transition(None) {
+                Ok(Some(stmt_t)) => stmt_t,
+                Ok(None) => return Err(ParseError::new("TODO")),
+                Err(parse_error) => return Err(parse_error),
+            }
+        } else if self.match_token(&[TokenType::ChangeState]) {
+            match self.change_state() {
+                Ok(Some(stmt_t)) => stmt_t,
+                Ok(None) => return Err(ParseError::new("TODO")),
+                Err(parse_error) => return Err(parse_error),
+            }
+        } else {
+            self.error_at_current("Expected '->' or '->>' after guard.");
+            return Err(ParseError::new("TODO"));
+        };
+
+        match &mut stmt_t {
+            StatementType::TransitionStmt {
+                transition_statement,
+            } => transition_statement.guard_opt = Some(guard),
+            StatementType::ChangeStateStmt { change_state_stmt } => {
+                change_state_stmt.guard_opt = Some(guard)
+            }
+            _ => {}
+        }
+
+        // A guard that passes ends the handler, as an unguarded transition would.
+        let statements = vec![DeclOrStmtType::StmtT { stmt_t }];
+        let terminator_node =
+            TerminatorExpr::new(TerminatorType::Return, None, self.previous().line);
+        let branch_node =
+            BoolTestConditionalBranchNode::new(false, expr_t, statements, Some(terminator_node));
+        let bool_test_node = BoolTestNode::new(vec![branch_node], None);
+        let bool_test_t = TestType::BoolTest { bool_test_node };
+        let test_stmt_node = TestStatementNode::new(bool_test_t);
+        Ok(Some(StatementType::TestStmt { test_stmt_node }))
+    }
+
+    /* --------------------------------------------------------------------- */
+
+    // Rebuild the source text of a guard from its tokens.
+
+    fn guard_text(&self, start: usize, end: usize) -> String {
+        let mut text = String::new();
+        let mut prev_opt: Option<&TokenType> = None;
+
+        for token in &self.tokens[start..end] {
+            let token_type = &token.token_type;
+            match token_type {
+                TokenType::SingleLineComment | TokenType::MultiLineComment => continue,
+                _ => {}
+            }
+
+            let glue_to_prev = match prev_opt {
+                None => true,
+                Some(prev) => {
+                    matches!(
+                        prev,
+                        TokenType::LParen
+                            | TokenType::LBracket
+                            | TokenType::PipePipeLBracket
+                            | TokenType::PipePipeDot
+                            | TokenType::Dot
+                            | TokenType::Bang
+                            | TokenType::State
+                            | TokenType::System
+                            | TokenType::At
+                    ) || matches!(
+                        token_type,
+                        TokenType::RParen
+                            | TokenType::RBracket
+                            | TokenType::Comma
+                            | TokenType::Dot
+                            | TokenType::LBracket
+                    ) || (*token_type == TokenType::LParen && *prev == TokenType::Identifier)
+                }
+            };
+            if !glue_to_prev {
+                text.push(' ');
+            }
+
+            match token_type {
+                TokenType::String => text.push_str(&format!("\"{}\"", token.lexeme)),
+                TokenType::SuperString => text.push_str(&format!("`{}`", token.lexeme)),
+                _ => text.push_str(&token.lexeme),
+            }
+            prev_opt = Some(token_type);
+        }
+
+        text
+    }
+
+    /* --------------------------------------------------------------------- */
+
     // match_number_test -> '?#'  ('/' match_number_pattern  ('|' match_number_pattern)* '/' (statement* branch_terminator?) ':>')+ ':' (statement* branch_terminator?) '::'
 
     fn number_match_test(&mut self, expr_t: ExprType) -> Result<NumberMatchTestNode, ParseError> {
@@ -3596,3 +4417,41 @@ impl<'a> Parser<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::frame_c::compiler::Exe;
+
+    fn compile(spec: &str) -> Result<String, String> {
+        Exe::new()
+            .run(&None, spec.to_string(), String::from("rust"))
+            .map_err(|err| err.error)
+    }
+
+    const HANDLER_BRACKETS: &str = "
+#Brackets
+    -interface-
+    go
+    inc [x:i32]
+    -machine-
+    $A
+        |go| [BRACKET] ->> $B ^
+        |inc| [x:i32] -> $B ^
+    $B
+##";
+
+    #[test]
+    fn lone_name_before_transition_is_ambiguous() {
+        let err = compile(&HANDLER_BRACKETS.replace("BRACKET", "armed")).unwrap_err();
+        assert!(err.contains("Ambiguous '[armed]'"), "{}", err);
+    }
+
+    #[test]
+    fn handler_brackets_are_told_apart_by_their_tokens() {
+        let guard = compile(&HANDLER_BRACKETS.replace("BRACKET", "armed == true")).unwrap();
+        assert!(guard.contains("armed == true"));
+        let param = compile(&HANDLER_BRACKETS.replace("BRACKET", "armed:bool")).unwrap();
+        assert!(param.contains("armed: bool"));
+        assert!(!param.contains("if armed"));
+    }
+}
@@ -7,6 +7,8 @@ enum MatchType {
     Bool,
     String,
     Number,
+    Regex,
+    Enum,
     //    None,
 }
 
@@ -38,6 +40,11 @@ impl Scanner {
             ("false".to_string(), TokenType::False),
             ("var".to_string(), TokenType::Var),
             ("const".to_string(), TokenType::Const),
+            ("loop".to_string(), TokenType::Loop),
+            ("while".to_string(), TokenType::While),
+            ("break".to_string(), TokenType::Break),
+            ("continue".to_string(), TokenType::Continue),
+            ("enum".to_string(), TokenType::Enum),
             ("-interface-".to_string(), TokenType::InterfaceBlock),
             ("-machine-".to_string(), TokenType::MachineBlock),
             ("-actions-".to_string(), TokenType::ActionsBlock),
@@ -260,6 +267,14 @@ impl Scanner {
                     self.add_token(TokenType::NumberTest);
                     // Store the context for the parse
                     self.test_t_stack.push(MatchType::Number);
+                } else if self.match_char('*') {
+                    self.add_token(TokenType::RegexTest);
+                    // Store the context for the parse
+                    self.test_t_stack.push(MatchType::Regex);
+                } else if self.match_char(':') {
+                    self.add_token(TokenType::EnumTest);
+                    // Store the context for the parse
+                    self.test_t_stack.push(MatchType::Enum);
                 } else {
                     self.add_token(TokenType::BoolTestTrue);
                     // Store the context for the parse
@@ -323,6 +338,7 @@ impl Scanner {
                 }
             }
             ';' => self.add_token(TokenType::Semicolon),
+            ',' => self.add_token(TokenType::Comma),
             '"' => self.string(),
             '`' => self.super_string(),
             '#' => {
@@ -490,6 +506,9 @@ impl Scanner {
         match self.test_t_stack.last() {
             Some(MatchType::String) => self.scan_string_match(),
             Some(MatchType::Number) => self.scan_number_match(),
+            Some(MatchType::Regex) => self.scan_regex_match(),
+            // enumerator patterns are scanned like string patterns
+            Some(MatchType::Enum) => self.scan_string_match(),
             Some(_) => {}
             None => {}
         }
@@ -513,6 +532,26 @@ impl Scanner {
         self.add_token_sync_start(TokenType::ForwardSlash);
     }
 
+    // Scan a regular expression up to the closing '/'. Unlike string
+    // matches, '|' is part of the pattern. A '/' in the pattern is
+    // written as '\/'.
+    // match_regex_test -> '/' regex_pattern '/'
+
+    fn scan_regex_match(&mut self) {
+        while !self.is_at_end() && self.peek() != '/' {
+            if self.peek() == '\\' && self.peek_next() == '/' {
+                self.advance();
+            }
+            self.advance();
+        }
+        self.add_token_sync_start(TokenType::MatchRegex);
+        if !self.match_char('/') {
+            self.error(self.line, "Unterminated regular expression.");
+            return;
+        }
+        self.add_token_sync_start(TokenType::ForwardSlash);
+    }
+
     // match_number_test -> '/' match_number_pattern ('|' match_number_pattern)* '/'
 
     fn scan_number_match(&mut self) {
@@ -709,6 +748,11 @@ pub enum TokenType {
     Number,                  // 1, 1.01
     Var,                     // let
     Const,                   // const
+    Loop,                    // loop
+    While,                   // while
+    Break,                   // break
+    Continue,                // continue
+    Enum,                    // enum
     SingleLineComment,       // --- comment
     MultiLineComment,        // {-- comments --}
     OpenBrace,               // {
@@ -719,18 +763,22 @@ pub enum TokenType {
     Nil,                     // nil
     Colon,                   // :
     Semicolon,               // ;
+    Comma,                   // ,
     Dispatch,                // =>
     Equals,                  // =
     BoolTestTrue,            // ?
     BoolTestFalse,           // ?!
     StringTest,              // ?~
     NumberTest,              // ?#
+    RegexTest,               // ?*
+    EnumTest,                // ?:
     ElseContinue,            // :>
     TestTerminator,          // ::
     ForwardSlash,            // /
     MatchString,             // /<string>/ - contains <string>
     MatchNullString,         // //!
     MatchEmptyString,        // //
+    MatchRegex,              // /<regex>/
     StateStackOperationPush, // $$[+]
     StateStackOperationPop,  // $$[-]
     Dot,                     // .
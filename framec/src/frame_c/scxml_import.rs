@@ -0,0 +1,398 @@
+//! Conversion of W3C SCXML state charts into Frame specification skeletons.
+//!
+//! Only the structural subset of SCXML is translated: states and their
+//! nesting, events, transitions with their conditions, `<onentry>` and
+//! `<onexit>`, and `<datamodel>` entries. Executable content has no Frame
+//! equivalent, so `<send>` and `<raise>` become calls to generated actions
+//! and everything else is kept as a comment for the author to rewrite.
+
+use crate::frame_c::utils::{frame_exitcode, RunError};
+use roxmltree::{Document, Node};
+
+struct ImportedTransition {
+    cond_opt: Option<String>,
+    target_opt: Option<String>,
+    statements: Vec<String>,
+}
+
+struct ImportedHandler {
+    message: String,
+    transitions: Vec<ImportedTransition>,
+}
+
+struct ImportedState {
+    name: String,
+    parent_opt: Option<String>,
+    comments: Vec<String>,
+    vars: Vec<String>,
+    enter_statements: Vec<String>,
+    exit_statements: Vec<String>,
+    eventless_transitions: Vec<ImportedTransition>,
+    handlers: Vec<ImportedHandler>,
+}
+
+#[derive(Default)]
+struct Importer {
+    states: Vec<ImportedState>,
+    events: Vec<String>,
+    actions: Vec<String>,
+    domain_vars: Vec<String>,
+}
+
+/// Translate an SCXML document into the source of a Frame system.
+pub fn import_scxml(content: &str) -> Result<String, RunError> {
+    let document = match Document::parse(content) {
+        Ok(document) => document,
+        Err(err) => return Err(import_error(&format!("Invalid SCXML document: {}", err))),
+    };
+    let root = document.root_element();
+    if root.tag_name().name() != "scxml" {
+        return Err(import_error(&format!(
+            "Expected an <scxml> root element but found <{}>.",
+            root.tag_name().name()
+        )));
+    }
+
+    let mut importer = Importer::default();
+    for child in root.children().filter(Node::is_element) {
+        match child.tag_name().name() {
+            "state" | "parallel" | "final" => importer.import_state(child, None)?,
+            "datamodel" => importer.domain_vars = import_datamodel(child),
+            _ => {}
+        }
+    }
+    if importer.states.is_empty() {
+        return Err(import_error("The SCXML document has no states."));
+    }
+
+    // Frame starts in the first state of the machine block.
+    let initial_opt = root
+        .attribute("initial")
+        .and_then(|initial| initial.split_whitespace().next())
+        .map(to_identifier);
+    if let Some(initial) = initial_opt {
+        match importer
+            .states
+            .iter()
+            .position(|state| state.name == initial)
+        {
+            Some(index) => {
+                let initial_state = importer.states.remove(index);
+                importer.states.insert(0, initial_state);
+            }
+            None => {
+                return Err(import_error(&format!(
+                    "Initial state '{}' is not defined.",
+                    initial
+                )))
+            }
+        }
+    }
+
+    let system_name = to_identifier(root.attribute("name").unwrap_or("Imported"));
+    Ok(importer.generate(&system_name))
+}
+
+fn import_error(msg: &str) -> RunError {
+    RunError::new(frame_exitcode::IMPORT_ERR, msg)
+}
+
+/// Replace characters that are not allowed in Frame identifiers, e.g. the
+/// dots in `error.execution`, with underscores.
+fn to_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+// Number, boolean and string literals are valid Frame as they are; other
+// expressions are passed through to the target language as superstrings.
+fn to_frame_expr(expr: &str) -> String {
+    let expr = expr.trim();
+    let is_string = expr.len() >= 2 && expr.starts_with('"') && expr.ends_with('"');
+    if is_string || expr == "true" || expr == "false" || expr.parse::<f64>().is_ok() {
+        expr.to_string()
+    } else {
+        format!("`{}`", expr.replace('`', "'"))
+    }
+}
+
+fn import_datamodel(datamodel: Node) -> Vec<String> {
+    datamodel
+        .children()
+        .filter(|child| child.has_tag_name("data"))
+        .filter_map(|data| {
+            let id = data.attribute("id")?;
+            let value = match data.attribute("expr") {
+                Some(expr) => to_frame_expr(expr),
+                None => String::from("nil"),
+            };
+            Some(format!("var {} = {}", to_identifier(id), value))
+        })
+        .collect()
+}
+
+fn comment(text: &str) -> String {
+    let text: Vec<&str> = text.split_whitespace().collect();
+    format!("--- {}", text.join(" "))
+}
+
+impl Importer {
+    fn import_state(&mut self, node: Node, parent_opt: Option<&str>) -> Result<(), RunError> {
+        let name = match node.attribute("id") {
+            Some(id) => to_identifier(id),
+            None => format!("State{}", self.states.len() + 1),
+        };
+        if self.states.iter().any(|state| state.name == name) {
+            return Err(import_error(&format!("Duplicate state id '{}'.", name)));
+        }
+
+        let mut state = ImportedState {
+            name: name.clone(),
+            parent_opt: parent_opt.map(String::from),
+            comments: Vec::new(),
+            vars: Vec::new(),
+            enter_statements: Vec::new(),
+            exit_statements: Vec::new(),
+            eventless_transitions: Vec::new(),
+            handlers: Vec::new(),
+        };
+        match node.tag_name().name() {
+            "parallel" => state.comments.push(comment(
+                "SCXML <parallel>: its regions are imported as ordinary child states",
+            )),
+            "final" => state.comments.push(comment("SCXML <final> state")),
+            _ => {}
+        }
+
+        let mut children = Vec::new();
+        for child in node.children().filter(Node::is_element) {
+            match child.tag_name().name() {
+                "state" | "parallel" | "final" => children.push(child),
+                "datamodel" => state.vars.extend(import_datamodel(child)),
+                "onentry" => {
+                    let statements = self.import_executable_content(child);
+                    state.enter_statements.extend(statements);
+                }
+                "onexit" => {
+                    let statements = self.import_executable_content(child);
+                    state.exit_statements.extend(statements);
+                }
+                "transition" => self.import_transition(child, &mut state),
+                tag => state
+                    .comments
+                    .push(comment(&format!("SCXML <{}> is not supported", tag))),
+            }
+        }
+        self.states.push(state);
+
+        for child in children {
+            self.import_state(child, Some(&name))?;
+        }
+        Ok(())
+    }
+
+    fn import_transition(&mut self, node: Node, state: &mut ImportedState) {
+        let statements = self.import_executable_content(node);
+        let transition = |statements: Vec<String>| ImportedTransition {
+            cond_opt: node.attribute("cond").map(to_frame_expr),
+            // only the first of several targets can be followed
+            target_opt: node
+                .attribute("target")
+                .and_then(|target| target.split_whitespace().next())
+                .map(to_identifier),
+            statements,
+        };
+
+        let events: Vec<&str> = node
+            .attribute("event")
+            .map(|event| event.split_whitespace().collect())
+            .unwrap_or_default();
+        if events.is_empty() {
+            state.eventless_transitions.push(transition(statements));
+            return;
+        }
+
+        for event in events {
+            let message = if event == "*" {
+                String::from("*")
+            } else {
+                let event = to_identifier(event.trim_end_matches(".*"));
+                if !self.events.contains(&event) {
+                    self.events.push(event.clone());
+                }
+                event
+            };
+            let transition = transition(statements.clone());
+            match state
+                .handlers
+                .iter_mut()
+                .find(|handler| handler.message == message)
+            {
+                Some(handler) => handler.transitions.push(transition),
+                None => state.handlers.push(ImportedHandler {
+                    message,
+                    transitions: vec![transition],
+                }),
+            }
+        }
+    }
+
+    fn import_executable_content(&mut self, node: Node) -> Vec<String> {
+        let mut statements = Vec::new();
+        for child in node.children().filter(Node::is_element) {
+            let tag = child.tag_name().name();
+            match (tag, child.attribute("event")) {
+                ("send", Some(event)) | ("raise", Some(event)) => {
+                    let action = format!("{}_{}", tag, to_identifier(event));
+                    statements.push(format!("{}()", action));
+                    if !self.actions.contains(&action) {
+                        self.actions.push(action);
+                    }
+                }
+                _ => {
+                    let mut text = format!("<{}>", tag);
+                    for attribute in child.attributes() {
+                        text.push_str(&format!(" {}=\"{}\"", attribute.name(), attribute.value()));
+                    }
+                    if let Some(body) = child.text() {
+                        text.push(' ');
+                        text.push_str(body);
+                    }
+                    statements.push(comment(&text));
+                }
+            }
+        }
+        statements
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate(&self, system_name: &str) -> String {
+        let mut code = format!("#{}\n", system_name);
+
+        code.push_str("    -interface-\n");
+        for event in &self.events {
+            code.push_str(&format!("    {}\n", event));
+        }
+
+        code.push_str("\n    -machine-\n");
+        for (index, state) in self.states.iter().enumerate() {
+            if index > 0 {
+                code.push('\n');
+            }
+            generate_state(state, &mut code);
+        }
+
+        code.push_str("\n    -actions-\n");
+        for action in &self.actions {
+            code.push_str(&format!("    {}\n", action));
+        }
+
+        code.push_str("\n    -domain-\n");
+        for var in &self.domain_vars {
+            code.push_str(&format!("    {}\n", var));
+        }
+        code.push_str("##\n");
+        code
+    }
+}
+
+fn generate_state(state: &ImportedState, code: &mut String) {
+    match &state.parent_opt {
+        Some(parent) => code.push_str(&format!("    ${} => ${}\n", state.name, parent)),
+        None => code.push_str(&format!("    ${}\n", state.name)),
+    }
+    for line in state.comments.iter().chain(state.vars.iter()) {
+        code.push_str(&format!("        {}\n", line));
+    }
+
+    // Eventless transitions are checked when the state is entered.
+    if !state.enter_statements.is_empty() || !state.eventless_transitions.is_empty() {
+        generate_handler(
+            "|>|",
+            &state.enter_statements,
+            &state.eventless_transitions,
+            code,
+        );
+    }
+    if !state.exit_statements.is_empty() {
+        generate_handler("|<|", &state.exit_statements, &[], code);
+    }
+    // A catch-all handler has to come after the handlers it would shadow.
+    let (any_handlers, handlers): (Vec<&ImportedHandler>, Vec<&ImportedHandler>) = state
+        .handlers
+        .iter()
+        .partition(|handler| handler.message == "*");
+    for handler in handlers.iter().chain(any_handlers.iter()) {
+        let selector = if handler.message == "*" {
+            String::from("||*")
+        } else {
+            format!("|{}|", handler.message)
+        };
+        generate_handler(&selector, &[], &handler.transitions, code);
+    }
+}
+
+fn transition_body(transition: &ImportedTransition) -> Vec<String> {
+    let mut body = transition.statements.clone();
+    if let Some(target) = &transition.target_opt {
+        body.push(format!("-> ${}", target));
+    }
+    body
+}
+
+fn generate_handler(
+    selector: &str,
+    statements: &[String],
+    transitions: &[ImportedTransition],
+    code: &mut String,
+) {
+    if statements.is_empty() && transitions.is_empty() {
+        code.push_str(&format!("        {} ^\n", selector));
+        return;
+    }
+    code.push_str(&format!("        {}\n", selector));
+    for statement in statements {
+        code.push_str(&format!("            {}\n", statement));
+    }
+    // SCXML takes the first enabled transition in document order, so the
+    // transitions form an else-if chain that ends with the first one
+    // without a condition.
+    let (conditional, default_opt) = match transitions.iter().position(|t| t.cond_opt.is_none()) {
+        Some(index) => (&transitions[..index], Some(&transitions[index])),
+        None => (transitions, None),
+    };
+    if conditional.is_empty() {
+        if let Some(transition) = default_opt {
+            for line in transition_body(transition) {
+                code.push_str(&format!("            {}\n", line));
+            }
+        }
+    } else {
+        for (index, transition) in conditional.iter().enumerate() {
+            let cond = transition.cond_opt.as_ref().unwrap();
+            if index == 0 {
+                code.push_str(&format!("            {} ?\n", cond));
+            } else {
+                code.push_str(&format!("            :> {} ?\n", cond));
+            }
+            for line in transition_body(transition) {
+                code.push_str(&format!("                {}\n", line));
+            }
+        }
+        if let Some(transition) = default_opt {
+            code.push_str("            :\n");
+            for line in transition_body(transition) {
+                code.push_str(&format!("                {}\n", line));
+            }
+        }
+        code.push_str("            ::\n");
+    }
+    code.push_str("            ^\n");
+}
@@ -106,6 +106,9 @@ pub enum SymbolType {
     DomainBlockScope {
         domain_block_symbol_rcref: Rc<RefCell<DomainBlockScopeSymbol>>,
     },
+    EnumDecl {
+        enum_decl_symbol_rcref: Rc<RefCell<EnumDeclSymbol>>,
+    },
     State {
         state_symbol_ref: Rc<RefCell<StateSymbol>>,
     },
@@ -162,6 +165,9 @@ impl Symbol for SymbolType {
             SymbolType::DomainBlockScope {
                 domain_block_symbol_rcref,
             } => domain_block_symbol_rcref.borrow().get_name(),
+            SymbolType::EnumDecl {
+                enum_decl_symbol_rcref,
+            } => enum_decl_symbol_rcref.borrow().get_name(),
             SymbolType::State { state_symbol_ref } => state_symbol_ref.borrow().get_name(),
             SymbolType::StateParamsScope {
                 state_params_scope_rcref,
@@ -509,6 +515,15 @@ impl SymbolTable {
                 }));
                 self.symbols.insert(name, symbol_type_rcref);
             }
+            SymbolType::EnumDecl {
+                enum_decl_symbol_rcref,
+            } => {
+                let name = enum_decl_symbol_rcref.borrow().name.clone();
+                let symbol_type_rcref = Rc::new(RefCell::new(SymbolType::EnumDecl {
+                    enum_decl_symbol_rcref: Rc::clone(enum_decl_symbol_rcref),
+                }));
+                self.symbols.insert(name, symbol_type_rcref);
+            }
             _ => panic!("Fatal error - missing symbol type"),
         }
     }
@@ -700,6 +715,24 @@ impl Arcanum {
 
     /* --------------------------------------------------------------------- */
 
+    // Enums are only declared in the -domain- block, which the system
+    // symtab searches on lookup.
+    pub fn lookup_enum(&self, name: &str) -> Option<Rc<RefCell<EnumDeclSymbol>>> {
+        let system_symbol_rcref = self.system_symbol_opt.as_ref()?;
+        let system_symbol = system_symbol_rcref.borrow();
+        let symtab = system_symbol.symtab_rcref.borrow();
+        let symbol_t_rcref = symtab.lookup(name, &IdentifierDeclScope::DomainBlock)?;
+        let symbol_t = symbol_t_rcref.borrow();
+        match &*symbol_t {
+            SymbolType::EnumDecl {
+                enum_decl_symbol_rcref,
+            } => Some(Rc::clone(enum_decl_symbol_rcref)),
+            _ => None,
+        }
+    }
+
+    /* --------------------------------------------------------------------- */
+
     pub fn enter_scope(&mut self, scope_t: ParseScopeType) {
         // do scope specific actions
         match &scope_t {
@@ -2199,6 +2232,33 @@ impl Symbol for ActionDeclSymbol {
 
 // ----------------------- //
 
+pub struct EnumDeclSymbol {
+    pub name: String,
+    pub ast_node: Rc<RefCell<EnumDeclNode>>,
+}
+
+impl EnumDeclSymbol {
+    pub fn new(name: String, ast_node: Rc<RefCell<EnumDeclNode>>) -> EnumDeclSymbol {
+        EnumDeclSymbol { name, ast_node }
+    }
+
+    pub fn has_enumerator(&self, enumerator: &str) -> bool {
+        self.ast_node
+            .borrow()
+            .enumerators
+            .iter()
+            .any(|e| e == enumerator)
+    }
+}
+
+impl Symbol for EnumDeclSymbol {
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+// ----------------------- //
+
 pub struct ActionCallSymbol {
     pub name: String,
     pub ast_node: Option<Rc<RefCell<ActionCallExprNode>>>,
@@ -0,0 +1,333 @@
+//! Registry of the target languages `framec` can generate.
+//!
+//! Every target has one canonical name, which is what `framec --list-targets`
+//! prints, plus any number of aliases accepted on the command line and in the
+//! `#[language="..."]` system attribute.
+
+use std::fmt;
+
+/// A target language supported by `framec`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetLanguage {
+    C99,
+    Cpp,
+    CSharp,
+    CSharpForBob,
+    GdScript,
+    Go,
+    Graphviz,
+    Java8,
+    JavaScript,
+    Kotlin,
+    Mermaid,
+    PlantUml,
+    Python3,
+    Rust,
+    Scxml,
+    Smcat,
+    Swift,
+    TypeScript,
+    XState,
+}
+
+/// Registry entry describing a target language.
+pub struct TargetInfo {
+    pub target: TargetLanguage,
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+}
+
+/// All supported targets, in the order they are listed by `--list-targets`.
+pub static TARGETS: &[TargetInfo] = &[
+    TargetInfo {
+        target: TargetLanguage::C99,
+        name: "c99",
+        aliases: &["c"],
+        description: "C99 without heap allocation",
+    },
+    TargetInfo {
+        target: TargetLanguage::Cpp,
+        name: "cpp",
+        aliases: &["c++"],
+        description: "C++",
+    },
+    TargetInfo {
+        target: TargetLanguage::CSharp,
+        name: "c_sharp",
+        aliases: &["cs", "csharp"],
+        description: "C#",
+    },
+    TargetInfo {
+        target: TargetLanguage::CSharpForBob,
+        name: "c_sharp_bob",
+        aliases: &[],
+        description: "C# with a FrameController base class",
+    },
+    TargetInfo {
+        target: TargetLanguage::GdScript,
+        name: "gdscript",
+        aliases: &["gd"],
+        description: "GDScript 3.2",
+    },
+    TargetInfo {
+        target: TargetLanguage::Go,
+        name: "go",
+        aliases: &["golang"],
+        description: "Go",
+    },
+    TargetInfo {
+        target: TargetLanguage::Graphviz,
+        name: "graphviz",
+        aliases: &["dot", "gv"],
+        description: "Graphviz DOT diagram",
+    },
+    TargetInfo {
+        target: TargetLanguage::Java8,
+        name: "java_8",
+        aliases: &["java"],
+        description: "Java 8",
+    },
+    TargetInfo {
+        target: TargetLanguage::JavaScript,
+        name: "javascript",
+        aliases: &["js"],
+        description: "JavaScript",
+    },
+    TargetInfo {
+        target: TargetLanguage::Kotlin,
+        name: "kotlin",
+        aliases: &["kt"],
+        description: "Kotlin",
+    },
+    TargetInfo {
+        target: TargetLanguage::Mermaid,
+        name: "mermaid",
+        aliases: &["mmd"],
+        description: "Mermaid state diagram",
+    },
+    TargetInfo {
+        target: TargetLanguage::PlantUml,
+        name: "plantuml",
+        aliases: &["puml"],
+        description: "PlantUML state diagram",
+    },
+    TargetInfo {
+        target: TargetLanguage::Python3,
+        name: "python_3",
+        aliases: &["py", "python"],
+        description: "Python 3",
+    },
+    TargetInfo {
+        target: TargetLanguage::Rust,
+        name: "rust",
+        aliases: &["rs"],
+        description: "Rust",
+    },
+    TargetInfo {
+        target: TargetLanguage::Scxml,
+        name: "scxml",
+        aliases: &[],
+        description: "W3C SCXML state chart",
+    },
+    TargetInfo {
+        target: TargetLanguage::Smcat,
+        name: "smcat",
+        aliases: &[],
+        description: "State Machine Cat diagram",
+    },
+    TargetInfo {
+        target: TargetLanguage::Swift,
+        name: "swift",
+        aliases: &[],
+        description: "Swift",
+    },
+    TargetInfo {
+        target: TargetLanguage::TypeScript,
+        name: "typescript",
+        aliases: &["ts"],
+        description: "TypeScript",
+    },
+    TargetInfo {
+        target: TargetLanguage::XState,
+        name: "xstate",
+        aliases: &["xtate"],
+        description: "XState machine config",
+    },
+];
+
+impl TargetLanguage {
+    /// Look up a target by its canonical name or one of its aliases. Names
+    /// are matched case-insensitively.
+    pub fn from_name(name: &str) -> Option<TargetLanguage> {
+        let name = name.trim().to_lowercase();
+        TARGETS
+            .iter()
+            .find(|info| info.name == name || info.aliases.contains(&name.as_str()))
+            .map(|info| info.target)
+    }
+
+    pub fn info(&self) -> &'static TargetInfo {
+        TARGETS
+            .iter()
+            .find(|info| info.target == *self)
+            .expect("every target language is registered")
+    }
+
+    /// The canonical name of this target.
+    pub fn name(&self) -> &'static str {
+        self.info().name
+    }
+}
+
+impl fmt::Display for TargetLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Find the canonical name of the target whose name or alias is closest to
+/// `name`, if any is close enough to be a plausible typo.
+pub fn suggest(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_lowercase();
+    let name = name.as_str();
+    let max_distance = std::cmp::max(2, name.chars().count() / 3);
+    TARGETS
+        .iter()
+        .flat_map(|info| {
+            std::iter::once(info.name)
+                .chain(info.aliases.iter().copied())
+                .map(move |candidate| (edit_distance(name, candidate), info.name))
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, target_name)| target_name)
+}
+
+/// Format the registry as the table printed by `framec --list-targets`.
+pub fn list_targets() -> String {
+    let mut output = String::new();
+    for info in TARGETS {
+        let aliases = if info.aliases.is_empty() {
+            String::new()
+        } else {
+            format!(" (aliases: {})", info.aliases.join(", "))
+        };
+        output.push_str(&format!(
+            "{:<14}{}{}\n",
+            info.name, info.description, aliases
+        ));
+    }
+    output
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_name_and_alias_resolves() {
+        for info in TARGETS {
+            assert_eq!(TargetLanguage::from_name(info.name), Some(info.target));
+            for alias in info.aliases {
+                assert_eq!(TargetLanguage::from_name(alias), Some(info.target));
+            }
+        }
+    }
+
+    #[test]
+    fn aliases() {
+        assert_eq!(TargetLanguage::from_name("c"), Some(TargetLanguage::C99));
+        assert_eq!(TargetLanguage::from_name("c++"), Some(TargetLanguage::Cpp));
+        assert_eq!(
+            TargetLanguage::from_name("golang"),
+            Some(TargetLanguage::Go)
+        );
+        assert_eq!(
+            TargetLanguage::from_name("dot"),
+            Some(TargetLanguage::Graphviz)
+        );
+        assert_eq!(
+            TargetLanguage::from_name("py"),
+            Some(TargetLanguage::Python3)
+        );
+        assert_eq!(
+            TargetLanguage::from_name("xtate"),
+            Some(TargetLanguage::XState)
+        );
+    }
+
+    #[test]
+    fn names_are_case_insensitive() {
+        assert_eq!(
+            TargetLanguage::from_name("Rust"),
+            Some(TargetLanguage::Rust)
+        );
+        assert_eq!(
+            TargetLanguage::from_name("TS"),
+            Some(TargetLanguage::TypeScript)
+        );
+        assert_eq!(
+            TargetLanguage::from_name(" PlantUML "),
+            Some(TargetLanguage::PlantUml)
+        );
+        assert_eq!(suggest("RUSTT"), Some("rust"));
+    }
+
+    #[test]
+    fn names_and_aliases_are_unique() {
+        let mut names: Vec<&str> = TARGETS
+            .iter()
+            .flat_map(|info| std::iter::once(info.name).chain(info.aliases.iter().copied()))
+            .collect();
+        let count = names.len();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn unknown_name() {
+        assert_eq!(TargetLanguage::from_name("cobol"), None);
+        assert_eq!(TargetLanguage::from_name(""), None);
+    }
+
+    #[test]
+    fn near_miss_suggestion() {
+        assert_eq!(suggest("rsut"), Some("rust"));
+        assert_eq!(suggest("pyhton"), Some("python_3"));
+        assert_eq!(suggest("typscript"), Some("typescript"));
+        assert_eq!(suggest("javascrpt"), Some("javascript"));
+    }
+
+    #[test]
+    fn no_suggestion_for_distant_name() {
+        assert_eq!(suggest("cobol"), None);
+        assert_eq!(suggest("fortran77"), None);
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("rust", "rust"), 0);
+        assert_eq!(edit_distance("rust", "rsut"), 2);
+        assert_eq!(edit_distance("kotlin", "kotln"), 1);
+        assert_eq!(edit_distance("", "go"), 2);
+    }
+}
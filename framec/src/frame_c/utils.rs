@@ -136,11 +136,13 @@ pub(crate) mod frame_exitcode {
     /// Framepiler parse error exit
     pub const PARSE_ERR: FrameExitCode = 1;
     pub const CONFIG_ERR: FrameExitCode = 2;
+    pub const IMPORT_ERR: FrameExitCode = 3;
 
     pub fn as_string(code: FrameExitCode) -> String {
         match code {
             PARSE_ERR => "Frame parse error".to_string(),
             CONFIG_ERR => "Configuration error".to_string(),
+            IMPORT_ERR => "SCXML import error".to_string(),
             _ => format!("Unknown error code {}", code),
         }
     }
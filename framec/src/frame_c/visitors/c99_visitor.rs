@@ -0,0 +1,1804 @@
+use crate::frame_c::ast::*;
+use crate::frame_c::config::{C99Config, FrameConfig};
+use crate::frame_c::scanner::{Token, TokenType};
+use crate::frame_c::symbol_table::*;
+use crate::frame_c::visitors::*;
+use std::collections::HashMap;
+
+/// One `if`/`else if` arm of a generated test.
+struct C99Branch<'a> {
+    condition: String,
+    statements: &'a [DeclOrStmtType],
+    terminator_opt: &'a Option<TerminatorExpr>,
+}
+
+/// Generates C99 from a Frame system for targets without a C++ runtime or
+/// a heap. The system is a struct the caller owns and initializes, the
+/// `-actions-` block becomes a struct of function pointers, and each state
+/// is a function switching on the event message. Event parameters are
+/// passed as pointers to per-message structs, state arguments and state
+/// variables live in a union of per-state structs, and the state stack is
+/// a fixed-size array.
+pub struct C99Visitor {
+    compiler_version: String,
+    config: C99Config,
+    arcanium: Arcanum,
+    symbol_config: SymbolConfig,
+    generate_state_stack: bool,
+    generate_transition_state: bool,
+    comments: Vec<Token>,
+    current_comment_idx: usize,
+    code: String,
+    dent: usize,
+    errors: Vec<String>,
+    system_name: String,
+    first_state_name: String,
+    has_states: bool,
+    current_state_name_opt: Option<String>,
+    // Messages sent by interface methods, with their parameter struct and
+    // return type when they have them.
+    interface_params: HashMap<String, String>,
+    interface_return_types: HashMap<String, String>,
+    // States whose arguments and variables, and whose enter arguments, are
+    // kept in the state context.
+    states_with_data: Vec<String>,
+    states_with_enter_args: Vec<String>,
+    current_return_type_opt: Option<String>,
+    uses_string_h: bool,
+}
+
+impl C99Visitor {
+    pub fn new(
+        arcanium: Arcanum,
+        config: FrameConfig,
+        generate_state_stack: bool,
+        generate_transition_state: bool,
+        compiler_version: &str,
+        comments: Vec<Token>,
+    ) -> C99Visitor {
+        C99Visitor {
+            compiler_version: compiler_version.to_string(),
+            config: config.codegen.c99,
+            arcanium,
+            symbol_config: SymbolConfig::new(),
+            generate_state_stack,
+            generate_transition_state,
+            comments,
+            current_comment_idx: 0,
+            code: String::new(),
+            dent: 0,
+            errors: Vec::new(),
+            system_name: String::new(),
+            first_state_name: String::new(),
+            has_states: false,
+            current_state_name_opt: None,
+            interface_params: HashMap::new(),
+            interface_return_types: HashMap::new(),
+            states_with_data: Vec::new(),
+            states_with_enter_args: Vec::new(),
+            current_return_type_opt: None,
+            uses_string_h: false,
+        }
+    }
+
+    pub fn run(&mut self, system_node: &SystemNode) {
+        system_node.accept(self);
+    }
+
+    pub fn get_code(&self) -> String {
+        if !self.errors.is_empty() {
+            let mut error_list = String::new();
+            for error in &self.errors {
+                error_list.push_str(error);
+                error_list.push('\n');
+            }
+            error_list
+        } else {
+            // Blank lines are left indented by `newline()`.
+            self.code
+                .lines()
+                .map(|line| line.trim_end())
+                .collect::<Vec<&str>>()
+                .join("\n")
+                + "\n"
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn add_code(&mut self, s: &str) {
+        self.code.push_str(s);
+    }
+
+    fn newline(&mut self) {
+        self.code.push('\n');
+        self.code.push_str(&"    ".repeat(self.dent));
+    }
+
+    fn indent(&mut self) {
+        self.dent += 1;
+    }
+
+    fn outdent(&mut self) {
+        self.dent -= 1;
+    }
+
+    fn generate_comment(&mut self, line: usize) {
+        while self.current_comment_idx < self.comments.len()
+            && line >= self.comments[self.current_comment_idx].line
+        {
+            let comment = self.comments[self.current_comment_idx].lexeme.clone();
+            if self.comments[self.current_comment_idx].token_type == TokenType::SingleLineComment {
+                self.newline();
+                self.add_code(&format!("// {}", comment[3..].trim()));
+            } else {
+                self.newline();
+                self.add_code(&format!("/* {} */", comment[3..comment.len() - 3].trim()));
+            }
+            self.current_comment_idx += 1;
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Frame strings are C strings and references are pointers. Other types
+    // are passed through, and untyped values are `int`.
+    fn format_type(type_opt: &Option<TypeNode>) -> String {
+        match type_opt {
+            Some(type_node) => {
+                let type_str = type_node.get_type_str();
+                match type_str.as_str() {
+                    "String" | "string" | "str" | "&str" | "&String" => String::from("const char*"),
+                    _ => match type_str.strip_prefix('&') {
+                        Some(referenced) => format!("{}*", referenced),
+                        None => type_str,
+                    },
+                }
+            }
+            None => String::from("int"),
+        }
+    }
+
+    // Untyped declarations take the type of a literal initializer.
+    fn infer_type(expr_t_opt: &Option<ExprType>) -> Option<String> {
+        match expr_t_opt {
+            Some(ExprType::LiteralExprT { literal_expr_node }) => match literal_expr_node.token_t {
+                TokenType::Number if literal_expr_node.value.contains('.') => {
+                    Some(String::from("double"))
+                }
+                TokenType::Number => Some(String::from("int")),
+                TokenType::String => Some(String::from("const char*")),
+                TokenType::True | TokenType::False => Some(String::from("bool")),
+                _ => None,
+            },
+            Some(ExprType::EnumeratorExprT {
+                enumerator_expr_node,
+            }) => Some(enumerator_expr_node.enum_type.clone()),
+            _ => None,
+        }
+    }
+
+    fn declared_or_inferred_type(variable_decl_node: &VariableDeclNode) -> String {
+        match &variable_decl_node.type_opt {
+            Some(_) => C99Visitor::format_type(&variable_decl_node.type_opt),
+            None => C99Visitor::infer_type(&variable_decl_node.initializer_expr_t_opt)
+                .unwrap_or_else(|| String::from("int")),
+        }
+    }
+
+    fn format_params(params_opt: &Option<Vec<ParameterNode>>) -> String {
+        match params_opt {
+            Some(params) => params
+                .iter()
+                .map(|param| {
+                    format!(
+                        ", {} {}",
+                        C99Visitor::format_type(&param.param_type_opt),
+                        param.param_name
+                    )
+                })
+                .collect(),
+            None => String::new(),
+        }
+    }
+
+    fn format_pascal_case(name: &str) -> String {
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+            None => String::new(),
+        }
+    }
+
+    // `TrafficLight` becomes `TRAFFIC_LIGHT`.
+    fn format_macro_prefix(&self) -> String {
+        let mut prefix = String::new();
+        let mut prev_is_lower = false;
+        for c in self.system_name.chars() {
+            if c.is_uppercase() && prev_is_lower {
+                prefix.push('_');
+            }
+            prev_is_lower = c.is_lowercase() || c.is_ascii_digit();
+            prefix.push(c.to_ascii_uppercase());
+        }
+        prefix
+    }
+
+    fn format_state_stack_capacity(&self) -> String {
+        format!("{}_STATE_STACK_CAPACITY", self.format_macro_prefix())
+    }
+
+    fn format_message(&self, msg: &str) -> String {
+        let symbol_config = &self.symbol_config;
+        let name = if msg == symbol_config.enter_msg_symbol {
+            "Enter"
+        } else if msg == symbol_config.exit_msg_symbol {
+            "Exit"
+        } else if msg == symbol_config.start_msg_symbol {
+            "SystemStart"
+        } else if msg == symbol_config.stop_msg_symbol {
+            "SystemStop"
+        } else if msg == symbol_config.save_msg_symbol {
+            "Save"
+        } else if msg == symbol_config.restore_msg_symbol {
+            "Restore"
+        } else {
+            msg
+        };
+        format!("{}_Message_{}", self.system_name, name)
+    }
+
+    fn format_state(&self, state_name: &str) -> String {
+        format!("{}_State_{}", self.system_name, state_name)
+    }
+
+    fn format_state_function(&self, state_name: &str) -> String {
+        format!("{}_state_{}", self.system_name, state_name)
+    }
+
+    fn format_state_data_type(&self, state_name: &str) -> String {
+        format!("{}_{}Context", self.system_name, state_name)
+    }
+
+    // Enter and exit parameters differ from state to state, the parameters
+    // of other messages are those of the interface method sending them.
+    fn format_params_type(&self, msg: &str, state_name: &str) -> String {
+        if msg == self.symbol_config.enter_msg_symbol {
+            format!("{}_{}EnterParams", self.system_name, state_name)
+        } else if msg == self.symbol_config.exit_msg_symbol {
+            format!("{}_{}ExitParams", self.system_name, state_name)
+        } else {
+            format!(
+                "{}_{}Params",
+                self.system_name,
+                C99Visitor::format_pascal_case(msg)
+            )
+        }
+    }
+
+    fn format_expr(&mut self, expr_t: &ExprType) -> String {
+        let mut output = String::new();
+        expr_t.accept_to_string(self, &mut output);
+        output
+    }
+
+    fn format_args(&mut self, exprs_t: &[ExprType]) -> Vec<String> {
+        exprs_t
+            .iter()
+            .map(|expr_t| self.format_expr(expr_t))
+            .collect()
+    }
+
+    fn format_variable_expr(&self, variable_node: &VariableNode) -> String {
+        let name = &variable_node.id_node.name.lexeme;
+        match variable_node.scope {
+            IdentifierDeclScope::DomainBlock => format!("self->{}", name),
+            IdentifierDeclScope::StateParam | IdentifierDeclScope::StateVar => format!(
+                "self->state_context.data.{}.{}",
+                self.current_state_name_opt.clone().unwrap_or_default(),
+                name
+            ),
+            IdentifierDeclScope::EventHandlerParam => format!("params->{}", name),
+            _ => name.clone(),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_struct(&mut self, type_name: &str, fields: &[(String, String)]) {
+        self.add_code("typedef struct {");
+        self.indent();
+        for (field_type, field_name) in fields {
+            self.newline();
+            self.add_code(&format!("{} {};", field_type, field_name));
+        }
+        self.outdent();
+        self.newline();
+        self.add_code(&format!("}} {};", type_name));
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_enum(&mut self, type_name: &str, enumerators: &[String]) {
+        self.add_code("typedef enum {");
+        self.indent();
+        for enumerator in enumerators {
+            self.newline();
+            self.add_code(&format!("{},", enumerator));
+        }
+        self.outdent();
+        self.newline();
+        self.add_code(&format!("}} {};", type_name));
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_messages(&mut self, system_node: &SystemNode) {
+        let mut messages = vec![
+            self.symbol_config.enter_msg_symbol.clone(),
+            self.symbol_config.exit_msg_symbol.clone(),
+        ];
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            for interface_method_node_rcref in &interface_block_node.interface_methods {
+                let interface_method_node = interface_method_node_rcref.borrow();
+                let message = match &interface_method_node.alias {
+                    Some(alias_message_node) => alias_message_node.name.clone(),
+                    None => interface_method_node.name.clone(),
+                };
+                if !messages.contains(&message) {
+                    messages.push(message);
+                }
+            }
+        }
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                for evt_handler_node_rcref in &state_node_rcref.borrow().evt_handlers_rcref {
+                    if let MessageType::CustomMessage { message_node } =
+                        &evt_handler_node_rcref.borrow().msg_t
+                    {
+                        if !messages.contains(&message_node.name) {
+                            messages.push(message_node.name.clone());
+                        }
+                    }
+                }
+            }
+        }
+        let enumerators: Vec<String> = messages
+            .iter()
+            .map(|message| self.format_message(message))
+            .collect();
+        self.generate_enum(&format!("{}_Message", self.system_name), &enumerators);
+    }
+
+    fn generate_params_types(&mut self, system_node: &SystemNode) {
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            for interface_method_node_rcref in &interface_block_node.interface_methods {
+                let interface_method_node = interface_method_node_rcref.borrow();
+                let message = match &interface_method_node.alias {
+                    Some(alias_message_node) => alias_message_node.name.clone(),
+                    None => interface_method_node.name.clone(),
+                };
+                if let Some(return_type) = &interface_method_node.return_type_opt {
+                    self.interface_return_types.insert(
+                        message.clone(),
+                        C99Visitor::format_type(&Some(return_type.clone())),
+                    );
+                }
+                let params = match &interface_method_node.params {
+                    Some(params) if !params.is_empty() => params,
+                    _ => continue,
+                };
+                let params_type = self.format_params_type(&message, "");
+                let fields: Vec<(String, String)> = params
+                    .iter()
+                    .map(|param| {
+                        (
+                            C99Visitor::format_type(&param.param_type_opt),
+                            param.param_name.clone(),
+                        )
+                    })
+                    .collect();
+                self.generate_struct(&params_type, &fields);
+                self.interface_params.insert(message, params_type);
+            }
+        }
+
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_node = state_node_rcref.borrow();
+                let handlers = [
+                    &state_node.enter_event_handler_opt,
+                    &state_node.exit_event_handler_opt,
+                ];
+                for evt_handler_node_rcref in handlers.iter().copied().flatten() {
+                    let evt_handler_node = evt_handler_node_rcref.borrow();
+                    let event_symbol = evt_handler_node.event_symbol_rcref.borrow();
+                    let params = match &event_symbol.params_opt {
+                        Some(params) if !params.is_empty() => params,
+                        _ => continue,
+                    };
+                    let fields: Vec<(String, String)> = params
+                        .iter()
+                        .map(|param| {
+                            (
+                                C99Visitor::format_type(&param.param_type_opt),
+                                param.name.clone(),
+                            )
+                        })
+                        .collect();
+                    let message = match &evt_handler_node.msg_t {
+                        MessageType::CustomMessage { message_node } => message_node.name.clone(),
+                        MessageType::AnyMessage { .. } => continue,
+                    };
+                    if message == self.symbol_config.enter_msg_symbol {
+                        self.states_with_enter_args.push(state_node.name.clone());
+                    }
+                    let params_type = self.format_params_type(&message, &state_node.name);
+                    self.generate_struct(&params_type, &fields);
+                }
+            }
+        }
+    }
+
+    fn generate_frame_event_type(&mut self) {
+        let fields = vec![
+            (format!("{}_Message", self.system_name), String::from("msg")),
+            (String::from("const void*"), String::from("params")),
+            (String::from("void*"), String::from("ret")),
+        ];
+        self.generate_struct(&format!("{}_FrameEvent", self.system_name), &fields);
+    }
+
+    // Every hook receives `user_data` first, so one set of functions can
+    // serve several machines.
+    fn generate_actions_type(&mut self, system_node: &SystemNode) {
+        let mut fields = vec![(String::from("void*"), String::from("user_data"))];
+        if let Some(actions_block_node) = &system_node.actions_block_node_opt {
+            for action_rcref in &actions_block_node.actions {
+                let action_node = action_rcref.borrow();
+                let return_type = match &action_node.type_opt {
+                    Some(_) => C99Visitor::format_type(&action_node.type_opt),
+                    None => String::from("void"),
+                };
+                fields.push((
+                    return_type,
+                    format!(
+                        "(*{})(void* user_data{})",
+                        action_node.name,
+                        C99Visitor::format_params(&action_node.params)
+                    ),
+                ));
+            }
+        }
+        self.generate_struct(&format!("{}_Actions", self.system_name), &fields);
+    }
+
+    fn generate_state_types(&mut self, system_node: &SystemNode) {
+        let machine_block_node = match &system_node.machine_block_node_opt {
+            Some(machine_block_node) => machine_block_node,
+            None => return,
+        };
+        let enumerators: Vec<String> = machine_block_node
+            .states
+            .iter()
+            .map(|state_node_rcref| self.format_state(&state_node_rcref.borrow().name))
+            .collect();
+        self.generate_enum(&format!("{}_State", self.system_name), &enumerators);
+
+        for state_node_rcref in &machine_block_node.states {
+            let state_node = state_node_rcref.borrow();
+            let mut fields = Vec::new();
+            if let Some(params) = &state_node.params_opt {
+                for param in params {
+                    fields.push((
+                        C99Visitor::format_type(&param.param_type_opt),
+                        param.param_name.clone(),
+                    ));
+                }
+            }
+            if let Some(vars) = &state_node.vars_opt {
+                for var_rcref in vars {
+                    let var = var_rcref.borrow();
+                    // Parameters and variables share the state's struct.
+                    if fields.iter().any(|(_, name)| *name == var.name) {
+                        self.errors.push(format!(
+                            "State ${} declares \"{}\" as both a parameter and a variable, which is not supported for C99.",
+                            state_node.name, var.name
+                        ));
+                    }
+                    fields.push((
+                        C99Visitor::declared_or_inferred_type(&var),
+                        var.name.clone(),
+                    ));
+                }
+            }
+            if !fields.is_empty() {
+                let data_type = self.format_state_data_type(&state_node.name);
+                self.generate_struct(&data_type, &fields);
+                self.states_with_data.push(state_node.name.clone());
+            }
+        }
+
+        self.add_code("typedef struct {");
+        self.indent();
+        self.newline();
+        self.add_code(&format!("{}_State state;", self.system_name));
+        if !self.states_with_data.is_empty() {
+            self.newline();
+            self.add_code("union {");
+            self.indent();
+            for state_name in self.states_with_data.clone() {
+                self.newline();
+                self.add_code(&format!(
+                    "{} {};",
+                    self.format_state_data_type(&state_name),
+                    state_name
+                ));
+            }
+            self.outdent();
+            self.newline();
+            self.add_code("} data;");
+        }
+        // Kept so that popping a state enters it with the same arguments.
+        if !self.states_with_enter_args.is_empty() {
+            self.newline();
+            self.add_code("union {");
+            self.indent();
+            for state_name in self.states_with_enter_args.clone() {
+                self.newline();
+                self.add_code(&format!(
+                    "{} {};",
+                    self.format_params_type(&self.symbol_config.enter_msg_symbol, &state_name),
+                    state_name
+                ));
+            }
+            self.outdent();
+            self.newline();
+            self.add_code("} enter_args;");
+        }
+        self.outdent();
+        self.newline();
+        self.add_code(&format!("}} {}_StateContext;", self.system_name));
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_system_type(&mut self, system_node: &SystemNode) {
+        let mut fields = vec![(
+            format!("{}_Actions", self.system_name),
+            String::from("actions"),
+        )];
+        if self.has_states {
+            let state_context_type = format!("{}_StateContext", self.system_name);
+            fields.push((state_context_type.clone(), String::from("state_context")));
+            if self.generate_state_stack {
+                fields.push((
+                    state_context_type,
+                    format!("state_stack[{}]", self.format_state_stack_capacity()),
+                ));
+                fields.push((String::from("size_t"), String::from("state_stack_depth")));
+            }
+        }
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for variable_decl_node_rcref in &domain_block_node.member_variables {
+                let variable_decl_node = variable_decl_node_rcref.borrow();
+                fields.push((
+                    C99Visitor::declared_or_inferred_type(&variable_decl_node),
+                    variable_decl_node.name.clone(),
+                ));
+            }
+        }
+        self.generate_struct(&self.system_name.clone(), &fields);
+    }
+
+    // State functions and the interface call each other in any order.
+    fn generate_prototypes(&mut self, system_node: &SystemNode) {
+        let system_name = self.system_name.clone();
+        self.add_code(&format!(
+            "void {}_init({}* self, {}_Actions actions);",
+            system_name, system_name, system_name
+        ));
+        if self.has_states {
+            self.newline();
+            self.add_code(&format!(
+                "{}_State {}_get_state(const {}* self);",
+                system_name, system_name, system_name
+            ));
+        }
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            for interface_method_node_rcref in &interface_block_node.interface_methods {
+                let signature =
+                    self.format_interface_signature(&interface_method_node_rcref.borrow());
+                self.newline();
+                self.add_code(&format!("{};", signature));
+            }
+        }
+        if self.has_states {
+            self.newline();
+            if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+                for state_node_rcref in &machine_block_node.states {
+                    self.newline();
+                    self.add_code(&format!(
+                        "static void {}({}* self, const {}_FrameEvent* e);",
+                        self.format_state_function(&state_node_rcref.borrow().name),
+                        system_name,
+                        system_name
+                    ));
+                }
+            }
+            self.newline();
+            self.add_code(&format!(
+                "static void {}_handle({}* self, const {}_FrameEvent* e);",
+                system_name, system_name, system_name
+            ));
+            if self.generate_transition_state {
+                self.newline();
+                self.add_code(&format!(
+                    "static void {}_transition({}* self, {}_StateContext next, const void* exit_params);",
+                    system_name, system_name, system_name
+                ));
+            }
+            if self.generate_state_stack {
+                self.newline();
+                self.add_code(&format!(
+                    "static void {}_state_stack_push({}* self);",
+                    system_name, system_name
+                ));
+                self.newline();
+                self.add_code(&format!(
+                    "static {}_StateContext {}_state_stack_pop({}* self);",
+                    system_name, system_name, system_name
+                ));
+            }
+        }
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_init(&mut self, system_node: &SystemNode) {
+        let system_name = self.system_name.clone();
+        self.add_code(&format!(
+            "void {}_init({}* self, {}_Actions actions) {{",
+            system_name, system_name, system_name
+        ));
+        self.indent();
+        self.newline();
+        self.add_code(&format!(
+            "*self = ({}){{ .actions = actions }};",
+            system_name
+        ));
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for variable_decl_node_rcref in &domain_block_node.member_variables {
+                let variable_decl_node = variable_decl_node_rcref.borrow();
+                if let Some(expr_t) = &variable_decl_node.initializer_expr_t_opt {
+                    let value = self.format_expr(expr_t);
+                    self.newline();
+                    self.add_code(&format!("self->{} = {};", variable_decl_node.name, value));
+                }
+            }
+        }
+        if self.has_states {
+            let first_state_name = self.first_state_name.clone();
+            let state_context = self.format_new_state_context(&first_state_name, &None);
+            self.newline();
+            self.add_code(&format!("self->state_context = {};", state_context));
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        if self.has_states {
+            self.newline();
+            self.add_code(&format!(
+                "{}_State {}_get_state(const {}* self) {{",
+                system_name, system_name, system_name
+            ));
+            self.indent();
+            self.newline();
+            self.add_code("return self->state_context.state;");
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+            self.newline();
+        }
+    }
+
+    fn generate_machinery(&mut self, system_node: &SystemNode) {
+        let system_name = self.system_name.clone();
+        let capacity = self.format_state_stack_capacity();
+
+        self.newline();
+        self.add_code("//=============== Machinery and Mechanisms ==============//");
+        self.newline();
+        self.newline();
+        self.add_code(&format!(
+            "static void {}_handle({}* self, const {}_FrameEvent* e) {{",
+            system_name, system_name, system_name
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("switch (self->state_context.state) {");
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_name = state_node_rcref.borrow().name.clone();
+                self.newline();
+                self.add_code(&format!("case {}:", self.format_state(&state_name)));
+                self.indent();
+                self.newline();
+                self.add_code(&format!(
+                    "{}(self, e);",
+                    self.format_state_function(&state_name)
+                ));
+                self.newline();
+                self.add_code("break;");
+                self.outdent();
+            }
+        }
+        self.newline();
+        self.add_code("}");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        if self.generate_transition_state {
+            self.newline();
+            self.add_code(&format!(
+                "static void {}_transition({}* self, {}_StateContext next, const void* exit_params) {{",
+                system_name, system_name, system_name
+            ));
+            self.indent();
+            self.newline();
+            self.add_code(&format!(
+                "{}_FrameEvent exit_event = {{ {}, exit_params, NULL }};",
+                system_name,
+                self.format_message(&self.symbol_config.exit_msg_symbol)
+            ));
+            self.newline();
+            self.add_code(&format!("{}_handle(self, &exit_event);", system_name));
+            self.newline();
+            self.add_code("self->state_context = next;");
+            let enter_args = if self.states_with_enter_args.is_empty() {
+                "NULL"
+            } else {
+                "&self->state_context.enter_args"
+            };
+            self.newline();
+            self.add_code(&format!(
+                "{}_FrameEvent enter_event = {{ {}, {}, NULL }};",
+                system_name,
+                self.format_message(&self.symbol_config.enter_msg_symbol),
+                enter_args
+            ));
+            self.newline();
+            self.add_code(&format!("{}_handle(self, &enter_event);", system_name));
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+            self.newline();
+        }
+
+        if !self.generate_state_stack {
+            return;
+        }
+        self.newline();
+        self.add_code(&format!(
+            "static void {}_state_stack_push({}* self) {{",
+            system_name, system_name
+        ));
+        self.indent();
+        self.newline();
+        self.add_code(&format!("assert(self->state_stack_depth < {});", capacity));
+        self.newline();
+        self.add_code("self->state_stack[self->state_stack_depth++] = self->state_context;");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        self.newline();
+        self.add_code(&format!(
+            "static {}_StateContext {}_state_stack_pop({}* self) {{",
+            system_name, system_name, system_name
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("assert(self->state_stack_depth > 0);");
+        self.newline();
+        self.add_code("return self->state_stack[--self->state_stack_depth];");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_interface_signature(&self, interface_method_node: &InterfaceMethodNode) -> String {
+        let return_type = match &interface_method_node.return_type_opt {
+            Some(_) => C99Visitor::format_type(&interface_method_node.return_type_opt),
+            None => String::from("void"),
+        };
+        format!(
+            "{} {}_{}({}* self{})",
+            return_type,
+            self.system_name,
+            interface_method_node.name,
+            self.system_name,
+            C99Visitor::format_params(&interface_method_node.params)
+        )
+    }
+
+    // Parameters and the return value live on the caller's stack for the
+    // duration of the call.
+    fn generate_interface_method(&mut self, interface_method_node: &InterfaceMethodNode) {
+        let message = match &interface_method_node.alias {
+            Some(alias_message_node) => alias_message_node.name.clone(),
+            None => interface_method_node.name.clone(),
+        };
+        let return_type_opt = interface_method_node
+            .return_type_opt
+            .as_ref()
+            .map(|_| C99Visitor::format_type(&interface_method_node.return_type_opt));
+
+        self.newline();
+        let signature = self.format_interface_signature(interface_method_node);
+        self.add_code(&format!("{} {{", signature));
+        self.indent();
+        if let Some(return_type) = &return_type_opt {
+            self.newline();
+            self.add_code(&format!("{} ret = {{0}};", return_type));
+        }
+        if self.has_states {
+            let params = match self.interface_params.get(&message).cloned() {
+                Some(params_type) => {
+                    let args: Vec<String> = interface_method_node
+                        .params
+                        .iter()
+                        .flatten()
+                        .map(|param| param.param_name.clone())
+                        .collect();
+                    self.newline();
+                    self.add_code(&format!(
+                        "{} params = {{ {} }};",
+                        params_type,
+                        args.join(", ")
+                    ));
+                    "&params"
+                }
+                None => "NULL",
+            };
+            let ret = if return_type_opt.is_some() {
+                "&ret"
+            } else {
+                "NULL"
+            };
+            self.newline();
+            self.add_code(&format!(
+                "{}_FrameEvent e = {{ {}, {}, {} }};",
+                self.system_name,
+                self.format_message(&message),
+                params,
+                ret
+            ));
+            self.newline();
+            self.add_code(&format!("{}_handle(self, &e);", self.system_name));
+        }
+        if return_type_opt.is_some() {
+            self.newline();
+            self.add_code("return ret;");
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    fn generate_event_handler(&mut self, evt_handler_node: &EventHandlerNode) {
+        self.newline();
+        let message_opt = match &evt_handler_node.msg_t {
+            MessageType::CustomMessage { message_node } => {
+                self.add_code(&format!(
+                    "case {}: {{",
+                    self.format_message(&message_node.name)
+                ));
+                Some(message_node.name.clone())
+            }
+            MessageType::AnyMessage { .. } => {
+                self.add_code("default: {");
+                None
+            }
+        };
+        self.indent();
+        self.generate_comment(evt_handler_node.line);
+
+        self.current_return_type_opt = match &message_opt {
+            Some(message) => self.interface_return_types.get(message).cloned(),
+            None => None,
+        };
+        let params_type_opt = match &message_opt {
+            Some(message)
+                if *message == self.symbol_config.enter_msg_symbol
+                    || *message == self.symbol_config.exit_msg_symbol =>
+            {
+                Some(self.format_params_type(message, &evt_handler_node.state_name))
+            }
+            Some(message) => self.interface_params.get(message).cloned(),
+            None => None,
+        };
+
+        // The parameters are only declared when the handler reads them.
+        let params_decl_idx = self.code.len();
+        self.visit_decl_stmts(&evt_handler_node.statements);
+        self.generate_terminator(&evt_handler_node.terminator_node);
+        if let TerminatorType::Continue = evt_handler_node.terminator_node.terminator_type {
+            self.newline();
+            self.add_code("break;");
+        }
+        if self.code[params_decl_idx..].contains("params->") {
+            let params_decl = match &params_type_opt {
+                Some(params_type) => format!(
+                    "\n{}const {}* params = e->params;",
+                    "    ".repeat(self.dent),
+                    params_type
+                ),
+                None => {
+                    self.errors.push(format!(
+                        "Parameters of the \"{}\" event handler are not declared by the interface.",
+                        message_opt.clone().unwrap_or_default()
+                    ));
+                    String::new()
+                }
+            };
+            self.code.insert_str(params_decl_idx, &params_decl);
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.current_return_type_opt = None;
+    }
+
+    fn generate_terminator(&mut self, terminator_expr: &TerminatorExpr) {
+        match &terminator_expr.terminator_type {
+            TerminatorType::Return => {
+                if let Some(expr_t) = &terminator_expr.return_expr_t_opt {
+                    let value = self.format_expr(expr_t);
+                    match self.current_return_type_opt.clone() {
+                        Some(return_type) => {
+                            self.newline();
+                            self.add_code(&format!("*({}*)e->ret = {};", return_type, value));
+                        }
+                        None => self.errors.push(String::from(
+                            "Return values are only supported for interface methods with a return type.",
+                        )),
+                    }
+                }
+                self.newline();
+                self.add_code("return;");
+            }
+            TerminatorType::Continue => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_decl_stmts(&mut self, decl_stmt_types: &[DeclOrStmtType]) {
+        for decl_stmt_t in decl_stmt_types {
+            match decl_stmt_t {
+                DeclOrStmtType::VarDeclT { var_decl_t_rc_ref } => {
+                    let variable_decl_node = var_decl_t_rc_ref.borrow();
+                    self.generate_var_decl(&variable_decl_node);
+                }
+                DeclOrStmtType::StmtT { stmt_t } => match stmt_t {
+                    StatementType::ExpressionStmt { expr_stmt_t } => {
+                        self.generate_expr_stmt(expr_stmt_t);
+                    }
+                    StatementType::TransitionStmt {
+                        transition_statement,
+                    } => {
+                        self.generate_transition(transition_statement);
+                    }
+                    StatementType::ChangeStateStmt { change_state_stmt } => {
+                        self.generate_change_state(change_state_stmt);
+                    }
+                    StatementType::TestStmt { test_stmt_node } => {
+                        self.generate_test(&test_stmt_node.test_t);
+                    }
+                    StatementType::StateStackStmt {
+                        state_stack_operation_statement_node,
+                    } => {
+                        self.newline();
+                        let operation = match state_stack_operation_statement_node
+                            .state_stack_operation_node
+                            .operation_t
+                        {
+                            StateStackOperationType::Push => "push",
+                            StateStackOperationType::Pop => "pop",
+                        };
+                        self.add_code(&format!(
+                            "{}_state_stack_{}(self);",
+                            self.system_name, operation
+                        ));
+                    }
+                    StatementType::LoopStmt { loop_stmt_node } => {
+                        self.generate_loop(loop_stmt_node);
+                    }
+                    StatementType::LoopControlStmt {
+                        loop_control_stmt_node,
+                    } => {
+                        self.newline();
+                        match loop_control_stmt_node.loop_control_t {
+                            LoopControlType::Break => self.add_code("break;"),
+                            LoopControlType::Continue => self.add_code("continue;"),
+                        }
+                    }
+                    StatementType::NoStmt => {}
+                },
+            }
+        }
+    }
+
+    fn generate_var_decl(&mut self, variable_decl_node: &VariableDeclNode) {
+        let value = match &variable_decl_node.initializer_expr_t_opt {
+            Some(expr_t) => self.format_expr(expr_t),
+            None => String::from("{0}"),
+        };
+        self.newline();
+        self.add_code(&format!(
+            "{} {} = {};",
+            C99Visitor::declared_or_inferred_type(variable_decl_node),
+            variable_decl_node.name,
+            value
+        ));
+    }
+
+    fn generate_expr_stmt(&mut self, expr_stmt_t: &ExprStmtType) {
+        let mut output = String::new();
+        match expr_stmt_t {
+            ExprStmtType::CallStmtT { call_stmt_node } => {
+                self.visit_call_expression_node_to_string(
+                    &call_stmt_node.call_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::ActionCallStmtT {
+                action_call_stmt_node,
+            } => {
+                self.visit_action_call_expression_node_to_string(
+                    &action_call_stmt_node.action_call_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::CallChainLiteralStmtT {
+                call_chain_literal_stmt_node,
+            } => {
+                self.visit_call_chain_literal_expr_node_to_string(
+                    &call_chain_literal_stmt_node.call_chain_literal_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::AssignmentStmtT {
+                assignment_stmt_node,
+            } => {
+                self.generate_comment(assignment_stmt_node.get_line());
+                self.visit_assignment_expr_node_to_string(
+                    &assignment_stmt_node.assignment_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::VariableStmtT { variable_stmt_node } => {
+                output.push_str("(void)");
+                output.push_str(&self.format_variable_expr(&variable_stmt_node.var_node));
+            }
+        }
+        self.newline();
+        self.add_code(&format!("{};", output));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_label(&mut self, label_opt: &Option<String>) {
+        if let Some(label) = label_opt {
+            self.newline();
+            self.add_code(&format!("// {}", label));
+        }
+    }
+
+    // Designated initializers pairing the arguments of an enter or exit
+    // event with its parameter names.
+    fn format_event_args(&mut self, msg: &str, args: &ExprListNode) -> Vec<String> {
+        let event_sym_opt = self.arcanium.get_event(msg, &self.current_state_name_opt);
+        let params = match event_sym_opt {
+            Some(event_sym) => match &event_sym.borrow().params_opt {
+                Some(params) => params
+                    .iter()
+                    .map(|param| param.name.clone())
+                    .collect::<Vec<String>>(),
+                None => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+        if params.len() != args.exprs_t.len() {
+            self.errors.push(format!(
+                "Invalid number of arguments for \"{}\" event handler.",
+                msg
+            ));
+        }
+        params
+            .into_iter()
+            .zip(args.exprs_t.iter())
+            .map(|(param, expr_t)| format!(".{} = {}", param, self.format_expr(expr_t)))
+            .collect()
+    }
+
+    fn format_exit_args(&mut self, exit_args_opt: &Option<ExprListNode>) -> String {
+        match exit_args_opt {
+            Some(exit_args) if !exit_args.exprs_t.is_empty() => {
+                let state_name = self.current_state_name_opt.clone().unwrap_or_default();
+                let msg = format!("{}:{}", state_name, self.symbol_config.exit_msg_symbol);
+                let initializers = self.format_event_args(&msg, exit_args);
+                format!(
+                    "&({}){{ {} }}",
+                    self.format_params_type(&self.symbol_config.exit_msg_symbol, &state_name),
+                    initializers.join(", ")
+                )
+            }
+            _ => String::from("NULL"),
+        }
+    }
+
+    // A compound literal for the target state's context, with its state
+    // arguments, the initial values of its state variables and its enter
+    // arguments.
+    fn format_new_state_context(
+        &mut self,
+        target_state_name: &str,
+        state_context_node_opt: &Option<&StateContextNode>,
+    ) -> String {
+        let mut initializers = Vec::new();
+
+        if let Some(state_context_node) = state_context_node_opt {
+            if let Some(state_args) = &state_context_node.state_ref_args_opt {
+                let params = match self.arcanium.get_state(target_state_name) {
+                    Some(state_symbol_rcref) => match &state_symbol_rcref.borrow().params_opt {
+                        Some(params) => params
+                            .iter()
+                            .map(|param| param.borrow().name.clone())
+                            .collect::<Vec<String>>(),
+                        None => Vec::new(),
+                    },
+                    None => Vec::new(),
+                };
+                if params.len() != state_args.exprs_t.len() {
+                    self.errors.push(format!(
+                        "Invalid number of arguments for \"{}\" state parameters.",
+                        target_state_name
+                    ));
+                }
+                for (param, expr_t) in params.into_iter().zip(state_args.exprs_t.iter()) {
+                    let value = self.format_expr(expr_t);
+                    initializers.push(format!(".{} = {}", param, value));
+                }
+            }
+        }
+
+        if let Some(state_symbol_rcref) = self.arcanium.get_state(target_state_name) {
+            let state_symbol = state_symbol_rcref.borrow();
+            let state_node = state_symbol.state_node.as_ref().unwrap().borrow();
+            if let Some(vars) = &state_node.vars_opt {
+                for var_rcref in vars {
+                    let var = var_rcref.borrow();
+                    let expr_t = var.initializer_expr_t_opt.as_ref().unwrap();
+                    let value = self.format_expr(expr_t);
+                    initializers.push(format!(".{} = {}", var.name, value));
+                }
+            }
+        }
+
+        let mut fields = vec![format!(".state = {}", self.format_state(target_state_name))];
+        if !initializers.is_empty() {
+            fields.push(format!(
+                ".data.{} = {{ {} }}",
+                target_state_name,
+                initializers.join(", ")
+            ));
+        }
+        if let Some(state_context_node) = state_context_node_opt {
+            if let Some(enter_args) = &state_context_node.enter_args_opt {
+                if !enter_args.exprs_t.is_empty() {
+                    let msg = format!(
+                        "{}:{}",
+                        target_state_name, self.symbol_config.enter_msg_symbol
+                    );
+                    let enter_initializers = self.format_event_args(&msg, enter_args);
+                    fields.push(format!(
+                        ".enter_args.{} = {{ {} }}",
+                        target_state_name,
+                        enter_initializers.join(", ")
+                    ));
+                }
+            }
+        }
+        format!(
+            "({}_StateContext){{ {} }}",
+            self.system_name,
+            fields.join(", ")
+        )
+    }
+
+    fn generate_transition(&mut self, transition_statement: &TransitionStatementNode) {
+        self.generate_label(&transition_statement.label_opt);
+        let exit_args = self.format_exit_args(&transition_statement.exit_args_opt);
+        let next = match &transition_statement.target_state_context_t {
+            StateContextType::StateRef { state_context_node } => self.format_new_state_context(
+                &state_context_node.state_ref_node.name,
+                &Some(state_context_node),
+            ),
+            StateContextType::StateStackPop {} => {
+                format!("{}_state_stack_pop(self)", self.system_name)
+            }
+        };
+        self.newline();
+        self.add_code(&format!(
+            "{}_transition(self, {}, {});",
+            self.system_name, next, exit_args
+        ));
+    }
+
+    fn generate_change_state(&mut self, change_state_stmt_node: &ChangeStateStatementNode) {
+        self.generate_label(&change_state_stmt_node.label_opt);
+        let next = match &change_state_stmt_node.state_context_t {
+            StateContextType::StateRef { state_context_node } => self.format_new_state_context(
+                &state_context_node.state_ref_node.name,
+                &Some(state_context_node),
+            ),
+            StateContextType::StateStackPop {} => {
+                format!("{}_state_stack_pop(self)", self.system_name)
+            }
+        };
+        self.newline();
+        self.add_code(&format!("self->state_context = {};", next));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_branch_body(
+        &mut self,
+        statements: &[DeclOrStmtType],
+        terminator_opt: &Option<TerminatorExpr>,
+    ) {
+        self.indent();
+        self.visit_decl_stmts(statements);
+        if let Some(terminator_expr) = terminator_opt {
+            self.generate_terminator(terminator_expr);
+        }
+        self.outdent();
+    }
+
+    fn generate_if_chain(
+        &mut self,
+        branches: Vec<C99Branch>,
+        else_opt: Option<(&[DeclOrStmtType], &Option<TerminatorExpr>)>,
+    ) {
+        self.newline();
+        for (i, branch) in branches.iter().enumerate() {
+            if i == 0 {
+                self.add_code(&format!("if ({}) {{", branch.condition));
+            } else {
+                self.add_code(&format!("}} else if ({}) {{", branch.condition));
+            }
+            self.generate_branch_body(branch.statements, branch.terminator_opt);
+            self.newline();
+        }
+        if let Some((statements, terminator_opt)) = else_opt {
+            self.add_code("} else {");
+            self.generate_branch_body(statements, terminator_opt);
+            self.newline();
+        }
+        self.add_code("}");
+    }
+
+    fn generate_test(&mut self, test_t: &TestType) {
+        match test_t {
+            TestType::BoolTest { bool_test_node } => {
+                let mut branches = Vec::new();
+                for branch_node in &bool_test_node.conditional_branch_nodes {
+                    let mut condition = self.format_expr(&branch_node.expr_t);
+                    if branch_node.is_negated {
+                        condition = format!("!({})", condition);
+                    }
+                    branches.push(C99Branch {
+                        condition,
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    });
+                }
+                let else_opt = bool_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::StringMatchTest {
+                string_match_test_node,
+            } => {
+                self.uses_string_h = true;
+                let subject = self.format_expr(&string_match_test_node.expr_t);
+                let branches = string_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| C99Branch {
+                        condition: branch_node
+                            .string_match_pattern_node
+                            .match_pattern_strings
+                            .iter()
+                            .map(|pattern| format!("strcmp({}, {:?}) == 0", subject, pattern))
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = string_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::NumberMatchTest {
+                number_match_test_node,
+            } => {
+                let subject = self.format_expr(&number_match_test_node.expr_t);
+                let branches = number_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| C99Branch {
+                        condition: branch_node
+                            .number_match_pattern_nodes
+                            .iter()
+                            .map(|pattern| {
+                                format!("{} == {}", subject, pattern.match_pattern_number)
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = number_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::RegexMatchTest { .. } => {
+                self.errors
+                    .push("Regex match tests are not supported for C99.".to_string());
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                let subject = self.format_expr(&enum_match_test_node.expr_t);
+                let branches = enum_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| C99Branch {
+                        condition: branch_node
+                            .enumerators
+                            .iter()
+                            .map(|enumerator| {
+                                format!(
+                                    "{} == {}_{}",
+                                    subject, enum_match_test_node.enum_type_name, enumerator
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = enum_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+        }
+    }
+
+    fn generate_loop(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.newline();
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(condition_expr_t) => {
+                let condition = self.format_expr(condition_expr_t);
+                self.add_code(&format!("while ({}) {{", condition));
+            }
+            None => self.add_code("for (;;) {"),
+        }
+        self.indent();
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+}
+
+//* --------------------------------------------------------------------- *//
+
+impl AstVisitor for C99Visitor {
+    fn visit_system_node(&mut self, system_node: &SystemNode) {
+        self.system_name = system_node.name.clone();
+        if system_node.has_deferred_events() {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the c99 target.",
+            ));
+        }
+        if let Some(first_state) = system_node.get_first_state() {
+            self.first_state_name = first_state.borrow().name.clone();
+            self.has_states = true;
+        }
+
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for enum_decl_node_rcref in &domain_block_node.enums {
+                let enum_decl_node = enum_decl_node_rcref.borrow();
+                let enumerators: Vec<String> = enum_decl_node
+                    .enumerators
+                    .iter()
+                    .map(|enumerator| format!("{}_{}", enum_decl_node.name, enumerator))
+                    .collect();
+                self.generate_enum(&enum_decl_node.name, &enumerators);
+            }
+        }
+        self.generate_messages(system_node);
+        self.generate_params_types(system_node);
+        self.generate_frame_event_type();
+        self.generate_actions_type(system_node);
+        if self.has_states {
+            self.generate_state_types(system_node);
+        }
+        self.generate_system_type(system_node);
+        self.generate_prototypes(system_node);
+        self.generate_init(system_node);
+
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            interface_block_node.accept(self);
+        }
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            machine_block_node.accept(self);
+        }
+        if self.has_states {
+            self.generate_machinery(system_node);
+        }
+
+        // The includes are only known once the body has been generated.
+        let mut header = format!(
+            "// {}\n\n#include <assert.h>\n#include <stdbool.h>\n#include <stddef.h>\n",
+            self.compiler_version
+        );
+        if self.uses_string_h {
+            header.push_str("#include <string.h>\n");
+        }
+        header.push('\n');
+        if self.generate_state_stack {
+            let capacity = self.format_state_stack_capacity();
+            header.push_str(&format!(
+                "#ifndef {}\n#define {} {}\n#endif\n\n",
+                capacity, capacity, self.config.code.state_stack_capacity
+            ));
+        }
+        self.code.insert_str(0, &header);
+    }
+
+    fn visit_interface_block_node(&mut self, interface_block_node: &InterfaceBlockNode) {
+        self.newline();
+        self.add_code("//===================== Interface Block ===================//");
+        self.newline();
+        for interface_method_node_rcref in &interface_block_node.interface_methods {
+            self.generate_interface_method(&interface_method_node_rcref.borrow());
+        }
+    }
+
+    fn visit_machine_block_node(&mut self, machine_block_node: &MachineBlockNode) {
+        self.newline();
+        self.add_code("//===================== Machine Block ===================//");
+        self.newline();
+        for state_node_rcref in &machine_block_node.states {
+            state_node_rcref.borrow().accept(self);
+        }
+    }
+
+    fn visit_state_node(&mut self, state_node: &StateNode) {
+        self.generate_comment(state_node.line);
+        self.current_state_name_opt = Some(state_node.name.clone());
+        self.newline();
+        self.add_code(&format!(
+            "static void {}({}* self, const {}_FrameEvent* e) {{",
+            self.format_state_function(&state_node.name),
+            self.system_name,
+            self.system_name
+        ));
+        self.indent();
+        let body_idx = self.code.len();
+
+        if let Some(calls) = &state_node.calls_opt {
+            for call in calls {
+                let mut output = String::new();
+                self.visit_call_chain_literal_expr_node_to_string(call, &mut output);
+                self.newline();
+                self.add_code(&format!("{};", output));
+            }
+        }
+
+        if !state_node.evt_handlers_rcref.is_empty() {
+            self.newline();
+            self.add_code("switch (e->msg) {");
+            let mut has_default = false;
+            for evt_handler_node_rcref in &state_node.evt_handlers_rcref {
+                let evt_handler_node = evt_handler_node_rcref.borrow();
+                if let MessageType::AnyMessage { .. } = evt_handler_node.msg_t {
+                    has_default = true;
+                }
+                self.generate_event_handler(&evt_handler_node);
+            }
+            if !has_default {
+                self.newline();
+                self.add_code("default:");
+                self.indent();
+                self.newline();
+                self.add_code("break;");
+                self.outdent();
+            }
+            self.newline();
+            self.add_code("}");
+        }
+
+        // Events the state does not handle go to its parent.
+        if let Some(dispatch_node) = &state_node.dispatch_opt {
+            self.newline();
+            self.add_code(&format!(
+                "{}(self, e);",
+                self.format_state_function(&dispatch_node.target_state_ref.name)
+            ));
+        }
+
+        // States that ignore the machine or the event would otherwise warn
+        // about unused parameters.
+        let body = self.code[body_idx..].to_string();
+        let mut unused = String::new();
+        if !body.contains("self") {
+            unused.push_str(&format!("\n{}(void)self;", "    ".repeat(self.dent)));
+        }
+        if !body.contains("e->") && !body.contains("(self, e)") {
+            unused.push_str(&format!("\n{}(void)e;", "    ".repeat(self.dent)));
+        }
+        self.code.insert_str(body_idx, &unused);
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.current_state_name_opt = None;
+    }
+
+    fn visit_action_node_rust_trait(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_action_node_rust_trait() not implemented.");
+    }
+
+    fn visit_actions_node_rust_impl(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_actions_node_rust_impl() not implemented.");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_assignment_expr_node_to_string(
+        &mut self,
+        assignment_expr_node: &AssignmentExprNode,
+        output: &mut String,
+    ) {
+        assignment_expr_node
+            .l_value_box
+            .accept_to_string(self, output);
+        output.push_str(" = ");
+        assignment_expr_node
+            .r_value_box
+            .accept_to_string(self, output);
+    }
+
+    fn visit_expression_list_node_to_string(
+        &mut self,
+        expr_list: &ExprListNode,
+        output: &mut String,
+    ) {
+        let args = self.format_args(&expr_list.exprs_t);
+        output.push_str(&format!("({})", args.join(", ")));
+    }
+
+    fn visit_call_expr_list_node_to_string(
+        &mut self,
+        call_expr_list: &CallExprListNode,
+        output: &mut String,
+    ) {
+        let args = self.format_args(&call_expr_list.exprs_t);
+        output.push_str(&format!("({})", args.join(", ")));
+    }
+
+    fn visit_call_expression_node_to_string(
+        &mut self,
+        method_call: &CallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&method_call.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(&method_call.call_expr_list, output);
+    }
+
+    fn visit_action_call_expression_node_to_string(
+        &mut self,
+        action_call: &ActionCallExprNode,
+        output: &mut String,
+    ) {
+        let mut args = vec![String::from("self->actions.user_data")];
+        args.extend(self.format_args(&action_call.call_expr_list.exprs_t));
+        output.push_str(&format!(
+            "self->actions.{}({})",
+            action_call.identifier.name.lexeme,
+            args.join(", ")
+        ));
+    }
+
+    fn visit_interface_method_call_expression_node_to_string(
+        &mut self,
+        interface_method_call_expr_node: &InterfaceMethodCallExprNode,
+        output: &mut String,
+    ) {
+        let mut args = vec![String::from("self")];
+        args.extend(self.format_args(&interface_method_call_expr_node.call_expr_list.exprs_t));
+        output.push_str(&format!(
+            "{}_{}({})",
+            self.system_name,
+            interface_method_call_expr_node.identifier.name.lexeme,
+            args.join(", ")
+        ));
+    }
+
+    fn visit_call_chain_literal_expr_node_to_string(
+        &mut self,
+        method_call_chain_expression_node: &CallChainLiteralExprNode,
+        output: &mut String,
+    ) {
+        let mut separator = "";
+        for node in &method_call_chain_expression_node.call_chain {
+            output.push_str(separator);
+            match node {
+                CallChainLiteralNodeType::VariableNodeT { var_node } => {
+                    output.push_str(&self.format_variable_expr(var_node));
+                }
+                CallChainLiteralNodeType::IdentifierNodeT { id_node } => {
+                    output.push_str(&id_node.name.lexeme);
+                }
+                CallChainLiteralNodeType::CallT { call } => {
+                    self.visit_call_expression_node_to_string(call, output);
+                }
+                CallChainLiteralNodeType::InterfaceMethodCallT {
+                    interface_method_call_expr_node,
+                } => {
+                    self.visit_interface_method_call_expression_node_to_string(
+                        interface_method_call_expr_node,
+                        output,
+                    );
+                }
+                CallChainLiteralNodeType::ActionCallT {
+                    action_call_expr_node,
+                } => {
+                    self.visit_action_call_expression_node_to_string(action_call_expr_node, output);
+                }
+            }
+            separator = ".";
+        }
+    }
+
+    fn visit_literal_expression_node_to_string(
+        &mut self,
+        literal_expression_node: &LiteralExprNode,
+        output: &mut String,
+    ) {
+        match &literal_expression_node.token_t {
+            TokenType::String => {
+                output.push_str(&format!("\"{}\"", literal_expression_node.value));
+            }
+            TokenType::True => output.push_str("true"),
+            TokenType::False => output.push_str("false"),
+            TokenType::Nil | TokenType::Null => output.push_str("NULL"),
+            _ => output.push_str(&literal_expression_node.value),
+        }
+    }
+
+    fn visit_variable_expr_node_to_string(
+        &mut self,
+        variable_node: &VariableNode,
+        output: &mut String,
+    ) {
+        output.push_str(&self.format_variable_expr(variable_node));
+    }
+
+    fn visit_frame_event_part_to_string(
+        &mut self,
+        frame_event_part: &FrameEventPart,
+        output: &mut String,
+    ) {
+        match frame_event_part {
+            FrameEventPart::Event { .. } => output.push('e'),
+            FrameEventPart::Message { .. } => output.push_str("e->msg"),
+            FrameEventPart::Param { param_tok, .. } => {
+                output.push_str(&format!("params->{}", param_tok.lexeme))
+            }
+            FrameEventPart::Return { .. } => match &self.current_return_type_opt {
+                Some(return_type) => output.push_str(&format!("*({}*)e->ret", return_type)),
+                None => output.push_str("e->ret"),
+            },
+        }
+    }
+
+    fn visit_unary_expr_node_to_string(
+        &mut self,
+        unary_expr_node: &UnaryExprNode,
+        output: &mut String,
+    ) {
+        unary_expr_node.operator.accept_to_string(self, output);
+        unary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_binary_expr_node_to_string(
+        &mut self,
+        binary_expr_node: &BinaryExprNode,
+        output: &mut String,
+    ) {
+        binary_expr_node
+            .left_rcref
+            .borrow()
+            .accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node.operator.accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}_{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    fn visit_operator_type_to_string(&mut self, operator_type: &OperatorType, output: &mut String) {
+        let operator = match operator_type {
+            OperatorType::Plus => "+",
+            OperatorType::Minus | OperatorType::Negated => "-",
+            OperatorType::Multiply => "*",
+            OperatorType::Divide => "/",
+            OperatorType::Greater => ">",
+            OperatorType::GreaterEqual => ">=",
+            OperatorType::Less => "<",
+            OperatorType::LessEqual => "<=",
+            OperatorType::EqualEqual => "==",
+            OperatorType::NotEqual => "!=",
+            OperatorType::Not => "!",
+            OperatorType::LogicalAnd => "&&",
+            OperatorType::LogicalOr => "||",
+            // Booleans differ exactly when one of them is true.
+            OperatorType::LogicalXor => "!=",
+        };
+        output.push_str(operator);
+    }
+}
@@ -358,6 +358,14 @@ impl CsVisitor {
                         StatementType::ChangeStateStmt { change_state_stmt } => {
                             change_state_stmt.accept(self);
                         }
+                        StatementType::LoopStmt { loop_stmt_node } => {
+                            loop_stmt_node.accept(self);
+                        }
+                        StatementType::LoopControlStmt {
+                            loop_control_stmt_node,
+                        } => {
+                            loop_control_stmt_node.accept(self);
+                        }
                         StatementType::NoStmt => {
                             // TODO
                             self.errors.push("Unknown error.".to_string());
@@ -938,6 +946,11 @@ impl AstVisitor for CsVisitor {
 
     fn visit_system_node(&mut self, system_node: &SystemNode) {
         self.system_name = system_node.name.clone();
+        if system_node.has_deferred_events() {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the c_sharp target.",
+            ));
+        }
         self.add_code(&format!("// {}", self.compiler_version));
         self.newline();
         self.add_code(
@@ -949,6 +962,13 @@ impl AstVisitor for CsVisitor {
         self.indent();
         self.newline();
 
+        // domain enums
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for enum_decl_node_rcref in &domain_block_node.enums {
+                enum_decl_node_rcref.borrow().accept(self);
+            }
+        }
+
         // First state name needed for machinery.
         // Don't generate if there isn't at least one state.
         match system_node.get_first_state() {
@@ -1270,6 +1290,18 @@ impl AstVisitor for CsVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enum_decl_node(&mut self, enum_decl_node: &EnumDeclNode) {
+        self.add_code(&format!(
+            "public enum {} {{ {} }}",
+            enum_decl_node.name,
+            enum_decl_node.enumerators.join(", ")
+        ));
+        self.newline();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_domain_block_node(&mut self, domain_block_node: &DomainBlockNode) {
         self.newline();
         self.newline();
@@ -1603,6 +1635,15 @@ impl AstVisitor for CsVisitor {
             } => {
                 number_match_test_node.accept(self);
             }
+            TestType::RegexMatchTest { .. } => {
+                self.errors
+                    .push("Regex match tests are not supported for C#.".to_string());
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
+            }
         }
     }
 
@@ -1955,6 +1996,156 @@ impl AstVisitor for CsVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        let mut if_or_else_if = "if";
+
+        self.newline();
+        for match_branch_node in &enum_match_test_node.match_branch_nodes {
+            self.add_code(&format!("{} ((", if_or_else_if));
+            match &enum_match_test_node.expr_t {
+                ExprType::CallExprT {
+                    call_expr_node: method_call_expr_node,
+                } => method_call_expr_node.accept(self),
+                ExprType::ActionCallExprT {
+                    action_call_expr_node,
+                } => action_call_expr_node.accept(self),
+                ExprType::CallChainLiteralExprT {
+                    call_chain_expr_node,
+                } => call_chain_expr_node.accept(self),
+                ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                ExprType::ExprListT { expr_list_node } => {
+                    // must be only 1 expression in the list
+                    if expr_list_node.exprs_t.len() != 1 {
+                        // TODO: how to do this better.
+                        self.errors
+                            .push("Error - expression list is not testable.".to_string());
+                    }
+                    let x = expr_list_node.exprs_t.first().unwrap();
+                    x.accept(self);
+                }
+
+                _ => self.errors.push("TODO".to_string()),
+            }
+
+            let mut first_match = true;
+            for enumerator in &match_branch_node.enumerators {
+                if first_match {
+                    self.add_code(&format!(
+                        " == {}.{})",
+                        enum_match_test_node.enum_type_name, enumerator
+                    ));
+                    first_match = false;
+                } else {
+                    self.add_code(" || (");
+                    match &enum_match_test_node.expr_t {
+                        ExprType::CallExprT {
+                            call_expr_node: method_call_expr_node,
+                        } => method_call_expr_node.accept(self),
+                        ExprType::ActionCallExprT {
+                            action_call_expr_node,
+                        } => action_call_expr_node.accept(self),
+                        ExprType::CallChainLiteralExprT {
+                            call_chain_expr_node,
+                        } => call_chain_expr_node.accept(self),
+                        ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                        _ => self.errors.push("TODO".to_string()),
+                    }
+                    self.add_code(&format!(
+                        " == {}.{})",
+                        enum_match_test_node.enum_type_name, enumerator
+                    ));
+                }
+            }
+            self.add_code(") {");
+            self.indent();
+
+            match_branch_node.accept(self);
+
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+
+            if_or_else_if = " else if";
+        }
+
+        // (':' enum_test_else_branch)?
+        if let Some(enum_match_else_branch_node) = &enum_match_test_node.else_branch_node_opt {
+            enum_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_match_branch_node(
+        &mut self,
+        enum_match_test_match_branch_node: &EnumMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&enum_match_test_match_branch_node.statements);
+
+        match &enum_match_test_match_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_else_branch_node(
+        &mut self,
+        enum_match_test_else_branch_node: &EnumMatchTestElseBranchNode,
+    ) {
+        self.add_code(" else {");
+        self.indent();
+
+        self.visit_decl_stmts(&enum_match_test_else_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &enum_match_test_else_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_string_match_test_pattern_node(
         &mut self,
         _string_match_test_else_branch_node: &StringMatchTestPatternNode,
@@ -2234,6 +2425,37 @@ impl AstVisitor for CsVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.newline();
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(expr_t) => {
+                self.add_code("while (");
+                expr_t.accept(self);
+                self.add_code(") {");
+            }
+            None => self.add_code("while (true) {"),
+        }
+        self.indent();
+
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_loop_control_stmt_node(&mut self, loop_control_stmt_node: &LoopControlStmtNode) {
+        self.newline();
+        match loop_control_stmt_node.loop_control_t {
+            LoopControlType::Break => self.add_code("break;"),
+            LoopControlType::Continue => self.add_code("continue;"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_state_stack_operation_statement_node(
         &mut self,
         state_stack_op_statement_node: &StateStackOperationStatementNode,
@@ -2496,6 +2718,27 @@ impl AstVisitor for CsVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enumerator_expr_node(&mut self, enumerator_expr_node: &EnumeratorExprNode) {
+        let mut output = String::new();
+        self.visit_enumerator_expr_node_to_string(enumerator_expr_node, &mut output);
+        self.add_code(&output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}.{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_binary_expr_node_to_string(
         &mut self,
         binary_expr_node: &BinaryExprNode,
@@ -269,6 +269,14 @@ impl GdScript32Visitor {
                         StatementType::ChangeStateStmt { change_state_stmt } => {
                             change_state_stmt.accept(self);
                         }
+                        StatementType::LoopStmt { loop_stmt_node } => {
+                            loop_stmt_node.accept(self);
+                        }
+                        StatementType::LoopControlStmt {
+                            loop_control_stmt_node,
+                        } => {
+                            loop_control_stmt_node.accept(self);
+                        }
                         StatementType::NoStmt => {
                             // TODO
                             self.errors.push("Unknown error.".to_string());
@@ -828,6 +836,11 @@ impl AstVisitor for GdScript32Visitor {
 
     fn visit_system_node(&mut self, system_node: &SystemNode) {
         self.system_name = system_node.name.clone();
+        if system_node.has_deferred_events() {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the gdscript target.",
+            ));
+        }
         self.add_code(&format!("# {}", self.compiler_version));
         self.newline();
         self.add_code("# get include files at https://github.com/frame-lang/frame-ancillary-files");
@@ -837,6 +850,14 @@ impl AstVisitor for GdScript32Visitor {
 
         self.newline();
 
+        // domain enums
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for enum_decl_node_rcref in &domain_block_node.enums {
+                enum_decl_node_rcref.borrow().accept(self);
+                self.newline();
+            }
+        }
+
         // First state name needed for machinery.
         // Don't generate if there isn't at least one state.
         match system_node.get_first_state() {
@@ -1137,6 +1158,16 @@ impl AstVisitor for GdScript32Visitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enum_decl_node(&mut self, enum_decl_node: &EnumDeclNode) {
+        self.add_code(&format!(
+            "enum {} {{ {} }}",
+            enum_decl_node.name,
+            enum_decl_node.enumerators.join(", ")
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_domain_block_node(&mut self, domain_block_node: &DomainBlockNode) {
         self.newline();
         self.newline();
@@ -1470,6 +1501,15 @@ impl AstVisitor for GdScript32Visitor {
             } => {
                 number_match_test_node.accept(self);
             }
+            TestType::RegexMatchTest { .. } => {
+                self.errors
+                    .push("Regex match tests are not supported for GDScript.".to_string());
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
+            }
         }
     }
 
@@ -1813,6 +1853,158 @@ impl AstVisitor for GdScript32Visitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        let mut if_or_else_if = "if";
+
+        self.newline();
+        for match_branch_node in &enum_match_test_node.match_branch_nodes {
+            self.add_code(&format!("{} (", if_or_else_if));
+            match &enum_match_test_node.expr_t {
+                ExprType::CallExprT {
+                    call_expr_node: method_call_expr_node,
+                } => method_call_expr_node.accept(self),
+                ExprType::ActionCallExprT {
+                    action_call_expr_node,
+                } => action_call_expr_node.accept(self),
+                ExprType::CallChainLiteralExprT {
+                    call_chain_expr_node,
+                } => call_chain_expr_node.accept(self),
+                ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                ExprType::ExprListT { expr_list_node } => {
+                    // must be only 1 expression in the list
+                    if expr_list_node.exprs_t.len() != 1 {
+                        // TODO: how to do this better.
+                        self.errors
+                            .push("Error - expression list is not testable.".to_string());
+                    }
+                    let x = expr_list_node.exprs_t.first().unwrap();
+                    x.accept(self);
+                }
+
+                _ => self.errors.push("TODO".to_string()),
+            }
+
+            let mut first_match = true;
+            for enumerator in &match_branch_node.enumerators {
+                if first_match {
+                    self.add_code(&format!(
+                        " == {}.{})",
+                        enum_match_test_node.enum_type_name, enumerator
+                    ));
+                    first_match = false;
+                } else {
+                    self.add_code(" || (");
+                    match &enum_match_test_node.expr_t {
+                        ExprType::CallExprT {
+                            call_expr_node: method_call_expr_node,
+                        } => method_call_expr_node.accept(self),
+                        ExprType::ActionCallExprT {
+                            action_call_expr_node,
+                        } => action_call_expr_node.accept(self),
+                        ExprType::CallChainLiteralExprT {
+                            call_chain_expr_node,
+                        } => call_chain_expr_node.accept(self),
+                        ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                        _ => self.errors.push("TODO".to_string()),
+                    }
+                    self.add_code(&format!(
+                        " == {}.{})",
+                        enum_match_test_node.enum_type_name, enumerator
+                    ));
+                }
+            }
+            self.add_code(":");
+            self.indent();
+
+            match_branch_node.accept(self);
+            if match_branch_node.statements.is_empty()
+                && match_branch_node.branch_terminator_expr_opt.is_none()
+            {
+                self.newline();
+                self.add_code("pass");
+            }
+
+            self.outdent();
+            self.newline();
+
+            if_or_else_if = "elif";
+        }
+
+        // (':' enum_test_else_branch)?
+        if let Some(enum_match_else_branch_node) = &enum_match_test_node.else_branch_node_opt {
+            enum_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_match_branch_node(
+        &mut self,
+        enum_match_test_match_branch_node: &EnumMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&enum_match_test_match_branch_node.statements);
+
+        match &enum_match_test_match_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.newline();
+                            self.add_code("return");
+                        }
+                        None => self.add_code("return"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break");
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_else_branch_node(
+        &mut self,
+        enum_match_test_else_branch_node: &EnumMatchTestElseBranchNode,
+    ) {
+        self.add_code("else:");
+        self.indent();
+
+        self.visit_decl_stmts(&enum_match_test_else_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &enum_match_test_else_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.newline();
+                            self.add_code("return");
+                        }
+                        None => self.add_code("return"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break");
+                    }
+                }
+            }
+            None => {}
+        }
+
+        self.outdent();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_string_match_test_pattern_node(
         &mut self,
         _string_match_test_else_branch_node: &StringMatchTestPatternNode,
@@ -2091,6 +2283,39 @@ impl AstVisitor for GdScript32Visitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.newline();
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(expr_t) => {
+                self.add_code("while ");
+                expr_t.accept(self);
+                self.add_code(":");
+            }
+            None => self.add_code("while true:"),
+        }
+        self.indent();
+
+        if loop_stmt_node.statements.is_empty() {
+            self.newline();
+            self.add_code("pass");
+        }
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+
+        self.outdent();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_loop_control_stmt_node(&mut self, loop_control_stmt_node: &LoopControlStmtNode) {
+        self.newline();
+        match loop_control_stmt_node.loop_control_t {
+            LoopControlType::Break => self.add_code("break"),
+            LoopControlType::Continue => self.add_code("continue"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_state_stack_operation_statement_node(
         &mut self,
         state_stack_op_statement_node: &StateStackOperationStatementNode,
@@ -2346,6 +2571,27 @@ impl AstVisitor for GdScript32Visitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enumerator_expr_node(&mut self, enumerator_expr_node: &EnumeratorExprNode) {
+        let mut output = String::new();
+        self.visit_enumerator_expr_node_to_string(enumerator_expr_node, &mut output);
+        self.add_code(&output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}.{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_binary_expr_node_to_string(
         &mut self,
         binary_expr_node: &BinaryExprNode,
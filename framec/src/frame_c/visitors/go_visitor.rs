@@ -0,0 +1,1580 @@
+use crate::frame_c::ast::*;
+use crate::frame_c::config::{FrameConfig, GoConfig};
+use crate::frame_c::scanner::{Token, TokenType};
+use crate::frame_c::symbol_table::*;
+use crate::frame_c::visitors::*;
+use std::collections::HashMap;
+
+/// One `if`/`else if` arm of a generated test.
+struct GoBranch<'a> {
+    condition: String,
+    // Statements binding regex captures at the top of the branch.
+    bindings: Vec<String>,
+    statements: &'a [DeclOrStmtType],
+    terminator_opt: &'a Option<TerminatorExpr>,
+}
+
+/// Generates a Go package from a Frame system. The system becomes a struct
+/// with exported interface methods, the `-actions-` block becomes an
+/// interface the caller implements, and each state is a method switching
+/// on the event message. State arguments and state variables are kept in
+/// untyped maps on a state context and asserted back to their declared
+/// types when read.
+pub struct GoVisitor {
+    compiler_version: String,
+    config: GoConfig,
+    arcanium: Arcanum,
+    symbol_config: SymbolConfig,
+    comments: Vec<Token>,
+    current_comment_idx: usize,
+    code: String,
+    dent: usize,
+    errors: Vec<String>,
+    system_name: String,
+    first_state_name: String,
+    has_states: bool,
+    current_state_name_opt: Option<String>,
+    // Declared types of the parameters of the event handler being visited.
+    current_event_param_types: HashMap<String, Option<String>>,
+    visiting_l_value: bool,
+    uses_regexp: bool,
+}
+
+impl GoVisitor {
+    pub fn new(
+        arcanium: Arcanum,
+        config: FrameConfig,
+        compiler_version: &str,
+        comments: Vec<Token>,
+    ) -> GoVisitor {
+        GoVisitor {
+            compiler_version: compiler_version.to_string(),
+            config: config.codegen.go,
+            arcanium,
+            symbol_config: SymbolConfig::new(),
+            comments,
+            current_comment_idx: 0,
+            code: String::new(),
+            dent: 0,
+            errors: Vec::new(),
+            system_name: String::new(),
+            first_state_name: String::new(),
+            has_states: false,
+            current_state_name_opt: None,
+            current_event_param_types: HashMap::new(),
+            visiting_l_value: false,
+            uses_regexp: false,
+        }
+    }
+
+    pub fn run(&mut self, system_node: &SystemNode) {
+        system_node.accept(self);
+    }
+
+    pub fn get_code(&self) -> String {
+        if !self.errors.is_empty() {
+            let mut error_list = String::new();
+            for error in &self.errors {
+                error_list.push_str(error);
+                error_list.push('\n');
+            }
+            error_list
+        } else {
+            self.code.clone()
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn add_code(&mut self, s: &str) {
+        self.code.push_str(s);
+    }
+
+    fn newline(&mut self) {
+        self.code.push('\n');
+        self.code.push_str(&"\t".repeat(self.dent));
+    }
+
+    fn indent(&mut self) {
+        self.dent += 1;
+    }
+
+    fn outdent(&mut self) {
+        self.dent -= 1;
+    }
+
+    fn generate_comment(&mut self, line: usize) {
+        while self.current_comment_idx < self.comments.len()
+            && line >= self.comments[self.current_comment_idx].line
+        {
+            let comment = self.comments[self.current_comment_idx].lexeme.clone();
+            if self.comments[self.current_comment_idx].token_type == TokenType::SingleLineComment {
+                self.newline();
+                self.add_code(&format!("// {}", comment[3..].trim()));
+            } else {
+                self.newline();
+                self.add_code(&format!("/* {} */", comment[3..comment.len() - 3].trim()));
+            }
+            self.current_comment_idx += 1;
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Interface methods and actions are exported, so `get_count` and
+    // `getCount` both become `GetCount`.
+    fn exported_name(name: &str) -> String {
+        name.split('_')
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let mut chars = part.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                    None => String::new(),
+                }
+            })
+            .collect()
+    }
+
+    fn format_type(type_opt: &Option<TypeNode>) -> String {
+        match type_opt {
+            Some(type_node) => type_node.get_type_str(),
+            None => String::from("interface{}"),
+        }
+    }
+
+    fn format_params(params_opt: &Option<Vec<ParameterNode>>) -> String {
+        match params_opt {
+            Some(params) => params
+                .iter()
+                .map(|param| {
+                    format!(
+                        "{} {}",
+                        param.param_name,
+                        GoVisitor::format_type(&param.param_type_opt)
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+            None => String::new(),
+        }
+    }
+
+    // Untyped declarations take the type of a literal initializer.
+    fn infer_type(expr_t_opt: &Option<ExprType>) -> Option<String> {
+        match expr_t_opt {
+            Some(ExprType::LiteralExprT { literal_expr_node }) => match literal_expr_node.token_t {
+                TokenType::Number if literal_expr_node.value.contains('.') => {
+                    Some(String::from("float64"))
+                }
+                TokenType::Number => Some(String::from("int")),
+                TokenType::String => Some(String::from("string")),
+                TokenType::True | TokenType::False => Some(String::from("bool")),
+                _ => None,
+            },
+            Some(ExprType::EnumeratorExprT {
+                enumerator_expr_node,
+            }) => Some(enumerator_expr_node.enum_type.clone()),
+            _ => None,
+        }
+    }
+
+    fn declared_or_inferred_type(variable_decl_node: &VariableDeclNode) -> Option<String> {
+        match &variable_decl_node.type_opt {
+            Some(type_node) => Some(type_node.get_type_str()),
+            None => GoVisitor::infer_type(&variable_decl_node.initializer_expr_t_opt),
+        }
+    }
+
+    fn format_state_type(&self) -> String {
+        format!("{}State", self.system_name)
+    }
+
+    fn format_state_name(&self, state_name: &str) -> String {
+        format!("{}State{}", self.system_name, state_name)
+    }
+
+    fn format_state_context_type(&self) -> String {
+        let mut chars = self.system_name.chars();
+        match chars.next() {
+            Some(first) => format!(
+                "{}{}StateContext",
+                first.to_lowercase().collect::<String>(),
+                chars.as_str()
+            ),
+            None => String::from("stateContext"),
+        }
+    }
+
+    fn format_frame_event_type(&self) -> String {
+        format!("{}FrameEvent", self.system_name)
+    }
+
+    fn format_expr(&mut self, expr_t: &ExprType) -> String {
+        let mut output = String::new();
+        expr_t.accept_to_string(self, &mut output);
+        output
+    }
+
+    fn format_args(&mut self, exprs_t: &[ExprType]) -> String {
+        let args: Vec<String> = exprs_t
+            .iter()
+            .map(|expr_t| self.format_expr(expr_t))
+            .collect();
+        format!("({})", args.join(", "))
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Values in the state context and event maps are `interface{}`, so
+    // reads are asserted to the declared type. Assignments write the map
+    // entry directly.
+
+    fn format_assertion(&self, access: String, type_opt: &Option<String>) -> String {
+        match type_opt {
+            Some(var_type) if !self.visiting_l_value => format!("{}.({})", access, var_type),
+            _ => access,
+        }
+    }
+
+    fn get_variable_type(symbol_type: &SymbolType) -> Option<String> {
+        match symbol_type {
+            SymbolType::StateParam {
+                state_param_symbol_rcref,
+            } => state_param_symbol_rcref
+                .borrow()
+                .param_type_opt
+                .as_ref()
+                .map(|type_node| type_node.get_type_str()),
+            SymbolType::StateVariable {
+                state_variable_symbol_rcref,
+            } => {
+                let variable_symbol = state_variable_symbol_rcref.borrow();
+                match (&variable_symbol.var_type, &variable_symbol.ast_node) {
+                    (Some(type_node), _) => Some(type_node.get_type_str()),
+                    (None, Some(ast_node)) => {
+                        GoVisitor::declared_or_inferred_type(&ast_node.borrow())
+                    }
+                    (None, None) => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn format_event_param(&self, name: &str) -> String {
+        let param_type = self
+            .current_event_param_types
+            .get(name)
+            .cloned()
+            .unwrap_or(None);
+        self.format_assertion(format!("e.Params[{:?}]", name), &param_type)
+    }
+
+    fn format_variable_expr(&self, variable_node: &VariableNode) -> String {
+        let name = &variable_node.id_node.name.lexeme;
+        match variable_node.scope {
+            IdentifierDeclScope::DomainBlock => format!("m.{}", name),
+            IdentifierDeclScope::StateParam | IdentifierDeclScope::StateVar => {
+                let map = if variable_node.scope == IdentifierDeclScope::StateParam {
+                    "stateArgs"
+                } else {
+                    "stateVars"
+                };
+                let var_type = match &variable_node.symbol_type_rcref_opt {
+                    Some(symbol_type_rcref) => {
+                        GoVisitor::get_variable_type(&symbol_type_rcref.borrow())
+                    }
+                    None => None,
+                };
+                self.format_assertion(format!("m.stateContext.{}[{:?}]", map, name), &var_type)
+            }
+            IdentifierDeclScope::EventHandlerParam => self.format_event_param(name),
+            _ => name.clone(),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_enums(&mut self, enum_decl_node: &EnumDeclNode) {
+        self.add_code(&format!("type {} int", enum_decl_node.name));
+        self.newline();
+        self.newline();
+        self.add_code("const (");
+        self.indent();
+        for (i, enumerator) in enum_decl_node.enumerators.iter().enumerate() {
+            self.newline();
+            if i == 0 {
+                self.add_code(&format!(
+                    "{}{} {} = iota",
+                    enum_decl_node.name, enumerator, enum_decl_node.name
+                ));
+            } else {
+                self.add_code(&format!("{}{}", enum_decl_node.name, enumerator));
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code(")");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_frame_event_type(&mut self) {
+        self.add_code(&format!(
+            "// {} carries a message, its parameters and a return value",
+            self.format_frame_event_type()
+        ));
+        self.newline();
+        self.add_code("// through the state machine.");
+        self.newline();
+        self.add_code(&format!(
+            "type {} struct {{",
+            self.format_frame_event_type()
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("Msg    string");
+        self.newline();
+        self.add_code("Params map[string]interface{}");
+        self.newline();
+        self.add_code("Ret    interface{}");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_actions_interface(&mut self, system_node: &SystemNode) {
+        self.add_code(&format!(
+            "// {}Actions is implemented by the caller to provide the actions of {}.",
+            self.system_name, self.system_name
+        ));
+        self.newline();
+        self.add_code(&format!("type {}Actions interface {{", self.system_name));
+        self.indent();
+        if let Some(actions_block_node) = &system_node.actions_block_node_opt {
+            for action_rcref in &actions_block_node.actions {
+                let action_node = action_rcref.borrow();
+                let return_type = match &action_node.type_opt {
+                    Some(type_node) => format!(" {}", type_node.get_type_str()),
+                    None => String::new(),
+                };
+                self.newline();
+                self.add_code(&format!(
+                    "{}({}){}",
+                    GoVisitor::exported_name(&action_node.name),
+                    GoVisitor::format_params(&action_node.params),
+                    return_type
+                ));
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_state_types(&mut self, system_node: &SystemNode) {
+        let state_type = self.format_state_type();
+        self.add_code(&format!(
+            "// {} identifies a state of {}.",
+            state_type, self.system_name
+        ));
+        self.newline();
+        self.add_code(&format!("type {} uint", state_type));
+        self.newline();
+        self.newline();
+        self.add_code("const (");
+        self.indent();
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for (i, state_node_rcref) in machine_block_node.states.iter().enumerate() {
+                let state_name = self.format_state_name(&state_node_rcref.borrow().name);
+                self.newline();
+                if i == 0 {
+                    self.add_code(&format!("{} {} = iota", state_name, state_type));
+                } else {
+                    self.add_code(&state_name);
+                }
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code(")");
+        self.newline();
+        self.newline();
+
+        let state_context_type = self.format_state_context_type();
+        self.add_code(&format!("type {} struct {{", state_context_type));
+        self.indent();
+        self.newline();
+        self.add_code(&format!("state     {}", state_type));
+        self.newline();
+        self.add_code("stateArgs map[string]interface{}");
+        self.newline();
+        self.add_code("stateVars map[string]interface{}");
+        self.newline();
+        self.add_code("enterArgs map[string]interface{}");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+        self.add_code(&format!(
+            "func new{}(state {}) *{} {{",
+            GoVisitor::exported_name(&state_context_type),
+            state_type,
+            state_context_type
+        ));
+        self.indent();
+        self.newline();
+        self.add_code(&format!("return &{}{{", state_context_type));
+        self.indent();
+        self.newline();
+        self.add_code("state:     state,");
+        self.newline();
+        self.add_code("stateArgs: map[string]interface{}{},");
+        self.newline();
+        self.add_code("stateVars: map[string]interface{}{},");
+        self.newline();
+        self.add_code("enterArgs: map[string]interface{}{},");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_system_struct(&mut self, system_node: &SystemNode) {
+        self.add_code(&format!("type {} struct {{", self.system_name));
+        self.indent();
+        self.newline();
+        self.add_code(&format!("actions      {}Actions", self.system_name));
+        if self.has_states {
+            let state_context_type = self.format_state_context_type();
+            self.newline();
+            self.add_code(&format!("stateContext *{}", state_context_type));
+            self.newline();
+            self.add_code(&format!("stateStack   []*{}", state_context_type));
+        }
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for variable_decl_node_rcref in &domain_block_node.member_variables {
+                let variable_decl_node = variable_decl_node_rcref.borrow();
+                let var_type = GoVisitor::declared_or_inferred_type(&variable_decl_node)
+                    .unwrap_or_else(|| String::from("interface{}"));
+                self.newline();
+                self.add_code(&format!("{} {}", variable_decl_node.name, var_type));
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_constructor(&mut self, system_node: &SystemNode) {
+        self.add_code(&format!(
+            "func New{}(actions {}Actions) *{} {{",
+            GoVisitor::exported_name(&self.system_name),
+            self.system_name,
+            self.system_name
+        ));
+        self.indent();
+        self.newline();
+        self.add_code(&format!("m := &{}{{actions: actions}}", self.system_name));
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for variable_decl_node_rcref in &domain_block_node.member_variables {
+                let variable_decl_node = variable_decl_node_rcref.borrow();
+                if let Some(expr_t) = &variable_decl_node.initializer_expr_t_opt {
+                    let value = self.format_expr(expr_t);
+                    self.newline();
+                    self.add_code(&format!("m.{} = {}", variable_decl_node.name, value));
+                }
+            }
+        }
+        if self.has_states {
+            let first_state_name = self.first_state_name.clone();
+            self.newline();
+            self.add_code(&format!(
+                "m.stateContext = new{}({})",
+                GoVisitor::exported_name(&self.format_state_context_type()),
+                self.format_state_name(&first_state_name)
+            ));
+            self.generate_state_vars(&first_state_name, "m.stateContext");
+        }
+        self.newline();
+        self.add_code("return m");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        if self.has_states {
+            self.newline();
+            self.add_code("// State returns the current state of the machine.");
+            self.newline();
+            self.add_code(&format!(
+                "func (m *{}) State() {} {{",
+                self.system_name,
+                self.format_state_type()
+            ));
+            self.indent();
+            self.newline();
+            self.add_code("return m.stateContext.state");
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+            self.newline();
+        }
+    }
+
+    fn generate_state_vars(&mut self, state_name: &str, state_context: &str) {
+        if let Some(state_symbol_rcref) = self.arcanium.get_state(state_name) {
+            let state_symbol = state_symbol_rcref.borrow();
+            let state_node = state_symbol.state_node.as_ref().unwrap().borrow();
+            if let Some(vars) = &state_node.vars_opt {
+                for var_rcref in vars {
+                    let var = var_rcref.borrow();
+                    let expr_t = var.initializer_expr_t_opt.as_ref().unwrap();
+                    let value = self.format_expr(expr_t);
+                    self.newline();
+                    self.add_code(&format!(
+                        "{}.stateVars[{:?}] = {}",
+                        state_context, var.name, value
+                    ));
+                }
+            }
+        }
+    }
+
+    fn generate_machinery(&mut self, system_node: &SystemNode) {
+        let event_type = self.format_frame_event_type();
+        let state_context_type = self.format_state_context_type();
+
+        self.newline();
+        self.add_code("//=============== Machinery and Mechanisms ==============//");
+        self.newline();
+        self.newline();
+        self.add_code(&format!(
+            "func (m *{}) dispatchEvent(e *{}) {{",
+            self.system_name, event_type
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("switch m.stateContext.state {");
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_name = state_node_rcref.borrow().name.clone();
+                self.newline();
+                self.add_code(&format!("case {}:", self.format_state_name(&state_name)));
+                self.indent();
+                self.newline();
+                self.add_code(&format!("m.state{}(e)", state_name));
+                self.outdent();
+            }
+        }
+        self.newline();
+        self.add_code("}");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        self.newline();
+        self.add_code(&format!(
+            "func (m *{}) transition(exitArgs map[string]interface{{}}, stateContext *{}) {{",
+            self.system_name, state_context_type
+        ));
+        self.indent();
+        self.newline();
+        self.add_code(&format!(
+            "m.dispatchEvent(&{}{{Msg: {:?}, Params: exitArgs}})",
+            event_type, self.symbol_config.exit_msg_symbol
+        ));
+        self.newline();
+        self.add_code("m.stateContext = stateContext");
+        self.newline();
+        self.add_code(&format!(
+            "m.dispatchEvent(&{}{{Msg: {:?}, Params: stateContext.enterArgs}})",
+            event_type, self.symbol_config.enter_msg_symbol
+        ));
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        self.newline();
+        self.add_code(&format!(
+            "func (m *{}) changeState(stateContext *{}) {{",
+            self.system_name, state_context_type
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("m.stateContext = stateContext");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        self.newline();
+        self.add_code(&format!(
+            "func (m *{}) stateStackPush() {{",
+            self.system_name
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("m.stateStack = append(m.stateStack, m.stateContext)");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        self.newline();
+        self.add_code(&format!(
+            "func (m *{}) stateStackPop() *{} {{",
+            self.system_name, state_context_type
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("if len(m.stateStack) == 0 {");
+        self.indent();
+        self.newline();
+        self.add_code("panic(\"state stack is empty\")");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.add_code("stateContext := m.stateStack[len(m.stateStack)-1]");
+        self.newline();
+        self.add_code("m.stateStack = m.stateStack[:len(m.stateStack)-1]");
+        self.newline();
+        self.add_code("return stateContext");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_interface_method(&mut self, interface_method_node: &InterfaceMethodNode) {
+        let message = match &interface_method_node.alias {
+            Some(alias_message_node) => &alias_message_node.name,
+            None => &interface_method_node.name,
+        };
+        let return_type = interface_method_node
+            .return_type_opt
+            .as_ref()
+            .map(|type_node| type_node.get_type_str());
+
+        self.newline();
+        self.add_code(&format!(
+            "func (m *{}) {}({}){} {{",
+            self.system_name,
+            GoVisitor::exported_name(&interface_method_node.name),
+            GoVisitor::format_params(&interface_method_node.params),
+            match &return_type {
+                Some(return_type) => format!(" {}", return_type),
+                None => String::new(),
+            }
+        ));
+        self.indent();
+        self.newline();
+        match &interface_method_node.params {
+            Some(params) => {
+                let entries: Vec<String> = params
+                    .iter()
+                    .map(|param| format!("{:?}: {}", param.param_name, param.param_name))
+                    .collect();
+                self.add_code(&format!(
+                    "e := {}{{Msg: {:?}, Params: map[string]interface{{}}{{{}}}}}",
+                    self.format_frame_event_type(),
+                    message,
+                    entries.join(", ")
+                ));
+            }
+            None => {
+                self.add_code(&format!(
+                    "e := {}{{Msg: {:?}}}",
+                    self.format_frame_event_type(),
+                    message
+                ));
+            }
+        }
+        if self.has_states {
+            self.newline();
+            self.add_code("m.dispatchEvent(&e)");
+        }
+        if let Some(return_type) = &return_type {
+            self.newline();
+            self.add_code(&format!("ret, _ := e.Ret.({})", return_type));
+            self.newline();
+            self.add_code("return ret");
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    fn generate_event_handler(&mut self, evt_handler_node: &EventHandlerNode) {
+        self.newline();
+        match &evt_handler_node.msg_t {
+            MessageType::CustomMessage { message_node } => {
+                self.add_code(&format!("case {:?}:", message_node.name));
+            }
+            MessageType::AnyMessage { .. } => self.add_code("default:"),
+        }
+        self.indent();
+        self.generate_comment(evt_handler_node.line);
+
+        self.current_event_param_types.clear();
+        if let Some(params) = &evt_handler_node.event_symbol_rcref.borrow().params_opt {
+            for param in params {
+                self.current_event_param_types.insert(
+                    param.name.clone(),
+                    param
+                        .param_type_opt
+                        .as_ref()
+                        .map(|type_node| type_node.get_type_str()),
+                );
+            }
+        }
+
+        self.visit_decl_stmts(&evt_handler_node.statements);
+        self.generate_terminator(&evt_handler_node.terminator_node);
+        self.outdent();
+    }
+
+    fn generate_terminator(&mut self, terminator_expr: &TerminatorExpr) {
+        match &terminator_expr.terminator_type {
+            TerminatorType::Return => {
+                if let Some(expr_t) = &terminator_expr.return_expr_t_opt {
+                    let value = self.format_expr(expr_t);
+                    self.newline();
+                    self.add_code(&format!("e.Ret = {}", value));
+                }
+                self.newline();
+                self.add_code("return");
+            }
+            TerminatorType::Continue => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_decl_stmts(&mut self, decl_stmt_types: &[DeclOrStmtType]) {
+        for decl_stmt_t in decl_stmt_types {
+            match decl_stmt_t {
+                DeclOrStmtType::VarDeclT { var_decl_t_rc_ref } => {
+                    let variable_decl_node = var_decl_t_rc_ref.borrow();
+                    self.generate_var_decl(&variable_decl_node);
+                }
+                DeclOrStmtType::StmtT { stmt_t } => match stmt_t {
+                    StatementType::ExpressionStmt { expr_stmt_t } => {
+                        self.generate_expr_stmt(expr_stmt_t);
+                    }
+                    StatementType::TransitionStmt {
+                        transition_statement,
+                    } => {
+                        self.generate_transition(transition_statement);
+                    }
+                    StatementType::ChangeStateStmt { change_state_stmt } => {
+                        self.generate_change_state(change_state_stmt);
+                    }
+                    StatementType::TestStmt { test_stmt_node } => {
+                        self.generate_test(&test_stmt_node.test_t);
+                    }
+                    StatementType::StateStackStmt {
+                        state_stack_operation_statement_node,
+                    } => {
+                        self.newline();
+                        match state_stack_operation_statement_node
+                            .state_stack_operation_node
+                            .operation_t
+                        {
+                            StateStackOperationType::Push => self.add_code("m.stateStackPush()"),
+                            StateStackOperationType::Pop => self.add_code("m.stateStackPop()"),
+                        }
+                    }
+                    StatementType::LoopStmt { loop_stmt_node } => {
+                        self.generate_loop(loop_stmt_node);
+                    }
+                    StatementType::LoopControlStmt {
+                        loop_control_stmt_node,
+                    } => {
+                        self.newline();
+                        match loop_control_stmt_node.loop_control_t {
+                            LoopControlType::Break => self.add_code("break"),
+                            LoopControlType::Continue => self.add_code("continue"),
+                        }
+                    }
+                    StatementType::NoStmt => {}
+                },
+            }
+        }
+    }
+
+    fn generate_var_decl(&mut self, variable_decl_node: &VariableDeclNode) {
+        let value = match &variable_decl_node.initializer_expr_t_opt {
+            Some(expr_t) => self.format_expr(expr_t),
+            None => String::from("nil"),
+        };
+        self.newline();
+        match &variable_decl_node.type_opt {
+            Some(type_node) => self.add_code(&format!(
+                "var {} {} = {}",
+                variable_decl_node.name,
+                type_node.get_type_str(),
+                value
+            )),
+            None => self.add_code(&format!("{} := {}", variable_decl_node.name, value)),
+        }
+    }
+
+    fn generate_expr_stmt(&mut self, expr_stmt_t: &ExprStmtType) {
+        let code = match expr_stmt_t {
+            ExprStmtType::CallStmtT { call_stmt_node } => {
+                let mut output = String::new();
+                self.visit_call_expression_node_to_string(
+                    &call_stmt_node.call_expr_node,
+                    &mut output,
+                );
+                output
+            }
+            ExprStmtType::ActionCallStmtT {
+                action_call_stmt_node,
+            } => {
+                let mut output = String::new();
+                self.visit_action_call_expression_node_to_string(
+                    &action_call_stmt_node.action_call_expr_node,
+                    &mut output,
+                );
+                output
+            }
+            ExprStmtType::CallChainLiteralStmtT {
+                call_chain_literal_stmt_node,
+            } => {
+                let mut output = String::new();
+                self.visit_call_chain_literal_expr_node_to_string(
+                    &call_chain_literal_stmt_node.call_chain_literal_expr_node,
+                    &mut output,
+                );
+                output
+            }
+            ExprStmtType::AssignmentStmtT {
+                assignment_stmt_node,
+            } => {
+                self.generate_comment(assignment_stmt_node.get_line());
+                let mut output = String::new();
+                self.visit_assignment_expr_node_to_string(
+                    &assignment_stmt_node.assignment_expr_node,
+                    &mut output,
+                );
+                output
+            }
+            ExprStmtType::VariableStmtT { variable_stmt_node } => {
+                // Go rejects expressions that are evaluated but not used.
+                format!(
+                    "_ = {}",
+                    self.format_variable_expr(&variable_stmt_node.var_node)
+                )
+            }
+        };
+        self.newline();
+        self.add_code(&code);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_label(&mut self, label_opt: &Option<String>) {
+        if let Some(label) = label_opt {
+            self.newline();
+            self.add_code(&format!("// {}", label));
+        }
+    }
+
+    // Builds an argument map keyed by the parameter names of an event.
+    fn generate_event_args(&mut self, msg: &str, args: &ExprListNode, var_name: &str) -> bool {
+        let event_sym_opt = self.arcanium.get_event(msg, &self.current_state_name_opt);
+        let params = match event_sym_opt {
+            Some(event_sym) => match &event_sym.borrow().params_opt {
+                Some(params) => params
+                    .iter()
+                    .map(|param| param.name.clone())
+                    .collect::<Vec<String>>(),
+                None => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+        if params.len() != args.exprs_t.len() {
+            self.errors.push(format!(
+                "Invalid number of arguments for \"{}\" event handler.",
+                msg
+            ));
+            return false;
+        }
+        for (param, expr_t) in params.iter().zip(args.exprs_t.iter()) {
+            let value = self.format_expr(expr_t);
+            self.newline();
+            self.add_code(&format!("{}[{:?}] = {}", var_name, param, value));
+        }
+        true
+    }
+
+    fn generate_exit_args(&mut self, exit_args_opt: &Option<ExprListNode>) -> String {
+        match exit_args_opt {
+            Some(exit_args) if !exit_args.exprs_t.is_empty() => {
+                let msg = format!(
+                    "{}:{}",
+                    self.current_state_name_opt.clone().unwrap_or_default(),
+                    self.symbol_config.exit_msg_symbol
+                );
+                self.newline();
+                self.add_code("exitArgs := map[string]interface{}{}");
+                self.generate_event_args(&msg, exit_args, "exitArgs");
+                String::from("exitArgs")
+            }
+            _ => String::from("nil"),
+        }
+    }
+
+    // Declares `stateContext` for the target state and fills in its state
+    // arguments and state variables.
+    fn generate_new_state_context(
+        &mut self,
+        target_state_name: &str,
+        state_args_opt: &Option<ExprListNode>,
+    ) {
+        self.newline();
+        self.add_code(&format!(
+            "stateContext := new{}({})",
+            GoVisitor::exported_name(&self.format_state_context_type()),
+            self.format_state_name(target_state_name)
+        ));
+        if let Some(state_args) = state_args_opt {
+            let params = match self.arcanium.get_state(target_state_name) {
+                Some(state_symbol_rcref) => match &state_symbol_rcref.borrow().params_opt {
+                    Some(params) => params
+                        .iter()
+                        .map(|param| param.borrow().name.clone())
+                        .collect::<Vec<String>>(),
+                    None => Vec::new(),
+                },
+                None => Vec::new(),
+            };
+            if params.len() != state_args.exprs_t.len() {
+                self.errors.push(format!(
+                    "Invalid number of arguments for \"{}\" state parameters.",
+                    target_state_name
+                ));
+            }
+            for (param, expr_t) in params.iter().zip(state_args.exprs_t.iter()) {
+                let value = self.format_expr(expr_t);
+                self.newline();
+                self.add_code(&format!("stateContext.stateArgs[{:?}] = {}", param, value));
+            }
+        }
+        self.generate_state_vars(target_state_name, "stateContext");
+    }
+
+    fn generate_transition(&mut self, transition_statement: &TransitionStatementNode) {
+        self.generate_label(&transition_statement.label_opt);
+        let exit_args = self.generate_exit_args(&transition_statement.exit_args_opt);
+        match &transition_statement.target_state_context_t {
+            StateContextType::StateRef { state_context_node } => {
+                let target_state_name = &state_context_node.state_ref_node.name;
+                self.generate_new_state_context(
+                    target_state_name,
+                    &state_context_node.state_ref_args_opt,
+                );
+                if let Some(enter_args) = &state_context_node.enter_args_opt {
+                    let msg = format!(
+                        "{}:{}",
+                        target_state_name, self.symbol_config.enter_msg_symbol
+                    );
+                    self.generate_event_args(&msg, enter_args, "stateContext.enterArgs");
+                }
+                self.newline();
+                self.add_code(&format!("m.transition({}, stateContext)", exit_args));
+            }
+            StateContextType::StateStackPop {} => {
+                self.newline();
+                self.add_code(&format!("m.transition({}, m.stateStackPop())", exit_args));
+            }
+        }
+    }
+
+    fn generate_change_state(&mut self, change_state_stmt_node: &ChangeStateStatementNode) {
+        self.generate_label(&change_state_stmt_node.label_opt);
+        match &change_state_stmt_node.state_context_t {
+            StateContextType::StateRef { state_context_node } => {
+                self.generate_new_state_context(
+                    &state_context_node.state_ref_node.name,
+                    &state_context_node.state_ref_args_opt,
+                );
+                self.newline();
+                self.add_code("m.changeState(stateContext)");
+            }
+            StateContextType::StateStackPop {} => {
+                self.newline();
+                self.add_code("m.changeState(m.stateStackPop())");
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_branch_body(
+        &mut self,
+        statements: &[DeclOrStmtType],
+        terminator_opt: &Option<TerminatorExpr>,
+    ) {
+        self.indent();
+        self.visit_decl_stmts(statements);
+        if let Some(terminator_expr) = terminator_opt {
+            self.generate_terminator(terminator_expr);
+        }
+        self.outdent();
+    }
+
+    fn generate_if_chain(
+        &mut self,
+        branches: Vec<GoBranch>,
+        else_opt: Option<(&[DeclOrStmtType], &Option<TerminatorExpr>)>,
+    ) {
+        self.newline();
+        for (i, branch) in branches.iter().enumerate() {
+            if i == 0 {
+                self.add_code(&format!("if {} {{", branch.condition));
+            } else {
+                self.add_code(&format!("}} else if {} {{", branch.condition));
+            }
+            self.indent();
+            for binding in &branch.bindings {
+                self.newline();
+                self.add_code(binding);
+            }
+            self.outdent();
+            self.generate_branch_body(branch.statements, branch.terminator_opt);
+            self.newline();
+        }
+        if let Some((statements, terminator_opt)) = else_opt {
+            self.add_code("} else {");
+            self.generate_branch_body(statements, terminator_opt);
+            self.newline();
+        }
+        self.add_code("}");
+    }
+
+    fn generate_test(&mut self, test_t: &TestType) {
+        match test_t {
+            TestType::BoolTest { bool_test_node } => {
+                let mut branches = Vec::new();
+                for branch_node in &bool_test_node.conditional_branch_nodes {
+                    let mut condition = self.format_expr(&branch_node.expr_t);
+                    if branch_node.is_negated {
+                        condition = format!("!({})", condition);
+                    }
+                    branches.push(GoBranch {
+                        condition,
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    });
+                }
+                let else_opt = bool_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::StringMatchTest {
+                string_match_test_node,
+            } => {
+                let subject = self.format_expr(&string_match_test_node.expr_t);
+                let branches = string_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| GoBranch {
+                        condition: branch_node
+                            .string_match_pattern_node
+                            .match_pattern_strings
+                            .iter()
+                            .map(|pattern| format!("{} == {:?}", subject, pattern))
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = string_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::NumberMatchTest {
+                number_match_test_node,
+            } => {
+                let subject = self.format_expr(&number_match_test_node.expr_t);
+                let branches = number_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| GoBranch {
+                        condition: branch_node
+                            .number_match_pattern_nodes
+                            .iter()
+                            .map(|pattern| {
+                                format!("{} == {}", subject, pattern.match_pattern_number)
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = number_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                self.uses_regexp = true;
+                let subject = self.format_expr(&regex_match_test_node.expr_t);
+                let branches = regex_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| {
+                        let pattern_node = &branch_node.regex_match_pattern_node;
+                        let pattern = pattern_node.pattern_with_group_prefix("?P<");
+                        let literal = if pattern.contains('`') {
+                            format!("{:?}", pattern)
+                        } else {
+                            format!("`{}`", pattern)
+                        };
+                        let mut bindings = Vec::new();
+                        if !pattern_node.capture_names.is_empty() {
+                            bindings
+                                .push(format!("regexMatch := re.FindStringSubmatch({})", subject));
+                            for name in &pattern_node.capture_names {
+                                bindings.push(format!(
+                                    "{} := regexMatch[re.SubexpIndex({:?})]",
+                                    name, name
+                                ));
+                            }
+                        }
+                        GoBranch {
+                            condition: format!(
+                                "re := regexp.MustCompile({}); re.MatchString({})",
+                                literal, subject
+                            ),
+                            bindings,
+                            statements: &branch_node.statements,
+                            terminator_opt: &branch_node.branch_terminator_expr_opt,
+                        }
+                    })
+                    .collect();
+                let else_opt = regex_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                let subject = self.format_expr(&enum_match_test_node.expr_t);
+                let branches = enum_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| GoBranch {
+                        condition: branch_node
+                            .enumerators
+                            .iter()
+                            .map(|enumerator| {
+                                format!(
+                                    "{} == {}{}",
+                                    subject, enum_match_test_node.enum_type_name, enumerator
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = enum_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+        }
+    }
+
+    fn generate_loop(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.newline();
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(condition_expr_t) => {
+                let condition = self.format_expr(condition_expr_t);
+                self.add_code(&format!("for {} {{", condition));
+            }
+            None => self.add_code("for {"),
+        }
+        self.indent();
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+}
+
+//* --------------------------------------------------------------------- *//
+
+impl AstVisitor for GoVisitor {
+    fn visit_system_node(&mut self, system_node: &SystemNode) {
+        self.system_name = system_node.name.clone();
+        if system_node.has_deferred_events() {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the go target.",
+            ));
+        }
+        if let Some(first_state) = system_node.get_first_state() {
+            self.first_state_name = first_state.borrow().name.clone();
+            self.has_states = true;
+        }
+
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for enum_decl_node_rcref in &domain_block_node.enums {
+                self.generate_enums(&enum_decl_node_rcref.borrow());
+            }
+        }
+        self.generate_frame_event_type();
+        self.generate_actions_interface(system_node);
+        if self.has_states {
+            self.generate_state_types(system_node);
+        }
+        self.generate_system_struct(system_node);
+        self.generate_constructor(system_node);
+
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            interface_block_node.accept(self);
+        }
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            machine_block_node.accept(self);
+        }
+        if self.has_states {
+            self.generate_machinery(system_node);
+        }
+
+        // The imports are only known once the body has been generated.
+        let package_name = if self.config.code.package_name.is_empty() {
+            self.system_name.to_lowercase()
+        } else {
+            self.config.code.package_name.clone()
+        };
+        let mut header = format!(
+            "// {}\n\npackage {}\n\n",
+            self.compiler_version, package_name
+        );
+        if self.uses_regexp {
+            header.push_str("import \"regexp\"\n\n");
+        }
+        self.code.insert_str(0, &header);
+    }
+
+    fn visit_interface_block_node(&mut self, interface_block_node: &InterfaceBlockNode) {
+        self.newline();
+        self.add_code("//===================== Interface Block ===================//");
+        self.newline();
+        for interface_method_node_rcref in &interface_block_node.interface_methods {
+            self.generate_interface_method(&interface_method_node_rcref.borrow());
+        }
+    }
+
+    fn visit_machine_block_node(&mut self, machine_block_node: &MachineBlockNode) {
+        self.newline();
+        self.add_code("//===================== Machine Block ===================//");
+        self.newline();
+        for state_node_rcref in &machine_block_node.states {
+            state_node_rcref.borrow().accept(self);
+        }
+    }
+
+    fn visit_state_node(&mut self, state_node: &StateNode) {
+        self.generate_comment(state_node.line);
+        self.current_state_name_opt = Some(state_node.name.clone());
+        self.newline();
+        self.add_code(&format!(
+            "func (m *{}) state{}(e *{}) {{",
+            self.system_name,
+            state_node.name,
+            self.format_frame_event_type()
+        ));
+        self.indent();
+
+        if let Some(calls) = &state_node.calls_opt {
+            for call in calls {
+                let mut output = String::new();
+                self.visit_call_chain_literal_expr_node_to_string(call, &mut output);
+                self.newline();
+                self.add_code(&output);
+            }
+        }
+
+        if !state_node.evt_handlers_rcref.is_empty() {
+            self.newline();
+            self.add_code("switch e.Msg {");
+            for evt_handler_node in &state_node.evt_handlers_rcref {
+                self.generate_event_handler(&evt_handler_node.borrow());
+            }
+            self.newline();
+            self.add_code("}");
+        }
+
+        // Events the state does not handle go to its parent.
+        if let Some(dispatch_node) = &state_node.dispatch_opt {
+            self.newline();
+            self.add_code(&format!(
+                "m.state{}(e)",
+                dispatch_node.target_state_ref.name
+            ));
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.current_state_name_opt = None;
+    }
+
+    fn visit_action_node_rust_trait(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_action_node_rust_trait() not implemented.");
+    }
+
+    fn visit_actions_node_rust_impl(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_actions_node_rust_impl() not implemented.");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_assignment_expr_node_to_string(
+        &mut self,
+        assignment_expr_node: &AssignmentExprNode,
+        output: &mut String,
+    ) {
+        self.visiting_l_value = true;
+        assignment_expr_node
+            .l_value_box
+            .accept_to_string(self, output);
+        self.visiting_l_value = false;
+        output.push_str(" = ");
+        assignment_expr_node
+            .r_value_box
+            .accept_to_string(self, output);
+    }
+
+    fn visit_expression_list_node_to_string(
+        &mut self,
+        expr_list: &ExprListNode,
+        output: &mut String,
+    ) {
+        let args = self.format_args(&expr_list.exprs_t);
+        output.push_str(&args);
+    }
+
+    fn visit_call_expr_list_node_to_string(
+        &mut self,
+        call_expr_list: &CallExprListNode,
+        output: &mut String,
+    ) {
+        let args = self.format_args(&call_expr_list.exprs_t);
+        output.push_str(&args);
+    }
+
+    fn visit_call_expression_node_to_string(
+        &mut self,
+        method_call: &CallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&method_call.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(&method_call.call_expr_list, output);
+    }
+
+    fn visit_action_call_expression_node_to_string(
+        &mut self,
+        action_call: &ActionCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "m.actions.{}",
+            GoVisitor::exported_name(&action_call.identifier.name.lexeme)
+        ));
+        self.visit_call_expr_list_node_to_string(&action_call.call_expr_list, output);
+    }
+
+    fn visit_interface_method_call_expression_node_to_string(
+        &mut self,
+        interface_method_call_expr_node: &InterfaceMethodCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "m.{}",
+            GoVisitor::exported_name(&interface_method_call_expr_node.identifier.name.lexeme)
+        ));
+        self.visit_call_expr_list_node_to_string(
+            &interface_method_call_expr_node.call_expr_list,
+            output,
+        );
+    }
+
+    fn visit_call_chain_literal_expr_node_to_string(
+        &mut self,
+        method_call_chain_expression_node: &CallChainLiteralExprNode,
+        output: &mut String,
+    ) {
+        let mut separator = "";
+        for node in &method_call_chain_expression_node.call_chain {
+            output.push_str(separator);
+            match node {
+                CallChainLiteralNodeType::VariableNodeT { var_node } => {
+                    output.push_str(&self.format_variable_expr(var_node));
+                }
+                CallChainLiteralNodeType::IdentifierNodeT { id_node } => {
+                    output.push_str(&id_node.name.lexeme);
+                }
+                CallChainLiteralNodeType::CallT { call } => {
+                    self.visit_call_expression_node_to_string(call, output);
+                }
+                CallChainLiteralNodeType::InterfaceMethodCallT {
+                    interface_method_call_expr_node,
+                } => {
+                    self.visit_interface_method_call_expression_node_to_string(
+                        interface_method_call_expr_node,
+                        output,
+                    );
+                }
+                CallChainLiteralNodeType::ActionCallT {
+                    action_call_expr_node,
+                } => {
+                    self.visit_action_call_expression_node_to_string(action_call_expr_node, output);
+                }
+            }
+            separator = ".";
+        }
+    }
+
+    fn visit_literal_expression_node_to_string(
+        &mut self,
+        literal_expression_node: &LiteralExprNode,
+        output: &mut String,
+    ) {
+        match &literal_expression_node.token_t {
+            TokenType::String => {
+                output.push_str(&format!("\"{}\"", literal_expression_node.value));
+            }
+            TokenType::True => output.push_str("true"),
+            TokenType::False => output.push_str("false"),
+            TokenType::Nil | TokenType::Null => output.push_str("nil"),
+            _ => output.push_str(&literal_expression_node.value),
+        }
+    }
+
+    fn visit_variable_expr_node_to_string(
+        &mut self,
+        variable_node: &VariableNode,
+        output: &mut String,
+    ) {
+        output.push_str(&self.format_variable_expr(variable_node));
+    }
+
+    fn visit_frame_event_part_to_string(
+        &mut self,
+        frame_event_part: &FrameEventPart,
+        output: &mut String,
+    ) {
+        match frame_event_part {
+            FrameEventPart::Event { .. } => output.push('e'),
+            FrameEventPart::Message { .. } => output.push_str("e.Msg"),
+            FrameEventPart::Param { param_tok, .. } => {
+                output.push_str(&self.format_event_param(&param_tok.lexeme))
+            }
+            FrameEventPart::Return { .. } => output.push_str("e.Ret"),
+        }
+    }
+
+    fn visit_unary_expr_node_to_string(
+        &mut self,
+        unary_expr_node: &UnaryExprNode,
+        output: &mut String,
+    ) {
+        unary_expr_node.operator.accept_to_string(self, output);
+        unary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_binary_expr_node_to_string(
+        &mut self,
+        binary_expr_node: &BinaryExprNode,
+        output: &mut String,
+    ) {
+        binary_expr_node
+            .left_rcref
+            .borrow()
+            .accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node.operator.accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    fn visit_operator_type_to_string(&mut self, operator_type: &OperatorType, output: &mut String) {
+        let operator = match operator_type {
+            OperatorType::Plus => "+",
+            OperatorType::Minus | OperatorType::Negated => "-",
+            OperatorType::Multiply => "*",
+            OperatorType::Divide => "/",
+            OperatorType::Greater => ">",
+            OperatorType::GreaterEqual => ">=",
+            OperatorType::Less => "<",
+            OperatorType::LessEqual => "<=",
+            OperatorType::EqualEqual => "==",
+            OperatorType::NotEqual => "!=",
+            OperatorType::Not => "!",
+            OperatorType::LogicalAnd => "&&",
+            OperatorType::LogicalOr => "||",
+            // Booleans differ exactly when one of them is true.
+            OperatorType::LogicalXor => "!=",
+        };
+        output.push_str(operator);
+    }
+}
@@ -0,0 +1,1551 @@
+use crate::frame_c::ast::*;
+use crate::frame_c::scanner::{Token, TokenType};
+use crate::frame_c::symbol_table::*;
+use crate::frame_c::visitors::*;
+use std::collections::HashMap;
+
+// Identifiers that Kotlin reserves everywhere. Soft and modifier keywords
+// are valid names.
+const KOTLIN_HARD_KEYWORDS: [&str; 28] = [
+    "as",
+    "break",
+    "class",
+    "continue",
+    "do",
+    "else",
+    "false",
+    "for",
+    "fun",
+    "if",
+    "in",
+    "interface",
+    "is",
+    "null",
+    "object",
+    "package",
+    "return",
+    "super",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typealias",
+    "typeof",
+    "val",
+    "var",
+    "when",
+    "while",
+];
+
+/// One `if`/`else if` arm of a generated test.
+struct KotlinBranch<'a> {
+    condition: String,
+    // Statements binding regex captures at the top of the branch.
+    bindings: Vec<String>,
+    statements: &'a [DeclOrStmtType],
+    terminator_opt: &'a Option<TerminatorExpr>,
+}
+
+/// Generates Kotlin from a Frame system. Events and states are sealed
+/// class hierarchies: each message is an event subclass carrying its
+/// parameters, and each state is a subclass holding its state parameters
+/// and state variables as properties, so the state stack simply keeps
+/// state instances. The `-actions-` block becomes an interface passed to
+/// the system's constructor.
+pub struct KotlinVisitor {
+    compiler_version: String,
+    arcanium: Arcanum,
+    symbol_config: SymbolConfig,
+    comments: Vec<Token>,
+    current_comment_idx: usize,
+    code: String,
+    dent: usize,
+    errors: Vec<String>,
+    system_name: String,
+    first_state_name: String,
+    has_states: bool,
+    current_state_name_opt: Option<String>,
+    // Declared types of the parameters of the event handler being visited.
+    current_event_param_types: HashMap<String, Option<String>>,
+    current_event_is_enter_or_exit: bool,
+    // State variable initializers are generated inside the state class,
+    // where state parameters and variables are in scope by name.
+    visiting_state_class: bool,
+}
+
+impl KotlinVisitor {
+    pub fn new(arcanium: Arcanum, compiler_version: &str, comments: Vec<Token>) -> KotlinVisitor {
+        KotlinVisitor {
+            compiler_version: compiler_version.to_string(),
+            arcanium,
+            symbol_config: SymbolConfig::new(),
+            comments,
+            current_comment_idx: 0,
+            code: String::new(),
+            dent: 0,
+            errors: Vec::new(),
+            system_name: String::new(),
+            first_state_name: String::new(),
+            has_states: false,
+            current_state_name_opt: None,
+            current_event_param_types: HashMap::new(),
+            current_event_is_enter_or_exit: false,
+            visiting_state_class: false,
+        }
+    }
+
+    pub fn run(&mut self, system_node: &SystemNode) {
+        system_node.accept(self);
+    }
+
+    pub fn get_code(&self) -> String {
+        if !self.errors.is_empty() {
+            let mut error_list = String::new();
+            for error in &self.errors {
+                error_list.push_str(error);
+                error_list.push('\n');
+            }
+            error_list
+        } else {
+            // Blank lines are left indented by `newline()`.
+            self.code
+                .lines()
+                .map(|line| line.trim_end())
+                .collect::<Vec<&str>>()
+                .join("\n")
+                + "\n"
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn add_code(&mut self, s: &str) {
+        self.code.push_str(s);
+    }
+
+    fn newline(&mut self) {
+        self.code.push('\n');
+        self.code.push_str(&"    ".repeat(self.dent));
+    }
+
+    fn indent(&mut self) {
+        self.dent += 1;
+    }
+
+    fn outdent(&mut self) {
+        self.dent -= 1;
+    }
+
+    fn generate_comment(&mut self, line: usize) {
+        while self.current_comment_idx < self.comments.len()
+            && line >= self.comments[self.current_comment_idx].line
+        {
+            let comment = self.comments[self.current_comment_idx].lexeme.clone();
+            if self.comments[self.current_comment_idx].token_type == TokenType::SingleLineComment {
+                self.newline();
+                self.add_code(&format!("// {}", comment[3..].trim()));
+            } else {
+                self.newline();
+                self.add_code(&format!("/* {} */", comment[3..comment.len() - 3].trim()));
+            }
+            self.current_comment_idx += 1;
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_type(type_opt: &Option<TypeNode>) -> String {
+        match type_opt {
+            Some(type_node) => type_node.get_type_str(),
+            None => String::from("Any?"),
+        }
+    }
+
+    // Frame identifiers that are hard keywords in Kotlin are escaped with
+    // backticks.
+    fn format_name(name: &str) -> String {
+        if KOTLIN_HARD_KEYWORDS.contains(&name) {
+            format!("`{}`", name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    fn format_params(params_opt: &Option<Vec<ParameterNode>>, prefix: &str) -> String {
+        match params_opt {
+            Some(params) => params
+                .iter()
+                .map(|param| {
+                    format!(
+                        "{}{}: {}",
+                        prefix,
+                        KotlinVisitor::format_name(&param.param_name),
+                        KotlinVisitor::format_type(&param.param_type_opt)
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+            None => String::new(),
+        }
+    }
+
+    // A handler may not set a return value, so interface methods return
+    // the nullable form of the declared type.
+    fn format_nullable_type(type_node: &TypeNode) -> String {
+        let type_str = type_node.get_type_str();
+        if type_str.ends_with('?') {
+            type_str
+        } else {
+            format!("{}?", type_str)
+        }
+    }
+
+    // Kotlin string templates start with `$`.
+    fn format_string_literal(value: &str) -> String {
+        format!("\"{}\"", value.replace('$', "\\$"))
+    }
+
+    // Event classes of user messages are in upper camel case, so they never
+    // contain an underscore. The classes of Frame messages start with one to
+    // stay clear of them.
+    fn format_event_class_name(&self, msg: &str) -> String {
+        let symbol_config = &self.symbol_config;
+        if msg == symbol_config.enter_msg_symbol {
+            String::from("_Enter")
+        } else if msg == symbol_config.exit_msg_symbol {
+            String::from("_Exit")
+        } else if msg == symbol_config.start_msg_symbol {
+            String::from("_Start")
+        } else if msg == symbol_config.stop_msg_symbol {
+            String::from("_Stop")
+        } else if msg == symbol_config.save_msg_symbol {
+            String::from("_Save")
+        } else if msg == symbol_config.restore_msg_symbol {
+            String::from("_Restore")
+        } else {
+            msg.split('_')
+                .filter(|part| !part.is_empty())
+                .map(|part| {
+                    let mut chars = part.chars();
+                    match chars.next() {
+                        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                        None => String::new(),
+                    }
+                })
+                .collect()
+        }
+    }
+
+    fn format_event_type(&self) -> String {
+        format!("{}Event", self.system_name)
+    }
+
+    fn format_state_type(&self) -> String {
+        format!("{}State", self.system_name)
+    }
+
+    fn state_has_properties(&mut self, state_name: &str) -> bool {
+        match self.arcanium.get_state(state_name) {
+            Some(state_symbol_rcref) => {
+                let state_symbol = state_symbol_rcref.borrow();
+                let state_node = state_symbol.state_node.as_ref().unwrap().borrow();
+                state_node.params_opt.is_some() || state_node.vars_opt.is_some()
+            }
+            None => false,
+        }
+    }
+
+    // States without properties are objects, the others are constructed
+    // with their state arguments.
+    fn format_new_state(
+        &mut self,
+        state_name: &str,
+        state_args_opt: &Option<ExprListNode>,
+    ) -> String {
+        let state_class = format!("{}.{}", self.format_state_type(), state_name);
+        if !self.state_has_properties(state_name) {
+            return state_class;
+        }
+        let args = match state_args_opt {
+            Some(state_args) => self.format_args(&state_args.exprs_t),
+            None => String::from("()"),
+        };
+        format!("{}{}", state_class, args)
+    }
+
+    fn format_expr(&mut self, expr_t: &ExprType) -> String {
+        let mut output = String::new();
+        expr_t.accept_to_string(self, &mut output);
+        output
+    }
+
+    fn format_args(&mut self, exprs_t: &[ExprType]) -> String {
+        let args: Vec<String> = exprs_t
+            .iter()
+            .map(|expr_t| self.format_expr(expr_t))
+            .collect();
+        format!("({})", args.join(", "))
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Interface events are smart cast to their subclass, so their
+    // parameters are typed properties. Enter and exit parameters depend on
+    // the state and are carried in an untyped map.
+    fn format_event_param(&self, name: &str) -> String {
+        if self.current_event_is_enter_or_exit {
+            let access = format!("e.args[{:?}]", name);
+            match self.current_event_param_types.get(name) {
+                Some(Some(param_type)) => format!("({} as {})", access, param_type),
+                _ => access,
+            }
+        } else {
+            format!("e.{}", KotlinVisitor::format_name(name))
+        }
+    }
+
+    fn format_variable_expr(&self, variable_node: &VariableNode) -> String {
+        let name = &KotlinVisitor::format_name(&variable_node.id_node.name.lexeme);
+        match variable_node.scope {
+            IdentifierDeclScope::StateParam | IdentifierDeclScope::StateVar => {
+                if self.visiting_state_class {
+                    name.clone()
+                } else {
+                    format!(
+                        "(state as {}.{}).{}",
+                        self.format_state_type(),
+                        self.current_state_name_opt.clone().unwrap_or_default(),
+                        name
+                    )
+                }
+            }
+            IdentifierDeclScope::EventHandlerParam => self.format_event_param(name),
+            _ => name.clone(),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_event_class(&mut self, msg: &str, params: Vec<String>) {
+        self.newline();
+        let class_name = self.format_event_class_name(msg);
+        if params.is_empty() {
+            self.add_code(&format!(
+                "class {} : {}({:?})",
+                class_name,
+                self.format_event_type(),
+                msg
+            ));
+        } else {
+            self.add_code(&format!(
+                "class {}({}) : {}({:?})",
+                class_name,
+                params.join(", "),
+                self.format_event_type(),
+                msg
+            ));
+        }
+    }
+
+    fn generate_event_classes(&mut self, system_node: &SystemNode) {
+        self.add_code(&format!(
+            "sealed class {}(val message: String) {{",
+            self.format_event_type()
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("var ret: Any? = null");
+        self.newline();
+
+        let mut messages: Vec<String> = Vec::new();
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            for interface_method_node_rcref in &interface_block_node.interface_methods {
+                let interface_method_node = interface_method_node_rcref.borrow();
+                let msg = match &interface_method_node.alias {
+                    Some(alias_message_node) => alias_message_node.name.clone(),
+                    None => interface_method_node.name.clone(),
+                };
+                let params = match &interface_method_node.params {
+                    Some(_) => vec![KotlinVisitor::format_params(
+                        &interface_method_node.params,
+                        "val ",
+                    )],
+                    None => Vec::new(),
+                };
+                self.generate_event_class(&msg, params);
+                messages.push(msg);
+            }
+        }
+
+        if self.has_states {
+            for msg in [
+                self.symbol_config.enter_msg_symbol.clone(),
+                self.symbol_config.exit_msg_symbol.clone(),
+            ] {
+                self.generate_event_class(&msg, vec![String::from("val args: Map<String, Any?>")]);
+                messages.push(msg);
+            }
+        }
+
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_node = state_node_rcref.borrow();
+                for evt_handler_node_rcref in &state_node.evt_handlers_rcref {
+                    let evt_handler_node = evt_handler_node_rcref.borrow();
+                    let msg = match &evt_handler_node.msg_t {
+                        MessageType::CustomMessage { message_node } => message_node.name.clone(),
+                        MessageType::AnyMessage { .. } => continue,
+                    };
+                    if messages.contains(&msg) {
+                        continue;
+                    }
+                    let params = match &evt_handler_node.event_symbol_rcref.borrow().params_opt {
+                        Some(params) => params
+                            .iter()
+                            .map(|param| {
+                                format!(
+                                    "val {}: {}",
+                                    KotlinVisitor::format_name(&param.name),
+                                    KotlinVisitor::format_type(&param.param_type_opt)
+                                )
+                            })
+                            .collect(),
+                        None => Vec::new(),
+                    };
+                    self.generate_event_class(&msg, params);
+                    messages.push(msg);
+                }
+            }
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_actions_interface(&mut self, system_node: &SystemNode) {
+        self.add_code(&format!("interface {}Actions {{", self.system_name));
+        self.indent();
+        if let Some(actions_block_node) = &system_node.actions_block_node_opt {
+            for action_rcref in &actions_block_node.actions {
+                let action_node = action_rcref.borrow();
+                let return_type = match &action_node.type_opt {
+                    Some(type_node) => format!(": {}", type_node.get_type_str()),
+                    None => String::new(),
+                };
+                self.newline();
+                self.add_code(&format!(
+                    "fun {}({}){}",
+                    KotlinVisitor::format_name(&action_node.name),
+                    KotlinVisitor::format_params(&action_node.params, ""),
+                    return_type
+                ));
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_state_classes(&mut self, system_node: &SystemNode) {
+        let state_type = self.format_state_type();
+        self.add_code(&format!("sealed class {} {{", state_type));
+        self.indent();
+        self.visiting_state_class = true;
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_node = state_node_rcref.borrow();
+                self.newline();
+                if state_node.params_opt.is_none() && state_node.vars_opt.is_none() {
+                    self.add_code(&format!("object {} : {}()", state_node.name, state_type));
+                    continue;
+                }
+                let params = KotlinVisitor::format_params(&state_node.params_opt, "val ");
+                if params.is_empty() {
+                    self.add_code(&format!("class {} : {}()", state_node.name, state_type));
+                } else {
+                    self.add_code(&format!(
+                        "class {}({}) : {}()",
+                        state_node.name, params, state_type
+                    ));
+                }
+                if let Some(vars) = &state_node.vars_opt {
+                    self.add_code(" {");
+                    self.indent();
+                    for var_rcref in vars {
+                        self.generate_var_decl(&var_rcref.borrow());
+                    }
+                    self.outdent();
+                    self.newline();
+                    self.add_code("}");
+                }
+            }
+        }
+        self.visiting_state_class = false;
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_interface_method(&mut self, interface_method_node: &InterfaceMethodNode) {
+        let msg = match &interface_method_node.alias {
+            Some(alias_message_node) => &alias_message_node.name,
+            None => &interface_method_node.name,
+        };
+        let args = match &interface_method_node.params {
+            Some(params) => params
+                .iter()
+                .map(|param| KotlinVisitor::format_name(&param.param_name))
+                .collect::<Vec<String>>()
+                .join(", "),
+            None => String::new(),
+        };
+        let event = format!(
+            "{}.{}({})",
+            self.format_event_type(),
+            self.format_event_class_name(msg),
+            args
+        );
+
+        self.newline();
+        match &interface_method_node.return_type_opt {
+            Some(return_type) => {
+                let return_type = KotlinVisitor::format_nullable_type(return_type);
+                self.add_code(&format!(
+                    "fun {}({}): {} {{",
+                    KotlinVisitor::format_name(&interface_method_node.name),
+                    KotlinVisitor::format_params(&interface_method_node.params, ""),
+                    return_type
+                ));
+                self.indent();
+                self.newline();
+                self.add_code(&format!("val e = {}", event));
+                if self.has_states {
+                    self.newline();
+                    self.add_code("handle(e)");
+                }
+                self.newline();
+                self.add_code(&format!("return e.ret as {}", return_type));
+            }
+            None => {
+                self.add_code(&format!(
+                    "fun {}({}) {{",
+                    KotlinVisitor::format_name(&interface_method_node.name),
+                    KotlinVisitor::format_params(&interface_method_node.params, "")
+                ));
+                self.indent();
+                self.newline();
+                if self.has_states {
+                    self.add_code(&format!("handle({})", event));
+                } else {
+                    self.add_code(&event);
+                }
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    fn generate_event_handler(&mut self, evt_handler_node: &EventHandlerNode) {
+        self.newline();
+        match &evt_handler_node.msg_t {
+            MessageType::CustomMessage { message_node } => {
+                self.add_code(&format!(
+                    "is {}.{} -> {{",
+                    self.format_event_type(),
+                    self.format_event_class_name(&message_node.name)
+                ));
+            }
+            MessageType::AnyMessage { .. } => self.add_code("else -> {"),
+        }
+        self.indent();
+        self.generate_comment(evt_handler_node.line);
+
+        {
+            let event_symbol = evt_handler_node.event_symbol_rcref.borrow();
+            self.current_event_is_enter_or_exit =
+                event_symbol.is_enter_msg || event_symbol.is_exit_msg;
+            self.current_event_param_types.clear();
+            if let Some(params) = &event_symbol.params_opt {
+                for param in params {
+                    self.current_event_param_types.insert(
+                        param.name.clone(),
+                        param
+                            .param_type_opt
+                            .as_ref()
+                            .map(|type_node| type_node.get_type_str()),
+                    );
+                }
+            }
+        }
+
+        self.visit_decl_stmts(&evt_handler_node.statements);
+        self.generate_terminator(&evt_handler_node.terminator_node);
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    fn generate_terminator(&mut self, terminator_expr: &TerminatorExpr) {
+        match &terminator_expr.terminator_type {
+            TerminatorType::Return => {
+                if let Some(expr_t) = &terminator_expr.return_expr_t_opt {
+                    let value = self.format_expr(expr_t);
+                    self.newline();
+                    self.add_code(&format!("e.ret = {}", value));
+                }
+                self.newline();
+                self.add_code("return");
+            }
+            TerminatorType::Continue => {}
+        }
+    }
+
+    fn generate_machinery(&mut self, system_node: &SystemNode) {
+        let event_type = self.format_event_type();
+        let state_type = self.format_state_type();
+
+        self.newline();
+        self.add_code("//=============== Machinery and Mechanisms ==============//");
+        self.newline();
+        self.newline();
+        self.add_code(&format!("private fun handle(e: {}) {{", event_type));
+        self.indent();
+        self.newline();
+        self.add_code("when (state) {");
+        self.indent();
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_name = state_node_rcref.borrow().name.clone();
+                self.newline();
+                self.add_code(&format!(
+                    "is {}.{} -> state{}(e)",
+                    state_type, state_name, state_name
+                ));
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        self.newline();
+        self.add_code("private fun transition(");
+        self.indent();
+        self.newline();
+        self.add_code(&format!("newState: {},", state_type));
+        self.newline();
+        self.add_code("exitArgs: Map<String, Any?> = emptyMap(),");
+        self.newline();
+        self.add_code("enterArgs: Map<String, Any?> = emptyMap(),");
+        self.outdent();
+        self.newline();
+        self.add_code(") {");
+        self.indent();
+        self.newline();
+        self.add_code(&format!("handle({}._Exit(exitArgs))", event_type));
+        self.newline();
+        self.add_code("state = newState");
+        self.newline();
+        self.add_code(&format!("handle({}._Enter(enterArgs))", event_type));
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_decl_stmts(&mut self, decl_stmt_types: &[DeclOrStmtType]) {
+        for decl_stmt_t in decl_stmt_types {
+            match decl_stmt_t {
+                DeclOrStmtType::VarDeclT { var_decl_t_rc_ref } => {
+                    let variable_decl_node = var_decl_t_rc_ref.borrow();
+                    self.generate_var_decl(&variable_decl_node);
+                }
+                DeclOrStmtType::StmtT { stmt_t } => match stmt_t {
+                    StatementType::ExpressionStmt { expr_stmt_t } => {
+                        self.generate_expr_stmt(expr_stmt_t);
+                    }
+                    StatementType::TransitionStmt {
+                        transition_statement,
+                    } => {
+                        self.generate_transition(transition_statement);
+                    }
+                    StatementType::ChangeStateStmt { change_state_stmt } => {
+                        self.generate_change_state(change_state_stmt);
+                    }
+                    StatementType::TestStmt { test_stmt_node } => {
+                        self.generate_test(&test_stmt_node.test_t);
+                    }
+                    StatementType::StateStackStmt {
+                        state_stack_operation_statement_node,
+                    } => {
+                        self.newline();
+                        match state_stack_operation_statement_node
+                            .state_stack_operation_node
+                            .operation_t
+                        {
+                            StateStackOperationType::Push => {
+                                self.add_code("stateStack.addLast(state)")
+                            }
+                            StateStackOperationType::Pop => {
+                                self.add_code("stateStack.removeLast()")
+                            }
+                        }
+                    }
+                    StatementType::LoopStmt { loop_stmt_node } => {
+                        self.generate_loop(loop_stmt_node);
+                    }
+                    StatementType::LoopControlStmt {
+                        loop_control_stmt_node,
+                    } => {
+                        self.newline();
+                        match loop_control_stmt_node.loop_control_t {
+                            LoopControlType::Break => self.add_code("break"),
+                            LoopControlType::Continue => self.add_code("continue"),
+                        }
+                    }
+                    StatementType::NoStmt => {}
+                },
+            }
+        }
+    }
+
+    // Domain variables, state variables and event handler variables are
+    // all declared as properties or locals with an optional type.
+    fn generate_var_decl(&mut self, variable_decl_node: &VariableDeclNode) {
+        let value = match &variable_decl_node.initializer_expr_t_opt {
+            Some(expr_t) => self.format_expr(expr_t),
+            None => String::from("null"),
+        };
+        let keyword = if variable_decl_node.is_constant {
+            "val"
+        } else {
+            "var"
+        };
+        let var_type = match &variable_decl_node.type_opt {
+            Some(type_node) => format!(": {}", type_node.get_type_str()),
+            None => String::new(),
+        };
+        self.newline();
+        self.add_code(&format!(
+            "{} {}{} = {}",
+            keyword,
+            KotlinVisitor::format_name(&variable_decl_node.name),
+            var_type,
+            value
+        ));
+    }
+
+    fn generate_expr_stmt(&mut self, expr_stmt_t: &ExprStmtType) {
+        let mut output = String::new();
+        match expr_stmt_t {
+            ExprStmtType::CallStmtT { call_stmt_node } => {
+                self.visit_call_expression_node_to_string(
+                    &call_stmt_node.call_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::ActionCallStmtT {
+                action_call_stmt_node,
+            } => {
+                self.visit_action_call_expression_node_to_string(
+                    &action_call_stmt_node.action_call_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::CallChainLiteralStmtT {
+                call_chain_literal_stmt_node,
+            } => {
+                self.visit_call_chain_literal_expr_node_to_string(
+                    &call_chain_literal_stmt_node.call_chain_literal_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::AssignmentStmtT {
+                assignment_stmt_node,
+            } => {
+                self.generate_comment(assignment_stmt_node.get_line());
+                self.visit_assignment_expr_node_to_string(
+                    &assignment_stmt_node.assignment_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::VariableStmtT { variable_stmt_node } => {
+                output.push_str(&self.format_variable_expr(&variable_stmt_node.var_node));
+            }
+        }
+        self.newline();
+        self.add_code(&output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_label(&mut self, label_opt: &Option<String>) {
+        if let Some(label) = label_opt {
+            self.newline();
+            self.add_code(&format!("// {}", label));
+        }
+    }
+
+    // Formats a `mapOf()` keyed by the parameter names of an event.
+    fn format_event_args(&mut self, msg: &str, args: &ExprListNode) -> String {
+        let event_sym_opt = self.arcanium.get_event(msg, &self.current_state_name_opt);
+        let params = match event_sym_opt {
+            Some(event_sym) => match &event_sym.borrow().params_opt {
+                Some(params) => params
+                    .iter()
+                    .map(|param| param.name.clone())
+                    .collect::<Vec<String>>(),
+                None => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+        if params.len() != args.exprs_t.len() {
+            self.errors.push(format!(
+                "Invalid number of arguments for \"{}\" event handler.",
+                msg
+            ));
+        }
+        let entries: Vec<String> = params
+            .iter()
+            .zip(args.exprs_t.iter())
+            .map(|(param, expr_t)| format!("{:?} to {}", param, self.format_expr(expr_t)))
+            .collect();
+        format!("mapOf({})", entries.join(", "))
+    }
+
+    fn format_exit_args(&mut self, exit_args_opt: &Option<ExprListNode>) -> Option<String> {
+        match exit_args_opt {
+            Some(exit_args) if !exit_args.exprs_t.is_empty() => {
+                let msg = format!(
+                    "{}:{}",
+                    self.current_state_name_opt.clone().unwrap_or_default(),
+                    self.symbol_config.exit_msg_symbol
+                );
+                Some(self.format_event_args(&msg, exit_args))
+            }
+            _ => None,
+        }
+    }
+
+    fn generate_transition(&mut self, transition_statement: &TransitionStatementNode) {
+        self.generate_label(&transition_statement.label_opt);
+        let mut args = Vec::new();
+        match &transition_statement.target_state_context_t {
+            StateContextType::StateRef { state_context_node } => {
+                let target_state_name = &state_context_node.state_ref_node.name;
+                args.push(
+                    self.format_new_state(
+                        target_state_name,
+                        &state_context_node.state_ref_args_opt,
+                    ),
+                );
+                if let Some(exit_args) = self.format_exit_args(&transition_statement.exit_args_opt)
+                {
+                    args.push(format!("exitArgs = {}", exit_args));
+                }
+                if let Some(enter_args) = &state_context_node.enter_args_opt {
+                    let msg = format!(
+                        "{}:{}",
+                        target_state_name, self.symbol_config.enter_msg_symbol
+                    );
+                    let enter_args = self.format_event_args(&msg, enter_args);
+                    args.push(format!("enterArgs = {}", enter_args));
+                }
+            }
+            StateContextType::StateStackPop {} => {
+                args.push(String::from("stateStack.removeLast()"));
+                if let Some(exit_args) = self.format_exit_args(&transition_statement.exit_args_opt)
+                {
+                    args.push(format!("exitArgs = {}", exit_args));
+                }
+            }
+        }
+        self.newline();
+        self.add_code(&format!("transition({})", args.join(", ")));
+    }
+
+    fn generate_change_state(&mut self, change_state_stmt_node: &ChangeStateStatementNode) {
+        self.generate_label(&change_state_stmt_node.label_opt);
+        let new_state = match &change_state_stmt_node.state_context_t {
+            StateContextType::StateRef { state_context_node } => self.format_new_state(
+                &state_context_node.state_ref_node.name,
+                &state_context_node.state_ref_args_opt,
+            ),
+            StateContextType::StateStackPop {} => String::from("stateStack.removeLast()"),
+        };
+        self.newline();
+        self.add_code(&format!("state = {}", new_state));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_branch_body(
+        &mut self,
+        statements: &[DeclOrStmtType],
+        terminator_opt: &Option<TerminatorExpr>,
+    ) {
+        self.indent();
+        self.visit_decl_stmts(statements);
+        if let Some(terminator_expr) = terminator_opt {
+            self.generate_terminator(terminator_expr);
+        }
+        self.outdent();
+    }
+
+    fn generate_if_chain(
+        &mut self,
+        branches: Vec<KotlinBranch>,
+        else_opt: Option<(&[DeclOrStmtType], &Option<TerminatorExpr>)>,
+    ) {
+        self.newline();
+        for (i, branch) in branches.iter().enumerate() {
+            if i == 0 {
+                self.add_code(&format!("if ({}) {{", branch.condition));
+            } else {
+                self.add_code(&format!("}} else if ({}) {{", branch.condition));
+            }
+            self.indent();
+            for binding in &branch.bindings {
+                self.newline();
+                self.add_code(binding);
+            }
+            self.outdent();
+            self.generate_branch_body(branch.statements, branch.terminator_opt);
+            self.newline();
+        }
+        if let Some((statements, terminator_opt)) = else_opt {
+            self.add_code("} else {");
+            self.generate_branch_body(statements, terminator_opt);
+            self.newline();
+        }
+        self.add_code("}");
+    }
+
+    fn generate_test(&mut self, test_t: &TestType) {
+        match test_t {
+            TestType::BoolTest { bool_test_node } => {
+                let mut branches = Vec::new();
+                for branch_node in &bool_test_node.conditional_branch_nodes {
+                    let mut condition = self.format_expr(&branch_node.expr_t);
+                    if branch_node.is_negated {
+                        condition = format!("!({})", condition);
+                    }
+                    branches.push(KotlinBranch {
+                        condition,
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    });
+                }
+                let else_opt = bool_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::StringMatchTest {
+                string_match_test_node,
+            } => {
+                let subject = self.format_expr(&string_match_test_node.expr_t);
+                let branches = string_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| KotlinBranch {
+                        condition: branch_node
+                            .string_match_pattern_node
+                            .match_pattern_strings
+                            .iter()
+                            .map(|pattern| {
+                                format!(
+                                    "{} == {}",
+                                    subject,
+                                    KotlinVisitor::format_string_literal(pattern)
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = string_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::NumberMatchTest {
+                number_match_test_node,
+            } => {
+                let subject = self.format_expr(&number_match_test_node.expr_t);
+                let branches = number_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| KotlinBranch {
+                        condition: branch_node
+                            .number_match_pattern_nodes
+                            .iter()
+                            .map(|pattern| {
+                                format!("{} == {}", subject, pattern.match_pattern_number)
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = number_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                let subject = self.format_expr(&regex_match_test_node.expr_t);
+                let branches = regex_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| {
+                        let pattern_node = &branch_node.regex_match_pattern_node;
+                        // Java regexes spell named groups `(?<name>...)`.
+                        let pattern = format!(
+                            "Regex({})",
+                            KotlinVisitor::format_string_literal(
+                                &pattern_node
+                                    .pattern_with_group_prefix("?<")
+                                    .replace('\\', "\\\\")
+                                    .replace('"', "\\\"")
+                            )
+                        );
+                        let mut bindings = Vec::new();
+                        if !pattern_node.capture_names.is_empty() {
+                            bindings
+                                .push(format!("val regexMatch = {}.find({})!!", pattern, subject));
+                            for name in &pattern_node.capture_names {
+                                bindings.push(format!(
+                                    "val {} = regexMatch.groups[{:?}]?.value ?: \"\"",
+                                    KotlinVisitor::format_name(name),
+                                    name
+                                ));
+                            }
+                        }
+                        KotlinBranch {
+                            condition: format!("{}.containsMatchIn({})", pattern, subject),
+                            bindings,
+                            statements: &branch_node.statements,
+                            terminator_opt: &branch_node.branch_terminator_expr_opt,
+                        }
+                    })
+                    .collect();
+                let else_opt = regex_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                let subject = self.format_expr(&enum_match_test_node.expr_t);
+                let branches = enum_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| KotlinBranch {
+                        condition: branch_node
+                            .enumerators
+                            .iter()
+                            .map(|enumerator| {
+                                format!(
+                                    "{} == {}.{}",
+                                    subject, enum_match_test_node.enum_type_name, enumerator
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = enum_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+        }
+    }
+
+    fn generate_loop(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.newline();
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(condition_expr_t) => {
+                let condition = self.format_expr(condition_expr_t);
+                self.add_code(&format!("while ({}) {{", condition));
+            }
+            None => self.add_code("while (true) {"),
+        }
+        self.indent();
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+}
+
+//* --------------------------------------------------------------------- *//
+
+impl AstVisitor for KotlinVisitor {
+    fn visit_system_node(&mut self, system_node: &SystemNode) {
+        self.system_name = system_node.name.clone();
+        if system_node.has_deferred_events() {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the kotlin target.",
+            ));
+        }
+        if let Some(first_state) = system_node.get_first_state() {
+            self.first_state_name = first_state.borrow().name.clone();
+            self.has_states = true;
+        }
+
+        self.add_code(&format!("// {}", self.compiler_version));
+        self.newline();
+        self.newline();
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for enum_decl_node_rcref in &domain_block_node.enums {
+                let enum_decl_node = enum_decl_node_rcref.borrow();
+                self.add_code(&format!(
+                    "enum class {} {{ {} }}",
+                    enum_decl_node.name,
+                    enum_decl_node.enumerators.join(", ")
+                ));
+                self.newline();
+                self.newline();
+            }
+        }
+        self.generate_event_classes(system_node);
+        self.generate_actions_interface(system_node);
+        if self.has_states {
+            self.generate_state_classes(system_node);
+        }
+
+        self.add_code(&format!(
+            "class {}(private val actions: {}Actions) {{",
+            self.system_name, self.system_name
+        ));
+        self.indent();
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for variable_decl_node_rcref in &domain_block_node.member_variables {
+                self.generate_var_decl(&variable_decl_node_rcref.borrow());
+            }
+        }
+        if self.has_states {
+            let first_state_name = self.first_state_name.clone();
+            let first_state = self.format_new_state(&first_state_name, &None);
+            self.newline();
+            self.add_code(&format!(
+                "var state: {} = {}",
+                self.format_state_type(),
+                first_state
+            ));
+            self.indent();
+            self.newline();
+            self.add_code("private set");
+            self.outdent();
+            self.newline();
+            self.add_code(&format!(
+                "private val stateStack = ArrayDeque<{}>()",
+                self.format_state_type()
+            ));
+        }
+        self.newline();
+
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            interface_block_node.accept(self);
+        }
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            machine_block_node.accept(self);
+        }
+        if self.has_states {
+            self.generate_machinery(system_node);
+        }
+
+        self.outdent();
+        self.code.truncate(self.code.trim_end().len());
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    fn visit_interface_block_node(&mut self, interface_block_node: &InterfaceBlockNode) {
+        self.newline();
+        self.add_code("//===================== Interface Block ===================//");
+        self.newline();
+        for interface_method_node_rcref in &interface_block_node.interface_methods {
+            self.generate_interface_method(&interface_method_node_rcref.borrow());
+        }
+    }
+
+    fn visit_machine_block_node(&mut self, machine_block_node: &MachineBlockNode) {
+        self.newline();
+        self.add_code("//===================== Machine Block ===================//");
+        self.newline();
+        for state_node_rcref in &machine_block_node.states {
+            state_node_rcref.borrow().accept(self);
+        }
+    }
+
+    fn visit_state_node(&mut self, state_node: &StateNode) {
+        self.generate_comment(state_node.line);
+        self.current_state_name_opt = Some(state_node.name.clone());
+        self.newline();
+        self.add_code(&format!(
+            "private fun state{}(e: {}) {{",
+            state_node.name,
+            self.format_event_type()
+        ));
+        self.indent();
+
+        if let Some(calls) = &state_node.calls_opt {
+            for call in calls {
+                let mut output = String::new();
+                self.visit_call_chain_literal_expr_node_to_string(call, &mut output);
+                self.newline();
+                self.add_code(&output);
+            }
+        }
+
+        if !state_node.evt_handlers_rcref.is_empty() {
+            self.newline();
+            self.add_code("when (e) {");
+            self.indent();
+            let mut has_any_message_handler = false;
+            for evt_handler_node in &state_node.evt_handlers_rcref {
+                let evt_handler_node = evt_handler_node.borrow();
+                if let MessageType::AnyMessage { .. } = evt_handler_node.msg_t {
+                    has_any_message_handler = true;
+                }
+                self.generate_event_handler(&evt_handler_node);
+            }
+            // `when` statements over a sealed class must be exhaustive.
+            if !has_any_message_handler {
+                self.newline();
+                self.add_code("else -> {}");
+            }
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+        }
+
+        // Events the state does not handle go to its parent.
+        if let Some(dispatch_node) = &state_node.dispatch_opt {
+            self.newline();
+            self.add_code(&format!("state{}(e)", dispatch_node.target_state_ref.name));
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.current_state_name_opt = None;
+    }
+
+    fn visit_action_node_rust_trait(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_action_node_rust_trait() not implemented.");
+    }
+
+    fn visit_actions_node_rust_impl(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_actions_node_rust_impl() not implemented.");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_assignment_expr_node_to_string(
+        &mut self,
+        assignment_expr_node: &AssignmentExprNode,
+        output: &mut String,
+    ) {
+        assignment_expr_node
+            .l_value_box
+            .accept_to_string(self, output);
+        output.push_str(" = ");
+        assignment_expr_node
+            .r_value_box
+            .accept_to_string(self, output);
+    }
+
+    fn visit_expression_list_node_to_string(
+        &mut self,
+        expr_list: &ExprListNode,
+        output: &mut String,
+    ) {
+        let args = self.format_args(&expr_list.exprs_t);
+        output.push_str(&args);
+    }
+
+    fn visit_call_expr_list_node_to_string(
+        &mut self,
+        call_expr_list: &CallExprListNode,
+        output: &mut String,
+    ) {
+        let args = self.format_args(&call_expr_list.exprs_t);
+        output.push_str(&args);
+    }
+
+    fn visit_call_expression_node_to_string(
+        &mut self,
+        method_call: &CallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&KotlinVisitor::format_name(
+            &method_call.identifier.name.lexeme,
+        ));
+        self.visit_call_expr_list_node_to_string(&method_call.call_expr_list, output);
+    }
+
+    fn visit_action_call_expression_node_to_string(
+        &mut self,
+        action_call: &ActionCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "actions.{}",
+            KotlinVisitor::format_name(&action_call.identifier.name.lexeme)
+        ));
+        self.visit_call_expr_list_node_to_string(&action_call.call_expr_list, output);
+    }
+
+    fn visit_interface_method_call_expression_node_to_string(
+        &mut self,
+        interface_method_call_expr_node: &InterfaceMethodCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&KotlinVisitor::format_name(
+            &interface_method_call_expr_node.identifier.name.lexeme,
+        ));
+        self.visit_call_expr_list_node_to_string(
+            &interface_method_call_expr_node.call_expr_list,
+            output,
+        );
+    }
+
+    fn visit_call_chain_literal_expr_node_to_string(
+        &mut self,
+        method_call_chain_expression_node: &CallChainLiteralExprNode,
+        output: &mut String,
+    ) {
+        let mut separator = "";
+        for node in &method_call_chain_expression_node.call_chain {
+            output.push_str(separator);
+            match node {
+                CallChainLiteralNodeType::VariableNodeT { var_node } => {
+                    output.push_str(&self.format_variable_expr(var_node));
+                }
+                CallChainLiteralNodeType::IdentifierNodeT { id_node } => {
+                    output.push_str(&KotlinVisitor::format_name(&id_node.name.lexeme));
+                }
+                CallChainLiteralNodeType::CallT { call } => {
+                    self.visit_call_expression_node_to_string(call, output);
+                }
+                CallChainLiteralNodeType::InterfaceMethodCallT {
+                    interface_method_call_expr_node,
+                } => {
+                    self.visit_interface_method_call_expression_node_to_string(
+                        interface_method_call_expr_node,
+                        output,
+                    );
+                }
+                CallChainLiteralNodeType::ActionCallT {
+                    action_call_expr_node,
+                } => {
+                    self.visit_action_call_expression_node_to_string(action_call_expr_node, output);
+                }
+            }
+            separator = ".";
+        }
+    }
+
+    fn visit_literal_expression_node_to_string(
+        &mut self,
+        literal_expression_node: &LiteralExprNode,
+        output: &mut String,
+    ) {
+        match &literal_expression_node.token_t {
+            TokenType::String => output.push_str(&KotlinVisitor::format_string_literal(
+                &literal_expression_node.value,
+            )),
+            TokenType::True => output.push_str("true"),
+            TokenType::False => output.push_str("false"),
+            TokenType::Nil | TokenType::Null => output.push_str("null"),
+            _ => output.push_str(&literal_expression_node.value),
+        }
+    }
+
+    fn visit_variable_expr_node_to_string(
+        &mut self,
+        variable_node: &VariableNode,
+        output: &mut String,
+    ) {
+        output.push_str(&self.format_variable_expr(variable_node));
+    }
+
+    fn visit_frame_event_part_to_string(
+        &mut self,
+        frame_event_part: &FrameEventPart,
+        output: &mut String,
+    ) {
+        match frame_event_part {
+            FrameEventPart::Event { .. } => output.push('e'),
+            FrameEventPart::Message { .. } => output.push_str("e.message"),
+            FrameEventPart::Param { param_tok, .. } => {
+                output.push_str(&self.format_event_param(&param_tok.lexeme))
+            }
+            FrameEventPart::Return { .. } => output.push_str("e.ret"),
+        }
+    }
+
+    fn visit_unary_expr_node_to_string(
+        &mut self,
+        unary_expr_node: &UnaryExprNode,
+        output: &mut String,
+    ) {
+        unary_expr_node.operator.accept_to_string(self, output);
+        unary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_binary_expr_node_to_string(
+        &mut self,
+        binary_expr_node: &BinaryExprNode,
+        output: &mut String,
+    ) {
+        binary_expr_node
+            .left_rcref
+            .borrow()
+            .accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node.operator.accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}.{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    fn visit_operator_type_to_string(&mut self, operator_type: &OperatorType, output: &mut String) {
+        let operator = match operator_type {
+            OperatorType::Plus => "+",
+            OperatorType::Minus | OperatorType::Negated => "-",
+            OperatorType::Multiply => "*",
+            OperatorType::Divide => "/",
+            OperatorType::Greater => ">",
+            OperatorType::GreaterEqual => ">=",
+            OperatorType::Less => "<",
+            OperatorType::LessEqual => "<=",
+            OperatorType::EqualEqual => "==",
+            OperatorType::NotEqual => "!=",
+            OperatorType::Not => "!",
+            OperatorType::LogicalAnd => "&&",
+            OperatorType::LogicalOr => "||",
+            OperatorType::LogicalXor => "xor",
+        };
+        output.push_str(operator);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frame_c::compiler::Exe;
+
+    fn generate(spec: &str) -> String {
+        Exe::new()
+            .run(&None, spec.to_string(), String::from("kotlin"))
+            .unwrap_or_else(|err| panic!("{}", err.error))
+    }
+
+    #[test]
+    fn keywords_are_escaped() {
+        let code = generate(
+            "
+#Keywords
+    -interface-
+    when [in:i32]
+    -machine-
+    $A
+        var object:i32 = 0
+
+        |when| [in:i32]
+            object = in
+            var is = object + 1
+            fun(is)
+            ^
+    -actions-
+    fun [val:i32]
+##",
+        );
+        assert!(code.contains("class When(val `in`: i32) : KeywordsEvent(\"when\")"));
+        assert!(code.contains("fun `fun`(`val`: i32)"));
+        assert!(code.contains("fun `when`(`in`: i32) {"));
+        assert!(code.contains("handle(KeywordsEvent.When(`in`))"));
+        assert!(code.contains("var `object`: i32 = 0"));
+        assert!(code.contains("(state as KeywordsState.A).`object` = e.`in`"));
+        assert!(code.contains("var `is` = (state as KeywordsState.A).`object` + 1"));
+        assert!(code.contains("actions.`fun`(`is`)"));
+    }
+
+    #[test]
+    fn frame_events_do_not_collide_with_user_events() {
+        let code = generate(
+            "
+#Lifecycle
+    -interface-
+    enter
+    exit
+    -machine-
+    $A
+        |>| ^
+        |enter| -> $B ^
+    $B
+        |exit| -> $A ^
+##",
+        );
+        assert!(code.contains("class Enter : LifecycleEvent(\"enter\")"));
+        assert!(code.contains("class Exit : LifecycleEvent(\"exit\")"));
+        assert!(code.contains("class _Enter(val args: Map<String, Any?>) : LifecycleEvent(\">\")"));
+        assert!(code.contains("class _Exit(val args: Map<String, Any?>) : LifecycleEvent(\"<\")"));
+        assert!(code.contains("is LifecycleEvent._Enter -> {"));
+        assert!(code.contains("is LifecycleEvent.Enter -> {"));
+        assert!(code.contains("handle(LifecycleEvent._Exit(exitArgs))"));
+    }
+}
@@ -0,0 +1,352 @@
+use crate::frame_c::ast::*;
+use crate::frame_c::scanner::TokenType;
+use crate::frame_c::utils::SystemHierarchy;
+use crate::frame_c::visitors::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+const STACK_POP_STATE: &str = "__stack_pop";
+
+fn indent_str(indent: usize) -> String {
+    "    ".repeat(indent)
+}
+
+// Mermaid ends a statement at `;` and reads `#` as the start of an entity
+// code, so both are written as entity codes in labels.
+fn escape_label(label: &str) -> String {
+    label.replace('#', "#35;").replace(';', "#59;")
+}
+
+fn format_variable(variable_decl_node: &VariableDeclNode) -> String {
+    let mut variable = format!(
+        "{} {}",
+        if variable_decl_node.is_constant {
+            "const"
+        } else {
+            "var"
+        },
+        variable_decl_node.name
+    );
+    if let Some(type_node) = &variable_decl_node.type_opt {
+        variable.push_str(&format!(":{}", type_node.get_type_str()));
+    }
+    // Only simple initializers are shown; anything else would need the
+    // target language's expression syntax.
+    match &variable_decl_node.initializer_expr_t_opt {
+        Some(ExprType::LiteralExprT { literal_expr_node }) => {
+            if let TokenType::String = literal_expr_node.token_t {
+                variable.push_str(&format!(" = \"{}\"", literal_expr_node.value));
+            } else {
+                variable.push_str(&format!(" = {}", literal_expr_node.value));
+            }
+        }
+        Some(ExprType::EnumeratorExprT {
+            enumerator_expr_node,
+        }) => {
+            variable.push_str(&format!(
+                " = {}.{}",
+                enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+            ));
+        }
+        _ => {}
+    }
+    escape_label(&variable)
+}
+
+pub struct MermaidVisitor {
+    _compiler_version: &'static str,
+    system_hierarchy: SystemHierarchy,
+    state_nodes: HashMap<String, Rc<RefCell<StateNode>>>,
+    current_state: Option<String>,
+    transition_msg: String,
+    uses_stack_pop: bool,
+    transitions: String,
+    code: String,
+}
+
+impl MermaidVisitor {
+    pub fn new(
+        compiler_version: &'static str,
+        system_hierarchy: SystemHierarchy,
+    ) -> MermaidVisitor {
+        MermaidVisitor {
+            _compiler_version: compiler_version,
+            system_hierarchy,
+            state_nodes: HashMap::new(),
+            current_state: None,
+            transition_msg: String::new(),
+            uses_stack_pop: false,
+            transitions: String::new(),
+            code: String::from(""),
+        }
+    }
+
+    pub fn run(&mut self, system_node: &SystemNode) {
+        system_node.accept(self);
+    }
+
+    fn add_code(&mut self, s: &str) {
+        self.code.push_str(s);
+    }
+
+    pub fn get_code(&self) -> String {
+        self.code.clone()
+    }
+
+    fn generate_states(&self, node_name: &str, indent: usize, output: &mut String) {
+        let node = self.system_hierarchy.get_node(node_name).unwrap();
+        let indent_str = indent_str(indent);
+
+        if node.children.is_empty() {
+            output.push_str(&format!("{}{}\n", indent_str, node_name));
+        } else {
+            output.push_str(&format!("{}state {} {{\n", indent_str, node_name));
+            for child_name in &node.children {
+                self.generate_states(child_name, indent + 1, output);
+            }
+            output.push_str(&format!("{}}}\n", indent_str));
+        }
+
+        self.generate_state_vars_note(node_name, indent, output);
+    }
+
+    fn generate_state_vars_note(&self, state_name: &str, indent: usize, output: &mut String) {
+        let state_node_rcref = match self.state_nodes.get(state_name) {
+            Some(state_node_rcref) => state_node_rcref,
+            None => return,
+        };
+        let state_node = state_node_rcref.borrow();
+        let vars = match &state_node.vars_opt {
+            Some(vars) if !vars.is_empty() => vars,
+            _ => return,
+        };
+        let indent_str = indent_str(indent);
+        output.push_str(&format!("{}note right of {}\n", indent_str, state_name));
+        for variable_decl_node_rcref in vars {
+            output.push_str(&format!(
+                "{}    {}\n",
+                indent_str,
+                format_variable(&variable_decl_node_rcref.borrow())
+            ));
+        }
+        output.push_str(&format!("{}end note\n", indent_str));
+    }
+
+    fn visit_decl_stmts(&mut self, decl_stmt_types: &[DeclOrStmtType]) {
+        for decl_stmt_t in decl_stmt_types.iter() {
+            match decl_stmt_t {
+                DeclOrStmtType::VarDeclT { .. } => {}
+                DeclOrStmtType::StmtT { stmt_t } => match stmt_t {
+                    StatementType::ExpressionStmt { .. } => {}
+                    StatementType::TransitionStmt {
+                        transition_statement,
+                    } => {
+                        transition_statement.accept(self);
+                    }
+                    StatementType::TestStmt { test_stmt_node } => {
+                        test_stmt_node.accept(self);
+                    }
+                    StatementType::StateStackStmt { .. } => {}
+                    StatementType::ChangeStateStmt { change_state_stmt } => {
+                        change_state_stmt.accept(self);
+                    }
+                    StatementType::LoopStmt { loop_stmt_node } => {
+                        loop_stmt_node.accept(self);
+                    }
+                    StatementType::LoopControlStmt { .. } => {}
+                    StatementType::NoStmt => {}
+                },
+            }
+        }
+    }
+
+    fn generate_transition(&mut self, state_context_t: &StateContextType, label: &str) {
+        let source_state = self.current_state.as_ref().unwrap().clone();
+        let target_state = match state_context_t {
+            StateContextType::StateRef { state_context_node } => {
+                state_context_node.state_ref_node.name.clone()
+            }
+            StateContextType::StateStackPop {} => {
+                self.uses_stack_pop = true;
+                String::from(STACK_POP_STATE)
+            }
+        };
+        self.transitions.push_str(&format!(
+            "    {} --> {} : {}\n",
+            source_state, target_state, label
+        ));
+    }
+}
+
+impl AstVisitor for MermaidVisitor {
+    fn visit_system_node(&mut self, system_node: &SystemNode) {
+        self.add_code("stateDiagram-v2\n");
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            machine_block_node.accept(self);
+        }
+    }
+
+    fn visit_machine_block_node(&mut self, machine_block_node: &MachineBlockNode) {
+        for state_node_rcref in &machine_block_node.states {
+            let name = state_node_rcref.borrow().name.clone();
+            self.state_nodes.insert(name, Rc::clone(state_node_rcref));
+        }
+
+        let mut output = String::new();
+        let system_name = &self.system_hierarchy.system_name;
+        let system_node = self.system_hierarchy.get_node(system_name).unwrap();
+        for state_name in &system_node.children {
+            self.generate_states(state_name, 1, &mut output);
+        }
+        self.add_code(&output);
+
+        if let Some(first_state) = machine_block_node.get_first_state() {
+            self.add_code(&format!("    [*] --> {}\n", first_state.borrow().name));
+        }
+
+        for state_node_rcref in &machine_block_node.states {
+            state_node_rcref.borrow().accept(self);
+        }
+
+        if self.uses_stack_pop {
+            self.add_code(&format!("    state \"$$[-]\" as {}\n", STACK_POP_STATE));
+        }
+
+        let transitions = std::mem::take(&mut self.transitions);
+        self.add_code(&transitions);
+    }
+
+    fn visit_action_node_rust_trait(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_action_node_rust_trait() not implemented.");
+    }
+
+    fn visit_actions_node_rust_impl(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_actions_node_rust_impl() not implemented.");
+    }
+
+    fn visit_state_node(&mut self, state_node: &StateNode) {
+        self.current_state = Some(state_node.name.clone());
+
+        for evt_handler_node in &state_node.evt_handlers_rcref {
+            evt_handler_node.as_ref().borrow().accept(self);
+        }
+
+        self.current_state = None;
+    }
+
+    fn visit_event_handler_node(&mut self, evt_handler_node: &EventHandlerNode) {
+        if let MessageType::CustomMessage { message_node } = &evt_handler_node.msg_t {
+            self.transition_msg = message_node.name.clone();
+        } else {
+            // AnyMessage ( ||* )
+            self.transition_msg = "||*".to_string();
+        }
+
+        self.visit_decl_stmts(&evt_handler_node.statements);
+    }
+
+    fn visit_transition_statement_node(&mut self, transition_statement: &TransitionStatementNode) {
+        let label = format_transition_label(
+            &self.transition_msg,
+            &transition_statement.label_opt,
+            &transition_statement.guard_opt,
+            escape_label,
+        );
+        self.generate_transition(&transition_statement.target_state_context_t, &label);
+    }
+
+    // Mermaid has no edge styles, so change-state is marked in the label.
+    fn visit_change_state_statement_node(
+        &mut self,
+        change_state_stmt_node: &ChangeStateStatementNode,
+    ) {
+        let label = format!(
+            "->> {}",
+            format_transition_label(
+                &self.transition_msg,
+                &change_state_stmt_node.label_opt,
+                &change_state_stmt_node.guard_opt,
+                escape_label
+            )
+        );
+        self.generate_transition(&change_state_stmt_node.state_context_t, &label);
+    }
+
+    fn visit_test_statement_node(&mut self, test_stmt_node: &TestStatementNode) {
+        match &test_stmt_node.test_t {
+            TestType::BoolTest { bool_test_node } => {
+                bool_test_node.accept(self);
+            }
+            TestType::StringMatchTest {
+                string_match_test_node,
+            } => {
+                string_match_test_node.accept(self);
+            }
+            TestType::NumberMatchTest {
+                number_match_test_node,
+            } => {
+                number_match_test_node.accept(self);
+            }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                regex_match_test_node.accept(self);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
+            }
+        }
+    }
+
+    fn visit_bool_test_node(&mut self, bool_test_node: &BoolTestNode) {
+        for branch_node in &bool_test_node.conditional_branch_nodes {
+            self.visit_decl_stmts(&branch_node.statements);
+        }
+        if let Some(else_branch_node) = &bool_test_node.else_branch_node_opt {
+            self.visit_decl_stmts(&else_branch_node.statements);
+        }
+    }
+
+    fn visit_string_match_test_node(&mut self, string_match_test_node: &StringMatchTestNode) {
+        for match_branch_node in &string_match_test_node.match_branch_nodes {
+            self.visit_decl_stmts(&match_branch_node.statements);
+        }
+        if let Some(else_branch_node) = &string_match_test_node.else_branch_node_opt {
+            self.visit_decl_stmts(&else_branch_node.statements);
+        }
+    }
+
+    fn visit_number_match_test_node(&mut self, number_match_test_node: &NumberMatchTestNode) {
+        for match_branch_node in &number_match_test_node.match_branch_nodes {
+            self.visit_decl_stmts(&match_branch_node.statements);
+        }
+        if let Some(else_branch_node) = &number_match_test_node.else_branch_node_opt {
+            self.visit_decl_stmts(&else_branch_node.statements);
+        }
+    }
+
+    fn visit_regex_match_test_node(&mut self, regex_match_test_node: &RegexMatchTestNode) {
+        for match_branch_node in &regex_match_test_node.match_branch_nodes {
+            self.visit_decl_stmts(&match_branch_node.statements);
+        }
+        if let Some(else_branch_node) = &regex_match_test_node.else_branch_node_opt {
+            self.visit_decl_stmts(&else_branch_node.statements);
+        }
+    }
+
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        for match_branch_node in &enum_match_test_node.match_branch_nodes {
+            self.visit_decl_stmts(&match_branch_node.statements);
+        }
+        if let Some(else_branch_node) = &enum_match_test_node.else_branch_node_opt {
+            self.visit_decl_stmts(&else_branch_node.statements);
+        }
+    }
+
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+    }
+}
@@ -1,17 +1,44 @@
+pub mod c99_visitor;
 pub mod cpp_visitor;
 pub mod cs_visitor;
 pub mod cs_visitor_for_bob;
 pub mod gdscript_3_2_visitor;
+pub mod go_visitor;
+pub mod graphviz_visitor;
 pub mod java_8_visitor;
 pub mod javascript_visitor;
+pub mod kotlin_visitor;
+pub mod mermaid_visitor;
 pub mod plantuml_visitor;
 pub mod python_visitor;
 pub mod rust_visitor;
+pub mod scxml_visitor;
 pub mod smcat_visitor;
-//pub mod xtate_visitor;
+pub mod swift_visitor;
+pub mod typescript_visitor;
+pub mod xtate_visitor;
 
 use super::ast::*;
 
+/// Format the label of a transition edge in a state diagram: the transition label, or the event
+/// message when there is none, followed by the guard in brackets. The `escape` function makes
+/// the text safe to embed in the target's diagram syntax.
+pub fn format_transition_label(
+    message: &str,
+    label_opt: &Option<String>,
+    guard_opt: &Option<String>,
+    escape: fn(&str) -> String,
+) -> String {
+    let label = match label_opt {
+        Some(label) => label.as_str(),
+        None => message,
+    };
+    match guard_opt {
+        Some(guard) => escape(&format!("{} [{}]", label, guard)),
+        None => escape(label),
+    }
+}
+
 #[rustfmt::skip]
 pub trait AstVisitor {
     fn visit_system_node(&mut self, _node: &SystemNode) {}
@@ -49,6 +76,13 @@ pub trait AstVisitor {
     fn visit_number_match_test_match_branch_node(&mut self, _node: &NumberMatchTestMatchBranchNode) {}
     fn visit_number_match_test_else_branch_node(&mut self, _node: &NumberMatchTestElseBranchNode) {}
     fn visit_number_match_test_pattern_node(&mut self, _node: &NumberMatchTestPatternNode) {}
+    fn visit_regex_match_test_node(&mut self, _node: &RegexMatchTestNode) {}
+    fn visit_regex_match_test_match_branch_node(&mut self, _node: &RegexMatchTestMatchBranchNode) {}
+    fn visit_regex_match_test_else_branch_node(&mut self, _node: &RegexMatchTestElseBranchNode) {}
+    fn visit_regex_match_test_pattern_node(&mut self, _node: &RegexMatchTestPatternNode) {}
+    fn visit_enum_match_test_node(&mut self, _node: &EnumMatchTestNode) {}
+    fn visit_enum_match_test_match_branch_node(&mut self, _node: &EnumMatchTestMatchBranchNode) {}
+    fn visit_enum_match_test_else_branch_node(&mut self, _node: &EnumMatchTestElseBranchNode) {}
     fn visit_expression_list_node(&mut self, _expr_list: &ExprListNode) {}
     fn visit_expression_list_node_to_string(&mut self, _expr_list: &ExprListNode, _output: &mut String) {}
     fn visit_literal_expression_node(&mut self, _node: &LiteralExprNode) {}
@@ -58,6 +92,8 @@ pub trait AstVisitor {
     fn visit_state_stack_operation_node(&mut self, _node: &StateStackOperationNode) {}
     fn visit_state_stack_operation_node_to_string(&mut self, _node: &StateStackOperationNode, _output: &mut String) {}
     fn visit_state_stack_operation_statement_node(&mut self, _node: &StateStackOperationStatementNode) {}
+    fn visit_loop_stmt_node(&mut self, _node: &LoopStmtNode) {}
+    fn visit_loop_control_stmt_node(&mut self, _node: &LoopControlStmtNode) {}
     fn visit_state_context_node(&mut self, _node: &StateContextNode) {}
     fn visit_change_state_statement_node(&mut self, _node: &ChangeStateStatementNode) {}
     fn visit_frame_event_part(&mut self, _event_part: &FrameEventPart) {}
@@ -71,6 +107,7 @@ pub trait AstVisitor {
     fn visit_action_call_expression_node_to_string(&mut self, _node: &ActionCallExprNode, _output: &mut String) {}
     fn visit_action_call_statement_node(&mut self, _node: &ActionCallStmtNode) {}
     fn visit_domain_block_node(&mut self, _node: &DomainBlockNode) {}
+    fn visit_enum_decl_node(&mut self, _node: &EnumDeclNode) {}
     fn visit_domain_variable_decl_node(&mut self, _node: &VariableDeclNode) {}
     fn visit_variable_decl_node(&mut self, _node: &VariableDeclNode) {}
     fn visit_variable_expr_node(&mut self, _node: &VariableNode) {}
@@ -83,6 +120,8 @@ pub trait AstVisitor {
     fn visit_unary_expr_node_to_string(&mut self, _node: &UnaryExprNode, _output: &mut String) {}
     fn visit_binary_expr_node(&mut self, _node: &BinaryExprNode) {}
     fn visit_binary_expr_node_to_string(&mut self, _node: &BinaryExprNode, _output: &mut String) {}
+    fn visit_enumerator_expr_node(&mut self, _node: &EnumeratorExprNode) {}
+    fn visit_enumerator_expr_node_to_string(&mut self, _node: &EnumeratorExprNode, _output: &mut String) {}
     fn visit_operator_type(&mut self, _operator_type: &OperatorType) {}
     fn visit_operator_type_to_string(&mut self, _operator_type: &OperatorType, _output: &mut String) {}
 }
@@ -11,6 +11,11 @@ use crate::frame_c::symbol_table::*;
 use crate::frame_c::utils::SystemHierarchy;
 use crate::frame_c::visitors::*;
 
+// PlantUML ends a transition label at `|`, so it is written as an entity.
+fn escape_label(label: &str) -> String {
+    label.replace('|', "&#124;")
+}
+
 pub struct PlantUmlVisitor {
     compiler_version: String,
     pub code: String,
@@ -331,6 +336,10 @@ impl PlantUmlVisitor {
                         StatementType::ChangeStateStmt { change_state_stmt } => {
                             change_state_stmt.accept(self);
                         }
+                        StatementType::LoopStmt { loop_stmt_node } => {
+                            loop_stmt_node.accept(self);
+                        }
+                        StatementType::LoopControlStmt { .. } => {}
                         StatementType::NoStmt => {
                             // TODO
                             panic!("todo");
@@ -486,13 +495,15 @@ impl PlantUmlVisitor {
             current_state = state_name.clone();
         }
 
-        let label = match &change_state_stmt_node.label_opt {
-            Some(label) => {
-                let cleaned = str::replace(label, "|", "&#124;");
-                format!(" : {}", cleaned)
-            }
-            None => format!(" : {}", self.event_handler_msg.clone()),
-        };
+        let label = format!(
+            " : {}",
+            format_transition_label(
+                &self.event_handler_msg,
+                &change_state_stmt_node.label_opt,
+                &change_state_stmt_node.guard_opt,
+                escape_label
+            )
+        );
 
         let transition_code = &format!(
             "{} -[dashed]-> {}{}\n",
@@ -724,13 +735,15 @@ impl PlantUmlVisitor {
             current_state = state_name.clone();
         }
 
-        let label = match &transition_statement.label_opt {
-            Some(label) => {
-                let cleaned = str::replace(label, "|", "&#124;");
-                format!(" : {}", cleaned)
-            }
-            None => format!(" : {}", self.event_handler_msg.clone()),
-        };
+        let label = format!(
+            " : {}",
+            format_transition_label(
+                &self.event_handler_msg,
+                &transition_statement.label_opt,
+                &transition_statement.guard_opt,
+                escape_label
+            )
+        );
 
         let transition_code = &format!(
             "{} --> {}{}\n",
@@ -822,13 +835,15 @@ impl PlantUmlVisitor {
         // self.add_code(&format!("StateContext stateContext = _stateStack_pop();"));
         // self.newline();
 
-        let label = match &transition_statement.label_opt {
-            Some(label) => {
-                let cleaned = str::replace(label, "|", "&#124;");
-                format!(" : {}", cleaned)
-            }
-            None => format!(" : {}", self.event_handler_msg.clone()),
-        };
+        let label = format!(
+            " : {}",
+            format_transition_label(
+                &self.event_handler_msg,
+                &transition_statement.label_opt,
+                &transition_statement.guard_opt,
+                escape_label
+            )
+        );
         self.transitions.push_str(&format!(
             "{} --> [H*]{}\n",
             &self.current_state_name_opt.as_ref().unwrap(),
@@ -1421,6 +1436,16 @@ impl AstVisitor for PlantUmlVisitor {
             } => {
                 number_match_test_node.accept(self);
             }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                regex_match_test_node.accept(self);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
+            }
         }
     }
 
@@ -1896,6 +1921,74 @@ impl AstVisitor for PlantUmlVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_regex_match_test_node(&mut self, regex_match_test_node: &RegexMatchTestNode) {
+        for match_branch_node in &regex_match_test_node.match_branch_nodes {
+            match_branch_node.accept(self);
+        }
+
+        // (':' regex_test_else_branch)?
+        if let Some(regex_match_else_branch_node) = &regex_match_test_node.else_branch_node_opt {
+            regex_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_regex_match_test_match_branch_node(
+        &mut self,
+        regex_match_test_match_branch_node: &RegexMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&regex_match_test_match_branch_node.statements);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_regex_match_test_else_branch_node(
+        &mut self,
+        regex_match_test_else_branch_node: &RegexMatchTestElseBranchNode,
+    ) {
+        self.visit_decl_stmts(&regex_match_test_else_branch_node.statements);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        for match_branch_node in &enum_match_test_node.match_branch_nodes {
+            match_branch_node.accept(self);
+        }
+
+        // (':' enum_test_else_branch)?
+        if let Some(enum_match_else_branch_node) = &enum_match_test_node.else_branch_node_opt {
+            enum_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_match_branch_node(
+        &mut self,
+        enum_match_test_match_branch_node: &EnumMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&enum_match_test_match_branch_node.statements);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_else_branch_node(
+        &mut self,
+        enum_match_test_else_branch_node: &EnumMatchTestElseBranchNode,
+    ) {
+        self.visit_decl_stmts(&enum_match_test_else_branch_node.statements);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_expression_list_node(&mut self, expr_list: &ExprListNode) {
         for expr in &expr_list.exprs_t {
             // self.add_code(&format!("{}",separator));
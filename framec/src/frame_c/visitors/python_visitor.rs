@@ -36,6 +36,8 @@ pub struct PythonVisitor {
     generate_state_stack: bool,
     generate_change_state: bool,
     generate_transition_state: bool,
+    generate_regex_match: bool,
+    generate_deferred_events: bool,
     event_handler_has_code: bool,
 }
 
@@ -49,6 +51,7 @@ impl PythonVisitor {
         generate_state_stack: bool,
         generate_change_state: bool,
         generate_transition_state: bool,
+        generate_regex_match: bool,
         compiler_version: &str,
         comments: Vec<Token>,
     ) -> PythonVisitor {
@@ -77,6 +80,8 @@ impl PythonVisitor {
             generate_state_stack,
             generate_change_state,
             generate_transition_state,
+            generate_regex_match,
+            generate_deferred_events: false,
             event_handler_has_code: false,
         }
     }
@@ -285,6 +290,14 @@ impl PythonVisitor {
                         StatementType::ChangeStateStmt { change_state_stmt } => {
                             change_state_stmt.accept(self);
                         }
+                        StatementType::LoopStmt { loop_stmt_node } => {
+                            loop_stmt_node.accept(self);
+                        }
+                        StatementType::LoopControlStmt {
+                            loop_control_stmt_node,
+                        } => {
+                            loop_control_stmt_node.accept(self);
+                        }
                         StatementType::NoStmt => {
                             // TODO
                             self.errors.push("Unknown error.".to_string());
@@ -355,6 +368,10 @@ impl PythonVisitor {
                     self.newline();
                 }
                 self.add_code(&"self._state_(enterEvent)".to_string());
+                if self.generate_deferred_events {
+                    self.newline();
+                    self.add_code("self._replayDeferredEvents_()");
+                }
                 self.outdent();
                 self.newline();
                 // self.add_code(&format!("}}"));
@@ -403,14 +420,21 @@ impl PythonVisitor {
             if self.generate_change_state {
                 self.newline();
                 self.newline();
-                self.add_code(&"def _changeState_(newState):".to_string());
+                self.add_code(&"def _changeState_(self, newState):".to_string());
                 self.indent();
                 self.newline();
                 self.add_code(&"self._state_ = newState".to_string());
+                if self.generate_deferred_events {
+                    self.newline();
+                    self.add_code("self._replayDeferredEvents_()");
+                }
                 self.outdent();
                 self.newline();
                 //                self.add_code(&format!("}}"));
             }
+            if self.generate_deferred_events {
+                self.generate_deferred_event_methods(system_node);
+            }
             self.newline();
 
             if self.arcanium.is_serializable() {
@@ -429,6 +453,65 @@ impl PythonVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn generate_deferred_event_methods(&mut self, system_node: &SystemNode) {
+        self.newline();
+        self.newline();
+        self.add_code("def _isDeferred_(self, e):");
+        self.indent();
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_node = state_node_rcref.borrow();
+                if let Some(deferred_events) = &state_node.deferred_events_opt {
+                    let msgs: Vec<String> = deferred_events
+                        .iter()
+                        .map(|msg| format!("\"{}\"", msg))
+                        .collect();
+                    self.newline();
+                    self.add_code(&format!("if self._state_ == self._s{}_:", state_node.name));
+                    self.indent();
+                    self.newline();
+                    self.add_code(&format!("return e._message in [{}]", msgs.join(", ")));
+                    self.outdent();
+                }
+            }
+        }
+        self.newline();
+        self.add_code("return False");
+        self.outdent();
+        self.newline();
+        self.newline();
+        self.add_code("def _replayDeferredEvents_(self):");
+        self.indent();
+        self.newline();
+        self.add_code("i = 0");
+        self.newline();
+        self.add_code("while i < len(self._deferredEvents_):");
+        self.indent();
+        self.newline();
+        self.add_code("e = self._deferredEvents_[i]");
+        self.newline();
+        self.add_code("if self._isDeferred_(e):");
+        self.indent();
+        self.newline();
+        self.add_code("i += 1");
+        self.outdent();
+        self.newline();
+        self.add_code("else:");
+        self.indent();
+        self.newline();
+        self.add_code("del self._deferredEvents_[i]");
+        self.newline();
+        self.add_code("self._state_(e)");
+        self.newline();
+        self.add_code("i = 0");
+        self.outdent();
+        self.outdent();
+        self.outdent();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn generate_subclass(&mut self) {
         for line in self.subclass_code.iter() {
             self.code.push_str(&*line.to_string());
@@ -486,7 +569,7 @@ impl PythonVisitor {
 
         self.newline();
         self.add_code(&format!(
-            "self._changeState_({});",
+            "self._changeState_(self.{})",
             self.format_target_state_name(target_state_name)
         ));
     }
@@ -854,6 +937,23 @@ impl AstVisitor for PythonVisitor {
         self.add_code("# get include files at https://github.com/frame-lang/frame-ancillary-files");
         self.newline();
         self.newline();
+        if self.generate_regex_match {
+            self.add_code("import re");
+            self.newline();
+            self.newline();
+        }
+        // domain enums
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            if !domain_block_node.enums.is_empty() {
+                self.add_code("from enum import Enum");
+                self.newline();
+                self.newline();
+                for enum_decl_node_rcref in &domain_block_node.enums {
+                    enum_decl_node_rcref.borrow().accept(self);
+                }
+            }
+        }
+
         self.add_code(&format!("class {}:", system_node.name));
         self.indent();
         self.newline();
@@ -867,6 +967,7 @@ impl AstVisitor for PythonVisitor {
             }
             None => {}
         }
+        self.generate_deferred_events = system_node.has_deferred_events();
 
         // generate constructor
 
@@ -908,6 +1009,11 @@ impl AstVisitor for PythonVisitor {
                 self.add_code(&"self._stateStack_ = []".to_string());
             }
 
+            if self.generate_deferred_events {
+                self.newline();
+                self.add_code("self._deferredEvents_ = []");
+            }
+
             if let Some(domain_block_node) = &system_node.domain_block_node_opt {
                 domain_block_node.accept(self);
             }
@@ -1084,6 +1190,16 @@ impl AstVisitor for PythonVisitor {
             "e = FrameEvent(\"{}\",{})",
             method_name_or_alias, params_param_code
         ));
+        if self.generate_deferred_events {
+            self.newline();
+            self.add_code("if self._isDeferred_(e):");
+            self.indent();
+            self.newline();
+            self.add_code("self._deferredEvents_.append(e)");
+            self.newline();
+            self.add_code("return");
+            self.outdent();
+        }
         self.newline();
         self.add_code(&"self._state_(e)".to_string());
 
@@ -1158,6 +1274,20 @@ impl AstVisitor for PythonVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enum_decl_node(&mut self, enum_decl_node: &EnumDeclNode) {
+        self.add_code(&format!("class {}(Enum):", enum_decl_node.name));
+        self.indent();
+        for (value, enumerator) in enum_decl_node.enumerators.iter().enumerate() {
+            self.newline();
+            self.add_code(&format!("{} = {}", enumerator, value));
+        }
+        self.outdent();
+        self.newline();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_domain_block_node(&mut self, domain_block_node: &DomainBlockNode) {
         self.newline();
         self.newline();
@@ -1491,6 +1621,16 @@ impl AstVisitor for PythonVisitor {
             } => {
                 number_match_test_node.accept(self);
             }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                regex_match_test_node.accept(self);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
+            }
         }
     }
 
@@ -1834,6 +1974,283 @@ impl AstVisitor for PythonVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        let mut if_or_else_if = "if";
+
+        self.newline();
+        for match_branch_node in &enum_match_test_node.match_branch_nodes {
+            self.add_code(&format!("{} (", if_or_else_if));
+            match &enum_match_test_node.expr_t {
+                ExprType::CallExprT {
+                    call_expr_node: method_call_expr_node,
+                } => method_call_expr_node.accept(self),
+                ExprType::ActionCallExprT {
+                    action_call_expr_node,
+                } => action_call_expr_node.accept(self),
+                ExprType::CallChainLiteralExprT {
+                    call_chain_expr_node,
+                } => call_chain_expr_node.accept(self),
+                ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                ExprType::ExprListT { expr_list_node } => {
+                    // must be only 1 expression in the list
+                    if expr_list_node.exprs_t.len() != 1 {
+                        // TODO: how to do this better.
+                        self.errors
+                            .push("Error - expression list is not testable.".to_string());
+                    }
+                    let x = expr_list_node.exprs_t.first().unwrap();
+                    x.accept(self);
+                }
+
+                _ => self.errors.push("TODO".to_string()),
+            }
+
+            let mut first_match = true;
+            for enumerator in &match_branch_node.enumerators {
+                if first_match {
+                    self.add_code(&format!(
+                        " == {}.{})",
+                        enum_match_test_node.enum_type_name, enumerator
+                    ));
+                    first_match = false;
+                } else {
+                    self.add_code(" or (");
+                    match &enum_match_test_node.expr_t {
+                        ExprType::CallExprT {
+                            call_expr_node: method_call_expr_node,
+                        } => method_call_expr_node.accept(self),
+                        ExprType::ActionCallExprT {
+                            action_call_expr_node,
+                        } => action_call_expr_node.accept(self),
+                        ExprType::CallChainLiteralExprT {
+                            call_chain_expr_node,
+                        } => call_chain_expr_node.accept(self),
+                        ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                        _ => self.errors.push("TODO".to_string()),
+                    }
+                    self.add_code(&format!(
+                        " == {}.{})",
+                        enum_match_test_node.enum_type_name, enumerator
+                    ));
+                }
+            }
+            self.add_code(":");
+            self.indent();
+
+            match_branch_node.accept(self);
+            if match_branch_node.statements.is_empty()
+                && match_branch_node.branch_terminator_expr_opt.is_none()
+            {
+                self.newline();
+                self.add_code("pass");
+            }
+
+            self.outdent();
+            self.newline();
+
+            if_or_else_if = "elif";
+        }
+
+        // (':' enum_test_else_branch)?
+        if let Some(enum_match_else_branch_node) = &enum_match_test_node.else_branch_node_opt {
+            enum_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_match_branch_node(
+        &mut self,
+        enum_match_test_match_branch_node: &EnumMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&enum_match_test_match_branch_node.statements);
+
+        match &enum_match_test_match_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.newline();
+                            self.add_code("return");
+                        }
+                        None => self.add_code("return"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break");
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_else_branch_node(
+        &mut self,
+        enum_match_test_else_branch_node: &EnumMatchTestElseBranchNode,
+    ) {
+        self.add_code(" else:");
+        self.indent();
+
+        self.visit_decl_stmts(&enum_match_test_else_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &enum_match_test_else_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.newline();
+                            self.add_code("return");
+                        }
+                        None => self.add_code("return"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break");
+                    }
+                }
+            }
+            None => {}
+        }
+
+        self.outdent();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_regex_match_test_node(&mut self, regex_match_test_node: &RegexMatchTestNode) {
+        let mut if_or_else_if = "if";
+
+        self.newline();
+        for match_branch_node in &regex_match_test_node.match_branch_nodes {
+            let pattern_node = &match_branch_node.regex_match_pattern_node;
+            self.add_code(&format!(
+                "{} (regex_match := re.search({:?}, ",
+                if_or_else_if,
+                pattern_node.pattern_with_group_prefix("?P<")
+            ));
+            match &regex_match_test_node.expr_t {
+                ExprType::CallExprT {
+                    call_expr_node: method_call_expr_node,
+                } => method_call_expr_node.accept(self),
+                ExprType::ActionCallExprT {
+                    action_call_expr_node,
+                } => action_call_expr_node.accept(self),
+                ExprType::CallChainLiteralExprT {
+                    call_chain_expr_node,
+                } => call_chain_expr_node.accept(self),
+                ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                ExprType::ExprListT { expr_list_node } => {
+                    // must be only 1 expression in the list
+                    if expr_list_node.exprs_t.len() != 1 {
+                        self.errors
+                            .push("Error - expression list is not testable.".to_string());
+                    }
+                    let x = expr_list_node.exprs_t.first().unwrap();
+                    x.accept(self);
+                }
+                _ => self.errors.push("TODO".to_string()),
+            }
+            self.add_code(")):");
+            self.indent();
+
+            // bind named capture groups to locals
+            for capture_name in &pattern_node.capture_names {
+                self.newline();
+                self.add_code(&format!(
+                    "{} = regex_match.group(\"{}\") or \"\"",
+                    capture_name, capture_name
+                ));
+            }
+
+            match_branch_node.accept(self);
+
+            self.outdent();
+            self.newline();
+
+            if_or_else_if = "elif";
+        }
+
+        // (':' regex_test_else_branch)?
+        if let Some(regex_match_else_branch_node) = &regex_match_test_node.else_branch_node_opt {
+            regex_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_regex_match_test_match_branch_node(
+        &mut self,
+        regex_match_test_match_branch_node: &RegexMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&regex_match_test_match_branch_node.statements);
+
+        if let Some(branch_terminator_expr) =
+            &regex_match_test_match_branch_node.branch_terminator_expr_opt
+        {
+            self.newline();
+            match &branch_terminator_expr.terminator_type {
+                TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                    Some(expr_t) => {
+                        self.add_code("e._return = ");
+                        expr_t.accept(self);
+                        self.newline();
+                        self.add_code("return");
+                    }
+                    None => self.add_code("return"),
+                },
+                TerminatorType::Continue => {
+                    self.add_code("break");
+                }
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_regex_match_test_else_branch_node(
+        &mut self,
+        regex_match_test_else_branch_node: &RegexMatchTestElseBranchNode,
+    ) {
+        self.add_code("else:");
+        self.indent();
+
+        self.visit_decl_stmts(&regex_match_test_else_branch_node.statements);
+
+        if let Some(branch_terminator_expr) =
+            &regex_match_test_else_branch_node.branch_terminator_expr_opt
+        {
+            self.newline();
+            match &branch_terminator_expr.terminator_type {
+                TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                    Some(expr_t) => {
+                        self.add_code("e._return = ");
+                        expr_t.accept(self);
+                        self.newline();
+                        self.add_code("return");
+                    }
+                    None => self.add_code("return"),
+                },
+                TerminatorType::Continue => {
+                    self.add_code("break");
+                }
+            }
+        }
+
+        self.outdent();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_string_match_test_pattern_node(
         &mut self,
         _string_match_test_else_branch_node: &StringMatchTestPatternNode,
@@ -2112,6 +2529,39 @@ impl AstVisitor for PythonVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.newline();
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(expr_t) => {
+                self.add_code("while ");
+                expr_t.accept(self);
+                self.add_code(":");
+            }
+            None => self.add_code("while True:"),
+        }
+        self.indent();
+
+        if loop_stmt_node.statements.is_empty() {
+            self.newline();
+            self.add_code("pass");
+        }
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+
+        self.outdent();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_loop_control_stmt_node(&mut self, loop_control_stmt_node: &LoopControlStmtNode) {
+        self.newline();
+        match loop_control_stmt_node.loop_control_t {
+            LoopControlType::Break => self.add_code("break"),
+            LoopControlType::Continue => self.add_code("continue"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_state_stack_operation_statement_node(
         &mut self,
         state_stack_op_statement_node: &StateStackOperationStatementNode,
@@ -2373,6 +2823,27 @@ impl AstVisitor for PythonVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enumerator_expr_node(&mut self, enumerator_expr_node: &EnumeratorExprNode) {
+        let mut output = String::new();
+        self.visit_enumerator_expr_node_to_string(enumerator_expr_node, &mut output);
+        self.add_code(&output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}.{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_binary_expr_node_to_string(
         &mut self,
         binary_expr_node: &BinaryExprNode,
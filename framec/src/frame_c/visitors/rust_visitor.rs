@@ -8,11 +8,26 @@ use crate::frame_c::scanner::{Token, TokenType};
 use crate::frame_c::symbol_table::*;
 use crate::frame_c::visitors::*;
 
+/// Traits derived by types that are persisted when the `serde` feature is enabled.
+const SERDE_TRAITS: &str = "serde::Serialize, serde::Deserialize";
+
+/// Format a visibility qualifier to precede an item, e.g. `pub(crate) `. An empty qualifier
+/// leaves the item private.
+fn format_visibility(visibility: &str) -> String {
+    let visibility = visibility.trim();
+    if visibility.is_empty() {
+        String::new()
+    } else {
+        format!("{} ", visibility)
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct TransitionInfo {
     is_change_state: bool,
     event_name: String,
     label: String,
+    guard: Option<String>,
     source_name: String,
     target_name: Option<String>,
 }
@@ -34,6 +49,9 @@ pub struct RustVisitor {
     generate_transition_state: bool,
     generate_change_state_hook: bool,
     generate_transition_hook: bool,
+    generate_deferred_events: bool,
+    generate_runtime_info: bool,
+    regex_patterns: Vec<String>,
 
     // static info about the state machine
     system_name: String,
@@ -48,9 +66,18 @@ pub struct RustVisitor {
     transitions: Vec<TransitionInfo>,
     visiting_call_chain_literal_variable: bool,
 
+    // local variables declared in the current block nest: the code offset
+    // where `mut ` is inserted and whether the variable is reassigned, and
+    // the names in scope with their index in the list of declarations
+    local_var_decls: Vec<(usize, bool)>,
+    local_var_scope: Vec<(String, usize)>,
+    decl_stmts_depth: usize,
+
     // code and other outputs
     code: String,
     dent: usize,
+    module_files: Vec<(String, String)>,
+    current_module: String,
     serialize: Vec<String>,
     deserialize: Vec<String>,
     errors: Vec<String>,
@@ -92,6 +119,10 @@ impl RustVisitor {
                 && generate_change_state,
             generate_transition_hook: rust_config.features.generate_hook_methods
                 && generate_transition_state,
+            generate_deferred_events: false,
+            generate_runtime_info: rust_config.features.runtime_info
+                || rust_config.features.runtime_support,
+            regex_patterns: Vec::new(),
 
             system_name: String::new(),
             state_names: Vec::new(),
@@ -104,8 +135,14 @@ impl RustVisitor {
             transitions: Vec::new(),
             visiting_call_chain_literal_variable: false,
 
+            local_var_decls: Vec::new(),
+            local_var_scope: Vec::new(),
+            decl_stmts_depth: 0,
+
             code: String::from(""),
             dent: 0,
+            module_files: Vec::new(),
+            current_module: String::from("mod"),
             serialize: Vec::new(),
             deserialize: Vec::new(),
             errors: Vec::new(),
@@ -140,7 +177,9 @@ impl RustVisitor {
                 "{}(_)",
                 self.format_type_name(&self.config.code.exit_msg.clone())
             ),
-            _ => self.format_type_name(&self.arcanum.get_interface_or_msg_from_msg(msg).unwrap()),
+            _ => self.format_message_type_name(
+                &self.arcanum.get_interface_or_msg_from_msg(msg).unwrap(),
+            ),
         }
     }
 
@@ -185,6 +224,398 @@ impl RustVisitor {
         }
     }
 
+    /// Get the files of a module tree, named relative to the module directory, or `None` if the
+    /// code was generated as a single file or has errors, as returned by `get_code()`.
+    pub fn get_module_files(&self) -> Option<Vec<(String, String)>> {
+        if !self.config.features.generate_module_tree || !self.errors.is_empty() {
+            return None;
+        }
+        let mut files = self.module_files.clone();
+        match files
+            .iter_mut()
+            .find(|(name, _)| *name == self.current_module)
+        {
+            Some((_, code)) => code.push_str(&self.code),
+            None => files.push((self.current_module.clone(), self.code.clone())),
+        }
+        Some(
+            files
+                .into_iter()
+                .map(|(name, mut code)| {
+                    if !code.ends_with('\n') {
+                        code.push('\n');
+                    }
+                    (format!("{}.rs", name), code)
+                })
+                .collect(),
+        )
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    /// When generating a module tree, send all code generated from now on to the named module.
+    /// A module's file is started the first time it is entered, and later code is appended.
+    fn enter_module(&mut self, module_name: String) {
+        if !self.config.features.generate_module_tree {
+            return;
+        }
+        let code = std::mem::take(&mut self.code);
+        let previous_module = std::mem::replace(&mut self.current_module, module_name.clone());
+        match self
+            .module_files
+            .iter_mut()
+            .find(|(name, _)| *name == previous_module)
+        {
+            Some((_, module_code)) => module_code.push_str(&code),
+            None => self.module_files.push((previous_module, code)),
+        }
+        match self
+            .module_files
+            .iter_mut()
+            .find(|(name, _)| *name == module_name)
+        {
+            Some((_, module_code)) => self.code = std::mem::take(module_code),
+            None => {
+                self.add_code(&format!("// {}", self.compiler_version));
+                self.newline();
+                // the runtime info only refers to the runtime's own types
+                if module_name != self.config.code.info_module_name {
+                    self.add_code("#[allow(unused_imports)]");
+                    self.newline();
+                    self.add_code("use super::*;");
+                    self.newline();
+                }
+            }
+        }
+    }
+
+    /// Declare the submodules of a module tree in `mod.rs`, and re-export their definitions.
+    fn generate_module_decls(&mut self) {
+        let mut modules = vec![
+            self.config.code.events_module_name.clone(),
+            self.config.code.states_module_name.clone(),
+        ];
+        if self.generate_runtime_info {
+            modules.push(self.config.code.info_module_name.clone());
+        }
+        modules.push(self.config.code.machine_module_name.clone());
+
+        self.newline();
+        for module in &modules {
+            self.add_code(&format!("mod {};", module));
+            self.newline();
+        }
+        self.newline();
+        self.add_code("#[allow(unused_imports)]");
+        self.newline();
+        self.add_code(&format!(
+            "{}use self::{}::*;",
+            self.internal_visibility(),
+            self.config.code.events_module_name
+        ));
+        self.newline();
+        self.add_code("#[allow(unused_imports)]");
+        self.newline();
+        self.add_code(&format!(
+            "{}use self::{}::*;",
+            self.system_visibility(),
+            self.config.code.states_module_name
+        ));
+        self.newline();
+        self.add_code("#[allow(unused_imports)]");
+        self.newline();
+        self.add_code(&format!(
+            "{}use self::{}::*;",
+            self.system_visibility(),
+            self.config.code.machine_module_name
+        ));
+        self.newline();
+
+        // start the files in the order they are declared
+        for module in modules {
+            self.enter_module(module);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_regex_static_name(&self, index: usize) -> String {
+        format!("{}{}", self.config.code.regex_static_name_prefix, index)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    /// Generate a test module that sends random sequences of interface calls to a new state
+    /// machine and checks its invariants after each call.
+    fn generate_test_harness(&mut self, system_node: &SystemNode) {
+        let system_type_name = self.system_type_name();
+        let methods = system_node
+            .interface_block_node_opt
+            .as_ref()
+            .map_or(Vec::new(), |interface_block_node| {
+                interface_block_node.interface_methods.clone()
+            });
+
+        self.newline();
+        self.add_code("#[cfg(test)]");
+        self.newline();
+        self.add_code(&format!(
+            "mod {}{}",
+            self.format_value_name(&self.system_name),
+            self.config.code.test_harness_module_suffix
+        ));
+        self.enter_block();
+        self.add_code("use super::*;");
+        if !methods.is_empty() {
+            self.newline();
+            self.add_code("use proptest::prelude::*;");
+        }
+        if self.config.features.runtime_support {
+            self.newline();
+            self.add_code(&format!(
+                "use super::{}::Machine;",
+                self.config.code.runtime_module_use_as_name
+            ));
+        }
+        self.newline();
+
+        // a type for calls to each interface method
+        if !methods.is_empty() {
+            self.newline();
+            self.add_code("/// A call to an interface method, with its arguments.");
+            self.newline();
+            self.add_code("#[derive(Clone, Debug)]");
+            self.newline();
+            self.add_code("pub enum InterfaceCall");
+            self.enter_block();
+            for (i, method_rcref) in methods.iter().enumerate() {
+                let method = method_rcref.borrow();
+                if i > 0 {
+                    self.newline();
+                }
+                self.add_code(&self.format_type_name(&method.name));
+                if let Some(params) = &method.params {
+                    let fields: Vec<String> = params
+                        .iter()
+                        .map(|param| {
+                            format!(
+                                "{}: {}",
+                                self.format_value_name(&param.param_name),
+                                match &param.param_type_opt {
+                                    Some(param_type) => param_type.get_type_str(),
+                                    None => String::from("<?>"),
+                                }
+                            )
+                        })
+                        .collect();
+                    self.add_code(&format!(" {{ {} }}", fields.join(", ")));
+                }
+                self.add_code(",");
+            }
+            self.exit_block();
+            self.newline();
+
+            // strategy generating arbitrary calls
+            self.newline();
+            self.add_code("/// Generate a call to any interface method.");
+            self.newline();
+            self.add_code("pub fn interface_call() -> impl Strategy<Value = InterfaceCall>");
+            self.enter_block();
+            self.add_code("prop_oneof![");
+            self.indent();
+            for method_rcref in &methods {
+                let method = method_rcref.borrow();
+                let variant = format!("InterfaceCall::{}", self.format_type_name(&method.name));
+                self.newline();
+                match &method.params {
+                    Some(params) => {
+                        let mut names = Vec::new();
+                        let mut strategies = Vec::new();
+                        for param in params {
+                            names.push(self.format_value_name(&param.param_name));
+                            strategies.push(format!(
+                                "any::<{}>()",
+                                match &param.param_type_opt {
+                                    Some(param_type) => param_type.get_type_str(),
+                                    None => String::from("<?>"),
+                                }
+                            ));
+                        }
+                        if params.len() == 1 {
+                            self.add_code(&format!(
+                                "{}.prop_map(|{}| {} {{ {} }}),",
+                                strategies[0], names[0], variant, names[0]
+                            ));
+                        } else {
+                            self.add_code(&format!(
+                                "({}).prop_map(|({})| {} {{ {} }}),",
+                                strategies.join(", "),
+                                names.join(", "),
+                                variant,
+                                names.join(", ")
+                            ));
+                        }
+                    }
+                    None => self.add_code(&format!("Just({}),", variant)),
+                }
+            }
+            self.outdent();
+            self.newline();
+            self.add_code("]");
+            self.exit_block();
+            self.newline();
+
+            // sending a call to the state machine
+            self.newline();
+            self.add_code("/// Send a call to the state machine, ignoring any returned value.");
+            self.newline();
+            self.add_code(&format!(
+                "pub fn apply(sm: &mut {}, call: InterfaceCall)",
+                system_type_name
+            ));
+            self.enter_block();
+            self.add_code("match call");
+            self.enter_block();
+            for (i, method_rcref) in methods.iter().enumerate() {
+                let method = method_rcref.borrow();
+                let names: Vec<String> = method
+                    .params
+                    .iter()
+                    .flatten()
+                    .map(|param| self.format_value_name(&param.param_name))
+                    .collect();
+                let pattern = if method.params.is_some() {
+                    format!(" {{ {} }}", names.join(", "))
+                } else {
+                    String::new()
+                };
+                let call = format!(
+                    "sm.{}({})",
+                    self.format_value_name(&method.name),
+                    names.join(", ")
+                );
+                if i > 0 {
+                    self.newline();
+                }
+                if method.return_type_opt.is_some() || self.config.features.fallible_actions {
+                    self.add_code(&format!(
+                        "InterfaceCall::{}{} =>",
+                        self.format_type_name(&method.name),
+                        pattern
+                    ));
+                    self.enter_block();
+                    self.add_code(&format!("let _ = {};", call));
+                    self.exit_block();
+                } else {
+                    self.add_code(&format!(
+                        "InterfaceCall::{}{} => {},",
+                        self.format_type_name(&method.name),
+                        pattern,
+                        call
+                    ));
+                }
+            }
+            self.exit_block();
+            self.exit_block();
+            self.newline();
+        }
+
+        // invariants checked between calls
+        self.newline();
+        self.add_code(
+            "/// Check the invariants that must hold whenever no event is being handled.",
+        );
+        self.newline();
+        self.add_code("#[allow(unused_variables)]");
+        self.newline();
+        self.add_code(&format!(
+            "pub fn check_invariants(sm: &{})",
+            system_type_name
+        ));
+        self.enter_block();
+        let mut has_invariants = false;
+        if self.config.features.runtime_support {
+            self.add_code("let name = sm.state().info().name;");
+            self.newline();
+            self.add_code(&format!(
+                "assert!({}::machine_info().get_state(name).is_some(), \"unknown state {{}}\", name);",
+                system_type_name
+            ));
+            has_invariants = true;
+        }
+        let invariants = self.config.code.test_invariants.clone();
+        for invariant in invariants.split(',').map(str::trim) {
+            if invariant.is_empty() {
+                continue;
+            }
+            if has_invariants {
+                self.newline();
+            }
+            self.add_code(&format!("{}(sm);", invariant));
+            has_invariants = true;
+        }
+        self.exit_block();
+        self.newline();
+
+        // the property test
+        self.newline();
+        if methods.is_empty() {
+            self.add_code("#[test]");
+            self.newline();
+            self.add_code("fn new_machine()");
+            self.enter_block();
+            self.add_code(&format!("check_invariants(&{}::new());", system_type_name));
+            self.exit_block();
+        } else {
+            self.add_code("proptest!");
+            self.enter_block();
+            self.add_code("#[test]");
+            self.newline();
+            self.add_code(&format!(
+                "fn interface_call_sequences(calls in proptest::collection::vec(interface_call(), 0..={}))",
+                self.config.code.test_sequence_length
+            ));
+            self.enter_block();
+            self.add_code(&format!("let mut sm = {}::new();", system_type_name));
+            self.newline();
+            self.add_code("check_invariants(&sm);");
+            self.newline();
+            self.add_code("for call in calls");
+            self.enter_block();
+            self.add_code("apply(&mut sm, call);");
+            self.newline();
+            self.add_code("check_invariants(&sm);");
+            self.exit_block();
+            self.exit_block();
+            self.exit_block();
+        }
+        self.exit_block();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Each regex match pattern is compiled once, on first use. Patterns are
+    // validated when the match test is visited, so compiling cannot fail.
+
+    fn generate_regex_statics(&mut self) {
+        self.newline();
+        for (index, pattern) in self.regex_patterns.clone().iter().enumerate() {
+            self.add_code(&format!(
+                "static {}: once_cell::sync::Lazy<regex::Regex> =",
+                self.format_regex_static_name(index)
+            ));
+            self.indent();
+            self.newline();
+            self.add_code(&format!(
+                "once_cell::sync::Lazy::new(|| regex::Regex::new({:?}).expect(\"invalid regex\"));",
+                pattern
+            ));
+            self.outdent();
+            self.newline();
+        }
+    }
+
     //* --------------------------------------------------------------------- *//
 
     fn format_variable_expr(&mut self, var_node: &VariableNode) -> String {
@@ -192,6 +623,12 @@ impl RustVisitor {
         let var_name = self.format_value_name(&var_node.id_node.name.lexeme);
         let borrow = if self.config.features.thread_safe {
             "lock().unwrap()"
+        } else if self.config.features.no_std {
+            if self.in_assignment_lvalue {
+                "borrow_mut()"
+            } else {
+                "borrow()"
+            }
         } else if self.in_assignment_lvalue {
             "as_ref().borrow_mut()"
         } else {
@@ -215,7 +652,7 @@ impl RustVisitor {
                 }
                 code.push_str(&format!(
                     "{}.{}.{}.{}",
-                    self.config.code.this_state_context_var_name,
+                    self.this_state_context_expr(),
                     self.config.code.state_args_var_name,
                     borrow,
                     var_name,
@@ -234,7 +671,7 @@ impl RustVisitor {
                 }
                 code.push_str(&format!(
                     "{}.{}.{}.{}",
-                    self.config.code.this_state_context_var_name,
+                    self.this_state_context_expr(),
                     self.config.code.state_vars_var_name,
                     borrow,
                     var_name,
@@ -243,6 +680,14 @@ impl RustVisitor {
                     code.push(')');
                 }
             }
+            IdentifierDeclScope::EventHandlerParam if self.config.features.typed_events => {
+                // typed event arguments are bound by reference in the handler's match arm, so
+                // their values are cloned out
+                if var_node.id_node.is_reference {
+                    code.push('&');
+                }
+                code.push_str(&format!("{}.clone()", var_name));
+            }
             IdentifierDeclScope::EventHandlerParam => {
                 borrowed = true;
                 if self.visiting_call_chain_literal_variable {
@@ -269,6 +714,9 @@ impl RustVisitor {
                 }
             }
             IdentifierDeclScope::EventHandlerVar => {
+                if self.in_assignment_lvalue {
+                    self.mark_local_var_assigned(&var_node.id_node.name.lexeme);
+                }
                 if var_node.id_node.is_reference {
                     code.push('&');
                 }
@@ -276,6 +724,9 @@ impl RustVisitor {
             }
             IdentifierDeclScope::None => {
                 // TODO: Explore labeling Variables as "extern" scope
+                if self.in_assignment_lvalue {
+                    self.mark_local_var_assigned(&var_node.id_node.name.lexeme);
+                }
                 if var_node.id_node.is_reference {
                     code.push('&');
                 }
@@ -348,6 +799,31 @@ impl RustVisitor {
         self.format_type_name(&self.system_name)
     }
 
+    /// Get the visibility of the state machine and its public methods, followed by a space.
+    fn system_visibility(&self) -> String {
+        format_visibility(&self.config.code.system_visibility)
+    }
+
+    /// Get the visibility, followed by a space if not empty, of definitions that are private to
+    /// a single generated file, but shared between the files of a module tree.
+    fn internal_visibility(&self) -> String {
+        if self.config.features.generate_module_tree {
+            format_visibility(&self.config.code.internal_visibility)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Get the name of the module containing the runtime info, which is a file of its own in a
+    /// module tree.
+    fn runtime_info_module_name(&self) -> &str {
+        if self.config.features.generate_module_tree {
+            &self.config.code.info_module_name
+        } else {
+            &self.config.code.runtime_info_module_name
+        }
+    }
+
     fn state_enum_type_name(&self) -> String {
         self.format_type_name(&format!(
             "{}{}",
@@ -355,6 +831,13 @@ impl RustVisitor {
         ))
     }
 
+    fn snapshot_type_name(&self) -> String {
+        self.format_type_name(&format!(
+            "{}{}",
+            self.system_name, self.config.code.snapshot_suffix
+        ))
+    }
+
     fn action_trait_type_name(&self) -> String {
         self.format_type_name(&format!(
             "{}{}{}",
@@ -364,6 +847,81 @@ impl RustVisitor {
         ))
     }
 
+    /// Get the type parameters of the state machine struct, including their bounds. These are only
+    /// generated by the `generic_actions` feature.
+    fn system_type_params(&self) -> String {
+        if !self.config.features.generic_actions {
+            return String::new();
+        }
+        let mut params = vec![format!(
+            "{}: {}",
+            self.config.code.actions_type_param,
+            self.action_trait_type_name()
+        )];
+        let user_params = self.config.code.system_type_params.trim();
+        if !user_params.is_empty() {
+            params.push(user_params.to_string());
+        }
+        format!("<{}>", params.join(", "))
+    }
+
+    /// Get the state machine type as referred to in `impl` blocks, e.g. `Sys<A, T>`.
+    fn system_type(&self) -> String {
+        if !self.config.features.generic_actions {
+            return self.system_type_name();
+        }
+        let mut names = vec![self.config.code.actions_type_param.clone()];
+
+        // split the user's parameters at top-level commas, then strip their bounds
+        let mut depth = 0;
+        let mut prev = ' ';
+        let mut param = String::new();
+        let mut user_params = Vec::new();
+        for c in self.config.code.system_type_params.chars() {
+            match c {
+                '<' | '(' | '[' => depth += 1,
+                '>' if prev != '-' => depth -= 1,
+                ')' | ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    user_params.push(param.clone());
+                    param.clear();
+                    prev = c;
+                    continue;
+                }
+                _ => {}
+            }
+            param.push(c);
+            prev = c;
+        }
+        user_params.push(param);
+        for param in &user_params {
+            let name = param.split(':').next().unwrap_or("").trim();
+            if !name.is_empty() {
+                names.push(name.to_string());
+            }
+        }
+        format!("{}<{}>", self.system_type_name(), names.join(", "))
+    }
+
+    /// Get the expression on which actions and hook methods are invoked.
+    fn actions_expr(&self) -> String {
+        if self.config.features.generic_actions {
+            format!("self.{}", self.config.code.actions_var_name)
+        } else {
+            String::from("self")
+        }
+    }
+
+    /// Get the receiver of action and hook method signatures. Injected actions objects are
+    /// exclusively owned by the state machine, so they may be mutated.
+    fn action_receiver(&self) -> &str {
+        if self.config.features.generic_actions {
+            "&mut self"
+        } else {
+            "&self"
+        }
+    }
+
     fn system_type_as_machine_trait(&self) -> String {
         format!(
             "<{} as {}::Machine>",
@@ -416,30 +974,472 @@ impl RustVisitor {
         }
     }
 
-    //* --------------------------------------------------------------------- *//
+    /// Get the type of a mutable cell containing `inner` that is shared between the state machine
+    /// and its events or state stack. In `no_std` mode, the cell is owned directly instead.
+    fn shared_cell_type(&self, inner: &str) -> String {
+        if self.config.features.no_std {
+            format!("RefCell<{}>", inner)
+        } else {
+            format!("{}<{}<{}>>", self.rc_type(), self.cell_type(), inner)
+        }
+    }
 
-    /// Disable formatting/style warnings on generated type definitions.
-    fn disable_type_style_warnings(&mut self) {
-        if !self.config.features.follow_rust_naming {
-            self.add_code("#[allow(clippy::upper_case_acronyms)]");
-            self.newline();
-            self.add_code("#[allow(non_camel_case_types)]");
-            self.newline();
-            self.add_code("#[allow(non_snake_case)]");
-            self.newline();
+    /// Get an expression that moves `value` into a new cell of `shared_cell_type`.
+    fn new_shared_cell(&self, value: &str) -> String {
+        if self.config.features.no_std {
+            format!("RefCell::new({})", value)
+        } else {
+            format!(
+                "{}::new({}::new({}))",
+                self.rc_type(),
+                self.cell_type(),
+                value
+            )
         }
-        self.add_code("#[allow(dead_code)]");
-        self.newline();
     }
 
-    /// Disable all formatting/style warnings on generated definitions.
-    fn disable_all_style_warnings(&mut self) {
-        self.add_code("#[allow(clippy::assign_op_pattern)]");
-        self.newline();
-        self.add_code("#[allow(clippy::branches_sharing_code)]");
-        self.newline();
-        self.add_code("#[allow(clippy::clone_on_copy)]");
-        self.newline();
+    /// Get the type of a reference-counted pointer to `inner`, or just `inner` in `no_std` mode.
+    fn rc_ptr_type(&self, inner: &str) -> String {
+        if self.config.features.no_std {
+            inner.to_string()
+        } else {
+            format!("{}<{}>", self.rc_type(), inner)
+        }
+    }
+
+    /// Get the start of an expression that moves a value into a new pointer of `rc_ptr_type`.
+    /// Must be followed by the value and then `rc_ptr_close`.
+    fn rc_ptr_open(&self) -> String {
+        if self.config.features.no_std {
+            String::new()
+        } else {
+            format!("{}::new(", self.rc_type())
+        }
+    }
+
+    /// Get the end of an expression started by `rc_ptr_open`.
+    fn rc_ptr_close(&self) -> &'static str {
+        if self.config.features.no_std {
+            ""
+        } else {
+            ")"
+        }
+    }
+
+    /// Get the type of the parameter through which event handlers receive events. Typed events are
+    /// passed by value, and other events are borrowed in `no_std` mode so that sending them does
+    /// not allocate.
+    fn frame_event_param_type(&self) -> String {
+        if self.config.features.typed_events {
+            self.config.code.frame_event_type_name.clone()
+        } else if self.config.features.no_std {
+            format!("&{}", self.config.code.frame_event_type_name)
+        } else {
+            self.rc_ptr_type(&self.config.code.frame_event_type_name)
+        }
+    }
+
+    /// Get the argument that passes the newly created event stored in local variable `var` to an
+    /// event handler.
+    fn frame_event_arg(&self, var: &str) -> String {
+        if self.config.features.no_std && !self.config.features.typed_events {
+            format!("&{}", var)
+        } else {
+            var.to_string()
+        }
+    }
+
+    /// Get the argument that forwards the event received by the current handler to another one.
+    fn forward_frame_event_arg(&self) -> String {
+        if self.config.features.no_std || self.config.features.typed_events {
+            self.config.code.frame_event_variable_name.clone()
+        } else {
+            format!("{}.clone()", self.config.code.frame_event_variable_name)
+        }
+    }
+
+    /// Get the return type annotation of event handlers, which return the event's return value
+    /// in `typed_events` mode.
+    fn handler_return_type(&self) -> String {
+        if self.config.features.typed_events {
+            format!(" -> {}", self.config.code.frame_event_return_type_name)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Get a statement that returns from an event handler without a return value.
+    fn handler_return_stmt(&self) -> String {
+        if self.config.features.typed_events {
+            format!(
+                "return {}::None;",
+                self.config.code.frame_event_return_type_name
+            )
+        } else {
+            String::from("return;")
+        }
+    }
+
+    /// Get the names of the local variables or parameters of the transition method holding the
+    /// exit and enter arguments. In `typed_events` mode, these hold the complete events instead.
+    fn transition_event_arg_names(&self) -> (String, String) {
+        if self.config.features.typed_events {
+            (String::from("exit_event"), String::from("enter_event"))
+        } else {
+            (
+                self.config.code.exit_args_member_name.clone(),
+                self.config.code.enter_args_member_name.clone(),
+            )
+        }
+    }
+
+    /// Get the type of the exit and enter argument parameters of the transition method.
+    fn transition_event_arg_type(&self) -> &str {
+        if self.config.features.typed_events {
+            &self.config.code.frame_event_type_name
+        } else {
+            &self.config.code.frame_event_args_type_name
+        }
+    }
+
+    /// Get the value passed to the transition method for an enter or exit event without
+    /// arguments. `msg` is the enter or exit message, and `state` is an expression for the state
+    /// that the event is sent to.
+    fn empty_transition_event_arg(&self, msg: &str, state: &str) -> String {
+        if self.config.features.typed_events {
+            format!(
+                "{}::{}({})",
+                self.config.code.frame_event_type_name, msg, state
+            )
+        } else {
+            format!("{}::None", self.config.code.frame_event_args_type_name)
+        }
+    }
+
+    /// Get the pattern matching the typed event that an event handler in the current state
+    /// handles, binding each of the event's arguments by reference.
+    fn typed_event_pattern(&mut self, msg: &str) -> String {
+        let is_enter_or_exit =
+            msg == self.symbol_config.enter_msg_symbol || msg == self.symbol_config.exit_msg_symbol;
+        let param_names: Option<Vec<String>> = self
+            .arcanum
+            .get_event(msg, &self.current_state_name_opt)
+            .and_then(|event_sym| {
+                event_sym.borrow().params_opt.as_ref().map(|params| {
+                    params
+                        .iter()
+                        .map(|p| self.format_value_name(&p.name))
+                        .collect()
+                })
+            });
+        let variant = match &param_names {
+            Some(_) if is_enter_or_exit => self
+                .format_state_event_type_name(self.current_state_name_opt.as_ref().unwrap(), msg),
+            _ => self.get_msg_enum_pattern(msg),
+        };
+        match param_names {
+            Some(names) => format!("{} {{ {} }}", variant, names.join(", ")),
+            None => variant,
+        }
+    }
+
+    /// Get the expression for the name of the message of the event being handled.
+    fn format_frame_event_message_expr(&self) -> String {
+        let name_method = if self.config.features.no_std {
+            "name"
+        } else {
+            "to_string"
+        };
+        if self.config.features.typed_events {
+            format!(
+                "{}.{}()",
+                self.config.code.frame_event_variable_name, name_method
+            )
+        } else {
+            format!(
+                "{}.{}.{}()",
+                self.config.code.frame_event_variable_name,
+                self.config.code.frame_event_message_attribute_name,
+                name_method
+            )
+        }
+    }
+
+    /// Get the expression through which handlers of the current state access its state context.
+    /// Outside of `no_std` mode, this is a local variable bound by `generate_this_state_context`.
+    fn this_state_context_expr(&self) -> String {
+        if self.config.features.no_std {
+            format!(
+                "self.{}.{}()",
+                self.config.code.state_context_var_name,
+                self.format_state_context_method_name(
+                    self.current_state_name_opt.as_ref().unwrap()
+                )
+            )
+        } else {
+            self.config.code.this_state_context_var_name.clone()
+        }
+    }
+
+    /// Report an error for each enabled feature that relies on `std` or allocation and so cannot
+    /// be used in `no_std` mode.
+    fn check_no_std_features(&mut self) {
+        let features = &self.config.features;
+        let conflicts = [
+            ("async_support", features.async_support),
+            ("runtime_support", features.runtime_support),
+            ("serde", features.serde),
+            ("thread_safe", features.thread_safe),
+        ];
+        for (name, enabled) in conflicts {
+            if enabled {
+                self.errors.push(format!(
+                    "The no_std feature cannot be combined with the {} feature.",
+                    name
+                ));
+            }
+        }
+        if self.generate_deferred_events {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the no_std feature.",
+            ));
+        }
+        // the default error type is boxed and refers to `std`
+        if self.config.features.fallible_actions
+            && self.config.code.error_type == RustCode::default().error_type
+        {
+            self.errors.push(String::from(
+                "The no_std feature can only be combined with the fallible_actions feature if \
+                 RustCode.error_type is set to a type that does not depend on std.",
+            ));
+        }
+    }
+
+    /// Report an error for each enabled feature that relies on the shared event structure and so
+    /// cannot be used in `typed_events` mode.
+    fn check_typed_events_features(&mut self) {
+        let features = &self.config.features;
+        let conflicts = [
+            ("async_support", features.async_support),
+            ("fallible_actions", features.fallible_actions),
+            ("runtime_support", features.runtime_support),
+        ];
+        for (name, enabled) in conflicts {
+            if enabled {
+                self.errors.push(format!(
+                    "The typed_events feature cannot be combined with the {} feature.",
+                    name
+                ));
+            }
+        }
+        if self.generate_deferred_events {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the typed_events feature.",
+            ));
+        }
+    }
+
+    /// Report an error for each enabled feature that relies on the state machine having a
+    /// concrete type and so cannot be used with the `generic_actions` feature.
+    fn check_generic_actions_features(&mut self) {
+        if self.config.features.runtime_support {
+            self.errors.push(String::from(
+                "The generic_actions feature cannot be combined with the runtime_support feature.",
+            ));
+        }
+        if self.config.features.serde && !self.config.code.system_type_params.trim().is_empty() {
+            self.errors.push(String::from(
+                "The system_type_params option cannot be combined with the serde feature.",
+            ));
+        }
+    }
+
+    /// Report an error if fallible actions or `|!|` handlers are used without the
+    /// `fallible_actions` feature, or if an `|!|` handler declares parameters other than the
+    /// error.
+    fn check_fallible_actions(&mut self, system_node: &SystemNode) {
+        let error_event_opt = self.arcanum.get_event(&self.config.code.error_token, &None);
+        if self.config.features.fallible_actions {
+            let param_count = error_event_opt
+                .and_then(|error_event| error_event.borrow().params_opt.as_ref().map(Vec::len))
+                .unwrap_or(0);
+            if param_count > 1 {
+                self.errors.push(String::from(
+                    "An error handler may only declare a single parameter, the error.",
+                ));
+            }
+            return;
+        }
+        let has_fallible_action =
+            system_node
+                .actions_block_node_opt
+                .as_ref()
+                .is_some_and(|actions_block_node| {
+                    actions_block_node
+                        .actions
+                        .iter()
+                        .any(|action_node| action_node.borrow().is_fallible)
+                });
+        if has_fallible_action || error_event_opt.is_some() {
+            self.errors.push(String::from(
+                "Fallible actions and error handlers require the fallible_actions feature.",
+            ));
+        }
+    }
+
+    /// Report an error for each enabled feature that prevents the generated test harness from
+    /// constructing and driving the state machine.
+    fn check_test_harness_features(&mut self) {
+        let features = &self.config.features;
+        let conflicts = [
+            ("async_support", features.async_support),
+            ("generic_actions", features.generic_actions),
+        ];
+        for (name, enabled) in conflicts {
+            if enabled {
+                self.errors.push(format!(
+                    "The generate_test_harness feature cannot be combined with the {} feature.",
+                    name
+                ));
+            }
+        }
+    }
+
+    /// Get the expression for a Frame string literal. Literals are `String` values, except in
+    /// `no_std` mode where they remain `&'static str`.
+    fn format_string_literal(&self, value: &str) -> String {
+        if self.config.features.no_std {
+            format!("\"{}\"", value)
+        } else {
+            format!("String::from(\"{}\")", value)
+        }
+    }
+
+    /// Get the qualifier for generated functions that may await actions, which is `async` if the
+    /// `async_support` feature is enabled.
+    fn async_qualifier(&self) -> &'static str {
+        if self.config.features.async_support {
+            "async "
+        } else {
+            ""
+        }
+    }
+
+    /// Get the suffix for calls to functions declared with `async_qualifier`.
+    fn await_suffix(&self) -> &'static str {
+        if self.config.features.async_support {
+            ".await"
+        } else {
+            ""
+        }
+    }
+
+    /// Get the return type annotation of an action. Fallible actions return a `Result` whose error
+    /// type is `RustCode.error_type`.
+    fn action_return_type(&self, action_node: &ActionNode) -> String {
+        let type_opt = action_node.type_opt.as_ref().map(|t| t.get_type_str());
+        if self.config.features.fallible_actions && action_node.is_fallible {
+            format!(
+                " -> Result<{}, {}>",
+                type_opt.unwrap_or_else(|| String::from("()")),
+                self.config.code.error_type
+            )
+        } else {
+            type_opt.map_or(String::new(), |t| format!(" -> {}", t))
+        }
+    }
+
+    /// Get the return type annotation of an interface method, which is a `Result` whenever the
+    /// `fallible_actions` feature is enabled.
+    fn interface_return_type(&self, interface_method_node: &InterfaceMethodNode) -> String {
+        let type_opt = interface_method_node
+            .return_type_opt
+            .as_ref()
+            .map(|t| t.get_type_str());
+        if self.config.features.fallible_actions {
+            format!(
+                " -> Result<{}, {}>",
+                type_opt.unwrap_or_else(|| String::from("()")),
+                self.config.code.error_type
+            )
+        } else {
+            type_opt.map_or(String::new(), |t| format!(" -> {}", t))
+        }
+    }
+
+    /// Check whether an action call must handle a possible error from the action.
+    fn is_fallible_action_call(&self, action_call: &ActionCallExprNode) -> bool {
+        self.config.features.fallible_actions
+            && action_call
+                .action_symbol_rcref_opt
+                .as_ref()
+                .and_then(|action_symbol| {
+                    let action_symbol = action_symbol.borrow();
+                    action_symbol
+                        .ast_node
+                        .as_ref()
+                        .map(|action_node| action_node.borrow().is_fallible)
+                })
+                .unwrap_or(false)
+    }
+
+    /// Get the statement that sends the error event for a failed action once the interface
+    /// method being called has handled its own event.
+    fn take_error_stmt(&self) -> String {
+        format!(
+            "self.{}(){}",
+            self.config.code.take_error_method_name,
+            self.await_suffix()
+        )
+    }
+
+    /// Derive the serde traits on the next type definition, if the `serde` feature is enabled.
+    fn generate_serde_derive(&mut self) {
+        if self.config.features.serde {
+            self.add_code(&format!("#[derive({})]", SERDE_TRAITS));
+            self.newline();
+        }
+    }
+
+    /// Get an expression that copies the contents of the shared cell in field `field` of `self`
+    /// into a new, unshared cell.
+    fn deep_clone_cell(&self, field: &str) -> String {
+        if self.config.features.no_std {
+            format!("self.{}.clone()", field)
+        } else if self.config.features.thread_safe {
+            format!(
+                "Arc::new(Mutex::new(self.{}.lock().unwrap().clone()))",
+                field
+            )
+        } else {
+            format!("Rc::new((*self.{}).clone())", field)
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    /// Disable formatting/style warnings on generated type definitions.
+    fn disable_type_style_warnings(&mut self) {
+        if !self.config.features.follow_rust_naming {
+            self.add_code("#[allow(clippy::upper_case_acronyms)]");
+            self.newline();
+            self.add_code("#[allow(non_camel_case_types)]");
+            self.newline();
+            self.add_code("#[allow(non_snake_case)]");
+            self.newline();
+        }
+        self.add_code("#[allow(dead_code)]");
+        self.newline();
+    }
+
+    /// Disable all formatting/style warnings on generated definitions.
+    fn disable_all_style_warnings(&mut self) {
+        self.add_code("#[allow(clippy::assign_op_pattern)]");
+        self.newline();
+        self.add_code("#[allow(clippy::branches_sharing_code)]");
+        self.newline();
+        self.add_code("#[allow(clippy::clone_on_copy)]");
+        self.newline();
         self.add_code("#[allow(clippy::collapsible_else_if)]");
         self.newline();
         self.add_code("#[allow(clippy::double_parens)]");
@@ -492,7 +1492,7 @@ impl RustVisitor {
         let (state_name_opt, event_name) = self.parse_event_name(raw_event_name);
         match state_name_opt {
             Some(state_name) => self.format_state_event_type_name(&state_name, &event_name),
-            None => self.format_type_name(&event_name),
+            None => self.format_message_type_name(&event_name),
         }
     }
 
@@ -503,7 +1503,17 @@ impl RustVisitor {
         } else if event_name.eq(&self.config.code.exit_token) {
             self.format_exit_event_type_name(state_name)
         } else {
-            self.format_type_name(&event_name.to_string())
+            self.format_message_type_name(event_name)
+        }
+    }
+
+    /// Get the type name for a message that is not specific to a state. The error message sent
+    /// to `|!|` handlers is named by `RustCode.error_msg`.
+    fn format_message_type_name(&self, msg: &str) -> String {
+        if msg == self.config.code.error_token {
+            self.format_type_name(&self.config.code.error_msg)
+        } else {
+            self.format_type_name(msg)
         }
     }
 
@@ -662,6 +1672,8 @@ impl RustVisitor {
     //* --------------------------------------------------------------------- *//
 
     fn visit_decl_stmts(&mut self, decl_stmt_types: &[DeclOrStmtType]) {
+        let scope_len = self.local_var_scope.len();
+        self.decl_stmts_depth += 1;
         for decl_stmt_t in decl_stmt_types.iter() {
             match decl_stmt_t {
                 DeclOrStmtType::VarDeclT { var_decl_t_rc_ref } => {
@@ -703,6 +1715,14 @@ impl RustVisitor {
                         StatementType::ChangeStateStmt { change_state_stmt } => {
                             change_state_stmt.accept(self);
                         }
+                        StatementType::LoopStmt { loop_stmt_node } => {
+                            loop_stmt_node.accept(self);
+                        }
+                        StatementType::LoopControlStmt {
+                            loop_control_stmt_node,
+                        } => {
+                            loop_control_stmt_node.accept(self);
+                        }
                         StatementType::NoStmt => {
                             // TODO
                             self.errors.push("Unknown error.".to_string());
@@ -711,6 +1731,29 @@ impl RustVisitor {
                 }
             }
         }
+        self.local_var_scope.truncate(scope_len);
+        self.decl_stmts_depth -= 1;
+        if self.decl_stmts_depth == 0 {
+            self.insert_local_var_mutability();
+        }
+    }
+
+    /// Make the local variables of the outermost block just generated mutable if they are
+    /// assigned after their declaration. Later offsets are patched first so that the earlier
+    /// ones stay valid.
+    fn insert_local_var_mutability(&mut self) {
+        for (offset, assigned) in self.local_var_decls.drain(..).rev() {
+            if assigned {
+                self.code.insert_str(offset, "mut ");
+            }
+        }
+    }
+
+    /// Record an assignment to the local variable with the given name, if one is in scope.
+    fn mark_local_var_assigned(&mut self, name: &str) {
+        if let Some((_, index)) = self.local_var_scope.iter().rev().find(|(n, _)| n == name) {
+            self.local_var_decls[*index].1 = true;
+        }
     }
 
     //* --------------------------------------------------------------------- *//
@@ -730,16 +1773,17 @@ impl RustVisitor {
             event_names.push(format!("{}:{}", state_name, self.config.code.exit_token,));
         }
 
-        // generate sub-module containing info definitions
-        self.add_code(&format!(
-            "mod {}",
-            self.config.code.runtime_info_module_name
-        ));
-        self.enter_block();
+        // generate sub-module containing info definitions, which is the whole file in a module tree
+        let module_tree = self.config.features.generate_module_tree;
+        if !module_tree {
+            self.add_code(&format!(
+                "mod {}",
+                self.config.code.runtime_info_module_name
+            ));
+            self.enter_block();
+        }
         self.add_code("use frame_runtime::info::*;");
         self.newline();
-        self.add_code("use once_cell::sync::OnceCell;");
-        self.newline();
         self.newline();
         self.generate_machine_info(system_node, num_interface_event_names);
         self.newline();
@@ -758,7 +1802,9 @@ impl RustVisitor {
                 self.generate_state_info(state.borrow(), &event_names);
             }
         }
-        self.exit_block();
+        if !module_tree {
+            self.exit_block();
+        }
     }
 
     /// Generate a single `NameInfo` value.
@@ -775,23 +1821,18 @@ impl RustVisitor {
     /// Generate the machine info and supporting definitions.
     #[allow(unused_variables)]
     fn generate_machine_info(&mut self, system_node: &SystemNode, num_interface_events: usize) {
-        // generate the public-facing function
+        // generate the public-facing function, which is const since the info is static data
         self.add_code(&format!(
-            "pub fn {}() -> &'static MachineInfo",
+            "pub const fn {}() -> &'static MachineInfo",
             self.config.code.machine_info_function_name
         ));
         self.enter_block();
-        self.add_code("if MACHINE_CELL.get().is_none()");
-        self.enter_block();
-        self.add_code("let _ = MACHINE_CELL.set(MACHINE);");
-        self.exit_block();
-        self.newline();
-        self.add_code("MACHINE");
+        self.add_code("&MACHINE");
         self.exit_block();
         self.newline();
 
-        // begin machine info value
-        self.add_code("static MACHINE: &MachineInfo = &MachineInfo");
+        // begin machine info value, which is a named static so that state infos can refer to it
+        self.add_code("static MACHINE: MachineInfo = MachineInfo");
         self.enter_block();
 
         // name
@@ -827,7 +1868,7 @@ impl RustVisitor {
             for state in &machine_block_node.states {
                 self.newline();
                 self.add_code(&format!(
-                    "{},",
+                    "&{},",
                     self.format_state_info_const_name(&state.borrow().name)
                 ));
             }
@@ -861,10 +1902,6 @@ impl RustVisitor {
         // end machine info value
         self.exit_block();
         self.add_code(";");
-        self.newline();
-
-        // OnceCell value to break cyclic definitions
-        self.add_code("static MACHINE_CELL: OnceCell<&MachineInfo> = OnceCell::new();");
     }
 
     /// Generate the info entries for all of the declared actions.
@@ -1015,16 +2052,21 @@ impl RustVisitor {
                 self.newline();
                 self.add_code(&format!("label: \"{}\",", transition.label));
                 self.newline();
+                match &transition.guard {
+                    Some(guard) => self.add_code(&format!("guard: Some({:?}),", guard)),
+                    None => self.add_code("guard: None,"),
+                }
+                self.newline();
                 self.add_code(&format!(
-                    "source: {},",
+                    "source: &{},",
                     self.format_state_info_const_name(&transition.source_name)
                 ));
                 self.newline();
                 self.add_code(&format!(
                     "target: {},",
                     match &transition.target_name {
-                        Some(name) => self.format_state_info_const_name(name),
-                        None => "SPECIAL_STATE_POP".to_string(),
+                        Some(name) => format!("&{}", self.format_state_info_const_name(name)),
+                        None => "&SPECIAL_STATE_POP".to_string(),
                     }
                 ));
                 self.exit_block();
@@ -1041,9 +2083,9 @@ impl RustVisitor {
     /// handlers. In actuality, a popped state may have a parent, variables, and handlers, and it
     /// certainly has a name, however, none of these are known statically.
     fn generate_pop_state_info(&mut self) {
-        self.add_code("static SPECIAL_STATE_POP: &StateInfo = &StateInfo");
+        self.add_code("static SPECIAL_STATE_POP: StateInfo = StateInfo");
         self.enter_block();
-        self.add_code("machine_cell: &MACHINE_CELL,");
+        self.add_code("machine: &MACHINE,");
         self.newline();
         self.add_code(&format!(
             "name: \"{}\",",
@@ -1073,11 +2115,11 @@ impl RustVisitor {
 
         // begin constant, machine, and name
         self.add_code(&format!(
-            "static {}: &StateInfo = &StateInfo",
+            "static {}: StateInfo = StateInfo",
             self.format_state_info_const_name(&state_name),
         ));
         self.enter_block();
-        self.add_code("machine_cell: &MACHINE_CELL,");
+        self.add_code("machine: &MACHINE,");
         self.newline();
         self.add_code(&format!("name: \"{}\",", state_name));
         self.newline();
@@ -1086,7 +2128,7 @@ impl RustVisitor {
         if let Some(dispatch_node) = &state_node.dispatch_opt {
             let parent_info =
                 self.format_state_info_const_name(&dispatch_node.target_state_ref.name);
-            self.add_code(&format!("parent: Some({}),", parent_info));
+            self.add_code(&format!("parent: Some(&{}),", parent_info));
         } else {
             self.add_code("parent: None,");
         }
@@ -1180,6 +2222,13 @@ impl RustVisitor {
             }
             None => {}
         }
+        if self.config.features.serde {
+            if traits.is_empty() {
+                traits = String::from(SERDE_TRAITS);
+            } else {
+                traits = format!("{}, {}", traits, SERDE_TRAITS);
+            }
+        }
         self.disable_type_style_warnings();
         if !traits.is_empty() {
             self.add_code(&format!("#[derive({})]", traits));
@@ -1188,7 +2237,11 @@ impl RustVisitor {
 
         // add the state enum type
         let state_enum_type = self.state_enum_type_name();
-        self.add_code(&format!("pub enum {} {{", state_enum_type));
+        self.add_code(&format!(
+            "{}enum {} {{",
+            self.system_visibility(),
+            state_enum_type
+        ));
         self.indent();
         for state_name in self.state_names.clone() {
             self.newline();
@@ -1223,7 +2276,7 @@ impl RustVisitor {
                     "{}::{} => {}::{}().states[{}],",
                     state_enum_type,
                     self.format_type_name(&state_name),
-                    self.config.code.runtime_info_module_name,
+                    self.runtime_info_module_name(),
                     self.config.code.machine_info_function_name,
                     state_index
                 ));
@@ -1257,6 +2310,12 @@ impl RustVisitor {
 
     /// Generate all of the definitions related to Frame events.
     fn generate_event_defs(&mut self, system_node: &SystemNode) {
+        // typed events are defined along with the messages, so only the return type is needed
+        if self.config.features.typed_events {
+            self.generate_event_return_defs(system_node);
+            return;
+        }
+
         // generate args and return types
         self.generate_event_arg_defs();
         self.newline();
@@ -1266,28 +2325,35 @@ impl RustVisitor {
         self.newline();
 
         // generate core event type
+        let visibility = self.internal_visibility();
         self.disable_type_style_warnings();
         self.add_code(&format!(
-            "pub struct {}",
+            "{}struct {}",
+            if self.config.features.generate_module_tree {
+                &visibility
+            } else {
+                "pub "
+            },
             self.config.code.frame_event_type_name,
         ));
         self.enter_block();
         self.add_code(&format!(
-            "{}: {},",
+            "{}{}: {},",
+            visibility,
             self.config.code.frame_event_message_attribute_name,
             self.config.code.frame_event_message_type_name
         ));
         self.newline();
         self.add_code(&format!(
-            "{}: {}<{}<{}>>,",
+            "{}{}: {},",
+            visibility,
             self.config.code.frame_event_args_attribute_name,
-            self.rc_type(),
-            self.cell_type(),
-            self.config.code.frame_event_args_type_name
+            self.shared_cell_type(&self.config.code.frame_event_args_type_name)
         ));
         self.newline();
         self.add_code(&format!(
-            "{}: {}<{}>,",
+            "{}{}: {}<{}>,",
+            visibility,
             self.config.code.frame_event_return_attribute_name,
             self.cell_type(),
             self.config.code.frame_event_return_type_name
@@ -1300,7 +2366,8 @@ impl RustVisitor {
         self.add_code(&format!("impl {}", self.config.code.frame_event_type_name));
         self.enter_block();
         self.add_code(&format!(
-            "fn new({}: {}, {}: {}) -> {}",
+            "{}fn new({}: {}, {}: {}) -> {}",
+            visibility,
             self.config.code.frame_event_message_attribute_name,
             self.config.code.frame_event_message_type_name,
             self.config.code.frame_event_args_attribute_name,
@@ -1316,10 +2383,9 @@ impl RustVisitor {
         ));
         self.newline();
         self.add_code(&format!(
-            "{0}: {1}::new({2}::new({0})),",
+            "{}: {},",
             self.config.code.frame_event_args_attribute_name,
-            self.rc_type(),
-            self.cell_type(),
+            self.new_shared_cell(&self.config.code.frame_event_args_attribute_name),
         ));
         self.newline();
         self.add_code(&format!(
@@ -1355,7 +2421,7 @@ impl RustVisitor {
             self.newline();
             self.add_code(&format!(
                 "{}::{}()",
-                self.config.code.runtime_info_module_name,
+                self.runtime_info_module_name(),
                 self.config.code.machine_info_function_name,
             ));
             self.indent();
@@ -1428,7 +2494,8 @@ impl RustVisitor {
         }
         self.newline();
         self.add_code(&format!(
-            "enum {}",
+            "{}enum {}",
+            self.internal_visibility(),
             self.config.code.frame_event_return_type_name
         ));
         self.enter_block();
@@ -1464,7 +2531,8 @@ impl RustVisitor {
                 if let Some(return_type) = &interface_method_node.borrow().return_type_opt {
                     self.newline();
                     self.add_code(&format!(
-                        "fn {}(&self) -> {}",
+                        "{}fn {}(&self) -> {}",
+                        self.internal_visibility(),
                         self.format_param_getter(&interface_method_node.borrow().name, "ret"),
                         return_type.get_type_str()
                     ));
@@ -1507,7 +2575,8 @@ impl RustVisitor {
                         self.add_code("#[derive(Clone)]");
                         self.newline();
                     }
-                    self.add_code(&format!("struct {} {{", args_struct_name));
+                    let visibility = self.internal_visibility();
+                    self.add_code(&format!("{}struct {} {{", visibility, args_struct_name));
                     self.indent();
                     for param in params {
                         let param_name = self.format_value_name(&param.name);
@@ -1516,7 +2585,7 @@ impl RustVisitor {
                             None => "<?>".to_string(),
                         };
                         self.newline();
-                        self.add_code(&format!("{}: {},", param_name, param_type));
+                        self.add_code(&format!("{}{}: {},", visibility, param_name, param_type));
                         bound_names.push(param_name);
                     }
                     self.exit_block();
@@ -1539,7 +2608,8 @@ impl RustVisitor {
             self.newline();
         }
         self.add_code(&format!(
-            "enum {}",
+            "{}enum {}",
+            self.internal_visibility(),
             self.config.code.frame_event_args_type_name,
         ));
         self.enter_block();
@@ -1624,7 +2694,8 @@ impl RustVisitor {
             for event_type_name in &has_params {
                 self.newline();
                 self.add_code(&format!(
-                    "fn {}(&self) -> &{} {{",
+                    "{}fn {}(&self) -> &{} {{",
+                    self.internal_visibility(),
                     self.format_args_method_name(event_type_name),
                     self.format_args_struct_name(event_type_name)
                 ));
@@ -1652,6 +2723,7 @@ impl RustVisitor {
     /// Generate the struct, enum, and supporting function definitions related to state contexts,
     /// which are used to store the values of state parameters and state variables.
     fn generate_state_context_defs(&mut self, system_node: &SystemNode) {
+        let visibility = self.internal_visibility();
         if let Some(machine_block_node) = &system_node.machine_block_node_opt {
             let states = machine_block_node.states.clone();
             for (state_index, state) in states.into_iter().enumerate() {
@@ -1666,11 +2738,17 @@ impl RustVisitor {
                         let mut bound_names: Vec<String> = Vec::new();
 
                         self.disable_type_style_warnings();
-                        if self.config.features.thread_safe {
+                        if self.config.features.serde {
+                            self.add_code(&format!("#[derive(Clone, {})]", SERDE_TRAITS));
+                            self.newline();
+                        } else if self.config.features.thread_safe || self.generate_state_stack {
                             self.add_code("#[derive(Clone)]");
                             self.newline();
                         }
-                        self.add_code(&format!("struct {} {{", state_args_struct_name));
+                        self.add_code(&format!(
+                            "{}struct {} {{",
+                            visibility, state_args_struct_name
+                        ));
                         self.indent();
                         for param in params {
                             let param_name = self.format_value_name(&param.param_name);
@@ -1679,7 +2757,10 @@ impl RustVisitor {
                                 None => String::from("<?>"),
                             };
                             self.newline();
-                            self.add_code(&format!("{}: {},", param_name, param_type));
+                            self.add_code(&format!(
+                                "{}{}: {},",
+                                visibility, param_name, param_type
+                            ));
                             bound_names.push(param_name);
                         }
                         self.exit_block();
@@ -1702,11 +2783,17 @@ impl RustVisitor {
                         let mut bound_names: Vec<String> = Vec::new();
 
                         self.disable_type_style_warnings();
-                        if self.generate_state_stack || self.config.features.thread_safe {
+                        if self.config.features.serde {
+                            self.add_code(&format!("#[derive(Clone, {})]", SERDE_TRAITS));
+                            self.newline();
+                        } else if self.generate_state_stack || self.config.features.thread_safe {
                             self.add_code("#[derive(Clone)]");
                             self.newline();
                         }
-                        self.add_code(&format!("struct {} {{", state_vars_struct_name));
+                        self.add_code(&format!(
+                            "{}struct {} {{",
+                            visibility, state_vars_struct_name
+                        ));
                         self.indent();
                         for var_decl_node in var_decl_nodes {
                             let var_name = self.format_value_name(&var_decl_node.borrow().name);
@@ -1715,7 +2802,7 @@ impl RustVisitor {
                                 None => "<?>".to_string(),
                             };
                             self.newline();
-                            self.add_code(&format!("{}: {},", var_name, var_type));
+                            self.add_code(&format!("{}{}: {},", visibility, var_name, var_type));
                             bound_names.push(var_name);
                         }
                         self.exit_block();
@@ -1732,28 +2819,27 @@ impl RustVisitor {
                 // generate state context struct for this state
                 let context_struct_name = self.format_state_context_struct_name(&state_node.name);
                 self.disable_type_style_warnings();
-                self.add_code(&format!("struct {} {{", context_struct_name));
+                self.generate_serde_derive();
+                self.add_code(&format!("{}struct {} {{", visibility, context_struct_name));
                 self.indent();
 
                 if has_state_args {
                     self.newline();
                     self.add_code(&format!(
-                        "{}: {}<{}<{}>>,",
+                        "{}{}: {},",
+                        visibility,
                         self.config.code.state_args_var_name,
-                        self.rc_type(),
-                        self.cell_type(),
-                        state_args_struct_name,
+                        self.shared_cell_type(&state_args_struct_name),
                     ));
                 }
 
                 if has_state_vars {
                     self.newline();
                     self.add_code(&format!(
-                        "{}: {}<{}<{}>>,",
+                        "{}{}: {},",
+                        visibility,
                         self.config.code.state_vars_var_name,
-                        self.rc_type(),
-                        self.cell_type(),
-                        state_vars_struct_name,
+                        self.shared_cell_type(&state_vars_struct_name),
                     ));
                 }
 
@@ -1762,28 +2848,25 @@ impl RustVisitor {
                 self.newline();
 
                 // generate a deep-clone function to save snapshots for state stack
-                if self.generate_state_stack {
+                if self.generate_state_stack || self.config.features.serde {
                     self.add_code(&format!("impl {}", context_struct_name));
                     self.enter_block();
-                    self.add_code(&format!("fn deep_clone(&self) -> {}", context_struct_name));
+                    self.add_code(&format!(
+                        "{}fn deep_clone(&self) -> {}",
+                        visibility, context_struct_name
+                    ));
                     self.enter_block();
                     self.add_code(&format!("{} {{", context_struct_name));
                     self.indent();
                     if has_state_args {
                         self.newline();
-                        self.add_code(&format!(
-                            "{0}: {1}::new((*self.{0}).clone())",
-                            self.config.code.state_args_var_name,
-                            self.rc_type(),
-                        ));
+                        let field = self.config.code.state_args_var_name.clone();
+                        self.add_code(&format!("{}: {},", field, self.deep_clone_cell(&field)));
                     }
                     if has_state_vars {
                         self.newline();
-                        self.add_code(&format!(
-                            "{0}: {1}::new((*self.{0}).clone())",
-                            self.config.code.state_vars_var_name,
-                            self.rc_type(),
-                        ));
+                        let field = self.config.code.state_vars_var_name.clone();
+                        self.add_code(&format!("{}: {},", field, self.deep_clone_cell(&field)));
                     }
                     self.exit_block();
                     self.exit_block();
@@ -1809,7 +2892,7 @@ impl RustVisitor {
                     self.enter_block();
                     self.add_code(&format!(
                         "{}::{}().states[{}]",
-                        self.config.code.runtime_info_module_name,
+                        self.runtime_info_module_name(),
                         self.config.code.machine_info_function_name,
                         state_index,
                     ));
@@ -1857,9 +2940,10 @@ impl RustVisitor {
 
             // generate the enum type that unions all the state context types
             self.disable_type_style_warnings();
+            self.generate_serde_derive();
             self.add_code(&format!(
-                "enum {} {{",
-                self.config.code.state_context_type_name
+                "{}enum {} {{",
+                visibility, self.config.code.state_context_type_name
             ));
             self.indent();
             for state_name in &state_names {
@@ -1885,7 +2969,8 @@ impl RustVisitor {
             for state_name in &state_names {
                 self.newline();
                 self.add_code(&format!(
-                    "fn {}(&self) -> &{} {{",
+                    "{}fn {}(&self) -> &{} {{",
+                    visibility,
                     self.format_state_context_method_name(state_name),
                     self.format_state_context_struct_name(state_name)
                 ));
@@ -1909,11 +2994,11 @@ impl RustVisitor {
             }
 
             // generate a deep-clone function to save snapshots for state stack
-            if self.generate_state_stack {
+            if self.generate_state_stack || self.config.features.serde {
                 self.newline();
                 self.add_code(&format!(
-                    "fn deep_clone(&self) -> {}",
-                    self.config.code.state_context_type_name
+                    "{}fn deep_clone(&self) -> {}",
+                    visibility, self.config.code.state_context_type_name
                 ));
                 self.enter_block();
                 self.add_code("match self {");
@@ -2047,7 +3132,20 @@ impl RustVisitor {
 
     /// Generate the constructor function.
     fn generate_constructor(&mut self, system_node: &SystemNode) {
-        self.add_code("pub fn new() -> Self {");
+        let actions_param = if self.config.features.generic_actions {
+            format!(
+                "{}: {}",
+                self.config.code.actions_var_name, self.config.code.actions_type_param
+            )
+        } else {
+            String::new()
+        };
+        self.add_code(&format!(
+            "{}{}fn new({}) -> Self {{",
+            self.system_visibility(),
+            self.async_qualifier(),
+            actions_param
+        ));
         self.indent();
 
         let init_state_name = self.init_state_name();
@@ -2090,10 +3188,31 @@ impl RustVisitor {
 
         // initialize the state stack
         if self.generate_state_stack {
+            self.newline();
+            if self.config.features.no_std {
+                self.add_code(&format!(
+                    "{}: core::array::from_fn(|_| None),",
+                    self.config.code.state_stack_var_name
+                ));
+                self.newline();
+                self.add_code(&format!(
+                    "{}: 0,",
+                    self.config.code.state_stack_depth_var_name
+                ));
+            } else {
+                self.add_code(&format!(
+                    "{}: Vec::new(),",
+                    self.config.code.state_stack_var_name
+                ));
+            }
+        }
+
+        // initialize the deferred event queue
+        if self.generate_deferred_events {
             self.newline();
             self.add_code(&format!(
                 "{}: Vec::new(),",
-                self.config.code.state_stack_var_name
+                self.config.code.deferred_events_var_name
             ));
         }
 
@@ -2109,6 +3228,21 @@ impl RustVisitor {
             ));
         }
 
+        // no action has failed yet
+        if self.config.features.fallible_actions {
+            self.newline();
+            self.add_code(&format!(
+                "{}: None,",
+                self.config.code.pending_error_var_name
+            ));
+        }
+
+        // store the injected actions
+        if self.config.features.generic_actions {
+            self.newline();
+            self.add_code(&format!("{},", self.config.code.actions_var_name));
+        }
+
         // initialize domain variables
         if let Some(domain_block_node) = &system_node.domain_block_node_opt {
             for variable_decl_node_rcref in &domain_block_node.member_variables {
@@ -2130,8 +3264,9 @@ impl RustVisitor {
         // run the initialize method on the new machine
         self.newline();
         self.add_code(&format!(
-            "machine.{}();",
-            self.config.code.initialize_method_name
+            "machine.{}(){};",
+            self.config.code.initialize_method_name,
+            self.await_suffix()
         ));
         self.newline();
 
@@ -2148,40 +3283,204 @@ impl RustVisitor {
     /// Generate the initialize method.
     fn generate_initialize(&mut self) {
         self.add_code(&format!(
-            "fn {}(&mut self)",
+            "{}fn {}(&mut self)",
+            self.async_qualifier(),
             self.config.code.initialize_method_name
         ));
         self.enter_block();
 
-        self.add_code(&format!(
-            "let {} = {}::new({}::new(",
-            self.config.code.frame_event_variable_name,
-            self.rc_type(),
-            self.config.code.frame_event_type_name,
-        ));
-        self.indent();
-        self.newline();
-        self.add_code(&format!(
-            "{}::{}(self.{}),",
-            self.config.code.frame_event_message_type_name,
-            self.config.code.enter_msg,
-            self.config.code.state_var_name,
+        if self.config.features.typed_events {
+            self.add_code(&format!(
+                "let {} = {}::{}(self.{});",
+                self.config.code.frame_event_variable_name,
+                self.config.code.frame_event_type_name,
+                self.config.code.enter_msg,
+                self.config.code.state_var_name,
+            ));
+        } else {
+            self.add_code(&format!(
+                "let {} = {}{}::new(",
+                self.config.code.frame_event_variable_name,
+                self.rc_ptr_open(),
+                self.config.code.frame_event_type_name,
+            ));
+            self.indent();
+            self.newline();
+            self.add_code(&format!(
+                "{}::{}(self.{}),",
+                self.config.code.frame_event_message_type_name,
+                self.config.code.enter_msg,
+                self.config.code.state_var_name,
+            ));
+            self.newline();
+            self.add_code(&format!(
+                "{}::None,",
+                self.config.code.frame_event_args_type_name,
+            ));
+            self.outdent();
+            self.newline();
+            self.add_code(&format!("){};", self.rc_ptr_close()));
+        }
+
+        if self.config.features.tracing {
+            self.newline();
+            self.generate_tracing_state_event("enter");
+        }
+        self.newline();
+        self.add_code(&format!(
+            "self.{}({}){};",
+            self.config.code.handle_event_method_name,
+            self.frame_event_arg(&self.config.code.frame_event_variable_name),
+            self.await_suffix(),
         ));
+
+        self.exit_block();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    /// Generate a serializable struct that captures the persistent parts of a running machine.
+    fn generate_snapshot_struct(&mut self, system_node: &SystemNode) {
+        self.add_code("// Snapshot");
         self.newline();
+        self.disable_type_style_warnings();
+        self.generate_serde_derive();
         self.add_code(&format!(
-            "{}::None,",
-            self.config.code.frame_event_args_type_name,
+            "{}struct {}",
+            self.system_visibility(),
+            self.snapshot_type_name()
         ));
-        self.outdent();
+        self.enter_block();
+        self.add_code(&format!(
+            "{}: {},",
+            self.config.code.state_var_name,
+            self.state_enum_type_name()
+        ));
+        if self.generate_state_context {
+            self.newline();
+            self.add_code(&format!(
+                "{}: {},",
+                self.config.code.state_context_var_name, self.config.code.state_context_type_name,
+            ));
+        }
+        if self.generate_state_stack {
+            self.newline();
+            if self.generate_state_context {
+                self.add_code(&format!(
+                    "{}: Vec<({}, {})>,",
+                    self.config.code.state_stack_var_name,
+                    self.state_enum_type_name(),
+                    self.config.code.state_context_type_name
+                ));
+            } else {
+                self.add_code(&format!(
+                    "{}: Vec<{}>,",
+                    self.config.code.state_stack_var_name,
+                    self.state_enum_type_name()
+                ));
+            }
+        }
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for variable_decl_node_rcref in &domain_block_node.member_variables {
+                variable_decl_node_rcref
+                    .borrow()
+                    .accept_rust_domain_var_decl(self);
+            }
+        }
+        self.exit_block();
         self.newline();
-        self.add_code("));");
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
 
+    /// Generate methods to take a snapshot of the machine and to restore the machine from one.
+    /// Restoring does not send an enter event to the restored state, since the state was already
+    /// entered before the snapshot was taken.
+    fn generate_snapshot_methods(&mut self, system_node: &SystemNode) {
+        let snapshot_type_name = self.snapshot_type_name();
+        let domain_vars: Vec<String> = match &system_node.domain_block_node_opt {
+            Some(domain_block_node) => domain_block_node
+                .member_variables
+                .iter()
+                .map(|decl_rc| self.format_value_name(&decl_rc.borrow().name))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        self.add_code("#[allow(clippy::clone_on_copy)]");
         self.newline();
         self.add_code(&format!(
-            "self.{}({});",
-            self.config.code.handle_event_method_name, self.config.code.frame_event_variable_name,
+            "{}fn {}(&self) -> {}",
+            self.system_visibility(),
+            self.config.code.snapshot_method_name,
+            snapshot_type_name
         ));
+        self.enter_block();
+        self.add_code(&format!("{} {{", snapshot_type_name));
+        self.indent();
+        self.newline();
+        self.add_code(&format!("{0}: self.{0},", self.config.code.state_var_name));
+        if self.generate_state_context {
+            self.newline();
+            self.add_code(&format!(
+                "{0}: self.{0}.deep_clone(),",
+                self.config.code.state_context_var_name
+            ));
+        }
+        if self.generate_state_stack {
+            self.newline();
+            if self.generate_state_context {
+                self.add_code(&format!(
+                    "{0}: self.{0}.iter().map(|(state, context)| (*state, context.deep_clone())).collect(),",
+                    self.config.code.state_stack_var_name
+                ));
+            } else {
+                self.add_code(&format!(
+                    "{0}: self.{0}.clone(),",
+                    self.config.code.state_stack_var_name
+                ));
+            }
+        }
+        for var_name in &domain_vars {
+            self.newline();
+            self.add_code(&format!("{0}: self.{0}.clone(),", var_name));
+        }
+        self.exit_block();
+        self.exit_block();
 
+        self.newline();
+        self.newline();
+        self.add_code(&format!(
+            "{}fn {}(&mut self, snapshot: {})",
+            self.system_visibility(),
+            self.config.code.restore_method_name,
+            snapshot_type_name
+        ));
+        self.enter_block();
+        self.add_code(&format!(
+            "self.{0} = snapshot.{0};",
+            self.config.code.state_var_name
+        ));
+        if self.generate_state_context {
+            self.newline();
+            self.add_code(&format!(
+                "self.{0} = {1}::new(snapshot.{0});",
+                self.config.code.state_context_var_name,
+                self.rc_type()
+            ));
+        }
+        if self.generate_state_stack {
+            self.newline();
+            self.add_code(&format!(
+                "self.{0} = snapshot.{0};",
+                self.config.code.state_stack_var_name
+            ));
+        }
+        for var_name in &domain_vars {
+            self.newline();
+            self.add_code(&format!("self.{0} = snapshot.{0};", var_name));
+        }
         self.exit_block();
     }
 
@@ -2196,6 +3495,14 @@ impl RustVisitor {
         if system_node.get_first_state().is_some() {
             self.newline();
             self.generate_handle_event();
+            if self.config.features.fallible_actions {
+                self.newline();
+                self.generate_take_error();
+            }
+            if self.generate_deferred_events {
+                self.newline();
+                self.generate_deferred_event_methods(system_node);
+            }
             if self.generate_transition_state {
                 self.newline();
                 self.generate_transition();
@@ -2223,6 +3530,50 @@ impl RustVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    /// Generate the parameters through which the transition and change-state methods receive
+    /// the ID and label of the transition being taken, for use in tracing events.
+    fn generate_tracing_transition_params(&mut self) {
+        if self.config.features.tracing {
+            self.add_code(&format!(
+                "{}: usize, {}: &'static str, ",
+                self.config.code.transition_id_arg_name, self.config.code.transition_label_arg_name,
+            ));
+        }
+    }
+
+    /// Generate the arguments identifying the most recently recorded transition, which are passed
+    /// to the transition and change-state methods for use in tracing events.
+    fn generate_tracing_transition_args(&mut self) {
+        if self.config.features.tracing {
+            let label = &self.transitions.last().unwrap().label;
+            self.add_code(&format!("{}, {:?}, ", self.transitions.len() - 1, label));
+        }
+    }
+
+    /// Generate a tracing event reporting that the current state is about to change to the state
+    /// in variable `new_state_var`. `kind` is the message of the event.
+    fn generate_tracing_transition_event(&mut self, kind: &str, new_state_var: &str) {
+        self.add_code(&format!(
+            "tracing::info!(id = {}, label = {}, source = ?self.{}, target = ?{}, \"{}\");",
+            self.config.code.transition_id_arg_name,
+            self.config.code.transition_label_arg_name,
+            self.config.code.state_var_name,
+            new_state_var,
+            kind,
+        ));
+    }
+
+    /// Generate a tracing event reporting that an event of kind `kind` is sent to the current
+    /// state.
+    fn generate_tracing_state_event(&mut self, kind: &str) {
+        self.add_code(&format!(
+            "tracing::debug!(state = ?self.{}, \"{}\");",
+            self.config.code.state_var_name, kind,
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     /// Generate the change_state method.
     fn generate_change_state(&mut self) {
         let old_state_context_var = self.old_var_name(&self.config.code.state_context_var_name);
@@ -2232,7 +3583,8 @@ impl RustVisitor {
 
         // generate method signature
         self.add_code(&format!(
-            "fn {}(&mut self, ",
+            "{}fn {}(&mut self, ",
+            self.async_qualifier(),
             self.config.code.change_state_method_name,
         ));
         if self.config.features.runtime_support {
@@ -2242,6 +3594,7 @@ impl RustVisitor {
                 self.config.code.runtime_module_use_as_name,
             ));
         }
+        self.generate_tracing_transition_params();
         self.add_code(&format!(
             "{}: {}",
             new_state_var,
@@ -2249,15 +3602,19 @@ impl RustVisitor {
         ));
         if self.generate_state_context {
             self.add_code(&format!(
-                ", {}: {}<{}>",
+                ", {}: {}",
                 new_state_context_var,
-                self.rc_type(),
-                self.config.code.state_context_type_name,
+                self.rc_ptr_type(&self.config.code.state_context_type_name),
             ));
         }
         self.add_code(")");
         self.enter_block();
 
+        if self.config.features.tracing {
+            self.generate_tracing_transition_event("change_state", &new_state_var);
+            self.newline();
+        }
+
         // save old state
         if self.generate_change_state_hook
             || self.config.features.runtime_support && !self.generate_state_context
@@ -2299,8 +3656,12 @@ impl RustVisitor {
         if self.generate_change_state_hook {
             self.newline();
             self.add_code(&format!(
-                "self.{}({}, {});",
-                self.config.code.change_state_hook_method_name, old_state_var, new_state_var,
+                "{}.{}({}, {}){};",
+                self.actions_expr(),
+                self.config.code.change_state_hook_method_name,
+                old_state_var,
+                new_state_var,
+                self.await_suffix(),
             ));
         }
 
@@ -2348,6 +3709,16 @@ impl RustVisitor {
             self.add_code("));");
         }
 
+        // replay events deferred by the old state
+        if self.generate_deferred_events {
+            self.newline();
+            self.add_code(&format!(
+                "self.{}(){};",
+                self.config.code.replay_deferred_events_method_name,
+                self.await_suffix(),
+            ));
+        }
+
         self.exit_block();
         self.newline();
     }
@@ -2363,7 +3734,8 @@ impl RustVisitor {
 
         // generate method signature
         self.add_code(&format!(
-            "fn {}(&mut self, ",
+            "{}fn {}(&mut self, ",
+            self.async_qualifier(),
             self.config.code.transition_method_name
         ));
         if self.config.features.runtime_support {
@@ -2373,17 +3745,20 @@ impl RustVisitor {
                 self.config.code.runtime_module_use_as_name,
             ));
         }
+        self.generate_tracing_transition_params();
+        let (exit_arg_name, enter_arg_name) = self.transition_event_arg_names();
         if self.generate_exit_args {
             self.add_code(&format!(
                 "{}: {}, ",
-                self.config.code.exit_args_member_name, self.config.code.frame_event_args_type_name
+                exit_arg_name,
+                self.transition_event_arg_type()
             ));
         }
         if self.generate_enter_args {
             self.add_code(&format!(
                 "{}: {}, ",
-                self.config.code.enter_args_member_name,
-                self.config.code.frame_event_args_type_name
+                enter_arg_name,
+                self.transition_event_arg_type()
             ));
         }
         self.add_code(&format!(
@@ -2393,54 +3768,94 @@ impl RustVisitor {
         ));
         if self.generate_state_context {
             self.add_code(&format!(
-                ", {}: {}<{}>",
+                ", {}: {}",
                 new_state_context_var,
-                self.rc_type(),
-                self.config.code.state_context_type_name,
+                self.rc_ptr_type(&self.config.code.state_context_type_name),
             ));
         }
         self.add_code(")");
         self.enter_block();
 
         // create exit event for old state
-        self.add_code(&format!(
-            "let exit_event = {}::new({}::new(",
-            self.rc_type(),
-            self.config.code.frame_event_type_name,
-        ));
-        self.indent();
-        self.newline();
-        self.add_code(&format!(
-            "{}::{}(self.{}),",
-            self.config.code.frame_event_message_type_name,
-            self.config.code.exit_msg,
-            self.config.code.state_var_name,
-        ));
-        self.newline();
-        if self.generate_exit_args {
-            self.add_code(&format!("{},", self.config.code.exit_args_member_name));
+        if self.config.features.typed_events {
+            // a typed exit event with arguments is passed in by the caller
+            if !self.generate_exit_args {
+                self.add_code(&format!(
+                    "let exit_event = {};",
+                    self.empty_transition_event_arg(
+                        &self.config.code.exit_msg,
+                        &format!("self.{}", self.config.code.state_var_name)
+                    ),
+                ));
+            }
         } else {
             self.add_code(&format!(
-                "{}::None,",
-                self.config.code.frame_event_args_type_name
+                "let exit_event = {}{}::new(",
+                self.rc_ptr_open(),
+                self.config.code.frame_event_type_name,
             ));
-        };
-        self.outdent();
-        self.newline();
-        self.add_code("));");
+            self.indent();
+            self.newline();
+            self.add_code(&format!(
+                "{}::{}(self.{}),",
+                self.config.code.frame_event_message_type_name,
+                self.config.code.exit_msg,
+                self.config.code.state_var_name,
+            ));
+            self.newline();
+            if self.generate_exit_args {
+                self.add_code(&format!("{},", self.config.code.exit_args_member_name));
+            } else {
+                self.add_code(&format!(
+                    "{}::None,",
+                    self.config.code.frame_event_args_type_name
+                ));
+            };
+            self.outdent();
+            self.newline();
+            self.add_code(&format!("){};", self.rc_ptr_close()));
+        }
 
         // send exit event
+        if self.config.features.fallible_actions {
+            self.newline();
+            self.add_code(&format!(
+                "let had_error = self.{}.is_some();",
+                self.config.code.pending_error_var_name
+            ));
+        }
+        if self.config.features.tracing {
+            self.newline();
+            self.generate_tracing_state_event("exit");
+        }
         self.newline();
         self.add_code(&format!(
-            "self.{}(exit_event{});",
+            "self.{}({}{}){};",
             self.config.code.handle_event_method_name,
+            self.frame_event_arg("exit_event"),
             if self.config.features.runtime_support {
                 ".clone()"
             } else {
                 ""
-            }
+            },
+            self.await_suffix(),
         ));
 
+        // the state is kept if its exit handler failed, but not because of an error still pending
+        // from the constructor
+        if self.config.features.fallible_actions {
+            self.newline();
+            self.add_code(&format!(
+                "if !had_error && self.{}.is_some() {{ return; }}",
+                self.config.code.pending_error_var_name
+            ));
+        }
+
+        if self.config.features.tracing {
+            self.newline();
+            self.generate_tracing_transition_event("transition", &new_state_var);
+        }
+
         // save old state
         if self.generate_transition_hook
             || self.config.features.runtime_support && !self.generate_state_context
@@ -2483,38 +3898,55 @@ impl RustVisitor {
         if self.generate_transition_hook {
             self.newline();
             self.add_code(&format!(
-                "self.{}({}, {});",
-                self.config.code.transition_hook_method_name, old_state_var, new_state_var,
+                "{}.{}({}, {}){};",
+                self.actions_expr(),
+                self.config.code.transition_hook_method_name,
+                old_state_var,
+                new_state_var,
+                self.await_suffix(),
             ));
         }
 
         // create enter event for new state
         self.newline();
-        self.add_code(&format!(
-            "let enter_event = {}::new({}::new(",
-            self.rc_type(),
-            self.config.code.frame_event_type_name,
-        ));
-        self.indent();
-        self.newline();
-        self.add_code(&format!(
-            "{}::{}(self.{}),",
-            self.config.code.frame_event_message_type_name,
-            self.config.code.enter_msg,
-            self.config.code.state_var_name,
-        ));
-        self.newline();
-        if self.generate_enter_args {
-            self.add_code(&format!("{},", self.config.code.enter_args_member_name));
+        if self.config.features.typed_events {
+            // a typed enter event with arguments is passed in by the caller
+            if !self.generate_enter_args {
+                self.add_code(&format!(
+                    "let enter_event = {};",
+                    self.empty_transition_event_arg(
+                        &self.config.code.enter_msg,
+                        &format!("self.{}", self.config.code.state_var_name)
+                    ),
+                ));
+            }
         } else {
             self.add_code(&format!(
-                "{}::None,",
-                self.config.code.frame_event_args_type_name
+                "let enter_event = {}{}::new(",
+                self.rc_ptr_open(),
+                self.config.code.frame_event_type_name,
             ));
-        };
-        self.outdent();
-        self.newline();
-        self.add_code("));");
+            self.indent();
+            self.newline();
+            self.add_code(&format!(
+                "{}::{}(self.{}),",
+                self.config.code.frame_event_message_type_name,
+                self.config.code.enter_msg,
+                self.config.code.state_var_name,
+            ));
+            self.newline();
+            if self.generate_enter_args {
+                self.add_code(&format!("{},", self.config.code.enter_args_member_name));
+            } else {
+                self.add_code(&format!(
+                    "{}::None,",
+                    self.config.code.frame_event_args_type_name
+                ));
+            };
+            self.outdent();
+            self.newline();
+            self.add_code(&format!("){};", self.rc_ptr_close()));
+        }
 
         // call transition callbacks
         if self.config.features.runtime_support {
@@ -2571,12 +4003,28 @@ impl RustVisitor {
         }
 
         // send enter event
-        self.newline();
+        if self.config.features.tracing {
+            self.newline();
+            self.generate_tracing_state_event("enter");
+        }
+        self.newline();
         self.add_code(&format!(
-            "self.{}(enter_event);",
+            "self.{}({}){};",
             self.config.code.handle_event_method_name,
+            self.frame_event_arg("enter_event"),
+            self.await_suffix(),
         ));
 
+        // replay events deferred by the old state
+        if self.generate_deferred_events {
+            self.newline();
+            self.add_code(&format!(
+                "self.{}(){};",
+                self.config.code.replay_deferred_events_method_name,
+                self.await_suffix(),
+            ));
+        }
+
         self.exit_block();
         self.newline();
     }
@@ -2588,17 +4036,49 @@ impl RustVisitor {
             self.config.code.state_stack_push_method_name
         ));
         self.enter_block();
-        if self.generate_state_context {
+        if self.config.features.tracing {
+            self.generate_tracing_state_event("push");
+            self.newline();
+        }
+        let elem = if self.generate_state_context {
+            format!(
+                "(self.{}, {}self.{}{}.deep_clone())",
+                self.config.code.state_var_name,
+                if self.config.features.no_std {
+                    ""
+                } else {
+                    "(*"
+                },
+                self.config.code.state_context_var_name,
+                if self.config.features.no_std { "" } else { ")" },
+            )
+        } else {
+            format!("self.{}", self.config.code.state_var_name)
+        };
+        if self.config.features.no_std {
             self.add_code(&format!(
-                "self.{}.push((self.{}, (*self.{}).deep_clone()));",
+                "if self.{} == {}",
+                self.config.code.state_stack_depth_var_name, self.config.code.state_stack_capacity,
+            ));
+            self.enter_block();
+            self.add_code("panic!(\"Error: attempted to push when history stack is full.\");");
+            self.exit_block();
+            self.newline();
+            self.add_code(&format!(
+                "self.{}[self.{}] = Some({});",
                 self.config.code.state_stack_var_name,
-                self.config.code.state_var_name,
-                self.config.code.state_context_var_name
+                self.config.code.state_stack_depth_var_name,
+                elem,
+            ));
+            self.newline();
+            self.add_code(&format!(
+                "self.{} += 1;",
+                self.config.code.state_stack_depth_var_name
             ));
         } else {
             self.add_code(&format!(
-                "self.{}.push(self.{});",
-                self.config.code.state_stack_var_name, self.config.code.state_var_name
+                "self.{}.push({});",
+                self.config.code.state_stack_var_name, elem
             ));
         }
         self.exit_block();
@@ -2620,12 +4100,46 @@ impl RustVisitor {
             ));
         }
         self.enter_block();
-        self.add_code(&format!(
-            "match self.{}.pop()",
-            self.config.code.state_stack_var_name
-        ));
+        if self.config.features.no_std {
+            self.add_code(&format!(
+                "if self.{} > 0",
+                self.config.code.state_stack_depth_var_name
+            ));
+            self.enter_block();
+            self.add_code(&format!(
+                "self.{} -= 1;",
+                self.config.code.state_stack_depth_var_name
+            ));
+            self.exit_block();
+            self.newline();
+            self.add_code(&format!(
+                "match self.{}[self.{}].take()",
+                self.config.code.state_stack_var_name, self.config.code.state_stack_depth_var_name,
+            ));
+        } else {
+            self.add_code(&format!(
+                "match self.{}.pop()",
+                self.config.code.state_stack_var_name
+            ));
+        }
         self.enter_block();
-        self.add_code("Some(elem) => elem,");
+        if self.config.features.tracing {
+            self.add_code("Some(elem) =>");
+            self.enter_block();
+            self.add_code(&format!(
+                "tracing::debug!(state = ?elem{}, \"pop\");",
+                if self.generate_state_context {
+                    ".0"
+                } else {
+                    ""
+                }
+            ));
+            self.newline();
+            self.add_code("elem");
+            self.exit_block();
+        } else {
+            self.add_code("Some(elem) => elem,");
+        }
         self.newline();
         self.add_code("None => panic!(\"Error: attempted to pop when history stack is empty.\")");
         self.exit_block();
@@ -2640,7 +4154,7 @@ impl RustVisitor {
     /// statement directly to ensure that the control-flow state is properly maintained.
     fn generate_return(&mut self) {
         self.newline();
-        self.add_code("return;");
+        self.add_code(&self.handler_return_stmt());
         self.this_branch_transitioned = false;
     }
 
@@ -2653,17 +4167,263 @@ impl RustVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    /// Generate the body of an interface method that sends a typed event and returns the value
+    /// produced by its handler.
+    fn generate_typed_interface_method_body(
+        &mut self,
+        interface_method_node: &InterfaceMethodNode,
+        event_type_name: &str,
+    ) {
+        self.add_code(&format!(
+            "let {} = {}::{}",
+            self.config.code.frame_event_variable_name,
+            self.config.code.frame_event_type_name,
+            event_type_name,
+        ));
+        if let Some(params) = &interface_method_node.params {
+            self.add_code(" { ");
+            for param in params {
+                self.add_code(&format!("{}, ", self.format_value_name(&param.param_name)));
+            }
+            self.add_code("}");
+        }
+        self.add_code(";");
+        self.newline();
+        if interface_method_node.return_type_opt.is_some() {
+            self.add_code(&format!(
+                "match self.{}({})",
+                self.config.code.handle_event_method_name,
+                self.config.code.frame_event_variable_name,
+            ));
+            self.enter_block();
+            self.add_code(&format!(
+                "{}::{} {{ return_value }} => return_value,",
+                self.config.code.frame_event_return_type_name, event_type_name
+            ));
+            self.newline();
+            self.add_code(&format!(
+                "_ => panic!(\"Bad return value for {}\"),",
+                &interface_method_node.name
+            ));
+            self.exit_block();
+        } else {
+            self.add_code(&format!(
+                "self.{}({});",
+                self.config.code.handle_event_method_name,
+                self.config.code.frame_event_variable_name,
+            ));
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    /// Generate the method that interface methods call to report an action that failed while
+    /// handling their event. The error is first sent to the current state as an error event, if
+    /// any state declares a `|!|` handler.
+    fn generate_take_error(&mut self) {
+        self.add_code(&format!(
+            "{}fn {}(&mut self) -> Result<(), {}>",
+            self.async_qualifier(),
+            self.config.code.take_error_method_name,
+            self.config.code.error_type
+        ));
+        self.enter_block();
+        let error_event_opt = self.arcanum.get_event(&self.config.code.error_token, &None);
+        let error_event = match error_event_opt {
+            Some(error_event) => error_event,
+            None => {
+                self.add_code(&format!(
+                    "self.{}.take().map_or(Ok(()), Err)",
+                    self.config.code.pending_error_var_name
+                ));
+                self.exit_block();
+                self.newline();
+                return;
+            }
+        };
+        self.add_code(&format!(
+            "let error = match self.{}.take()",
+            self.config.code.pending_error_var_name
+        ));
+        self.enter_block();
+        self.add_code("Some(error) => error,");
+        self.newline();
+        self.add_code("None => return Ok(()),");
+        self.exit_block();
+        self.add_code(";");
+
+        // the error is moved into the event so that the handler can refer to it
+        let error_type_name = self.format_message_type_name(&self.config.code.error_token);
+        let param_name_opt = error_event
+            .borrow()
+            .params_opt
+            .as_ref()
+            .and_then(|params| params.first())
+            .map(|param| self.format_value_name(&param.name));
+        self.newline();
+        match &param_name_opt {
+            Some(param_name) => self.add_code(&format!(
+                "let frame_args = {}::{}({} {{ {} }});",
+                self.config.code.frame_event_args_type_name,
+                error_type_name,
+                self.format_args_struct_name(&error_type_name),
+                if param_name == "error" {
+                    String::from("error")
+                } else {
+                    format!("{}: error", param_name)
+                },
+            )),
+            None => self.add_code(&format!(
+                "let frame_args = {}::None;",
+                self.config.code.frame_event_args_type_name
+            )),
+        }
+        self.newline();
+        self.add_code(&format!(
+            "let {} = {}{}::new({}::{}, frame_args){};",
+            self.config.code.frame_event_variable_name,
+            self.rc_ptr_open(),
+            self.config.code.frame_event_type_name,
+            self.config.code.frame_event_message_type_name,
+            error_type_name,
+            self.rc_ptr_close(),
+        ));
+        self.newline();
+        self.add_code(&format!(
+            "self.{}({}){};",
+            self.config.code.handle_event_method_name,
+            if self.config.features.no_std {
+                self.frame_event_arg(&self.config.code.frame_event_variable_name)
+            } else {
+                format!("{}.clone()", self.config.code.frame_event_variable_name)
+            },
+            self.await_suffix(),
+        ));
+
+        // take the error back from the handled event
+        if let Some(param_name) = &param_name_opt {
+            self.newline();
+            self.add_code(&format!(
+                "let error = match {}::mem::replace(&mut *{}.{}.{}, {}::None)",
+                if self.config.features.no_std {
+                    "core"
+                } else {
+                    "std"
+                },
+                self.config.code.frame_event_variable_name,
+                self.config.code.frame_event_args_attribute_name,
+                if self.config.features.thread_safe {
+                    "lock().unwrap()"
+                } else if self.config.features.no_std {
+                    "borrow_mut()"
+                } else {
+                    "as_ref().borrow_mut()"
+                },
+                self.config.code.frame_event_args_type_name,
+            ));
+            self.enter_block();
+            self.add_code(&format!(
+                "{}::{}(args) => args.{},",
+                self.config.code.frame_event_args_type_name, error_type_name, param_name,
+            ));
+            self.newline();
+            self.add_code("_ => unreachable!(),");
+            self.exit_block();
+            self.add_code(";");
+        }
+
+        // an error raised by the error handler itself takes precedence
+        self.newline();
+        self.add_code(&format!(
+            "Err(self.{}.take().unwrap_or(error))",
+            self.config.code.pending_error_var_name
+        ));
+        self.exit_block();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn generate_handle_event(&mut self) {
         self.add_code("#[allow(clippy::redundant_clone)]");
         self.newline();
         self.add_code(&format!(
-            "fn {}(&mut self, {}: {}<{}>)",
+            "fn {}(&mut self, {}: {}){}",
             self.config.code.handle_event_method_name,
             self.config.code.frame_event_variable_name,
-            self.rc_type(),
-            self.config.code.frame_event_type_name,
+            self.frame_event_param_type(),
+            self.handler_return_type(),
         ));
-        self.enter_block();
+
+        // each event is handled within its own span
+        let span = if self.config.features.tracing {
+            format!(
+                "tracing::debug_span!(\"{}\", system = \"{}\", state = ?self.{}, message = %{})",
+                self.config.code.handle_event_method_name,
+                self.system_name,
+                self.config.code.state_var_name,
+                if self.config.features.typed_events {
+                    self.config.code.frame_event_variable_name.clone()
+                } else {
+                    format!(
+                        "{}.{}",
+                        self.config.code.frame_event_variable_name,
+                        self.config.code.frame_event_message_attribute_name
+                    )
+                },
+            )
+        } else {
+            String::new()
+        };
+
+        // Event handling is recursive since transitions send enter and exit events, so in async
+        // mode the future is boxed to give it a known size. A span cannot be held open across
+        // await points, so the future is instrumented with it instead.
+        if self.config.features.async_support {
+            self.add_code(&format!(
+                " -> std::pin::Pin<Box<dyn std::future::Future<Output = ()>{} + '_>>",
+                if self.config.features.thread_safe {
+                    " + Send"
+                } else {
+                    ""
+                }
+            ));
+            self.enter_block();
+            if self.config.features.tracing {
+                self.add_code(&format!("let span = {};", span));
+                self.newline();
+                self.add_code("Box::pin(tracing::Instrument::instrument(async move {");
+            } else {
+                self.add_code("Box::pin(async move {");
+            }
+            self.indent();
+            self.newline();
+        } else {
+            self.enter_block();
+            if self.config.features.tracing {
+                self.add_code(&format!("let _span = {}.entered();", span));
+                self.newline();
+            }
+        }
+
+        if self.generate_deferred_events {
+            self.add_code(&format!(
+                "if self.{}(&{}.{})",
+                self.config.code.is_deferred_method_name,
+                self.config.code.frame_event_variable_name,
+                self.config.code.frame_event_message_attribute_name,
+            ));
+            self.enter_block();
+            self.add_code(&format!(
+                "self.{}.push({});",
+                self.config.code.deferred_events_var_name,
+                self.config.code.frame_event_variable_name,
+            ));
+            self.newline();
+            self.add_code("return;");
+            self.exit_block();
+            self.newline();
+        }
 
         if self.config.features.runtime_support {
             self.add_code(&format!(
@@ -2681,11 +4441,12 @@ impl RustVisitor {
         for state_name in &self.state_names.clone() {
             self.newline();
             self.add_code(&format!(
-                "{}::{} => self.{}({}.clone()),",
+                "{}::{} => self.{}({}){},",
                 self.state_enum_type_name(),
                 self.format_type_name(state_name),
                 self.format_state_handler_name(state_name),
-                self.config.code.frame_event_variable_name,
+                self.forward_frame_event_arg(),
+                self.await_suffix(),
             ));
         }
         self.exit_block();
@@ -2698,6 +4459,109 @@ impl RustVisitor {
             ));
         }
 
+        if self.config.features.async_support {
+            self.outdent();
+            self.newline();
+            if self.config.features.tracing {
+                self.add_code("}, span))");
+            } else {
+                self.add_code("})");
+            }
+        }
+        self.exit_block();
+        self.newline();
+    }
+
+    /// Generate the methods that test whether the current state defers an event and that replay
+    /// deferred events once the machine is in a state that no longer defers them.
+    fn generate_deferred_event_methods(&mut self, system_node: &SystemNode) {
+        self.add_code("#[allow(unreachable_patterns)]");
+        self.newline();
+        self.add_code(&format!(
+            "fn {}(&self, msg: &{}) -> bool",
+            self.config.code.is_deferred_method_name,
+            self.config.code.frame_event_message_type_name,
+        ));
+        self.enter_block();
+        self.add_code(&format!(
+            "match self.{} {{",
+            self.config.code.state_var_name
+        ));
+        self.indent();
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_node = state_node_rcref.borrow();
+                if let Some(deferred_events) = &state_node.deferred_events_opt {
+                    let patterns: Vec<String> = deferred_events
+                        .iter()
+                        .map(|msg| {
+                            format!(
+                                "{}::{}",
+                                self.config.code.frame_event_message_type_name,
+                                self.get_msg_enum_pattern(msg)
+                            )
+                        })
+                        .collect();
+                    self.newline();
+                    self.add_code(&format!(
+                        "{}::{} => matches!(msg, {}),",
+                        self.state_enum_type_name(),
+                        self.format_type_name(&state_node.name),
+                        patterns.join(" | "),
+                    ));
+                }
+            }
+        }
+        self.newline();
+        self.add_code("_ => false,");
+        self.exit_block();
+        self.exit_block();
+
+        self.newline();
+        self.newline();
+        self.add_code(&format!(
+            "{}fn {}(&mut self)",
+            self.async_qualifier(),
+            self.config.code.replay_deferred_events_method_name,
+        ));
+        self.enter_block();
+        self.add_code("let mut i = 0;");
+        self.newline();
+        self.add_code(&format!(
+            "while i < self.{}.len()",
+            self.config.code.deferred_events_var_name
+        ));
+        self.enter_block();
+        self.add_code(&format!(
+            "if self.{}(&self.{}[i].{})",
+            self.config.code.is_deferred_method_name,
+            self.config.code.deferred_events_var_name,
+            self.config.code.frame_event_message_attribute_name,
+        ));
+        self.enter_block();
+        self.add_code("i += 1;");
+        self.outdent();
+        self.newline();
+        self.add_code("} else {");
+        self.indent();
+        self.newline();
+        self.add_code(&format!(
+            "let {} = self.{}.remove(i);",
+            self.config.code.frame_event_variable_name, self.config.code.deferred_events_var_name,
+        ));
+        self.newline();
+        self.add_code(&format!(
+            "self.{}({}){};",
+            self.config.code.handle_event_method_name,
+            self.config.code.frame_event_variable_name,
+            self.await_suffix(),
+        ));
+        self.newline();
+        self.add_code("// handling the event may have changed the state, so start over");
+        self.newline();
+        self.add_code("i = 0;");
+        self.exit_block();
+        self.exit_block();
         self.exit_block();
         self.newline();
     }
@@ -2796,20 +4660,35 @@ impl RustVisitor {
                 match &event_sym.borrow().params_opt {
                     Some(event_params) => {
                         let param_names = event_params.iter().map(|p| &p.name).collect();
-                        has_args = self.generate_arguments(
-                            &arg_struct_name,
-                            param_names,
-                            exit_args,
-                            arg_code,
-                        );
-                        arg_code.insert_str(
-                            0,
-                            &format!(
-                                "{}::{}(",
-                                self.config.code.frame_event_args_type_name, exit_event_type_name
-                            ),
-                        );
-                        arg_code.push(')');
+                        if self.config.features.typed_events {
+                            // typed events carry their arguments directly
+                            let variant_name = format!(
+                                "{}::{}",
+                                self.config.code.frame_event_type_name, exit_event_type_name
+                            );
+                            has_args = self.generate_arguments(
+                                &variant_name,
+                                param_names,
+                                exit_args,
+                                arg_code,
+                            );
+                        } else {
+                            has_args = self.generate_arguments(
+                                &arg_struct_name,
+                                param_names,
+                                exit_args,
+                                arg_code,
+                            );
+                            arg_code.insert_str(
+                                0,
+                                &format!(
+                                    "{}::{}(",
+                                    self.config.code.frame_event_args_type_name,
+                                    exit_event_type_name
+                                ),
+                            );
+                            arg_code.push(')');
+                        }
                     }
                     None => self.errors.push(format!(
                         "Invalid number of arguments for \"{}\" event handler.",
@@ -2820,10 +4699,14 @@ impl RustVisitor {
                 self.warnings.push(format!("State {} does not have an exit event handler but is being passed parameters in a transition", current_state_name));
             }
         } else {
-            arg_code.push_str(&format!(
-                "{}::None",
-                self.config.code.frame_event_args_type_name
-            ));
+            let current_state = format!(
+                "{}::{}",
+                self.state_enum_type_name(),
+                self.format_type_name(self.current_state_name_opt.as_ref().unwrap())
+            );
+            arg_code.push_str(
+                &self.empty_transition_event_arg(&self.config.code.exit_msg, &current_state),
+            );
         }
         has_args
     }
@@ -2853,20 +4736,35 @@ impl RustVisitor {
                 match &event_sym.borrow().params_opt {
                     Some(event_params) => {
                         let param_names = event_params.iter().map(|p| &p.name).collect();
-                        has_args = self.generate_arguments(
-                            &arg_struct_name,
-                            param_names,
-                            enter_args,
-                            arg_code,
-                        );
-                        arg_code.insert_str(
-                            0,
-                            &format!(
-                                "{}::{}(",
-                                self.config.code.frame_event_args_type_name, enter_event_type_name
-                            ),
-                        );
-                        arg_code.push(')');
+                        if self.config.features.typed_events {
+                            // typed events carry their arguments directly
+                            let variant_name = format!(
+                                "{}::{}",
+                                self.config.code.frame_event_type_name, enter_event_type_name
+                            );
+                            has_args = self.generate_arguments(
+                                &variant_name,
+                                param_names,
+                                enter_args,
+                                arg_code,
+                            );
+                        } else {
+                            has_args = self.generate_arguments(
+                                &arg_struct_name,
+                                param_names,
+                                enter_args,
+                                arg_code,
+                            );
+                            arg_code.insert_str(
+                                0,
+                                &format!(
+                                    "{}::{}(",
+                                    self.config.code.frame_event_args_type_name,
+                                    enter_event_type_name
+                                ),
+                            );
+                            arg_code.push(')');
+                        }
                     }
                     None => {
                         self.errors.push(format!(
@@ -2879,10 +4777,14 @@ impl RustVisitor {
                 self.warnings.push(format!("State {} does not have an enter event handler but is being passed parameters in a transition", target_state_name));
             }
         } else {
-            arg_code.push_str(&format!(
-                "{}::None",
-                self.config.code.frame_event_args_type_name
-            ));
+            let target_state = format!(
+                "{}::{}",
+                self.state_enum_type_name(),
+                self.format_type_name(target_state_name)
+            );
+            arg_code.push_str(
+                &self.empty_transition_event_arg(&self.config.code.enter_msg, &target_state),
+            );
         }
         has_args
     }
@@ -2998,21 +4900,17 @@ impl RustVisitor {
         if has_state_args {
             self.newline();
             self.add_code(&format!(
-                "{}: {}::new({}::new({})),",
+                "{}: {},",
                 self.config.code.state_args_var_name,
-                self.rc_type(),
-                self.cell_type(),
-                state_args,
+                self.new_shared_cell(state_args),
             ));
         }
         if has_state_vars {
             self.newline();
             self.add_code(&format!(
-                "{}: {}::new({}::new({})),",
+                "{}: {},",
                 self.config.code.state_vars_var_name,
-                self.rc_type(),
-                self.cell_type(),
-                state_vars,
+                self.new_shared_cell(state_vars),
             ));
         }
         self.outdent();
@@ -3020,10 +4918,11 @@ impl RustVisitor {
         self.add_code("};");
         self.newline();
         self.add_code(&format!(
-            "let next_state_context = {}::new({}::{}(context));",
-            self.rc_type(),
+            "let next_state_context = {}{}::{}(context){};",
+            self.rc_ptr_open(),
             self.config.code.state_context_type_name,
-            self.format_type_name(&target_state_name.to_string())
+            self.format_type_name(&target_state_name.to_string()),
+            self.rc_ptr_close(),
         ));
     }
 
@@ -3097,6 +4996,7 @@ impl RustVisitor {
             is_change_state: true,
             event_name,
             label,
+            guard: change_state_stmt.guard_opt.clone(),
             source_name: source_state_name.to_string(),
             target_name: Some(target_state_name.to_string()),
         });
@@ -3110,11 +5010,12 @@ impl RustVisitor {
         if self.config.features.runtime_support {
             self.add_code(&format!(
                 "{}::{}().transitions[{}], ",
-                self.config.code.runtime_info_module_name,
+                self.runtime_info_module_name(),
                 self.config.code.machine_info_function_name,
                 self.transitions.len() - 1,
             ));
         }
+        self.generate_tracing_transition_args();
         self.add_code(&format!(
             "{}::{}",
             self.state_enum_type_name(),
@@ -3123,7 +5024,7 @@ impl RustVisitor {
         if self.generate_state_context {
             self.add_code(", next_state_context");
         }
-        self.add_code(");");
+        self.add_code(&format!("){};", self.await_suffix()));
     }
 
     //* --------------------------------------------------------------------- *//
@@ -3152,14 +5053,12 @@ impl RustVisitor {
         }
 
         // generate exit arguments
+        let (exit_arg_name, enter_arg_name) = self.transition_event_arg_names();
         if self.generate_exit_args {
             let mut exit_args_code = String::new();
             self.generate_exit_arguments(transition_stmt, &mut exit_args_code);
             self.newline();
-            self.add_code(&format!(
-                "let {} = {};",
-                self.config.code.exit_args_member_name, exit_args_code
-            ));
+            self.add_code(&format!("let {} = {};", exit_arg_name, exit_args_code));
         }
 
         // generate enter arguments
@@ -3177,13 +5076,16 @@ impl RustVisitor {
                 StateContextType::StateStackPop {} => {}
             }
             if !has_enter_args {
-                enter_args_code = format!("{}::None", self.config.code.frame_event_args_type_name);
+                let target_state = format!(
+                    "{}::{}",
+                    self.state_enum_type_name(),
+                    self.format_type_name(target_state_name)
+                );
+                enter_args_code =
+                    self.empty_transition_event_arg(&self.config.code.enter_msg, &target_state);
             }
             self.newline();
-            self.add_code(&format!(
-                "let {} = {};",
-                self.config.code.enter_args_member_name, enter_args_code
-            ));
+            self.add_code(&format!("let {} = {};", enter_arg_name, enter_args_code));
         }
 
         // indent to generate parts of context at the right indentation level
@@ -3230,6 +5132,7 @@ impl RustVisitor {
             is_change_state: false,
             event_name,
             label,
+            guard: transition_stmt.guard_opt.clone(),
             source_name: source_state_name.to_string(),
             target_name: Some(target_state_name.to_string()),
         });
@@ -3243,16 +5146,17 @@ impl RustVisitor {
         if self.config.features.runtime_support {
             self.add_code(&format!(
                 "{}::{}().transitions[{}], ",
-                self.config.code.runtime_info_module_name,
+                self.runtime_info_module_name(),
                 self.config.code.machine_info_function_name,
                 self.transitions.len() - 1,
             ));
         }
+        self.generate_tracing_transition_args();
         if self.generate_exit_args {
-            self.add_code(&format!("{}, ", self.config.code.exit_args_member_name));
+            self.add_code(&format!("{}, ", exit_arg_name));
         }
         if self.generate_enter_args {
-            self.add_code(&format!("{}, ", self.config.code.enter_args_member_name));
+            self.add_code(&format!("{}, ", enter_arg_name));
         }
         self.add_code(&format!(
             "{}::{}",
@@ -3262,7 +5166,7 @@ impl RustVisitor {
         if self.generate_state_context {
             self.add_code(", next_state_context");
         }
-        self.add_code(");");
+        self.add_code(&format!("){};", self.await_suffix()));
     }
 
     //* --------------------------------------------------------------------- *//
@@ -3292,6 +5196,7 @@ impl RustVisitor {
             is_change_state: true,
             event_name,
             label,
+            guard: change_state_stmt.guard_opt.clone(),
             source_name: source_state_name.to_string(),
             target_name: None,
         });
@@ -3305,8 +5210,9 @@ impl RustVisitor {
             ));
             self.newline();
             self.add_code(&format!(
-                "let next_state_context = {}::new(popped_state_context);",
-                self.rc_type()
+                "let next_state_context = {}popped_state_context{};",
+                self.rc_ptr_open(),
+                self.rc_ptr_close(),
             ));
         } else {
             self.add_code(&format!(
@@ -3324,16 +5230,17 @@ impl RustVisitor {
         if self.config.features.runtime_support {
             self.add_code(&format!(
                 "{}::{}().transitions[{}], ",
-                self.config.code.runtime_info_module_name,
+                self.runtime_info_module_name(),
                 self.config.code.machine_info_function_name,
                 self.transitions.len() - 1,
             ));
         }
+        self.generate_tracing_transition_args();
         self.add_code("next_state");
         if self.generate_state_context {
             self.add_code(", next_state_context");
         }
-        self.add_code(");");
+        self.add_code(&format!("){};", self.await_suffix()));
     }
 
     //* --------------------------------------------------------------------- *//
@@ -3361,6 +5268,7 @@ impl RustVisitor {
             is_change_state: false,
             event_name,
             label,
+            guard: transition_stmt.guard_opt.clone(),
             source_name: source_state_name.to_string(),
             target_name: None,
         });
@@ -3374,8 +5282,9 @@ impl RustVisitor {
             ));
             self.newline();
             self.add_code(&format!(
-                "let next_state_context = {}::new(popped_state_context);",
-                self.rc_type()
+                "let next_state_context = {}popped_state_context{};",
+                self.rc_ptr_open(),
+                self.rc_ptr_close(),
             ));
         } else {
             self.add_code(&format!(
@@ -3393,16 +5302,35 @@ impl RustVisitor {
         if self.config.features.runtime_support {
             self.add_code(&format!(
                 "{}::{}().transitions[{}], ",
-                self.config.code.runtime_info_module_name,
+                self.runtime_info_module_name(),
                 self.config.code.machine_info_function_name,
                 self.transitions.len() - 1,
             ));
         }
+        self.generate_tracing_transition_args();
+        // the popped state's enter event and the current state's exit event take no arguments
+        if self.generate_exit_args {
+            let current_state = format!(
+                "{}::{}",
+                self.state_enum_type_name(),
+                self.format_type_name(self.current_state_name_opt.as_ref().unwrap())
+            );
+            self.add_code(&format!(
+                "{}, ",
+                self.empty_transition_event_arg(&self.config.code.exit_msg, &current_state)
+            ));
+        }
+        if self.generate_enter_args {
+            self.add_code(&format!(
+                "{}, ",
+                self.empty_transition_event_arg(&self.config.code.enter_msg, "next_state")
+            ));
+        }
         self.add_code("next_state");
         if self.generate_state_context {
             self.add_code(", next_state_context");
         }
-        self.add_code(");");
+        self.add_code(&format!("){};", self.await_suffix()));
     }
 }
 
@@ -3419,6 +5347,20 @@ impl AstVisitor for RustVisitor {
                 self.has_states = true;
             }
         }
+        self.generate_deferred_events = system_node.has_deferred_events();
+        if self.config.features.no_std {
+            self.check_no_std_features();
+        }
+        if self.config.features.generic_actions {
+            self.check_generic_actions_features();
+        }
+        if self.config.features.typed_events {
+            self.check_typed_events_features();
+        }
+        self.check_fallible_actions(system_node);
+        if self.config.features.generate_test_harness {
+            self.check_test_harness_features();
+        }
 
         self.add_code(&format!("// {}", self.compiler_version));
         self.newline();
@@ -3426,11 +5368,17 @@ impl AstVisitor for RustVisitor {
         self.newline();
         self.add_code("#[allow(unused_imports)]");
         self.newline();
-        self.add_code("use std::borrow::Borrow;");
+        if self.config.features.no_std {
+            self.add_code("use core::borrow::Borrow;");
+        } else {
+            self.add_code("use std::borrow::Borrow;");
+        }
         self.newline();
         self.add_code("#[allow(unused_imports)]");
         self.newline();
-        if self.config.features.thread_safe {
+        if self.config.features.no_std {
+            self.add_code("use core::cell::RefCell;");
+        } else if self.config.features.thread_safe {
             self.add_code("use std::sync::{Arc, Mutex};");
         } else {
             self.add_code("use std::cell::RefCell;");
@@ -3453,17 +5401,32 @@ impl AstVisitor for RustVisitor {
             self.newline();
         }
 
+        if self.config.features.generate_module_tree {
+            self.generate_module_decls();
+            self.enter_module(self.config.code.events_module_name.clone());
+        }
+
         if let Some(interface_block_node) = &system_node.interface_block_node_opt {
             interface_block_node.accept_frame_messages_enum(self);
             interface_block_node.accept_frame_parameters(self);
         }
 
+        // domain enums
+        self.enter_module(self.config.code.machine_module_name.clone());
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for enum_decl_node_rcref in &domain_block_node.enums {
+                enum_decl_node_rcref.borrow().accept(self);
+            }
+        }
+
         // FrameEvent
+        self.enter_module(self.config.code.events_module_name.clone());
         self.newline();
         self.newline();
         self.generate_event_defs(system_node);
 
         // state enum
+        self.enter_module(self.config.code.states_module_name.clone());
         self.newline();
         self.newline();
         self.generate_state_enum(system_node);
@@ -3475,11 +5438,13 @@ impl AstVisitor for RustVisitor {
             self.generate_state_context_defs(system_node);
         }
 
+        self.enter_module(self.config.code.machine_module_name.clone());
         if let Some(actions_block_node) = &system_node.actions_block_node_opt {
             actions_block_node.accept_rust_trait(self);
             self.newline();
             self.newline();
-        } else if self.config.features.generate_hook_methods {
+        } else if self.config.features.generate_hook_methods || self.config.features.generic_actions
+        {
             let empty_actions_block_node = ActionsBlockNode {
                 actions: Vec::new(),
             };
@@ -3492,7 +5457,12 @@ impl AstVisitor for RustVisitor {
         self.add_code("// System Controller ");
         self.newline();
         self.disable_type_style_warnings();
-        self.add_code(&format!("pub struct {}", self.system_type_name()));
+        self.add_code(&format!(
+            "{}struct {}{}",
+            self.system_visibility(),
+            self.system_type_name(),
+            self.system_type_params()
+        ));
         self.enter_block();
 
         // state variable
@@ -3506,32 +5476,55 @@ impl AstVisitor for RustVisitor {
         if self.generate_state_context {
             self.newline();
             self.add_code(&format!(
-                "{}: {}<{}>,",
+                "{}: {},",
                 self.config.code.state_context_var_name,
-                self.rc_type(),
-                self.config.code.state_context_type_name,
+                self.rc_ptr_type(&self.config.code.state_context_type_name),
             ));
         }
 
         // state stack variable
         if self.generate_state_stack {
             self.newline();
-            if self.generate_state_context {
-                self.add_code(&format!(
-                    "{}: Vec<({}, {})>,",
-                    self.config.code.state_stack_var_name,
+            let elem_type = if self.generate_state_context {
+                format!(
+                    "({}, {})",
                     self.state_enum_type_name(),
                     self.config.code.state_context_type_name
+                )
+            } else {
+                self.state_enum_type_name()
+            };
+            if self.config.features.no_std {
+                self.add_code(&format!(
+                    "{}: [Option<{}>; {}],",
+                    self.config.code.state_stack_var_name,
+                    elem_type,
+                    self.config.code.state_stack_capacity,
+                ));
+                self.newline();
+                self.add_code(&format!(
+                    "{}: usize,",
+                    self.config.code.state_stack_depth_var_name
                 ));
             } else {
                 self.add_code(&format!(
                     "{}: Vec<{}>,",
-                    self.config.code.state_stack_var_name,
-                    self.state_enum_type_name()
+                    self.config.code.state_stack_var_name, elem_type
                 ));
             }
         }
 
+        // deferred event queue
+        if self.generate_deferred_events {
+            self.newline();
+            self.add_code(&format!(
+                "{}: Vec<{}<{}>>,",
+                self.config.code.deferred_events_var_name,
+                self.rc_type(),
+                self.config.code.frame_event_type_name,
+            ));
+        }
+
         // event monitor
         if self.config.features.runtime_support {
             self.newline();
@@ -3542,6 +5535,24 @@ impl AstVisitor for RustVisitor {
             ));
         }
 
+        // error of a failed action, kept until the interface method returns
+        if self.config.features.fallible_actions {
+            self.newline();
+            self.add_code(&format!(
+                "{}: Option<{}>,",
+                self.config.code.pending_error_var_name, self.config.code.error_type
+            ));
+        }
+
+        // injected actions
+        if self.config.features.generic_actions {
+            self.newline();
+            self.add_code(&format!(
+                "{}: {},",
+                self.config.code.actions_var_name, self.config.code.actions_type_param
+            ));
+        }
+
         // domain variables
         let mut domain_vars: Vec<String> = Vec::new();
         if let Some(domain_block_node) = &system_node.domain_block_node_opt {
@@ -3557,6 +5568,11 @@ impl AstVisitor for RustVisitor {
         self.newline();
         self.newline();
 
+        // define snapshot struct
+        if self.config.features.serde && self.has_states {
+            self.generate_snapshot_struct(system_node);
+        }
+
         // add runtime support
         if self.config.features.runtime_support {
             self.generate_environment_impl(&self.system_type_name(), &domain_vars);
@@ -3668,7 +5684,7 @@ impl AstVisitor for RustVisitor {
             self.enter_block();
             self.add_code(&format!(
                 "{}::{}()",
-                self.config.code.runtime_info_module_name,
+                self.runtime_info_module_name(),
                 self.config.code.machine_info_function_name,
             ));
             self.exit_block();
@@ -3702,7 +5718,11 @@ impl AstVisitor for RustVisitor {
 
         // add state machine methods
         self.disable_all_style_warnings();
-        self.add_code(&format!("impl {} {{", self.system_type_name()));
+        self.add_code(&format!(
+            "impl{} {} {{",
+            self.system_type_params(),
+            self.system_type()
+        ));
         self.indent();
 
         // generate constructor and initialize method
@@ -3713,6 +5733,27 @@ impl AstVisitor for RustVisitor {
             self.newline();
             self.newline();
             self.generate_initialize();
+            if self.config.features.serde {
+                self.newline();
+                self.newline();
+                self.generate_snapshot_methods(system_node);
+            }
+        }
+
+        // without the runtime interface, the static info is only reachable from here
+        if self.generate_runtime_info && !self.config.features.runtime_support {
+            self.newline();
+            self.newline();
+            self.add_code(
+                "pub const fn machine_info() -> &'static frame_runtime::info::MachineInfo",
+            );
+            self.enter_block();
+            self.add_code(&format!(
+                "{}::{}()",
+                self.runtime_info_module_name(),
+                self.config.code.machine_info_function_name,
+            ));
+            self.exit_block();
         }
 
         self.serialize.push("".to_string());
@@ -3762,23 +5803,51 @@ impl AstVisitor for RustVisitor {
         self.newline();
 
         // generate Default trait implementation
-        if self.has_states {
+        if self.has_states && !self.config.features.async_support {
             self.newline();
-            self.add_code(&format!("impl Default for {}", self.system_type_name()));
+            if self.config.features.generic_actions {
+                self.add_code(&format!(
+                    "impl{} Default for {} where {}: Default",
+                    self.system_type_params(),
+                    self.system_type(),
+                    self.config.code.actions_type_param,
+                ));
+            } else {
+                self.add_code(&format!("impl Default for {}", self.system_type_name()));
+            }
             self.enter_block();
             self.add_code("fn default() -> Self");
             self.enter_block();
-            self.add_code("Self::new()");
+            if self.config.features.generic_actions {
+                self.add_code(&format!(
+                    "Self::new({}::default())",
+                    self.config.code.actions_type_param
+                ));
+            } else {
+                self.add_code("Self::new()");
+            }
             self.exit_block();
             self.exit_block();
             self.newline();
         }
 
+        // compiled patterns for regex match tests
+        if !self.regex_patterns.is_empty() {
+            if self.config.features.no_std {
+                self.errors.push(String::from(
+                    "Regex match tests are not supported by the no_std feature.",
+                ));
+            }
+            self.generate_regex_statics();
+        }
+
         // generate runtime info module used by implementations of the runtime interface
-        if self.config.features.runtime_support {
+        if self.generate_runtime_info {
+            self.enter_module(self.config.code.info_module_name.clone());
             self.newline();
             self.generate_runtime_info(system_node);
             self.newline();
+            self.enter_module(self.config.code.machine_module_name.clone());
         }
 
         if let Some(actions_block_node) = &system_node.actions_block_node_opt {
@@ -3792,17 +5861,84 @@ impl AstVisitor for RustVisitor {
             self.newline();
         }
 
+        if self.config.features.generate_test_harness && self.has_states {
+            self.generate_test_harness(system_node);
+        }
+
         // self.generate_subclass();
     }
 
     //* --------------------------------------------------------------------- *//
 
     fn visit_frame_messages_enum(&mut self, _interface_block_node: &InterfaceBlockNode) {
+        // in typed_events mode, this enum is the event type itself and its variants carry the
+        // event arguments, including those of enter and exit events
+        let typed = self.config.features.typed_events;
+        let enum_name = if typed {
+            self.config.code.frame_event_type_name.clone()
+        } else {
+            self.config.code.frame_event_message_type_name.clone()
+        };
+
+        // the declaration, pattern, and name of each variant besides the plain enter/exit ones
+        let mut messages: Vec<Result<(String, String, String), String>> = Vec::new();
+        let events = self.arcanum.get_event_names();
+        for event in &events {
+            let (variant_name, message_name) = if self.is_enter_or_exit_message(event) {
+                if !typed {
+                    continue;
+                }
+                (self.format_event_type_name(event), event.clone())
+            } else {
+                match self.arcanum.get_interface_or_msg_from_msg(event) {
+                    Some(canonical_message_name) => (
+                        self.format_message_type_name(&canonical_message_name),
+                        canonical_message_name,
+                    ),
+                    None => {
+                        messages.push(Err(event.clone()));
+                        continue;
+                    }
+                }
+            };
+            let fields_opt: Option<Vec<String>> = if typed {
+                self.arcanum.get_event(event, &None).and_then(|event_sym| {
+                    event_sym.borrow().params_opt.as_ref().map(|params| {
+                        params
+                            .iter()
+                            .map(|param| {
+                                format!(
+                                    "{}: {}",
+                                    self.format_value_name(&param.name),
+                                    match &param.param_type_opt {
+                                        Some(param_type) => param_type.get_type_str(),
+                                        None => "<?>".to_string(),
+                                    }
+                                )
+                            })
+                            .collect()
+                    })
+                })
+            } else {
+                None
+            };
+            match fields_opt {
+                Some(fields) => messages.push(Ok((
+                    format!("{} {{ {} }}", variant_name, fields.join(", ")),
+                    format!("{} {{ .. }}", variant_name),
+                    message_name,
+                ))),
+                None if self.is_enter_or_exit_message(event) => {}
+                None => messages.push(Ok((variant_name.clone(), variant_name, message_name))),
+            }
+        }
+
         self.newline();
         self.disable_type_style_warnings();
         self.add_code(&format!(
-            "enum {} {{",
-            self.config.code.frame_event_message_type_name
+            "{}enum {} {{",
+            self.internal_visibility(),
+            enum_name
         ));
         self.indent();
         self.newline();
@@ -3817,25 +5953,11 @@ impl AstVisitor for RustVisitor {
             self.config.code.exit_msg,
             self.state_enum_type_name(),
         ));
-
-        let events = self.arcanum.get_event_names();
-        for event in &events {
-            if self.is_enter_or_exit_message(event) {
-                continue;
-            }
-            let message_opt = self.arcanum.get_interface_or_msg_from_msg(event);
-            match message_opt {
-                Some(canonical_message_name) => {
-                    self.newline();
-                    self.add_code(&format!(
-                        "{},",
-                        self.format_type_name(&canonical_message_name)
-                    ));
-                }
-                None => {
-                    self.newline();
-                    self.add_code(&format!("<Error - unknown message {}>,", &event));
-                }
+        for message in &messages {
+            self.newline();
+            match message {
+                Ok((decl, _, _)) => self.add_code(&format!("{},", decl)),
+                Err(event) => self.add_code(&format!("<Error - unknown message {}>,", event)),
             }
         }
 
@@ -3846,13 +5968,29 @@ impl AstVisitor for RustVisitor {
         self.newline();
         self.newline();
         self.disable_type_style_warnings();
-        self.add_code(&format!(
-            "impl std::fmt::Display for {} {{",
-            self.config.code.frame_event_message_type_name
-        ));
-        self.indent();
-        self.newline();
-        self.add_code("fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {");
+        // in no_std mode, message names are static strings that are also used for formatting
+        let no_std = self.config.features.no_std;
+        let name_arm = |name: String| {
+            if no_std {
+                format!("\"{}\"", name)
+            } else {
+                format!("write!(f, \"{}\")", name)
+            }
+        };
+        if no_std {
+            self.add_code(&format!("impl {} {{", enum_name));
+            self.indent();
+            self.newline();
+            self.add_code(&format!(
+                "{}fn name(&self) -> &'static str {{",
+                self.internal_visibility()
+            ));
+        } else {
+            self.add_code(&format!("impl std::fmt::Display for {} {{", enum_name));
+            self.indent();
+            self.newline();
+            self.add_code("fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {");
+        }
         self.indent();
         self.newline();
         self.add_code("match self {");
@@ -3863,66 +6001,54 @@ impl AstVisitor for RustVisitor {
         for state_name in &state_names {
             self.newline();
             self.add_code(&format!(
-                "{}::{}({}::{}) => write!(f, \"{}:{}\"),",
-                self.config.code.frame_event_message_type_name,
+                "{}::{}({}::{}) => {},",
+                enum_name,
                 self.config.code.enter_msg,
                 self.state_enum_type_name(),
                 self.format_type_name(state_name),
-                state_name,
-                self.config.code.enter_token,
+                name_arm(format!("{}:{}", state_name, self.config.code.enter_token)),
             ));
         }
         for state_name in &state_names {
             self.newline();
             self.add_code(&format!(
-                "{}::{}({}::{}) => write!(f, \"{}:{}\"),",
-                self.config.code.frame_event_message_type_name,
+                "{}::{}({}::{}) => {},",
+                enum_name,
                 self.config.code.exit_msg,
                 self.state_enum_type_name(),
                 self.format_type_name(state_name),
-                state_name,
-                self.config.code.exit_token,
+                name_arm(format!("{}:{}", state_name, self.config.code.exit_token)),
             ));
         }
         // special case for machine with no states...
         if state_names.is_empty() {
             self.newline();
             self.add_code(&format!(
-                "{}::{}(_) => write!(f, \":{}\"),",
-                self.config.code.frame_event_message_type_name,
+                "{}::{}(_) => {},",
+                enum_name,
                 self.config.code.enter_msg,
-                self.config.code.enter_token,
+                name_arm(format!(":{}", self.config.code.enter_token)),
             ));
             self.newline();
             self.add_code(&format!(
-                "{}::{}(_) => write!(f, \":{}\"),",
-                self.config.code.frame_event_message_type_name,
+                "{}::{}(_) => {},",
+                enum_name,
                 self.config.code.exit_msg,
-                self.config.code.exit_token,
+                name_arm(format!(":{}", self.config.code.exit_token)),
             ));
         }
 
         // other events
-        for event in &events {
-            if self.is_enter_or_exit_message(event) {
-                continue;
-            }
-            let message_opt = self.arcanum.get_interface_or_msg_from_msg(event);
-            match message_opt {
-                Some(canonical_message_name) => {
-                    let formatted_message_name = self.format_type_name(&canonical_message_name);
-                    self.newline();
-                    self.add_code(&format!(
-                        "{}::{} => write!(f, \"{}\"),",
-                        self.config.code.frame_event_message_type_name,
-                        formatted_message_name,
-                        canonical_message_name,
-                    ));
-                }
-                None => {
-                    self.newline();
-                    self.add_code(&format!("<Error - unknown message {}>,", &event));
-                }
+        for message in &messages {
+            self.newline();
+            match message {
+                Ok((_, pattern, name)) => self.add_code(&format!(
+                    "{}::{} => {},",
+                    enum_name,
+                    pattern,
+                    name_arm(name.clone())
+                )),
+                Err(event) => self.add_code(&format!("<Error - unknown message {}>,", event)),
             }
         }
         self.outdent();
@@ -3934,6 +6060,19 @@ impl AstVisitor for RustVisitor {
         self.outdent();
         self.newline();
         self.add_code("}");
+
+        if no_std {
+            self.newline();
+            self.newline();
+            self.disable_type_style_warnings();
+            self.add_code(&format!("impl core::fmt::Display for {}", enum_name));
+            self.enter_block();
+            self.add_code("fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result");
+            self.enter_block();
+            self.add_code("f.write_str(self.name())");
+            self.exit_block();
+            self.exit_block();
+        }
     }
 
     //* --------------------------------------------------------------------- *//
@@ -3947,6 +6086,7 @@ impl AstVisitor for RustVisitor {
             self.format_value_name(&interface_method_call_expr_node.identifier.name.lexeme)
         ));
         interface_method_call_expr_node.call_expr_list.accept(self);
+        self.add_code(self.await_suffix());
         // TODO: review this return as I think it is a nop.
     }
 
@@ -3964,6 +6104,7 @@ impl AstVisitor for RustVisitor {
         interface_method_call_expr_node
             .call_expr_list
             .accept_to_string(self, output);
+        output.push_str(self.await_suffix());
         // TODO: review this return as I think it is a nop.
     }
 
@@ -3985,7 +6126,9 @@ impl AstVisitor for RustVisitor {
     fn visit_interface_method_node(&mut self, interface_method_node: &InterfaceMethodNode) {
         self.newline();
         self.add_code(&format!(
-            "pub fn {}(&mut self",
+            "{}{}fn {}(&mut self",
+            self.system_visibility(),
+            self.async_qualifier(),
             self.format_value_name(&interface_method_node.name)
         ));
 
@@ -3997,15 +6140,16 @@ impl AstVisitor for RustVisitor {
         }
 
         self.add_code(")");
-        match &interface_method_node.return_type_opt {
-            Some(return_type) => {
-                self.add_code(&format!(" -> {}", return_type.get_type_str()));
-            }
-            None => {}
-        }
+        self.add_code(&self.interface_return_type(interface_method_node));
         self.enter_block();
 
         let event_type_name = self.format_type_name(&interface_method_node.name);
+        if self.config.features.typed_events {
+            self.generate_typed_interface_method_body(interface_method_node, &event_type_name);
+            self.exit_block();
+            self.newline();
+            return;
+        }
         self.add_code(&format!(
             "let frame_args = {}::",
             self.config.code.frame_event_args_type_name
@@ -4031,21 +6175,31 @@ impl AstVisitor for RustVisitor {
 
         self.newline();
         self.add_code(&format!(
-            "let {} = {}::new({}::new({}::{}, frame_args));",
+            "let {} = {}{}::new({}::{}, frame_args){};",
             self.config.code.frame_event_variable_name,
-            self.rc_type(),
+            self.rc_ptr_open(),
             self.config.code.frame_event_type_name,
             self.config.code.frame_event_message_type_name,
             event_type_name,
+            self.rc_ptr_close(),
         ));
         self.newline();
         if interface_method_node.return_type_opt.is_some() {
             self.add_code(&format!(
-                "self.{}({}.clone());",
+                "self.{}({}){};",
                 self.config.code.handle_event_method_name,
-                self.config.code.frame_event_variable_name,
+                if self.config.features.no_std {
+                    self.frame_event_arg(&self.config.code.frame_event_variable_name)
+                } else {
+                    format!("{}.clone()", self.config.code.frame_event_variable_name)
+                },
+                self.await_suffix(),
             ));
             self.newline();
+            if self.config.features.fallible_actions {
+                self.add_code(&format!("{}?;", self.take_error_stmt()));
+                self.newline();
+            }
             self.add_code(&format!(
                 "let return_value = match *{}.{}.{}",
                 self.config.code.frame_event_variable_name,
@@ -4070,13 +6224,22 @@ impl AstVisitor for RustVisitor {
             self.exit_block();
             self.add_code(";");
             self.newline();
-            self.add_code("return_value");
+            if self.config.features.fallible_actions {
+                self.add_code("Ok(return_value)");
+            } else {
+                self.add_code("return_value");
+            }
         } else {
             self.add_code(&format!(
-                "self.{}({});",
+                "self.{}({}){};",
                 self.config.code.handle_event_method_name,
-                self.config.code.frame_event_variable_name,
+                self.frame_event_arg(&self.config.code.frame_event_variable_name),
+                self.await_suffix(),
             ));
+            if self.config.features.fallible_actions {
+                self.newline();
+                self.add_code(&self.take_error_stmt());
+            }
         }
 
         self.exit_block();
@@ -4122,11 +6285,20 @@ impl AstVisitor for RustVisitor {
     //* --------------------------------------------------------------------- *//
 
     fn visit_action_node_rust_trait(&mut self, actions_block_node: &ActionsBlockNode) {
-        if self.config.features.generate_action_impl {
+        let generic_actions = self.config.features.generic_actions;
+        if self.config.features.generate_action_impl || generic_actions {
             self.add_code("#[allow(clippy::ptr_arg)]");
             self.newline();
             self.disable_type_style_warnings();
-            self.add_code(&format!("trait {} {{ ", self.action_trait_type_name(),));
+            self.add_code(&format!(
+                "{}trait {} {{ ",
+                if generic_actions {
+                    String::from("pub ")
+                } else {
+                    self.internal_visibility()
+                },
+                self.action_trait_type_name(),
+            ));
             self.indent();
 
             // add action signatures
@@ -4141,8 +6313,10 @@ impl AstVisitor for RustVisitor {
             if self.generate_transition_hook {
                 self.newline();
                 self.add_code(&format!(
-                    "fn {}(&self, {}: {enum_type}, {}: {enum_type});",
+                    "{}fn {}({}, {}: {enum_type}, {}: {enum_type});",
+                    self.async_qualifier(),
                     self.config.code.transition_hook_method_name,
+                    self.action_receiver(),
                     old_state_var,
                     new_state_var,
                     enum_type = self.state_enum_type_name()
@@ -4151,8 +6325,10 @@ impl AstVisitor for RustVisitor {
             if self.generate_change_state_hook {
                 self.newline();
                 self.add_code(&format!(
-                    "fn {}(&self, {}: {enum_type}, {}: {enum_type});",
+                    "{}fn {}({}, {}: {enum_type}, {}: {enum_type});",
+                    self.async_qualifier(),
                     self.config.code.change_state_hook_method_name,
+                    self.action_receiver(),
                     old_state_var,
                     new_state_var,
                     enum_type = self.state_enum_type_name()
@@ -4168,7 +6344,8 @@ impl AstVisitor for RustVisitor {
     //* --------------------------------------------------------------------- *//
 
     fn visit_actions_node_rust_impl(&mut self, actions_block_node: &ActionsBlockNode) {
-        if self.config.features.generate_action_impl {
+        // injected actions are implemented by the user
+        if self.config.features.generate_action_impl && !self.config.features.generic_actions {
             self.newline();
             self.disable_all_style_warnings();
             self.add_code(&format!(
@@ -4190,7 +6367,8 @@ impl AstVisitor for RustVisitor {
             if self.generate_transition_hook {
                 self.newline();
                 self.add_code(&format!(
-                    "fn {}(&self, {}: {enum_type}, {}: {enum_type}) {{}}",
+                    "{}fn {}(&self, {}: {enum_type}, {}: {enum_type}) {{}}",
+                    self.async_qualifier(),
                     self.config.code.transition_hook_method_name,
                     old_state_var,
                     new_state_var,
@@ -4200,7 +6378,8 @@ impl AstVisitor for RustVisitor {
             if self.generate_change_state_hook {
                 self.newline();
                 self.add_code(&format!(
-                    "fn {}(&self, {}: {enum_type}, {}: {enum_type}) {{}}",
+                    "{}fn {}(&self, {}: {enum_type}, {}: {enum_type}) {{}}",
+                    self.async_qualifier(),
                     self.config.code.change_state_hook_method_name,
                     old_state_var,
                     new_state_var,
@@ -4216,6 +6395,31 @@ impl AstVisitor for RustVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enum_decl_node(&mut self, enum_decl_node: &EnumDeclNode) {
+        self.newline();
+        self.newline();
+        if self.config.features.serde {
+            self.add_code(&format!(
+                "#[derive(Clone, Copy, Debug, PartialEq, Eq, {})]",
+                SERDE_TRAITS
+            ));
+        } else {
+            self.add_code("#[derive(Clone, Copy, Debug, PartialEq, Eq)]");
+        }
+        self.newline();
+        self.add_code(&format!("pub enum {}", enum_decl_node.name));
+        self.enter_block();
+        for (index, enumerator) in enum_decl_node.enumerators.iter().enumerate() {
+            if index > 0 {
+                self.newline();
+            }
+            self.add_code(&format!("{},", enumerator));
+        }
+        self.exit_block();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_domain_block_node(&mut self, domain_block_node: &DomainBlockNode) {
         let var_nodes = &domain_block_node.member_variables;
         if !var_nodes.is_empty() {
@@ -4251,14 +6455,15 @@ impl AstVisitor for RustVisitor {
         self.add_code("#[allow(unused_variables)]");
         self.newline();
         self.add_code(&format!(
-            "fn {}(&mut self, {}: {}<{}>) {{",
+            "{}fn {}(&mut self, {}: {}){} {{",
+            self.async_qualifier(),
             self.format_state_handler_name(&state_node.name),
             self.config.code.frame_event_variable_name,
-            self.rc_type(),
-            self.config.code.frame_event_type_name,
+            self.frame_event_param_type(),
+            self.handler_return_type(),
         ));
         self.indent();
-        if self.generate_state_context {
+        if self.generate_state_context && !self.config.features.no_std {
             self.newline();
             self.generate_this_state_context();
         }
@@ -4279,11 +6484,18 @@ impl AstVisitor for RustVisitor {
         }
 
         self.newline();
-        self.add_code(&format!(
-            "match {}.{} {{",
-            self.config.code.frame_event_variable_name,
-            self.config.code.frame_event_message_attribute_name
-        ));
+        if self.config.features.typed_events {
+            self.add_code(&format!(
+                "match &{} {{",
+                self.config.code.frame_event_variable_name
+            ));
+        } else {
+            self.add_code(&format!(
+                "match {}.{} {{",
+                self.config.code.frame_event_variable_name,
+                self.config.code.frame_event_message_attribute_name
+            ));
+        }
         self.indent();
 
         if !state_node.evt_handlers_rcref.is_empty() {
@@ -4302,7 +6514,16 @@ impl AstVisitor for RustVisitor {
             Some(dispatch) => {
                 dispatch.accept(self);
             }
-            None => {}
+            None => {
+                // typed event handlers return their result
+                if self.config.features.typed_events {
+                    self.newline();
+                    self.add_code(&format!(
+                        "{}::None",
+                        self.config.code.frame_event_return_type_name
+                    ));
+                }
+            }
         }
         self.outdent();
         self.newline();
@@ -4320,11 +6541,19 @@ impl AstVisitor for RustVisitor {
         //        let mut generate_final_close_paren = true;
         if let MessageType::CustomMessage { message_node } = &evt_handler_node.msg_t {
             self.current_message = message_node.name.clone();
-            self.add_code(&format!(
-                "{}::{} => {{",
-                self.config.code.frame_event_message_type_name,
-                self.get_msg_enum_pattern(&message_node.name)
-            ));
+            if self.config.features.typed_events {
+                let pattern = self.typed_event_pattern(&message_node.name);
+                self.add_code(&format!(
+                    "{}::{} => {{",
+                    self.config.code.frame_event_type_name, pattern
+                ));
+            } else {
+                self.add_code(&format!(
+                    "{}::{} => {{",
+                    self.config.code.frame_event_message_type_name,
+                    self.get_msg_enum_pattern(&message_node.name)
+                ));
+            }
         } else {
             // AnyMessage ( ||* )
             // This feature requires dynamic dispatch.
@@ -4369,6 +6598,22 @@ impl AstVisitor for RustVisitor {
         match &evt_handler_terminator_node.terminator_type {
             TerminatorType::Return => {
                 match &evt_handler_terminator_node.return_expr_t_opt {
+                    Some(expr_t) if self.config.features.typed_events => {
+                        // typed events return the value directly from the handler
+                        self.newline();
+                        self.add_code(&format!(
+                            "return {}::{}",
+                            self.config.code.frame_event_return_type_name,
+                            self.format_type_name(&self.current_message),
+                        ));
+                        self.enter_block();
+                        self.add_code("return_value: ");
+                        expr_t.accept(self);
+                        self.exit_block();
+                        self.add_code(";");
+                        self.this_branch_transitioned = false;
+                        return;
+                    }
                     Some(expr_t) => {
                         self.newline();
                         if self.config.features.thread_safe {
@@ -4493,9 +6738,16 @@ impl AstVisitor for RustVisitor {
     //* --------------------------------------------------------------------- *//
 
     fn visit_action_call_expression_node(&mut self, action_call: &ActionCallExprNode) {
+        if self.is_fallible_action_call(action_call) {
+            let mut code = String::new();
+            self.visit_action_call_expression_node_to_string(action_call, &mut code);
+            self.add_code(&code);
+            return;
+        }
         let action_name = self.format_action_name(&action_call.identifier.name.lexeme);
-        self.add_code(&format!("self.{}", action_name));
+        self.add_code(&format!("{}.{}", self.actions_expr(), action_name));
         action_call.call_expr_list.accept(self);
+        self.add_code(self.await_suffix());
     }
 
     //* --------------------------------------------------------------------- *//
@@ -4506,8 +6758,20 @@ impl AstVisitor for RustVisitor {
         output: &mut String,
     ) {
         let action_name = self.format_action_name(&action_call.identifier.name.lexeme);
-        output.push_str(&format!("self.{}", action_name));
-        action_call.call_expr_list.accept_to_string(self, output);
+        let mut call = format!("{}.{}", self.actions_expr(), action_name);
+        action_call.call_expr_list.accept_to_string(self, &mut call);
+        call.push_str(self.await_suffix());
+        if self.is_fallible_action_call(action_call) {
+            // the first error is kept for the interface method and ends the handler
+            output.push_str(&format!(
+                "match {} {{ Ok(value) => value, Err(error) => {{ self.{}.get_or_insert(error); {} }} }}",
+                call,
+                self.config.code.pending_error_var_name,
+                self.handler_return_stmt()
+            ));
+        } else {
+            output.push_str(&call);
+        }
     }
 
     //* --------------------------------------------------------------------- *//
@@ -4566,10 +6830,17 @@ impl AstVisitor for RustVisitor {
 
     fn visit_dispatch_node(&mut self, dispatch_node: &DispatchNode) {
         self.newline();
+        // in typed_events mode, the parent's result is returned from the handler
         self.add_code(&format!(
-            "self.{}({});",
+            "self.{}({}){}{}",
             self.format_state_handler_name(&dispatch_node.target_state_ref.name),
-            self.config.code.frame_event_variable_name
+            self.config.code.frame_event_variable_name,
+            self.await_suffix(),
+            if self.config.features.typed_events {
+                ""
+            } else {
+                ";"
+            },
         ));
         self.generate_comment(dispatch_node.line);
     }
@@ -4591,6 +6862,16 @@ impl AstVisitor for RustVisitor {
             } => {
                 number_match_test_node.accept(self);
             }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                regex_match_test_node.accept(self);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
+            }
         }
     }
 
@@ -4665,7 +6946,7 @@ impl AstVisitor for RustVisitor {
                 interface_method_call_expr_node.accept(self);
                 self.add_code(";");
                 self.newline();
-                self.add_code("return;");
+                self.add_code(&self.handler_return_stmt());
                 return;
             }
         }
@@ -4990,6 +7271,256 @@ impl AstVisitor for RustVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_regex_match_test_node(&mut self, regex_match_test_node: &RegexMatchTestNode) {
+        let mut if_or_else_if = "if";
+        let captures_var = self.config.code.regex_captures_var_name.clone();
+
+        self.newline();
+        for match_branch_node in &regex_match_test_node.match_branch_nodes {
+            let pattern_node = &match_branch_node.regex_match_pattern_node;
+            let regex_index = self.regex_patterns.len();
+            let pattern = pattern_node.pattern_with_group_prefix("?P<");
+            if let Err(err) = regex::Regex::new(&pattern) {
+                self.errors.push(format!(
+                    "[line {}] Error : Invalid regular expression /{}/ : {}",
+                    pattern_node.line, pattern_node.pattern, err
+                ));
+            }
+            self.regex_patterns.push(pattern);
+
+            self.add_code(&format!(
+                "{} let Some({}) = {}.captures(&",
+                if_or_else_if,
+                captures_var,
+                self.format_regex_static_name(regex_index)
+            ));
+            match &regex_match_test_node.expr_t {
+                ExprType::CallExprT {
+                    call_expr_node: method_call_expr_node,
+                } => method_call_expr_node.accept(self),
+                ExprType::ActionCallExprT {
+                    action_call_expr_node,
+                } => action_call_expr_node.accept(self),
+                ExprType::CallChainLiteralExprT {
+                    call_chain_expr_node,
+                } => call_chain_expr_node.accept(self),
+                ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                ExprType::ExprListT { expr_list_node } => {
+                    // must be only 1 expression in the list
+                    if expr_list_node.exprs_t.len() != 1 {
+                        self.errors
+                            .push("Error - expression list is not testable.".to_string());
+                    }
+                    let x = expr_list_node.exprs_t.first().unwrap();
+                    x.accept(self);
+                }
+                _ => self.errors.push("TODO".to_string()),
+            }
+            self.add_code(") {");
+            self.indent();
+
+            // bind named capture groups to locals
+            for capture_name in &pattern_node.capture_names {
+                self.newline();
+                self.add_code(&format!(
+                    "let {} = {}.name(\"{}\").map_or(\"\", |m| m.as_str()).to_string();",
+                    self.format_value_name(capture_name),
+                    captures_var,
+                    capture_name
+                ));
+            }
+
+            match_branch_node.accept(self);
+
+            self.generate_return_if_transitioned();
+
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+
+            if_or_else_if = " else if";
+        }
+
+        // (':' regex_test_else_branch)?
+        if let Some(regex_match_else_branch_node) = &regex_match_test_node.else_branch_node_opt {
+            regex_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_regex_match_test_match_branch_node(
+        &mut self,
+        regex_match_test_match_branch_node: &RegexMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&regex_match_test_match_branch_node.statements);
+
+        match &regex_match_test_match_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => {
+                        if let Some(expr_t) = &branch_terminator_expr.return_expr_t_opt {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                        }
+                        self.generate_return();
+                    }
+                    TerminatorType::Continue => {
+                        self.generate_return_if_transitioned();
+                    }
+                }
+            }
+            None => {
+                self.generate_return_if_transitioned();
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_regex_match_test_else_branch_node(
+        &mut self,
+        regex_match_test_else_branch_node: &RegexMatchTestElseBranchNode,
+    ) {
+        self.add_code(" else {");
+        self.indent();
+
+        self.visit_decl_stmts(&regex_match_test_else_branch_node.statements);
+
+        match &regex_match_test_else_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => {
+                        if let Some(expr_t) = &branch_terminator_expr.return_expr_t_opt {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                        }
+                        self.generate_return();
+                    }
+                    TerminatorType::Continue => {
+                        self.generate_return_if_transitioned();
+                    }
+                }
+            }
+            None => {
+                self.generate_return_if_transitioned();
+            }
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        self.newline();
+        self.add_code("match ");
+        match &enum_match_test_node.expr_t {
+            ExprType::CallExprT {
+                call_expr_node: method_call_expr_node,
+            } => method_call_expr_node.accept(self),
+            ExprType::ActionCallExprT {
+                action_call_expr_node,
+            } => action_call_expr_node.accept(self),
+            ExprType::CallChainLiteralExprT {
+                call_chain_expr_node,
+            } => call_chain_expr_node.accept(self),
+            ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+            ExprType::ExprListT { expr_list_node } => {
+                // must be only 1 expression in the list
+                if expr_list_node.exprs_t.len() != 1 {
+                    self.errors
+                        .push("Error - expression list is not testable.".to_string());
+                }
+                let x = expr_list_node.exprs_t.first().unwrap();
+                x.accept(self);
+            }
+            _ => self.errors.push("TODO".to_string()),
+        }
+        self.add_code(" {");
+        self.indent();
+
+        for match_branch_node in &enum_match_test_node.match_branch_nodes {
+            let patterns: Vec<String> = match_branch_node
+                .enumerators
+                .iter()
+                .map(|enumerator| {
+                    format!("{}::{}", enum_match_test_node.enum_type_name, enumerator)
+                })
+                .collect();
+            self.newline();
+            self.add_code(&format!("{} => {{", patterns.join(" | ")));
+            self.indent();
+
+            match_branch_node.accept(self);
+
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+        }
+
+        // (':' enum_test_else_branch)?
+        if let Some(enum_match_else_branch_node) = &enum_match_test_node.else_branch_node_opt {
+            enum_match_else_branch_node.accept(self);
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_match_branch_node(
+        &mut self,
+        enum_match_test_match_branch_node: &EnumMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&enum_match_test_match_branch_node.statements);
+
+        match &enum_match_test_match_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.visit_event_handler_terminator_node(branch_terminator_expr);
+            }
+            None => {
+                self.generate_return_if_transitioned();
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_else_branch_node(
+        &mut self,
+        enum_match_test_else_branch_node: &EnumMatchTestElseBranchNode,
+    ) {
+        self.newline();
+        self.add_code("_ => {");
+        self.indent();
+
+        self.visit_decl_stmts(&enum_match_test_else_branch_node.statements);
+
+        match &enum_match_test_else_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.visit_event_handler_terminator_node(branch_terminator_expr);
+            }
+            None => {
+                self.generate_return_if_transitioned();
+            }
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_string_match_test_pattern_node(
         &mut self,
         _string_match_test_else_branch_node: &StringMatchTestPatternNode,
@@ -5205,10 +7736,7 @@ impl AstVisitor for RustVisitor {
                 if literal_expression_node.is_reference {
                     self.add_code("&");
                 }
-                self.add_code(&format!(
-                    "String::from(\"{}\")",
-                    literal_expression_node.value
-                ));
+                self.add_code(&self.format_string_literal(&literal_expression_node.value));
             }
             TokenType::True => self.add_code("true"),
             TokenType::False => self.add_code("false"),
@@ -5234,10 +7762,7 @@ impl AstVisitor for RustVisitor {
         match &literal_expression_node.token_t {
             TokenType::Number => output.push_str(&literal_expression_node.value.to_string()),
             TokenType::String => {
-                output.push_str(&format!(
-                    "String::from(\"{}\")",
-                    literal_expression_node.value
-                ));
+                output.push_str(&self.format_string_literal(&literal_expression_node.value));
             }
             TokenType::True => {
                 output.push_str("true");
@@ -5297,6 +7822,37 @@ impl AstVisitor for RustVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.newline();
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(expr_t) => {
+                self.add_code("while ");
+                expr_t.accept(self);
+                self.add_code(" {");
+            }
+            None => self.add_code("loop {"),
+        }
+        self.indent();
+
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_loop_control_stmt_node(&mut self, loop_control_stmt_node: &LoopControlStmtNode) {
+        self.newline();
+        match loop_control_stmt_node.loop_control_t {
+            LoopControlType::Break => self.add_code("break;"),
+            LoopControlType::Continue => self.add_code("continue;"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_state_stack_operation_statement_node(
         &mut self,
         state_stack_op_statement_node: &StateStackOperationStatementNode,
@@ -5311,7 +7867,7 @@ impl AstVisitor for RustVisitor {
                     "self.{}();",
                     self.config.code.state_stack_push_method_name
                 ));
-                if self.generate_state_context {
+                if self.generate_state_context && !self.config.features.no_std {
                     self.newline();
                     self.generate_this_state_context();
                 }
@@ -5354,10 +7910,9 @@ impl AstVisitor for RustVisitor {
                 self.config.code.frame_event_variable_name
             )),
             FrameEventPart::Message { is_reference } => self.add_code(&format!(
-                "{}{}.{}.to_string()",
+                "{}{}",
                 if *is_reference { "&" } else { "" },
-                self.config.code.frame_event_variable_name,
-                self.config.code.frame_event_message_attribute_name
+                self.format_frame_event_message_expr(),
             )),
             // FrameEventPart::Param {param_tok} => self.add_code(&format!("{}._parameters[\"{}\"]"
             //                                                             ,self.config.code.frame_event_variable_name
@@ -5365,6 +7920,14 @@ impl AstVisitor for RustVisitor {
                 param_tok,
                 is_reference,
             } => {
+                if self.config.features.typed_events {
+                    self.add_code(&format!(
+                        "{}{}.clone()",
+                        if *is_reference { "&" } else { "" },
+                        self.format_value_name(&param_tok.lexeme)
+                    ));
+                    return;
+                }
                 let event_name = self.format_event_type_name(&self.current_message);
                 self.add_code(&format!(
                     "{}{}.{}.{}().{}",
@@ -5407,15 +7970,22 @@ impl AstVisitor for RustVisitor {
                 self.config.code.frame_event_variable_name
             )),
             FrameEventPart::Message { is_reference } => output.push_str(&format!(
-                "{}{}.{}.to_string()",
+                "{}{}",
                 if *is_reference { "&" } else { "" },
-                self.config.code.frame_event_variable_name,
-                self.config.code.frame_event_message_attribute_name
+                self.format_frame_event_message_expr(),
             )),
             FrameEventPart::Param {
                 param_tok,
                 is_reference,
             } => {
+                if self.config.features.typed_events {
+                    output.push_str(&format!(
+                        "{}{}.clone()",
+                        if *is_reference { "&" } else { "" },
+                        self.format_value_name(&param_tok.lexeme)
+                    ));
+                    return;
+                }
                 let event_name = self.format_event_type_name(&self.current_message);
                 output.push_str(&format!(
                     "{}{}.{}.{}().{}",
@@ -5445,7 +8015,12 @@ impl AstVisitor for RustVisitor {
         //        self.newline_to_string(&mut subclass_code);
 
         let action_name = self.format_action_name(&action_decl_node.name);
-        self.add_code(&format!("fn {}(&self", action_name));
+        self.add_code(&format!(
+            "{}fn {}({}",
+            self.async_qualifier(),
+            action_name,
+            self.action_receiver()
+        ));
         //        subclass_code.push_str(&format!("fn {}(",action_name));
 
         match &action_decl_node.params {
@@ -5458,13 +8033,7 @@ impl AstVisitor for RustVisitor {
         // self.subclass_code.push(subclass_code);
 
         self.add_code(")");
-        match &action_decl_node.type_opt {
-            Some(ret_type) => {
-                self.add_code(&format!(" -> {}", ret_type.get_type_str()));
-            }
-            None => {}
-        };
-
+        self.add_code(&self.action_return_type(action_decl_node));
         self.add_code(";");
     }
 
@@ -5477,7 +8046,11 @@ impl AstVisitor for RustVisitor {
         //        self.newline_to_string(&mut subclass_code);
 
         let action_name = self.format_action_name(&action_node.name);
-        self.add_code(&format!("fn {}(&self", action_name));
+        self.add_code(&format!(
+            "{}fn {}(&self",
+            self.async_qualifier(),
+            action_name
+        ));
         //        subclass_code.push_str(&format!("fn {}(",action_name));
 
         match &action_node.params {
@@ -5490,13 +8063,7 @@ impl AstVisitor for RustVisitor {
         // self.subclass_code.push(subclass_code);
 
         self.add_code(")");
-        match &action_node.type_opt {
-            Some(ret_type) => {
-                self.add_code(&format!(" -> {}", ret_type.get_type_str()));
-            }
-            None => {}
-        };
-
+        self.add_code(&self.action_return_type(action_node));
         self.add_code(" {");
 
         match &action_node.code_opt {
@@ -5519,7 +8086,12 @@ impl AstVisitor for RustVisitor {
         };
         let var_name = self.format_value_name(&variable_decl_node.name);
         self.newline();
-        self.add_code(&format!("{}: {},", var_name, var_type));
+        self.add_code(&format!(
+            "{}{}: {},",
+            self.internal_visibility(),
+            var_name,
+            var_type
+        ));
 
         // currently unused serialization code
         // self.serialize.push(format!("\tbag.domain[\"{}\"] = {};",var_name,var_name));
@@ -5538,6 +8110,12 @@ impl AstVisitor for RustVisitor {
         self.newline();
         let mut code = String::new();
         var_init_expr.accept_to_string(self, &mut code);
+        if !variable_decl_node.is_constant {
+            self.local_var_scope
+                .push((variable_decl_node.name.clone(), self.local_var_decls.len()));
+            self.local_var_decls
+                .push((self.code.len() + "let ".len(), false));
+        }
         self.add_code(&format!("let {}{} = {};", var_name, var_type, code));
 
         // currently unused serialization code
@@ -5711,6 +8289,27 @@ impl AstVisitor for RustVisitor {
 
     //* --------------------------------------------------------------------- *//
 
+    fn visit_enumerator_expr_node(&mut self, enumerator_expr_node: &EnumeratorExprNode) {
+        let mut output = String::new();
+        self.visit_enumerator_expr_node_to_string(enumerator_expr_node, &mut output);
+        self.add_code(&output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}::{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
     fn visit_binary_expr_node_to_string(
         &mut self,
         binary_expr_node: &BinaryExprNode,
@@ -0,0 +1,789 @@
+use crate::frame_c::ast::*;
+use crate::frame_c::scanner::TokenType;
+use crate::frame_c::utils::SystemHierarchy;
+use crate::frame_c::visitors::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+fn escape_text(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn escape_attribute(s: &str) -> String {
+    escape_text(s).replace('"', "&quot;")
+}
+
+/// A transition found in an event handler. A transition without a target
+/// only runs its actions.
+struct ScxmlTransition {
+    target_opt: Option<String>,
+    cond_opt: Option<String>,
+    is_stack_pop: bool,
+    actions: Vec<String>,
+}
+
+/// The actions and transitions of one event handler.
+struct ScxmlHandler {
+    event: String,
+    actions: Vec<String>,
+    transitions: Vec<ScxmlTransition>,
+}
+
+/// Generates a W3C SCXML document from a Frame system. States nest the same
+/// way they do in the Frame hierarchy, action calls become `<script>`
+/// placeholders, and tests around a transition become its `cond`.
+pub struct ScxmlVisitor {
+    compiler_version: &'static str,
+    system_hierarchy: SystemHierarchy,
+    state_nodes: HashMap<String, Rc<RefCell<StateNode>>>,
+    code: String,
+    dent: usize,
+    // Conditions of the tests enclosing the statement being visited.
+    conds: Vec<String>,
+    actions: Vec<String>,
+    transitions: Vec<ScxmlTransition>,
+}
+
+impl ScxmlVisitor {
+    pub fn new(compiler_version: &'static str, system_hierarchy: SystemHierarchy) -> ScxmlVisitor {
+        ScxmlVisitor {
+            compiler_version,
+            system_hierarchy,
+            state_nodes: HashMap::new(),
+            code: String::new(),
+            dent: 0,
+            conds: Vec::new(),
+            actions: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+
+    pub fn run(&mut self, system_node: &SystemNode) {
+        system_node.accept(self);
+    }
+
+    pub fn get_code(&self) -> String {
+        self.code.clone()
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn add_code(&mut self, s: &str) {
+        self.code.push_str(s);
+    }
+
+    fn newline(&mut self) {
+        self.code.push('\n');
+        self.code.push_str(&"  ".repeat(self.dent));
+    }
+
+    fn indent(&mut self) {
+        self.dent += 1;
+    }
+
+    fn outdent(&mut self) {
+        self.dent -= 1;
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_expr(&mut self, expr_t: &ExprType) -> String {
+        let mut output = String::new();
+        expr_t.accept_to_string(self, &mut output);
+        output
+    }
+
+    fn negate(condition: &str) -> String {
+        format!("!({})", condition)
+    }
+
+    // Variables become `<data>` elements. Only literal and enumerator
+    // initializers have a target-independent value, so anything else is
+    // left without an `expr`.
+    fn generate_datamodel(&mut self, variables: &[Rc<RefCell<VariableDeclNode>>]) {
+        if variables.is_empty() {
+            return;
+        }
+        self.newline();
+        self.add_code("<datamodel>");
+        self.indent();
+        for variable_decl_node_rcref in variables {
+            let variable_decl_node = variable_decl_node_rcref.borrow();
+            let expr_opt = match &variable_decl_node.initializer_expr_t_opt {
+                Some(ExprType::LiteralExprT { literal_expr_node }) => {
+                    match literal_expr_node.token_t {
+                        TokenType::Number
+                        | TokenType::String
+                        | TokenType::True
+                        | TokenType::False => {
+                            let mut output = String::new();
+                            self.visit_literal_expression_node_to_string(
+                                literal_expr_node,
+                                &mut output,
+                            );
+                            Some(output)
+                        }
+                        _ => None,
+                    }
+                }
+                Some(ExprType::EnumeratorExprT {
+                    enumerator_expr_node,
+                }) => Some(format!("{:?}", enumerator_expr_node.enumerator)),
+                _ => None,
+            };
+            self.newline();
+            match expr_opt {
+                Some(expr) => self.add_code(&format!(
+                    "<data id=\"{}\" expr=\"{}\"/>",
+                    variable_decl_node.name,
+                    escape_attribute(&expr)
+                )),
+                None => self.add_code(&format!("<data id=\"{}\"/>", variable_decl_node.name)),
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("</datamodel>");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_decl_stmts(&mut self, decl_stmt_types: &[DeclOrStmtType]) {
+        for decl_stmt_t in decl_stmt_types.iter() {
+            match decl_stmt_t {
+                DeclOrStmtType::VarDeclT { .. } => {}
+                DeclOrStmtType::StmtT { stmt_t } => match stmt_t {
+                    StatementType::ExpressionStmt { expr_stmt_t } => {
+                        self.collect_action(expr_stmt_t);
+                    }
+                    StatementType::TransitionStmt {
+                        transition_statement,
+                    } => {
+                        transition_statement.accept(self);
+                    }
+                    StatementType::TestStmt { test_stmt_node } => {
+                        test_stmt_node.accept(self);
+                    }
+                    StatementType::StateStackStmt { .. } => {}
+                    StatementType::ChangeStateStmt { change_state_stmt } => {
+                        change_state_stmt.accept(self);
+                    }
+                    StatementType::LoopStmt { loop_stmt_node } => {
+                        loop_stmt_node.accept(self);
+                    }
+                    StatementType::LoopControlStmt { .. } => {}
+                    StatementType::NoStmt => {}
+                },
+            }
+        }
+    }
+
+    // Action calls are kept as script placeholders; the SCXML author
+    // replaces them with real executable content.
+    fn collect_action(&mut self, expr_stmt_t: &ExprStmtType) {
+        let mut output = String::new();
+        match expr_stmt_t {
+            ExprStmtType::ActionCallStmtT {
+                action_call_stmt_node,
+            } => self.visit_action_call_expression_node_to_string(
+                &action_call_stmt_node.action_call_expr_node,
+                &mut output,
+            ),
+            ExprStmtType::CallStmtT { call_stmt_node } => self
+                .visit_call_expression_node_to_string(&call_stmt_node.call_expr_node, &mut output),
+            ExprStmtType::CallChainLiteralStmtT {
+                call_chain_literal_stmt_node,
+            } => self.visit_call_chain_literal_expr_node_to_string(
+                &call_chain_literal_stmt_node.call_chain_literal_expr_node,
+                &mut output,
+            ),
+            _ => {}
+        }
+        if !output.is_empty() {
+            self.actions.push(output);
+        }
+    }
+
+    fn add_transition(&mut self, target_opt: Option<String>, is_stack_pop: bool) {
+        let cond_opt = if self.conds.is_empty() {
+            None
+        } else {
+            let conds: Vec<String> = self
+                .conds
+                .iter()
+                .map(|cond| {
+                    if self.conds.len() > 1 && cond.contains("||") {
+                        format!("({})", cond)
+                    } else {
+                        cond.clone()
+                    }
+                })
+                .collect();
+            Some(conds.join(" && "))
+        };
+        self.transitions.push(ScxmlTransition {
+            target_opt,
+            cond_opt,
+            is_stack_pop,
+            actions: self.actions.clone(),
+        });
+    }
+
+    fn add_state_context_transition(&mut self, state_context_t: &StateContextType) {
+        match state_context_t {
+            StateContextType::StateRef { state_context_node } => {
+                self.add_transition(Some(state_context_node.state_ref_node.name.clone()), false);
+            }
+            // SCXML history states only remember the children of one
+            // compound state, so the state stack has no equivalent.
+            StateContextType::StateStackPop {} => {
+                self.add_transition(None, true);
+            }
+        }
+    }
+
+    // Visit a test branch with its condition pushed on the cond stack.
+    fn visit_conditional_branch(&mut self, cond: String, statements: &[DeclOrStmtType]) {
+        self.conds.push(cond);
+        self.visit_decl_stmts(statements);
+        self.conds.pop();
+    }
+
+    fn visit_else_branch(&mut self, conditions: &[String], statements: &[DeclOrStmtType]) {
+        let negated: Vec<String> = conditions.iter().map(|c| ScxmlVisitor::negate(c)).collect();
+        self.visit_conditional_branch(negated.join(" && "), statements);
+    }
+
+    fn collect_handler(&mut self, evt_handler_node: &EventHandlerNode) -> ScxmlHandler {
+        let event = match &evt_handler_node.msg_t {
+            MessageType::CustomMessage { message_node } => message_node.name.clone(),
+            MessageType::AnyMessage { .. } => String::from("*"),
+        };
+        self.actions.clear();
+        self.transitions.clear();
+        self.conds.clear();
+        self.visit_decl_stmts(&evt_handler_node.statements);
+        ScxmlHandler {
+            event,
+            actions: std::mem::take(&mut self.actions),
+            transitions: std::mem::take(&mut self.transitions),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_actions(&mut self, actions: &[String]) {
+        for action in actions {
+            self.newline();
+            self.add_code(&format!("<script>{}</script>", escape_text(action)));
+        }
+    }
+
+    fn generate_transition(&mut self, event_opt: Option<&str>, transition: &ScxmlTransition) {
+        let mut attributes = String::new();
+        if let Some(event) = event_opt {
+            attributes.push_str(&format!(" event=\"{}\"", escape_attribute(event)));
+        }
+        if let Some(cond) = &transition.cond_opt {
+            attributes.push_str(&format!(" cond=\"{}\"", escape_attribute(cond)));
+        }
+        if let Some(target) = &transition.target_opt {
+            attributes.push_str(&format!(" target=\"{}\"", target));
+        }
+        self.newline();
+        if transition.actions.is_empty() && !transition.is_stack_pop {
+            self.add_code(&format!("<transition{}/>", attributes));
+        } else {
+            self.add_code(&format!("<transition{}>", attributes));
+            self.indent();
+            self.generate_actions(&transition.actions);
+            if transition.is_stack_pop {
+                self.newline();
+                self.add_code("<!-- -> $$[-] -->");
+            }
+            self.outdent();
+            self.newline();
+            self.add_code("</transition>");
+        }
+    }
+
+    fn generate_state(&mut self, state_name: &str) {
+        let state_node_rcref = Rc::clone(&self.state_nodes[state_name]);
+        let children = match self.system_hierarchy.get_node(state_name) {
+            Some(node) => node.children.clone(),
+            None => Vec::new(),
+        };
+
+        let mut enter_handler_opt = None;
+        let mut exit_handler_opt = None;
+        let mut event_handlers = Vec::new();
+        for evt_handler_rcref in &state_node_rcref.borrow().evt_handlers_rcref {
+            let handler = self.collect_handler(&evt_handler_rcref.borrow());
+            match handler.event.as_str() {
+                ">" => enter_handler_opt = Some(handler),
+                "<" => exit_handler_opt = Some(handler),
+                _ => event_handlers.push(handler),
+            }
+        }
+
+        self.newline();
+        self.add_code(&format!("<state id=\"{}\">", state_name));
+        self.indent();
+
+        if let Some(vars) = &state_node_rcref.borrow().vars_opt {
+            self.generate_datamodel(vars);
+        }
+
+        if let Some(enter_handler) = &enter_handler_opt {
+            if !enter_handler.actions.is_empty() {
+                self.newline();
+                self.add_code("<onentry>");
+                self.indent();
+                self.generate_actions(&enter_handler.actions);
+                self.outdent();
+                self.newline();
+                self.add_code("</onentry>");
+            }
+        }
+
+        if let Some(exit_handler) = &exit_handler_opt {
+            if !exit_handler.actions.is_empty() {
+                self.newline();
+                self.add_code("<onexit>");
+                self.indent();
+                self.generate_actions(&exit_handler.actions);
+                self.outdent();
+                self.newline();
+                self.add_code("</onexit>");
+            }
+        }
+
+        // transitions out of an enter handler are eventless
+        if let Some(enter_handler) = &enter_handler_opt {
+            for transition in &enter_handler.transitions {
+                self.generate_transition(None, transition);
+            }
+        }
+
+        for handler in &event_handlers {
+            if handler.transitions.is_empty() {
+                let transition = ScxmlTransition {
+                    target_opt: None,
+                    cond_opt: None,
+                    is_stack_pop: false,
+                    actions: handler.actions.clone(),
+                };
+                self.generate_transition(Some(&handler.event), &transition);
+            } else {
+                for transition in &handler.transitions {
+                    self.generate_transition(Some(&handler.event), transition);
+                }
+            }
+        }
+
+        for child in &children {
+            self.generate_state(child);
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("</state>");
+    }
+}
+
+//* --------------------------------------------------------------------- *//
+
+impl AstVisitor for ScxmlVisitor {
+    fn visit_system_node(&mut self, system_node: &SystemNode) {
+        self.add_code("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        self.newline();
+        self.add_code(&format!("<!-- {} -->", self.compiler_version));
+        self.newline();
+        self.add_code(&format!(
+            "<scxml xmlns=\"http://www.w3.org/2005/07/scxml\" version=\"1.0\" name=\"{}\"",
+            system_node.name
+        ));
+        if let Some(first_state) = system_node.get_first_state() {
+            self.add_code(&format!(" initial=\"{}\"", first_state.borrow().name));
+        }
+        self.add_code(">");
+        self.indent();
+
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            self.generate_datamodel(&domain_block_node.member_variables);
+        }
+
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            machine_block_node.accept(self);
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("</scxml>");
+        self.newline();
+    }
+
+    fn visit_machine_block_node(&mut self, machine_block_node: &MachineBlockNode) {
+        for state_node_rcref in &machine_block_node.states {
+            let name = state_node_rcref.borrow().name.clone();
+            self.state_nodes.insert(name, Rc::clone(state_node_rcref));
+        }
+
+        let system_name = self.system_hierarchy.system_name.clone();
+        let top_level_states = match self.system_hierarchy.get_node(&system_name) {
+            Some(node) => node.children.clone(),
+            None => Vec::new(),
+        };
+        for state_name in &top_level_states {
+            self.generate_state(state_name);
+        }
+    }
+
+    fn visit_action_node_rust_trait(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_action_node_rust_trait() not implemented.");
+    }
+
+    fn visit_actions_node_rust_impl(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_actions_node_rust_impl() not implemented.");
+    }
+
+    fn visit_transition_statement_node(&mut self, transition_statement: &TransitionStatementNode) {
+        self.add_state_context_transition(&transition_statement.target_state_context_t);
+    }
+
+    fn visit_change_state_statement_node(
+        &mut self,
+        change_state_stmt_node: &ChangeStateStatementNode,
+    ) {
+        self.add_state_context_transition(&change_state_stmt_node.state_context_t);
+    }
+
+    fn visit_test_statement_node(&mut self, test_stmt_node: &TestStatementNode) {
+        match &test_stmt_node.test_t {
+            TestType::BoolTest { bool_test_node } => {
+                bool_test_node.accept(self);
+            }
+            TestType::StringMatchTest {
+                string_match_test_node,
+            } => {
+                string_match_test_node.accept(self);
+            }
+            TestType::NumberMatchTest {
+                number_match_test_node,
+            } => {
+                number_match_test_node.accept(self);
+            }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                regex_match_test_node.accept(self);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
+            }
+        }
+    }
+
+    fn visit_bool_test_node(&mut self, bool_test_node: &BoolTestNode) {
+        let mut conditions = Vec::new();
+        for branch_node in &bool_test_node.conditional_branch_nodes {
+            let mut condition = self.format_expr(&branch_node.expr_t);
+            if branch_node.is_negated {
+                condition = ScxmlVisitor::negate(&condition);
+            }
+            self.visit_conditional_branch(condition.clone(), &branch_node.statements);
+            conditions.push(condition);
+        }
+        if let Some(else_branch_node) = &bool_test_node.else_branch_node_opt {
+            self.visit_else_branch(&conditions, &else_branch_node.statements);
+        }
+    }
+
+    fn visit_string_match_test_node(&mut self, string_match_test_node: &StringMatchTestNode) {
+        let subject = self.format_expr(&string_match_test_node.expr_t);
+        let mut conditions = Vec::new();
+        for branch_node in &string_match_test_node.match_branch_nodes {
+            let patterns: Vec<String> = branch_node
+                .string_match_pattern_node
+                .match_pattern_strings
+                .iter()
+                .map(|pattern| format!("{} == {:?}", subject, pattern))
+                .collect();
+            let condition = patterns.join(" || ");
+            self.visit_conditional_branch(condition.clone(), &branch_node.statements);
+            conditions.push(condition);
+        }
+        if let Some(else_branch_node) = &string_match_test_node.else_branch_node_opt {
+            self.visit_else_branch(&conditions, &else_branch_node.statements);
+        }
+    }
+
+    fn visit_number_match_test_node(&mut self, number_match_test_node: &NumberMatchTestNode) {
+        let subject = self.format_expr(&number_match_test_node.expr_t);
+        let mut conditions = Vec::new();
+        for branch_node in &number_match_test_node.match_branch_nodes {
+            let patterns: Vec<String> = branch_node
+                .number_match_pattern_nodes
+                .iter()
+                .map(|pattern| format!("{} == {}", subject, pattern.match_pattern_number))
+                .collect();
+            let condition = patterns.join(" || ");
+            self.visit_conditional_branch(condition.clone(), &branch_node.statements);
+            conditions.push(condition);
+        }
+        if let Some(else_branch_node) = &number_match_test_node.else_branch_node_opt {
+            self.visit_else_branch(&conditions, &else_branch_node.statements);
+        }
+    }
+
+    fn visit_regex_match_test_node(&mut self, regex_match_test_node: &RegexMatchTestNode) {
+        let subject = self.format_expr(&regex_match_test_node.expr_t);
+        let mut conditions = Vec::new();
+        for branch_node in &regex_match_test_node.match_branch_nodes {
+            let condition = format!(
+                "/{}/.test({})",
+                branch_node.regex_match_pattern_node.pattern, subject
+            );
+            self.visit_conditional_branch(condition.clone(), &branch_node.statements);
+            conditions.push(condition);
+        }
+        if let Some(else_branch_node) = &regex_match_test_node.else_branch_node_opt {
+            self.visit_else_branch(&conditions, &else_branch_node.statements);
+        }
+    }
+
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        let subject = self.format_expr(&enum_match_test_node.expr_t);
+        let mut conditions = Vec::new();
+        for branch_node in &enum_match_test_node.match_branch_nodes {
+            let patterns: Vec<String> = branch_node
+                .enumerators
+                .iter()
+                .map(|enumerator| {
+                    format!(
+                        "{} == {}.{}",
+                        subject, enum_match_test_node.enum_type_name, enumerator
+                    )
+                })
+                .collect();
+            let condition = patterns.join(" || ");
+            self.visit_conditional_branch(condition.clone(), &branch_node.statements);
+            conditions.push(condition);
+        }
+        if let Some(else_branch_node) = &enum_match_test_node.else_branch_node_opt {
+            self.visit_else_branch(&conditions, &else_branch_node.statements);
+        }
+    }
+
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(condition_expr_t) => {
+                let condition = self.format_expr(condition_expr_t);
+                self.visit_conditional_branch(condition, &loop_stmt_node.statements);
+            }
+            None => self.visit_decl_stmts(&loop_stmt_node.statements),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Conditions and script placeholders are written in the ECMAScript
+    // form SCXML's default data model expects.
+
+    fn visit_expression_list_node_to_string(
+        &mut self,
+        expr_list: &ExprListNode,
+        output: &mut String,
+    ) {
+        let exprs: Vec<String> = expr_list
+            .exprs_t
+            .iter()
+            .map(|expr_t| self.format_expr(expr_t))
+            .collect();
+        output.push_str(&format!("({})", exprs.join(", ")));
+    }
+
+    fn visit_call_expr_list_node_to_string(
+        &mut self,
+        call_expr_list: &CallExprListNode,
+        output: &mut String,
+    ) {
+        let exprs: Vec<String> = call_expr_list
+            .exprs_t
+            .iter()
+            .map(|expr_t| self.format_expr(expr_t))
+            .collect();
+        output.push_str(&format!("({})", exprs.join(", ")));
+    }
+
+    fn visit_call_expression_node_to_string(
+        &mut self,
+        method_call: &CallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&method_call.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(&method_call.call_expr_list, output);
+    }
+
+    fn visit_action_call_expression_node_to_string(
+        &mut self,
+        action_call: &ActionCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&action_call.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(&action_call.call_expr_list, output);
+    }
+
+    fn visit_interface_method_call_expression_node_to_string(
+        &mut self,
+        interface_method_call_expr_node: &InterfaceMethodCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&interface_method_call_expr_node.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(
+            &interface_method_call_expr_node.call_expr_list,
+            output,
+        );
+    }
+
+    fn visit_call_chain_literal_expr_node_to_string(
+        &mut self,
+        method_call_chain_expression_node: &CallChainLiteralExprNode,
+        output: &mut String,
+    ) {
+        let mut separator = "";
+        for node in &method_call_chain_expression_node.call_chain {
+            output.push_str(separator);
+            match node {
+                CallChainLiteralNodeType::VariableNodeT { var_node } => {
+                    output.push_str(&var_node.id_node.name.lexeme);
+                }
+                CallChainLiteralNodeType::IdentifierNodeT { id_node } => {
+                    output.push_str(&id_node.name.lexeme);
+                }
+                CallChainLiteralNodeType::CallT { call } => {
+                    self.visit_call_expression_node_to_string(call, output);
+                }
+                CallChainLiteralNodeType::InterfaceMethodCallT {
+                    interface_method_call_expr_node,
+                } => {
+                    self.visit_interface_method_call_expression_node_to_string(
+                        interface_method_call_expr_node,
+                        output,
+                    );
+                }
+                CallChainLiteralNodeType::ActionCallT {
+                    action_call_expr_node,
+                } => {
+                    self.visit_action_call_expression_node_to_string(action_call_expr_node, output);
+                }
+            }
+            separator = ".";
+        }
+    }
+
+    fn visit_literal_expression_node_to_string(
+        &mut self,
+        literal_expression_node: &LiteralExprNode,
+        output: &mut String,
+    ) {
+        match &literal_expression_node.token_t {
+            TokenType::String => {
+                output.push_str(&format!("\"{}\"", literal_expression_node.value));
+            }
+            TokenType::True => output.push_str("true"),
+            TokenType::False => output.push_str("false"),
+            TokenType::Nil | TokenType::Null => output.push_str("null"),
+            _ => output.push_str(&literal_expression_node.value),
+        }
+    }
+
+    fn visit_variable_expr_node_to_string(
+        &mut self,
+        variable_node: &VariableNode,
+        output: &mut String,
+    ) {
+        output.push_str(&variable_node.id_node.name.lexeme);
+    }
+
+    fn visit_frame_event_part_to_string(
+        &mut self,
+        frame_event_part: &FrameEventPart,
+        output: &mut String,
+    ) {
+        match frame_event_part {
+            FrameEventPart::Event { .. } => output.push('@'),
+            FrameEventPart::Message { .. } => output.push_str("@||"),
+            FrameEventPart::Param { param_tok, .. } => {
+                output.push_str(&format!("@[{}]", param_tok.lexeme))
+            }
+            FrameEventPart::Return { .. } => output.push_str("@^"),
+        }
+    }
+
+    fn visit_unary_expr_node_to_string(
+        &mut self,
+        unary_expr_node: &UnaryExprNode,
+        output: &mut String,
+    ) {
+        unary_expr_node.operator.accept_to_string(self, output);
+        unary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_binary_expr_node_to_string(
+        &mut self,
+        binary_expr_node: &BinaryExprNode,
+        output: &mut String,
+    ) {
+        binary_expr_node
+            .left_rcref
+            .borrow()
+            .accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node.operator.accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}.{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    fn visit_operator_type_to_string(&mut self, operator_type: &OperatorType, output: &mut String) {
+        let operator = match operator_type {
+            OperatorType::Plus => "+",
+            OperatorType::Minus | OperatorType::Negated => "-",
+            OperatorType::Multiply => "*",
+            OperatorType::Divide => "/",
+            OperatorType::Greater => ">",
+            OperatorType::GreaterEqual => ">=",
+            OperatorType::Less => "<",
+            OperatorType::LessEqual => "<=",
+            OperatorType::EqualEqual => "==",
+            OperatorType::NotEqual => "!=",
+            OperatorType::Not => "!",
+            OperatorType::LogicalAnd => "&&",
+            OperatorType::LogicalOr => "||",
+            OperatorType::LogicalXor => "^",
+        };
+        output.push_str(operator);
+    }
+}
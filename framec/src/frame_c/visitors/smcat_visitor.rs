@@ -100,6 +100,10 @@ impl SmcatVisitor {
                         StatementType::ChangeStateStmt { change_state_stmt } => {
                             change_state_stmt.accept(self);
                         }
+                        StatementType::LoopStmt { loop_stmt_node } => {
+                            loop_stmt_node.accept(self);
+                        }
+                        StatementType::LoopControlStmt { .. } => {}
                         StatementType::NoStmt => {}
                     }
                 }
@@ -117,14 +121,15 @@ impl SmcatVisitor {
                 panic!("TODO")
             }
         };
-        let label = match &change_state_stmt.label_opt {
-            Some(label) => {
-                format!("{};", label.clone())
-            }
-            None => {
-                format!("{};", self.transition_msg.clone())
-            }
-        };
+        let label = format!(
+            "{};",
+            format_transition_label(
+                &self.transition_msg,
+                &change_state_stmt.label_opt,
+                &change_state_stmt.guard_opt,
+                str::to_string
+            )
+        );
         self.add_code(&format!(
             "{} => {} {}: {}\n",
             source_state,
@@ -144,14 +149,15 @@ impl SmcatVisitor {
                 panic!("TODO")
             }
         };
-        let label = match &transition_stmt.label_opt {
-            Some(label) => {
-                format!("{};", label.clone())
-            }
-            None => {
-                format!("{};", self.transition_msg.clone())
-            }
-        };
+        let label = format!(
+            "{};",
+            format_transition_label(
+                &self.transition_msg,
+                &transition_stmt.label_opt,
+                &transition_stmt.guard_opt,
+                str::to_string
+            )
+        );
         self.add_code(&format!(
             "{} => {} {}: {}\n",
             source_state,
@@ -168,10 +174,12 @@ impl SmcatVisitor {
         &mut self,
         transition_statement: &TransitionStatementNode,
     ) {
-        let label = match &transition_statement.label_opt {
-            Some(label) => label,
-            None => &self.transition_msg,
-        };
+        let label = format_transition_label(
+            &self.transition_msg,
+            &transition_statement.label_opt,
+            &transition_statement.guard_opt,
+            str::to_string,
+        );
         // .deephistory suffix overrides target state label with H* and sets shape to
         // circle
         let transition = &format!(
@@ -302,6 +310,16 @@ impl AstVisitor for SmcatVisitor {
             } => {
                 number_match_test_node.accept(self);
             }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                regex_match_test_node.accept(self);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
+            }
         }
     }
 
@@ -390,6 +408,66 @@ impl AstVisitor for SmcatVisitor {
 
     //-----------------------------------------------------//
 
+    fn visit_regex_match_test_node(&mut self, regex_match_test_node: &RegexMatchTestNode) {
+        for match_branch_node in &regex_match_test_node.match_branch_nodes {
+            match_branch_node.accept(self);
+        }
+
+        // (':' regex_test_else_branch)?
+        if let Some(regex_match_else_branch_node) = &regex_match_test_node.else_branch_node_opt {
+            regex_match_else_branch_node.accept(self);
+        }
+    }
+
+    fn visit_regex_match_test_match_branch_node(
+        &mut self,
+        regex_match_test_match_branch_node: &RegexMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&regex_match_test_match_branch_node.statements);
+    }
+
+    fn visit_regex_match_test_else_branch_node(
+        &mut self,
+        regex_match_test_else_branch_node: &RegexMatchTestElseBranchNode,
+    ) {
+        self.visit_decl_stmts(&regex_match_test_else_branch_node.statements);
+    }
+
+    //-----------------------------------------------------//
+
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        for match_branch_node in &enum_match_test_node.match_branch_nodes {
+            match_branch_node.accept(self);
+        }
+
+        // (':' enum_test_else_branch)?
+        if let Some(enum_match_else_branch_node) = &enum_match_test_node.else_branch_node_opt {
+            enum_match_else_branch_node.accept(self);
+        }
+    }
+
+    fn visit_enum_match_test_match_branch_node(
+        &mut self,
+        enum_match_test_match_branch_node: &EnumMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&enum_match_test_match_branch_node.statements);
+    }
+
+    fn visit_enum_match_test_else_branch_node(
+        &mut self,
+        enum_match_test_else_branch_node: &EnumMatchTestElseBranchNode,
+    ) {
+        self.visit_decl_stmts(&enum_match_test_else_branch_node.statements);
+    }
+
+    //-----------------------------------------------------//
+
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+    }
+
+    //-----------------------------------------------------//
+
     fn visit_number_match_test_node(&mut self, number_match_test_node: &NumberMatchTestNode) {
         for match_branch_node in &number_match_test_node.match_branch_nodes {
             // self.add_code(&format!("{} (", if_or_else_if));
@@ -0,0 +1,1459 @@
+use crate::frame_c::ast::*;
+use crate::frame_c::scanner::{Token, TokenType};
+use crate::frame_c::symbol_table::*;
+use crate::frame_c::visitors::*;
+use std::collections::HashMap;
+
+/// One `if`/`else if` arm of a generated test.
+struct SwiftBranch<'a> {
+    condition: String,
+    // Statements binding regex captures at the top of the branch.
+    bindings: Vec<String>,
+    statements: &'a [DeclOrStmtType],
+    terminator_opt: &'a Option<TerminatorExpr>,
+}
+
+/// Generates Swift from a Frame system. The system is a final class
+/// driven by a `FrameEvent` class, its states are cases of an enum, and
+/// each state is a method switching on the event message. State arguments
+/// and state variables live in a state context struct and are cast back
+/// to their declared types when read. The `-actions-` block becomes a
+/// protocol passed to the initializer.
+pub struct SwiftVisitor {
+    compiler_version: String,
+    arcanium: Arcanum,
+    symbol_config: SymbolConfig,
+    comments: Vec<Token>,
+    current_comment_idx: usize,
+    code: String,
+    dent: usize,
+    errors: Vec<String>,
+    system_name: String,
+    first_state_name: String,
+    has_states: bool,
+    current_state_name_opt: Option<String>,
+    // Declared types of the parameters of the event handler being visited.
+    current_event_param_types: HashMap<String, Option<String>>,
+    visiting_l_value: bool,
+}
+
+impl SwiftVisitor {
+    pub fn new(arcanium: Arcanum, compiler_version: &str, comments: Vec<Token>) -> SwiftVisitor {
+        SwiftVisitor {
+            compiler_version: compiler_version.to_string(),
+            arcanium,
+            symbol_config: SymbolConfig::new(),
+            comments,
+            current_comment_idx: 0,
+            code: String::new(),
+            dent: 0,
+            errors: Vec::new(),
+            system_name: String::new(),
+            first_state_name: String::new(),
+            has_states: false,
+            current_state_name_opt: None,
+            current_event_param_types: HashMap::new(),
+            visiting_l_value: false,
+        }
+    }
+
+    pub fn run(&mut self, system_node: &SystemNode) {
+        system_node.accept(self);
+    }
+
+    pub fn get_code(&self) -> String {
+        if !self.errors.is_empty() {
+            let mut error_list = String::new();
+            for error in &self.errors {
+                error_list.push_str(error);
+                error_list.push('\n');
+            }
+            error_list
+        } else {
+            // Blank lines are left indented by `newline()`.
+            self.code
+                .lines()
+                .map(|line| line.trim_end())
+                .collect::<Vec<&str>>()
+                .join("\n")
+                + "\n"
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn add_code(&mut self, s: &str) {
+        self.code.push_str(s);
+    }
+
+    fn newline(&mut self) {
+        self.code.push('\n');
+        self.code.push_str(&"    ".repeat(self.dent));
+    }
+
+    fn indent(&mut self) {
+        self.dent += 1;
+    }
+
+    fn outdent(&mut self) {
+        self.dent -= 1;
+    }
+
+    fn generate_comment(&mut self, line: usize) {
+        while self.current_comment_idx < self.comments.len()
+            && line >= self.comments[self.current_comment_idx].line
+        {
+            let comment = self.comments[self.current_comment_idx].lexeme.clone();
+            if self.comments[self.current_comment_idx].token_type == TokenType::SingleLineComment {
+                self.newline();
+                self.add_code(&format!("// {}", comment[3..].trim()));
+            } else {
+                self.newline();
+                self.add_code(&format!("/* {} */", comment[3..comment.len() - 3].trim()));
+            }
+            self.current_comment_idx += 1;
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_type(type_opt: &Option<TypeNode>) -> String {
+        match type_opt {
+            Some(type_node) => type_node.get_type_str(),
+            None => String::from("Any"),
+        }
+    }
+
+    // Parameters are unlabeled so generated calls can pass arguments in
+    // order, the way Frame does.
+    fn format_params(params_opt: &Option<Vec<ParameterNode>>) -> String {
+        match params_opt {
+            Some(params) => params
+                .iter()
+                .map(|param| {
+                    format!(
+                        "_ {}: {}",
+                        param.param_name,
+                        SwiftVisitor::format_type(&param.param_type_opt)
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+            None => String::new(),
+        }
+    }
+
+    // Dictionary literal of name/value pairs, `[:]` when empty.
+    fn format_dictionary(entries: &[(String, String)]) -> String {
+        if entries.is_empty() {
+            return String::from("[:]");
+        }
+        let entries: Vec<String> = entries
+            .iter()
+            .map(|(name, value)| format!("{:?}: {}", name, value))
+            .collect();
+        format!("[{}]", entries.join(", "))
+    }
+
+    fn format_state_type(&self) -> String {
+        format!("{}State", self.system_name)
+    }
+
+    // `$Idle` is the `.idle` case of the state enum.
+    fn format_state_case(state_name: &str) -> String {
+        let mut chars = state_name.chars();
+        match chars.next() {
+            Some(first) => first.to_lowercase().collect::<String>() + chars.as_str(),
+            None => String::new(),
+        }
+    }
+
+    fn format_state_context_type(&self) -> String {
+        format!("{}StateContext", self.system_name)
+    }
+
+    fn format_frame_event_type(&self) -> String {
+        format!("{}FrameEvent", self.system_name)
+    }
+
+    fn format_expr(&mut self, expr_t: &ExprType) -> String {
+        let mut output = String::new();
+        expr_t.accept_to_string(self, &mut output);
+        output
+    }
+
+    fn format_args(&mut self, exprs_t: &[ExprType]) -> String {
+        let args: Vec<String> = exprs_t
+            .iter()
+            .map(|expr_t| self.format_expr(expr_t))
+            .collect();
+        format!("({})", args.join(", "))
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Values in the state context and event dictionaries are `Any`, so
+    // reads are cast to the declared type. Assignments write the
+    // dictionary entry directly.
+
+    fn format_cast(&self, access: String, type_opt: &Option<String>) -> String {
+        if self.visiting_l_value {
+            return access;
+        }
+        match type_opt {
+            Some(var_type) => format!("({} as! {})", access, var_type),
+            None => format!("{}!", access),
+        }
+    }
+
+    // Untyped state variables take the type of a literal initializer.
+    fn infer_type(expr_t_opt: &Option<ExprType>) -> Option<String> {
+        match expr_t_opt {
+            Some(ExprType::LiteralExprT { literal_expr_node }) => match literal_expr_node.token_t {
+                TokenType::Number if literal_expr_node.value.contains('.') => {
+                    Some(String::from("Double"))
+                }
+                TokenType::Number => Some(String::from("Int")),
+                TokenType::String => Some(String::from("String")),
+                TokenType::True | TokenType::False => Some(String::from("Bool")),
+                _ => None,
+            },
+            Some(ExprType::EnumeratorExprT {
+                enumerator_expr_node,
+            }) => Some(enumerator_expr_node.enum_type.clone()),
+            _ => None,
+        }
+    }
+
+    fn get_variable_type(symbol_type: &SymbolType) -> Option<String> {
+        match symbol_type {
+            SymbolType::StateParam {
+                state_param_symbol_rcref,
+            } => state_param_symbol_rcref
+                .borrow()
+                .param_type_opt
+                .as_ref()
+                .map(|type_node| type_node.get_type_str()),
+            SymbolType::StateVariable {
+                state_variable_symbol_rcref,
+            } => {
+                let variable_symbol = state_variable_symbol_rcref.borrow();
+                match (&variable_symbol.var_type, &variable_symbol.ast_node) {
+                    (Some(type_node), _) => Some(type_node.get_type_str()),
+                    (None, Some(ast_node)) => {
+                        SwiftVisitor::infer_type(&ast_node.borrow().initializer_expr_t_opt)
+                    }
+                    (None, None) => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn format_event_param(&self, name: &str) -> String {
+        let param_type = self
+            .current_event_param_types
+            .get(name)
+            .cloned()
+            .unwrap_or(None);
+        self.format_cast(format!("e.parameters[{:?}]", name), &param_type)
+    }
+
+    fn format_variable_expr(&self, variable_node: &VariableNode) -> String {
+        let name = &variable_node.id_node.name.lexeme;
+        match variable_node.scope {
+            IdentifierDeclScope::StateParam | IdentifierDeclScope::StateVar => {
+                let dictionary = if variable_node.scope == IdentifierDeclScope::StateParam {
+                    "stateArgs"
+                } else {
+                    "stateVars"
+                };
+                let var_type = match &variable_node.symbol_type_rcref_opt {
+                    Some(symbol_type_rcref) => {
+                        SwiftVisitor::get_variable_type(&symbol_type_rcref.borrow())
+                    }
+                    None => None,
+                };
+                self.format_cast(
+                    format!("stateContext.{}[{:?}]", dictionary, name),
+                    &var_type,
+                )
+            }
+            IdentifierDeclScope::EventHandlerParam => self.format_event_param(name),
+            _ => name.clone(),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_enum(&mut self, enum_decl_node: &EnumDeclNode) {
+        self.add_code(&format!("enum {} {{", enum_decl_node.name));
+        self.indent();
+        self.newline();
+        self.add_code(&format!("case {}", enum_decl_node.enumerators.join(", ")));
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_frame_event_type(&mut self) {
+        self.add_code(&format!(
+            "final class {} {{",
+            self.format_frame_event_type()
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("let message: String");
+        self.newline();
+        self.add_code("let parameters: [String: Any]");
+        self.newline();
+        self.add_code("var ret: Any?");
+        self.newline();
+        self.newline();
+        self.add_code("init(message: String, parameters: [String: Any] = [:]) {");
+        self.indent();
+        self.newline();
+        self.add_code("self.message = message");
+        self.newline();
+        self.add_code("self.parameters = parameters");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_actions_protocol(&mut self, system_node: &SystemNode) {
+        self.add_code(&format!("protocol {}Actions {{", self.system_name));
+        self.indent();
+        if let Some(actions_block_node) = &system_node.actions_block_node_opt {
+            for action_rcref in &actions_block_node.actions {
+                let action_node = action_rcref.borrow();
+                let return_type = match &action_node.type_opt {
+                    Some(type_node) => format!(" -> {}", type_node.get_type_str()),
+                    None => String::new(),
+                };
+                self.newline();
+                self.add_code(&format!(
+                    "func {}({}){}",
+                    action_node.name,
+                    SwiftVisitor::format_params(&action_node.params),
+                    return_type
+                ));
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_state_types(&mut self, system_node: &SystemNode) {
+        self.add_code(&format!("enum {} {{", self.format_state_type()));
+        self.indent();
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                self.newline();
+                self.add_code(&format!(
+                    "case {}",
+                    SwiftVisitor::format_state_case(&state_node_rcref.borrow().name)
+                ));
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+
+        self.add_code(&format!("struct {} {{", self.format_state_context_type()));
+        self.indent();
+        self.newline();
+        self.add_code(&format!("let state: {}", self.format_state_type()));
+        self.newline();
+        self.add_code("var stateArgs: [String: Any] = [:]");
+        self.newline();
+        self.add_code("var stateVars: [String: Any] = [:]");
+        self.newline();
+        self.add_code("var enterArgs: [String: Any] = [:]");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+    }
+
+    fn generate_initializer(&mut self) {
+        self.newline();
+        self.add_code(&format!("init(actions: {}Actions) {{", self.system_name));
+        self.indent();
+        self.newline();
+        self.add_code("self.actions = actions");
+        if self.has_states {
+            let first_state_name = self.first_state_name.clone();
+            let state_context = self.format_new_state_context(&first_state_name, &None);
+            self.newline();
+            self.add_code(&format!("stateContext = {}", state_context));
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        if self.has_states {
+            self.newline();
+            self.add_code(&format!(
+                "var state: {} {{ stateContext.state }}",
+                self.format_state_type()
+            ));
+            self.newline();
+        }
+    }
+
+    fn format_state_vars(&mut self, state_name: &str) -> Vec<(String, String)> {
+        let mut state_vars = Vec::new();
+        if let Some(state_symbol_rcref) = self.arcanium.get_state(state_name) {
+            let state_symbol = state_symbol_rcref.borrow();
+            let state_node = state_symbol.state_node.as_ref().unwrap().borrow();
+            if let Some(vars) = &state_node.vars_opt {
+                for var_rcref in vars {
+                    let var = var_rcref.borrow();
+                    let expr_t = var.initializer_expr_t_opt.as_ref().unwrap();
+                    state_vars.push((var.name.clone(), self.format_expr(expr_t)));
+                }
+            }
+        }
+        state_vars
+    }
+
+    fn generate_machinery(&mut self, system_node: &SystemNode) {
+        let event_type = self.format_frame_event_type();
+        let state_context_type = self.format_state_context_type();
+
+        self.newline();
+        self.add_code("//=============== Machinery and Mechanisms ==============//");
+        self.newline();
+        self.newline();
+        self.add_code(&format!("private func handle(_ e: {}) {{", event_type));
+        self.indent();
+        self.newline();
+        self.add_code("switch stateContext.state {");
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_name = state_node_rcref.borrow().name.clone();
+                self.newline();
+                self.add_code(&format!(
+                    "case .{}: state{}(e)",
+                    SwiftVisitor::format_state_case(&state_name),
+                    state_name
+                ));
+            }
+        }
+        self.newline();
+        self.add_code("}");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        self.newline();
+        self.add_code(&format!(
+            "private func transition(to newStateContext: {}, exitArgs: [String: Any] = [:]) {{",
+            state_context_type
+        ));
+        self.indent();
+        self.newline();
+        self.add_code(&format!(
+            "handle({}(message: {:?}, parameters: exitArgs))",
+            event_type, self.symbol_config.exit_msg_symbol
+        ));
+        self.newline();
+        self.add_code("stateContext = newStateContext");
+        self.newline();
+        self.add_code(&format!(
+            "handle({}(message: {:?}, parameters: stateContext.enterArgs))",
+            event_type, self.symbol_config.enter_msg_symbol
+        ));
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        self.newline();
+        self.add_code("private func stateStackPush() {");
+        self.indent();
+        self.newline();
+        self.add_code("stateStack.append(stateContext)");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+
+        self.newline();
+        self.add_code("@discardableResult");
+        self.newline();
+        self.add_code(&format!(
+            "private func stateStackPop() -> {} {{",
+            state_context_type
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("return stateStack.removeLast()");
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_interface_method(&mut self, interface_method_node: &InterfaceMethodNode) {
+        let message = match &interface_method_node.alias {
+            Some(alias_message_node) => &alias_message_node.name,
+            None => &interface_method_node.name,
+        };
+        // A handler may not set a return value, so the declared type is
+        // returned as an optional.
+        let return_type_opt = interface_method_node
+            .return_type_opt
+            .as_ref()
+            .map(|type_node| type_node.get_type_str().trim_end_matches('?').to_string());
+
+        self.newline();
+        self.add_code(&format!(
+            "func {}({}){} {{",
+            interface_method_node.name,
+            SwiftVisitor::format_params(&interface_method_node.params),
+            match &return_type_opt {
+                Some(return_type) => format!(" -> {}?", return_type),
+                None => String::new(),
+            }
+        ));
+        self.indent();
+        self.newline();
+        let parameters = match &interface_method_node.params {
+            Some(params) => {
+                let entries: Vec<(String, String)> = params
+                    .iter()
+                    .map(|param| (param.param_name.clone(), param.param_name.clone()))
+                    .collect();
+                format!(
+                    ", parameters: {}",
+                    SwiftVisitor::format_dictionary(&entries)
+                )
+            }
+            None => String::new(),
+        };
+        self.add_code(&format!(
+            "let e = {}(message: {:?}{})",
+            self.format_frame_event_type(),
+            message,
+            parameters
+        ));
+        if self.has_states {
+            self.newline();
+            self.add_code("handle(e)");
+        }
+        if let Some(return_type) = &return_type_opt {
+            self.newline();
+            self.add_code(&format!("return e.ret as? {}", return_type));
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    fn generate_event_handler(&mut self, evt_handler_node: &EventHandlerNode) {
+        self.newline();
+        match &evt_handler_node.msg_t {
+            MessageType::CustomMessage { message_node } => {
+                self.add_code(&format!("case {:?}:", message_node.name));
+            }
+            MessageType::AnyMessage { .. } => self.add_code("default:"),
+        }
+        self.indent();
+        self.generate_comment(evt_handler_node.line);
+
+        self.current_event_param_types.clear();
+        if let Some(params) = &evt_handler_node.event_symbol_rcref.borrow().params_opt {
+            for param in params {
+                self.current_event_param_types.insert(
+                    param.name.clone(),
+                    param
+                        .param_type_opt
+                        .as_ref()
+                        .map(|type_node| type_node.get_type_str()),
+                );
+            }
+        }
+
+        let code_len = self.code.len();
+        self.visit_decl_stmts(&evt_handler_node.statements);
+        self.generate_terminator(&evt_handler_node.terminator_node);
+        // Every case of a Swift switch needs at least one statement.
+        if self.code.len() == code_len {
+            self.newline();
+            self.add_code("break");
+        }
+        self.outdent();
+    }
+
+    fn generate_terminator(&mut self, terminator_expr: &TerminatorExpr) {
+        match &terminator_expr.terminator_type {
+            TerminatorType::Return => {
+                if let Some(expr_t) = &terminator_expr.return_expr_t_opt {
+                    let value = self.format_expr(expr_t);
+                    self.newline();
+                    self.add_code(&format!("e.ret = {}", value));
+                }
+                self.newline();
+                self.add_code("return");
+            }
+            TerminatorType::Continue => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_decl_stmts(&mut self, decl_stmt_types: &[DeclOrStmtType]) {
+        for decl_stmt_t in decl_stmt_types {
+            match decl_stmt_t {
+                DeclOrStmtType::VarDeclT { var_decl_t_rc_ref } => {
+                    let variable_decl_node = var_decl_t_rc_ref.borrow();
+                    self.generate_var_decl(&variable_decl_node);
+                }
+                DeclOrStmtType::StmtT { stmt_t } => match stmt_t {
+                    StatementType::ExpressionStmt { expr_stmt_t } => {
+                        self.generate_expr_stmt(expr_stmt_t);
+                    }
+                    StatementType::TransitionStmt {
+                        transition_statement,
+                    } => {
+                        self.generate_transition(transition_statement);
+                    }
+                    StatementType::ChangeStateStmt { change_state_stmt } => {
+                        self.generate_change_state(change_state_stmt);
+                    }
+                    StatementType::TestStmt { test_stmt_node } => {
+                        self.generate_test(&test_stmt_node.test_t);
+                    }
+                    StatementType::StateStackStmt {
+                        state_stack_operation_statement_node,
+                    } => {
+                        self.newline();
+                        match state_stack_operation_statement_node
+                            .state_stack_operation_node
+                            .operation_t
+                        {
+                            StateStackOperationType::Push => self.add_code("stateStackPush()"),
+                            StateStackOperationType::Pop => self.add_code("stateStackPop()"),
+                        }
+                    }
+                    StatementType::LoopStmt { loop_stmt_node } => {
+                        self.generate_loop(loop_stmt_node);
+                    }
+                    StatementType::LoopControlStmt {
+                        loop_control_stmt_node,
+                    } => {
+                        self.newline();
+                        match loop_control_stmt_node.loop_control_t {
+                            LoopControlType::Break => self.add_code("break"),
+                            LoopControlType::Continue => self.add_code("continue"),
+                        }
+                    }
+                    StatementType::NoStmt => {}
+                },
+            }
+        }
+    }
+
+    // Domain variables and event handler variables share a syntax.
+    fn generate_var_decl(&mut self, variable_decl_node: &VariableDeclNode) {
+        let value = match &variable_decl_node.initializer_expr_t_opt {
+            Some(expr_t) => self.format_expr(expr_t),
+            None => String::from("nil"),
+        };
+        let keyword = if variable_decl_node.is_constant {
+            "let"
+        } else {
+            "var"
+        };
+        let var_type = match &variable_decl_node.type_opt {
+            Some(type_node) => format!(": {}", type_node.get_type_str()),
+            // `nil` alone does not give Swift a type to infer.
+            None if variable_decl_node.initializer_expr_t_opt.is_none() => String::from(": Any?"),
+            None => String::new(),
+        };
+        self.newline();
+        self.add_code(&format!(
+            "{} {}{} = {}",
+            keyword, variable_decl_node.name, var_type, value
+        ));
+    }
+
+    fn generate_expr_stmt(&mut self, expr_stmt_t: &ExprStmtType) {
+        let mut output = String::new();
+        match expr_stmt_t {
+            ExprStmtType::CallStmtT { call_stmt_node } => {
+                self.visit_call_expression_node_to_string(
+                    &call_stmt_node.call_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::ActionCallStmtT {
+                action_call_stmt_node,
+            } => {
+                self.visit_action_call_expression_node_to_string(
+                    &action_call_stmt_node.action_call_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::CallChainLiteralStmtT {
+                call_chain_literal_stmt_node,
+            } => {
+                self.visit_call_chain_literal_expr_node_to_string(
+                    &call_chain_literal_stmt_node.call_chain_literal_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::AssignmentStmtT {
+                assignment_stmt_node,
+            } => {
+                self.generate_comment(assignment_stmt_node.get_line());
+                self.visit_assignment_expr_node_to_string(
+                    &assignment_stmt_node.assignment_expr_node,
+                    &mut output,
+                );
+            }
+            ExprStmtType::VariableStmtT { variable_stmt_node } => {
+                output.push_str("_ = ");
+                output.push_str(&self.format_variable_expr(&variable_stmt_node.var_node));
+            }
+        }
+        self.newline();
+        self.add_code(&output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_label(&mut self, label_opt: &Option<String>) {
+        if let Some(label) = label_opt {
+            self.newline();
+            self.add_code(&format!("// {}", label));
+        }
+    }
+
+    // Pairs the arguments of an enter or exit event with its parameter names.
+    fn format_event_args(&mut self, msg: &str, args: &ExprListNode) -> Vec<(String, String)> {
+        let event_sym_opt = self.arcanium.get_event(msg, &self.current_state_name_opt);
+        let params = match event_sym_opt {
+            Some(event_sym) => match &event_sym.borrow().params_opt {
+                Some(params) => params
+                    .iter()
+                    .map(|param| param.name.clone())
+                    .collect::<Vec<String>>(),
+                None => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+        if params.len() != args.exprs_t.len() {
+            self.errors.push(format!(
+                "Invalid number of arguments for \"{}\" event handler.",
+                msg
+            ));
+        }
+        params
+            .into_iter()
+            .zip(args.exprs_t.iter())
+            .map(|(param, expr_t)| (param, self.format_expr(expr_t)))
+            .collect()
+    }
+
+    fn format_exit_args(&mut self, exit_args_opt: &Option<ExprListNode>) -> Option<String> {
+        match exit_args_opt {
+            Some(exit_args) if !exit_args.exprs_t.is_empty() => {
+                let msg = format!(
+                    "{}:{}",
+                    self.current_state_name_opt.clone().unwrap_or_default(),
+                    self.symbol_config.exit_msg_symbol
+                );
+                let entries = self.format_event_args(&msg, exit_args);
+                Some(SwiftVisitor::format_dictionary(&entries))
+            }
+            _ => None,
+        }
+    }
+
+    // A state context for the target state with its state arguments,
+    // initial state variables and enter arguments. Empty dictionaries are
+    // left to the memberwise initializer's defaults.
+    fn format_new_state_context(
+        &mut self,
+        target_state_name: &str,
+        state_context_node_opt: &Option<&StateContextNode>,
+    ) -> String {
+        let mut args = vec![format!(
+            "state: .{}",
+            SwiftVisitor::format_state_case(target_state_name)
+        )];
+
+        if let Some(state_context_node) = state_context_node_opt {
+            if let Some(state_args) = &state_context_node.state_ref_args_opt {
+                let params = match self.arcanium.get_state(target_state_name) {
+                    Some(state_symbol_rcref) => match &state_symbol_rcref.borrow().params_opt {
+                        Some(params) => params
+                            .iter()
+                            .map(|param| param.borrow().name.clone())
+                            .collect::<Vec<String>>(),
+                        None => Vec::new(),
+                    },
+                    None => Vec::new(),
+                };
+                if params.len() != state_args.exprs_t.len() {
+                    self.errors.push(format!(
+                        "Invalid number of arguments for \"{}\" state parameters.",
+                        target_state_name
+                    ));
+                }
+                let entries: Vec<(String, String)> = params
+                    .into_iter()
+                    .zip(state_args.exprs_t.iter())
+                    .map(|(param, expr_t)| (param, self.format_expr(expr_t)))
+                    .collect();
+                if !entries.is_empty() {
+                    args.push(format!(
+                        "stateArgs: {}",
+                        SwiftVisitor::format_dictionary(&entries)
+                    ));
+                }
+            }
+        }
+
+        let state_vars = self.format_state_vars(target_state_name);
+        if !state_vars.is_empty() {
+            args.push(format!(
+                "stateVars: {}",
+                SwiftVisitor::format_dictionary(&state_vars)
+            ));
+        }
+
+        if let Some(state_context_node) = state_context_node_opt {
+            if let Some(enter_args) = &state_context_node.enter_args_opt {
+                let msg = format!(
+                    "{}:{}",
+                    target_state_name, self.symbol_config.enter_msg_symbol
+                );
+                let entries = self.format_event_args(&msg, enter_args);
+                if !entries.is_empty() {
+                    args.push(format!(
+                        "enterArgs: {}",
+                        SwiftVisitor::format_dictionary(&entries)
+                    ));
+                }
+            }
+        }
+
+        format!("{}({})", self.format_state_context_type(), args.join(", "))
+    }
+
+    fn generate_transition(&mut self, transition_statement: &TransitionStatementNode) {
+        self.generate_label(&transition_statement.label_opt);
+        let new_state_context = match &transition_statement.target_state_context_t {
+            StateContextType::StateRef { state_context_node } => self.format_new_state_context(
+                &state_context_node.state_ref_node.name,
+                &Some(state_context_node),
+            ),
+            StateContextType::StateStackPop {} => String::from("stateStackPop()"),
+        };
+        let exit_args = match self.format_exit_args(&transition_statement.exit_args_opt) {
+            Some(exit_args) => format!(", exitArgs: {}", exit_args),
+            None => String::new(),
+        };
+        self.newline();
+        self.add_code(&format!(
+            "transition(to: {}{})",
+            new_state_context, exit_args
+        ));
+    }
+
+    fn generate_change_state(&mut self, change_state_stmt_node: &ChangeStateStatementNode) {
+        self.generate_label(&change_state_stmt_node.label_opt);
+        let new_state_context = match &change_state_stmt_node.state_context_t {
+            StateContextType::StateRef { state_context_node } => self.format_new_state_context(
+                &state_context_node.state_ref_node.name,
+                &Some(state_context_node),
+            ),
+            StateContextType::StateStackPop {} => String::from("stateStackPop()"),
+        };
+        self.newline();
+        self.add_code(&format!("stateContext = {}", new_state_context));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_branch_body(
+        &mut self,
+        statements: &[DeclOrStmtType],
+        terminator_opt: &Option<TerminatorExpr>,
+    ) {
+        self.indent();
+        self.visit_decl_stmts(statements);
+        if let Some(terminator_expr) = terminator_opt {
+            self.generate_terminator(terminator_expr);
+        }
+        self.outdent();
+    }
+
+    fn generate_if_chain(
+        &mut self,
+        branches: Vec<SwiftBranch>,
+        else_opt: Option<(&[DeclOrStmtType], &Option<TerminatorExpr>)>,
+    ) {
+        self.newline();
+        for (i, branch) in branches.iter().enumerate() {
+            if i == 0 {
+                self.add_code(&format!("if {} {{", branch.condition));
+            } else {
+                self.add_code(&format!("}} else if {} {{", branch.condition));
+            }
+            self.indent();
+            for binding in &branch.bindings {
+                self.newline();
+                self.add_code(binding);
+            }
+            self.outdent();
+            self.generate_branch_body(branch.statements, branch.terminator_opt);
+            self.newline();
+        }
+        if let Some((statements, terminator_opt)) = else_opt {
+            self.add_code("} else {");
+            self.generate_branch_body(statements, terminator_opt);
+            self.newline();
+        }
+        self.add_code("}");
+    }
+
+    fn generate_test(&mut self, test_t: &TestType) {
+        match test_t {
+            TestType::BoolTest { bool_test_node } => {
+                let mut branches = Vec::new();
+                for branch_node in &bool_test_node.conditional_branch_nodes {
+                    let mut condition = self.format_expr(&branch_node.expr_t);
+                    if branch_node.is_negated {
+                        condition = format!("!({})", condition);
+                    }
+                    branches.push(SwiftBranch {
+                        condition,
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    });
+                }
+                let else_opt = bool_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::StringMatchTest {
+                string_match_test_node,
+            } => {
+                let subject = self.format_expr(&string_match_test_node.expr_t);
+                let branches = string_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| SwiftBranch {
+                        condition: branch_node
+                            .string_match_pattern_node
+                            .match_pattern_strings
+                            .iter()
+                            .map(|pattern| format!("{} == {:?}", subject, pattern))
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = string_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::NumberMatchTest {
+                number_match_test_node,
+            } => {
+                let subject = self.format_expr(&number_match_test_node.expr_t);
+                let branches = number_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| SwiftBranch {
+                        condition: branch_node
+                            .number_match_pattern_nodes
+                            .iter()
+                            .map(|pattern| {
+                                format!("{} == {}", subject, pattern.match_pattern_number)
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = number_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                let subject = self.format_expr(&regex_match_test_node.expr_t);
+                let branches = regex_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| {
+                        let pattern_node = &branch_node.regex_match_pattern_node;
+                        let regex = format!(
+                            "try! Regex(#\"{}\"#)",
+                            pattern_node.pattern_with_group_prefix("?<")
+                        );
+                        let mut bindings = Vec::new();
+                        let condition = if pattern_node.capture_names.is_empty() {
+                            format!("{}.contains({})", subject, regex)
+                        } else {
+                            for name in &pattern_node.capture_names {
+                                bindings.push(format!(
+                                    "let {} = regexMatch.output[{:?}]?.substring.map(String.init) ?? \"\"",
+                                    name, name
+                                ));
+                            }
+                            format!("let regexMatch = {}.firstMatch(of: {})", subject, regex)
+                        };
+                        SwiftBranch {
+                            condition,
+                            bindings,
+                            statements: &branch_node.statements,
+                            terminator_opt: &branch_node.branch_terminator_expr_opt,
+                        }
+                    })
+                    .collect();
+                let else_opt = regex_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                let subject = self.format_expr(&enum_match_test_node.expr_t);
+                let branches = enum_match_test_node
+                    .match_branch_nodes
+                    .iter()
+                    .map(|branch_node| SwiftBranch {
+                        condition: branch_node
+                            .enumerators
+                            .iter()
+                            .map(|enumerator| {
+                                format!(
+                                    "{} == {}.{}",
+                                    subject, enum_match_test_node.enum_type_name, enumerator
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join(" || "),
+                        bindings: Vec::new(),
+                        statements: &branch_node.statements,
+                        terminator_opt: &branch_node.branch_terminator_expr_opt,
+                    })
+                    .collect();
+                let else_opt = enum_match_test_node
+                    .else_branch_node_opt
+                    .as_ref()
+                    .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+                self.generate_if_chain(branches, else_opt);
+            }
+        }
+    }
+
+    fn generate_loop(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.newline();
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(condition_expr_t) => {
+                let condition = self.format_expr(condition_expr_t);
+                self.add_code(&format!("while {} {{", condition));
+            }
+            None => self.add_code("while true {"),
+        }
+        self.indent();
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+}
+
+//* --------------------------------------------------------------------- *//
+
+impl AstVisitor for SwiftVisitor {
+    fn visit_system_node(&mut self, system_node: &SystemNode) {
+        self.system_name = system_node.name.clone();
+        if system_node.has_deferred_events() {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the swift target.",
+            ));
+        }
+        if let Some(first_state) = system_node.get_first_state() {
+            self.first_state_name = first_state.borrow().name.clone();
+            self.has_states = true;
+        }
+
+        self.add_code(&format!("// {}", self.compiler_version));
+        self.newline();
+        self.newline();
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for enum_decl_node_rcref in &domain_block_node.enums {
+                self.generate_enum(&enum_decl_node_rcref.borrow());
+            }
+        }
+        self.generate_frame_event_type();
+        self.generate_actions_protocol(system_node);
+        if self.has_states {
+            self.generate_state_types(system_node);
+        }
+
+        self.add_code(&format!("final class {} {{", self.system_name));
+        self.indent();
+        self.newline();
+        self.add_code(&format!("private let actions: {}Actions", self.system_name));
+        if self.has_states {
+            let state_context_type = self.format_state_context_type();
+            self.newline();
+            self.add_code(&format!("private var stateContext: {}", state_context_type));
+            self.newline();
+            self.add_code(&format!(
+                "private var stateStack: [{}] = []",
+                state_context_type
+            ));
+        }
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for variable_decl_node_rcref in &domain_block_node.member_variables {
+                self.generate_var_decl(&variable_decl_node_rcref.borrow());
+            }
+        }
+        self.newline();
+        self.generate_initializer();
+
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            interface_block_node.accept(self);
+        }
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            machine_block_node.accept(self);
+        }
+        if self.has_states {
+            self.generate_machinery(system_node);
+        }
+
+        self.code.truncate(self.code.trim_end().len());
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    fn visit_interface_block_node(&mut self, interface_block_node: &InterfaceBlockNode) {
+        self.newline();
+        self.add_code("//===================== Interface Block ===================//");
+        self.newline();
+        for interface_method_node_rcref in &interface_block_node.interface_methods {
+            self.generate_interface_method(&interface_method_node_rcref.borrow());
+        }
+    }
+
+    fn visit_machine_block_node(&mut self, machine_block_node: &MachineBlockNode) {
+        self.newline();
+        self.add_code("//===================== Machine Block ===================//");
+        self.newline();
+        for state_node_rcref in &machine_block_node.states {
+            state_node_rcref.borrow().accept(self);
+        }
+    }
+
+    fn visit_state_node(&mut self, state_node: &StateNode) {
+        self.generate_comment(state_node.line);
+        self.current_state_name_opt = Some(state_node.name.clone());
+        self.newline();
+        self.add_code(&format!(
+            "private func state{}(_ e: {}) {{",
+            state_node.name,
+            self.format_frame_event_type()
+        ));
+        self.indent();
+
+        if let Some(calls) = &state_node.calls_opt {
+            for call in calls {
+                let mut output = String::new();
+                self.visit_call_chain_literal_expr_node_to_string(call, &mut output);
+                self.newline();
+                self.add_code(&output);
+            }
+        }
+
+        if !state_node.evt_handlers_rcref.is_empty() {
+            self.newline();
+            self.add_code("switch e.message {");
+            let mut has_default = false;
+            for evt_handler_node_rcref in &state_node.evt_handlers_rcref {
+                let evt_handler_node = evt_handler_node_rcref.borrow();
+                if let MessageType::AnyMessage { .. } = evt_handler_node.msg_t {
+                    has_default = true;
+                }
+                self.generate_event_handler(&evt_handler_node);
+            }
+            // Switches over strings must be exhaustive.
+            if !has_default {
+                self.newline();
+                self.add_code("default:");
+                self.indent();
+                self.newline();
+                self.add_code("break");
+                self.outdent();
+            }
+            self.newline();
+            self.add_code("}");
+        }
+
+        // Events the state does not handle go to its parent.
+        if let Some(dispatch_node) = &state_node.dispatch_opt {
+            self.newline();
+            self.add_code(&format!("state{}(e)", dispatch_node.target_state_ref.name));
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.current_state_name_opt = None;
+    }
+
+    fn visit_action_node_rust_trait(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_action_node_rust_trait() not implemented.");
+    }
+
+    fn visit_actions_node_rust_impl(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_actions_node_rust_impl() not implemented.");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_assignment_expr_node_to_string(
+        &mut self,
+        assignment_expr_node: &AssignmentExprNode,
+        output: &mut String,
+    ) {
+        self.visiting_l_value = true;
+        assignment_expr_node
+            .l_value_box
+            .accept_to_string(self, output);
+        self.visiting_l_value = false;
+        output.push_str(" = ");
+        assignment_expr_node
+            .r_value_box
+            .accept_to_string(self, output);
+    }
+
+    fn visit_expression_list_node_to_string(
+        &mut self,
+        expr_list: &ExprListNode,
+        output: &mut String,
+    ) {
+        let args = self.format_args(&expr_list.exprs_t);
+        output.push_str(&args);
+    }
+
+    fn visit_call_expr_list_node_to_string(
+        &mut self,
+        call_expr_list: &CallExprListNode,
+        output: &mut String,
+    ) {
+        let args = self.format_args(&call_expr_list.exprs_t);
+        output.push_str(&args);
+    }
+
+    fn visit_call_expression_node_to_string(
+        &mut self,
+        method_call: &CallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&method_call.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(&method_call.call_expr_list, output);
+    }
+
+    fn visit_action_call_expression_node_to_string(
+        &mut self,
+        action_call: &ActionCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!("actions.{}", action_call.identifier.name.lexeme));
+        self.visit_call_expr_list_node_to_string(&action_call.call_expr_list, output);
+    }
+
+    fn visit_interface_method_call_expression_node_to_string(
+        &mut self,
+        interface_method_call_expr_node: &InterfaceMethodCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&interface_method_call_expr_node.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(
+            &interface_method_call_expr_node.call_expr_list,
+            output,
+        );
+    }
+
+    fn visit_call_chain_literal_expr_node_to_string(
+        &mut self,
+        method_call_chain_expression_node: &CallChainLiteralExprNode,
+        output: &mut String,
+    ) {
+        let mut separator = "";
+        for node in &method_call_chain_expression_node.call_chain {
+            output.push_str(separator);
+            match node {
+                CallChainLiteralNodeType::VariableNodeT { var_node } => {
+                    output.push_str(&self.format_variable_expr(var_node));
+                }
+                CallChainLiteralNodeType::IdentifierNodeT { id_node } => {
+                    output.push_str(&id_node.name.lexeme);
+                }
+                CallChainLiteralNodeType::CallT { call } => {
+                    self.visit_call_expression_node_to_string(call, output);
+                }
+                CallChainLiteralNodeType::InterfaceMethodCallT {
+                    interface_method_call_expr_node,
+                } => {
+                    self.visit_interface_method_call_expression_node_to_string(
+                        interface_method_call_expr_node,
+                        output,
+                    );
+                }
+                CallChainLiteralNodeType::ActionCallT {
+                    action_call_expr_node,
+                } => {
+                    self.visit_action_call_expression_node_to_string(action_call_expr_node, output);
+                }
+            }
+            separator = ".";
+        }
+    }
+
+    fn visit_literal_expression_node_to_string(
+        &mut self,
+        literal_expression_node: &LiteralExprNode,
+        output: &mut String,
+    ) {
+        match &literal_expression_node.token_t {
+            TokenType::String => {
+                output.push_str(&format!("\"{}\"", literal_expression_node.value));
+            }
+            TokenType::True => output.push_str("true"),
+            TokenType::False => output.push_str("false"),
+            TokenType::Nil | TokenType::Null => output.push_str("nil"),
+            _ => output.push_str(&literal_expression_node.value),
+        }
+    }
+
+    fn visit_variable_expr_node_to_string(
+        &mut self,
+        variable_node: &VariableNode,
+        output: &mut String,
+    ) {
+        output.push_str(&self.format_variable_expr(variable_node));
+    }
+
+    fn visit_frame_event_part_to_string(
+        &mut self,
+        frame_event_part: &FrameEventPart,
+        output: &mut String,
+    ) {
+        match frame_event_part {
+            FrameEventPart::Event { .. } => output.push('e'),
+            FrameEventPart::Message { .. } => output.push_str("e.message"),
+            FrameEventPart::Param { param_tok, .. } => {
+                output.push_str(&self.format_event_param(&param_tok.lexeme))
+            }
+            FrameEventPart::Return { .. } => output.push_str("e.ret"),
+        }
+    }
+
+    fn visit_unary_expr_node_to_string(
+        &mut self,
+        unary_expr_node: &UnaryExprNode,
+        output: &mut String,
+    ) {
+        unary_expr_node.operator.accept_to_string(self, output);
+        unary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_binary_expr_node_to_string(
+        &mut self,
+        binary_expr_node: &BinaryExprNode,
+        output: &mut String,
+    ) {
+        binary_expr_node
+            .left_rcref
+            .borrow()
+            .accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node.operator.accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}.{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    fn visit_operator_type_to_string(&mut self, operator_type: &OperatorType, output: &mut String) {
+        let operator = match operator_type {
+            OperatorType::Plus => "+",
+            OperatorType::Minus | OperatorType::Negated => "-",
+            OperatorType::Multiply => "*",
+            OperatorType::Divide => "/",
+            OperatorType::Greater => ">",
+            OperatorType::GreaterEqual => ">=",
+            OperatorType::Less => "<",
+            OperatorType::LessEqual => "<=",
+            OperatorType::EqualEqual => "==",
+            OperatorType::NotEqual => "!=",
+            OperatorType::Not => "!",
+            OperatorType::LogicalAnd => "&&",
+            OperatorType::LogicalOr => "||",
+            // Booleans differ exactly when one of them is true.
+            OperatorType::LogicalXor => "!=",
+        };
+        output.push_str(operator);
+    }
+}
@@ -0,0 +1,3052 @@
+// TODO fix these issues and disable warning suppression
+#![allow(unknown_lints)]
+#![allow(clippy::branches_sharing_code)]
+#![allow(clippy::single_match)]
+#![allow(clippy::ptr_arg)]
+#![allow(non_snake_case)]
+
+use crate::frame_c::ast::*;
+use crate::frame_c::scanner::{Token, TokenType};
+use crate::frame_c::symbol_table::*;
+use crate::frame_c::visitors::*;
+use std::collections::HashMap;
+
+pub struct TypeScriptVisitor {
+    compiler_version: String,
+    pub code: String,
+    pub dent: usize,
+    pub current_state_name_opt: Option<String>,
+    arcanium: Arcanum,
+    symbol_config: SymbolConfig,
+    comments: Vec<Token>,
+    current_comment_idx: usize,
+    first_event_handler: bool,
+    system_name: String,
+    first_state_name: String,
+    warnings: Vec<String>,
+    has_states: bool,
+    errors: Vec<String>,
+    visiting_l_value: bool,
+    // Declared types of the parameters of the event handler being visited.
+    current_event_param_types: HashMap<String, String>,
+    current_event_is_enter_or_exit: bool,
+    current_event_is_enter: bool,
+    generate_exit_args: bool,
+    generate_state_context: bool,
+    generate_state_stack: bool,
+    generate_change_state: bool,
+    generate_transition_state: bool,
+}
+
+impl TypeScriptVisitor {
+    //* --------------------------------------------------------------------- *//
+
+    pub fn new(
+        arcanium: Arcanum,
+        generate_exit_args: bool,
+        generate_state_context: bool,
+        generate_state_stack: bool,
+        generate_change_state: bool,
+        generate_transition_state: bool,
+        compiler_version: &str,
+        comments: Vec<Token>,
+    ) -> TypeScriptVisitor {
+        TypeScriptVisitor {
+            compiler_version: compiler_version.to_string(),
+            code: String::from(""),
+            dent: 0,
+            current_state_name_opt: None,
+            arcanium,
+            symbol_config: SymbolConfig::new(),
+            comments,
+            current_comment_idx: 0,
+            first_event_handler: true,
+            system_name: String::new(),
+            first_state_name: String::new(),
+            has_states: false,
+            warnings: Vec::new(),
+            errors: Vec::new(),
+            visiting_l_value: false,
+            current_event_param_types: HashMap::new(),
+            current_event_is_enter_or_exit: false,
+            current_event_is_enter: false,
+            generate_exit_args,
+            generate_state_context,
+            generate_state_stack,
+            generate_change_state,
+            generate_transition_state,
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    pub fn get_code(&self) -> String {
+        if !self.errors.is_empty() {
+            let mut error_list = String::new();
+            for error in &self.errors {
+                error_list.push_str(&error.clone());
+            }
+            error_list
+        } else {
+            self.code.clone()
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Untyped Frame declarations are left to the TypeScript `any` type.
+
+    fn format_type(type_opt: &Option<TypeNode>) -> String {
+        match type_opt {
+            Some(type_node) => type_node.get_type_str(),
+            None => String::from("any"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_params(params_opt: &Option<Vec<ParameterNode>>) -> String {
+        match params_opt {
+            Some(params) => params
+                .iter()
+                .map(|param| {
+                    format!(
+                        "{}: {}",
+                        param.param_name,
+                        TypeScriptVisitor::format_type(&param.param_type_opt)
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+            None => String::new(),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // State arguments and state variables are read through the context of
+    // the current state, so their declared types are checked.
+
+    fn format_variable_expr(&self, variable_node: &VariableNode) -> String {
+        let mut code = String::new();
+        let name = &variable_node.id_node.name.lexeme;
+
+        match variable_node.scope {
+            IdentifierDeclScope::DomainBlock => {
+                code.push_str(&format!("this.{}", name));
+            }
+            IdentifierDeclScope::StateParam => {
+                code.push_str(&format!(
+                    "{}.stateArgs.{}",
+                    self.format_this_state_context(),
+                    name
+                ));
+            }
+            IdentifierDeclScope::StateVar => {
+                code.push_str(&format!(
+                    "{}.stateVars.{}",
+                    self.format_this_state_context(),
+                    name
+                ));
+            }
+            IdentifierDeclScope::EventHandlerParam => {
+                code.push_str(&self.format_event_param(name));
+            }
+            IdentifierDeclScope::EventHandlerVar => {
+                code.push_str(&name.to_string());
+            }
+            IdentifierDeclScope::None => {
+                // TODO: Explore labeling Variables as "extern" scope
+                code.push_str(&name.to_string());
+            } // Actions?
+            _ => panic!("Illegal scope."),
+        }
+
+        code
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_cast(&self, access: String, var_type: &str) -> String {
+        if self.visiting_l_value {
+            access
+        } else {
+            format!("({} as {})", access, var_type)
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Interface events are narrowed to their member of the FrameEvent union,
+    // so their parameters are typed. Enter arguments are read from the typed
+    // context of the state. Exit parameters depend on the state and are
+    // carried in an untyped record.
+
+    fn format_event_param(&self, name: &str) -> String {
+        if self.current_event_is_enter && self.generate_state_context {
+            format!("{}.enterArgs!.{}", self.format_this_state_context(), name)
+        } else if self.current_event_is_enter_or_exit {
+            let param_type = match self.current_event_param_types.get(name) {
+                Some(param_type) => param_type.clone(),
+                None => String::from("any"),
+            };
+            self.format_cast(format!("e._parameters![\"{}\"]", name), &param_type)
+        } else {
+            format!("e._parameters.{}", name)
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_action_name(&mut self, action_name: &String) -> String {
+        format!("this.actions.{}", action_name)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_state_type(&self) -> String {
+        format!("{}State", self.system_name)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_state_context_type(&self) -> String {
+        format!("{}StateContext", self.system_name)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_state_context_member_type(&self, state_name: &str) -> String {
+        format!("{}{}Context", self.system_name, state_name)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // The context of the current state, narrowed from the StateContext union
+    // at the top of the state function.
+
+    fn format_this_state_context(&self) -> String {
+        String::from("thisStateContext")
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_this_state_context(&mut self, state_node: &StateNode) {
+        let has_enter_params = match &state_node.enter_event_handler_opt {
+            Some(enter_handler_rcref) => enter_handler_rcref
+                .borrow()
+                .event_symbol_rcref
+                .borrow()
+                .params_opt
+                .is_some(),
+            None => false,
+        };
+        if !self.generate_state_context
+            || (state_node.params_opt.is_none()
+                && state_node.vars_opt.is_none()
+                && !has_enter_params)
+        {
+            return;
+        }
+        self.newline();
+        self.add_code(&format!(
+            "const {} = this._stateContext_ as {};",
+            self.format_this_state_context(),
+            self.format_state_context_member_type(&state_node.name)
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_record_type(fields: &[(String, String)]) -> String {
+        if fields.is_empty() {
+            return String::from("Record<string, never>");
+        }
+        let fields = fields
+            .iter()
+            .map(|(name, field_type)| format!("{}: {}", name, field_type))
+            .collect::<Vec<String>>()
+            .join("; ");
+        format!("{{ {} }}", fields)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_record(fields: &[(String, String)]) -> String {
+        if fields.is_empty() {
+            return String::from("{}");
+        }
+        let fields = fields
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<String>>()
+            .join(", ");
+        format!("{{ {} }}", fields)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_frame_event_type(&self) -> String {
+        format!("{}FrameEvent", self.system_name)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    pub fn run(&mut self, system_node: &SystemNode) {
+        system_node.accept(self);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn add_code(&mut self, s: &str) {
+        self.code.push_str(s);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn newline(&mut self) {
+        self.code.push_str(&format!("\n{}", self.dent()));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn newline_to_string(&mut self, output: &mut String) {
+        output.push_str(&format!("\n{}", self.dent()));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn dent(&self) -> String {
+        (0..self.dent).map(|_| "    ").collect::<String>()
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn indent(&mut self) {
+        self.dent += 1;
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn outdent(&mut self) {
+        self.dent -= 1;
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_decl_stmts(&mut self, decl_stmt_types: &Vec<DeclOrStmtType>) {
+        for decl_stmt_t in decl_stmt_types.iter() {
+            match decl_stmt_t {
+                DeclOrStmtType::VarDeclT { var_decl_t_rc_ref } => {
+                    let variable_decl_node = var_decl_t_rc_ref.borrow();
+                    variable_decl_node.accept(self);
+                }
+                DeclOrStmtType::StmtT { stmt_t } => {
+                    match stmt_t {
+                        StatementType::ExpressionStmt { expr_stmt_t } => {
+                            match expr_stmt_t {
+                                ExprStmtType::ActionCallStmtT {
+                                    action_call_stmt_node,
+                                } => action_call_stmt_node.accept(self), // // TODO
+                                ExprStmtType::CallStmtT { call_stmt_node } => {
+                                    call_stmt_node.accept(self)
+                                }
+                                ExprStmtType::CallChainLiteralStmtT {
+                                    call_chain_literal_stmt_node,
+                                } => call_chain_literal_stmt_node.accept(self),
+                                ExprStmtType::AssignmentStmtT {
+                                    assignment_stmt_node,
+                                } => assignment_stmt_node.accept(self),
+                                ExprStmtType::VariableStmtT { variable_stmt_node } => {
+                                    variable_stmt_node.accept(self)
+                                }
+                            }
+                        }
+                        StatementType::TransitionStmt {
+                            transition_statement,
+                        } => {
+                            transition_statement.accept(self);
+                        }
+                        StatementType::TestStmt { test_stmt_node } => {
+                            test_stmt_node.accept(self);
+                        }
+                        StatementType::StateStackStmt {
+                            state_stack_operation_statement_node,
+                        } => {
+                            state_stack_operation_statement_node.accept(self);
+                        }
+                        StatementType::ChangeStateStmt { change_state_stmt } => {
+                            change_state_stmt.accept(self);
+                        }
+                        StatementType::LoopStmt { loop_stmt_node } => {
+                            loop_stmt_node.accept(self);
+                        }
+                        StatementType::LoopControlStmt {
+                            loop_control_stmt_node,
+                        } => {
+                            loop_control_stmt_node.accept(self);
+                        }
+                        StatementType::NoStmt => {
+                            // TODO
+                            panic!("todo");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Every message the machine can receive gets a member in the FrameEvent
+    // union: the interface messages, any other messages with handlers, and
+    // the enter and exit events sent on transitions.
+
+    fn generate_frame_event_type(&mut self, system_node: &SystemNode) {
+        let mut members: Vec<(String, String)> = Vec::new();
+
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            for interface_method_node_rcref in &interface_block_node.interface_methods {
+                let interface_method_node = interface_method_node_rcref.borrow();
+                let msg = match &interface_method_node.alias {
+                    Some(alias_message_node) => alias_message_node.name.clone(),
+                    None => interface_method_node.name.clone(),
+                };
+                let params = match &interface_method_node.params {
+                    Some(_) => format!(
+                        "{{ {} }}",
+                        TypeScriptVisitor::format_params(&interface_method_node.params)
+                            .replace(", ", "; ")
+                    ),
+                    None => String::from("null"),
+                };
+                let return_type = match &interface_method_node.return_type_opt {
+                    Some(return_type) => return_type.get_type_str(),
+                    None => String::from("undefined"),
+                };
+                members.push((msg, format!("{}; _return?: {}", params, return_type)));
+            }
+        }
+
+        if self.has_states {
+            let enter_or_exit = String::from("Record<string, unknown> | null; _return?: undefined");
+            members.push((
+                self.symbol_config.enter_msg_symbol.clone(),
+                enter_or_exit.clone(),
+            ));
+            members.push((self.symbol_config.exit_msg_symbol.clone(), enter_or_exit));
+        }
+
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            for state_node_rcref in &machine_block_node.states {
+                let state_node = state_node_rcref.borrow();
+                for evt_handler_node_rcref in &state_node.evt_handlers_rcref {
+                    let evt_handler_node = evt_handler_node_rcref.borrow();
+                    let msg = match &evt_handler_node.msg_t {
+                        MessageType::CustomMessage { message_node } => message_node.name.clone(),
+                        MessageType::AnyMessage { .. } => continue,
+                    };
+                    if members.iter().any(|(member_msg, _)| *member_msg == msg) {
+                        continue;
+                    }
+                    let event_symbol = evt_handler_node.event_symbol_rcref.borrow();
+                    let params = match &event_symbol.params_opt {
+                        Some(params) => format!(
+                            "{{ {} }}",
+                            params
+                                .iter()
+                                .map(|param| format!(
+                                    "{}: {}",
+                                    param.name,
+                                    TypeScriptVisitor::format_type(&param.param_type_opt)
+                                ))
+                                .collect::<Vec<String>>()
+                                .join("; ")
+                        ),
+                        None => String::from("null"),
+                    };
+                    let return_type = match &event_symbol.ret_type_opt {
+                        Some(return_type) => return_type.get_type_str(),
+                        None => String::from("undefined"),
+                    };
+                    members.push((msg, format!("{}; _return?: {}", params, return_type)));
+                }
+            }
+        }
+
+        self.add_code(&format!("export type {} =", self.format_frame_event_type()));
+        self.indent();
+        if members.is_empty() {
+            self.newline();
+            self.add_code("never");
+        }
+        for (msg, params_and_return) in &members {
+            self.newline();
+            self.add_code(&format!(
+                "| {{ _message: {:?}; _parameters: {} }}",
+                msg, params_and_return
+            ));
+        }
+        self.add_code(";");
+        self.outdent();
+        self.newline();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Each state gets a context interface typing its state arguments, state
+    // variables and enter arguments. The StateContext type is their union.
+
+    fn generate_state_context_class(&mut self, system_node: &SystemNode) {
+        self.add_code(&format!(
+            "type {} = (e: {}) => void;",
+            self.format_state_type(),
+            self.format_frame_event_type()
+        ));
+        self.newline();
+        self.newline();
+        if !self.generate_state_context {
+            return;
+        }
+        let machine_block_node = match &system_node.machine_block_node_opt {
+            Some(machine_block_node) => machine_block_node,
+            None => return,
+        };
+        let mut context_types = Vec::new();
+        for state_node_rcref in &machine_block_node.states {
+            let state_node = state_node_rcref.borrow();
+            let context_type = self.format_state_context_member_type(&state_node.name);
+            let mut state_args = Vec::new();
+            if let Some(params) = &state_node.params_opt {
+                for param in params {
+                    state_args.push((
+                        param.param_name.clone(),
+                        TypeScriptVisitor::format_type(&param.param_type_opt),
+                    ));
+                }
+            }
+            let mut state_vars = Vec::new();
+            if let Some(vars) = &state_node.vars_opt {
+                for var_rcref in vars {
+                    let var = var_rcref.borrow();
+                    state_vars.push((
+                        var.name.clone(),
+                        TypeScriptVisitor::format_type(&var.type_opt),
+                    ));
+                }
+            }
+            let mut enter_args_type = String::from("null");
+            if let Some(enter_handler_rcref) = &state_node.enter_event_handler_opt {
+                let enter_handler = enter_handler_rcref.borrow();
+                let event_symbol = enter_handler.event_symbol_rcref.borrow();
+                if let Some(params) = &event_symbol.params_opt {
+                    let enter_args = params
+                        .iter()
+                        .map(|param| {
+                            (
+                                param.name.clone(),
+                                TypeScriptVisitor::format_type(&param.param_type_opt),
+                            )
+                        })
+                        .collect::<Vec<(String, String)>>();
+                    enter_args_type = format!(
+                        "{} | null",
+                        TypeScriptVisitor::format_record_type(&enter_args)
+                    );
+                }
+            }
+            self.add_code(&format!("interface {} {{", context_type));
+            self.indent();
+            self.newline();
+            self.add_code(&format!("readonly state: {};", self.format_state_type()));
+            self.newline();
+            self.add_code(&format!(
+                "readonly stateArgs: {};",
+                TypeScriptVisitor::format_record_type(&state_args)
+            ));
+            self.newline();
+            self.add_code(&format!(
+                "readonly stateVars: {};",
+                TypeScriptVisitor::format_record_type(&state_vars)
+            ));
+            self.newline();
+            self.add_code(&format!("readonly enterArgs: {};", enter_args_type));
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+            self.newline();
+            self.newline();
+            context_types.push(context_type);
+        }
+        self.add_code(&format!(
+            "type {} = {};",
+            self.format_state_context_type(),
+            context_types.join(" | ")
+        ));
+        self.newline();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_machinery_fields(&mut self) {
+        self.newline();
+        self.add_code(&format!("private _state_: {};", self.format_state_type()));
+        if self.generate_state_context {
+            self.newline();
+            self.add_code(&format!(
+                "private _stateContext_: {};",
+                self.format_state_context_type()
+            ));
+        }
+        if self.generate_state_stack {
+            self.newline();
+            if self.generate_state_context {
+                self.add_code(&format!(
+                    "private _stateStack_: {}[] = [];",
+                    self.format_state_context_type()
+                ));
+            } else {
+                self.add_code(&format!(
+                    "private _stateStack_: {}[] = [];",
+                    self.format_state_type()
+                ));
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_constructor(&mut self) {
+        self.newline();
+        self.newline();
+        self.add_code(&format!(
+            "constructor(actions: {}Actions) {{",
+            self.system_name
+        ));
+        self.indent();
+        self.newline();
+        self.add_code("this.actions = actions;");
+        if self.has_states {
+            self.newline();
+            self.add_code(&format!(
+                "this._state_ = this._s{}_;",
+                self.first_state_name
+            ));
+            if self.generate_state_context {
+                self.newline();
+                let first_state_name = self.first_state_name.clone();
+                let state_vars = self.generate_state_vars(&first_state_name);
+                self.add_code(&format!(
+                    "this._stateContext_ = {};",
+                    self.format_state_context("this._state_", &[], &state_vars, None)
+                ));
+            }
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_state_vars(&mut self, state_name: &str) -> Vec<(String, String)> {
+        let mut state_vars = Vec::new();
+        if let Some(state_symbol_rcref) = self.arcanium.get_state(state_name) {
+            let state_symbol = state_symbol_rcref.borrow();
+            let state_node = &state_symbol.state_node.as_ref().unwrap().borrow();
+            // generate local state variables
+            if let Some(vars) = &state_node.vars_opt {
+                for var_rcref in vars {
+                    let var = var_rcref.borrow();
+                    let expr_t = var.initializer_expr_t_opt.as_ref().unwrap();
+                    let mut expr_code = String::new();
+                    expr_t.accept_to_string(self, &mut expr_code);
+                    state_vars.push((var.name.clone(), expr_code));
+                }
+            }
+        }
+        state_vars
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_state_context(
+        &self,
+        state: &str,
+        state_args: &[(String, String)],
+        state_vars: &[(String, String)],
+        enter_args_opt: Option<&[(String, String)]>,
+    ) -> String {
+        let enter_args = match enter_args_opt {
+            Some(enter_args) => TypeScriptVisitor::format_record(enter_args),
+            None => String::from("null"),
+        };
+        let dent = self.dent();
+        format!(
+            "{{\n{0}    state: {1},\n{0}    stateArgs: {2},\n{0}    stateVars: {3},\n{0}    enterArgs: {4},\n{0}}}",
+            dent,
+            state,
+            TypeScriptVisitor::format_record(state_args),
+            TypeScriptVisitor::format_record(state_vars),
+            enter_args
+        )
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_machinery(&mut self, system_node: &SystemNode) {
+        self.newline();
+        self.newline();
+        self.add_code("//=============== Machinery and Mechanisms ==============//");
+        if system_node.get_first_state().is_some() {
+            if self.generate_transition_state {
+                let mut params = format!("newState: {}", self.format_state_type());
+                if self.generate_exit_args {
+                    params.push_str(", exitArgs: Record<string, unknown> | null");
+                }
+                if self.generate_state_context {
+                    params.push_str(&format!(
+                        ", stateContext: {}",
+                        self.format_state_context_type()
+                    ));
+                }
+                self.newline();
+                self.newline();
+                self.add_code(&format!("private _transition_({}): void {{", params));
+                self.indent();
+                self.newline();
+                if self.generate_exit_args {
+                    self.add_code("this._state_({ _message: \"<\", _parameters: exitArgs });");
+                } else {
+                    self.add_code("this._state_({ _message: \"<\", _parameters: null });");
+                }
+                self.newline();
+                self.add_code("this._state_ = newState;");
+                self.newline();
+                if self.generate_state_context {
+                    self.add_code("this._stateContext_ = stateContext;");
+                    self.newline();
+                    self.add_code(
+                        "this._state_({ _message: \">\", _parameters: stateContext.enterArgs });",
+                    );
+                } else {
+                    self.add_code("this._state_({ _message: \">\", _parameters: null });");
+                }
+                self.outdent();
+                self.newline();
+                self.add_code("}");
+            }
+            if self.generate_state_stack {
+                let stack_type = if self.generate_state_context {
+                    self.format_state_context_type()
+                } else {
+                    self.format_state_type()
+                };
+                self.newline();
+                self.newline();
+                self.add_code(&format!(
+                    "private _stateStack_push_(stateData: {}): void {{",
+                    stack_type
+                ));
+                self.indent();
+                self.newline();
+                self.add_code("this._stateStack_.push(stateData);");
+                self.outdent();
+                self.newline();
+                self.add_code("}");
+                self.newline();
+                self.newline();
+                self.add_code(&format!("private _stateStack_pop_(): {} {{", stack_type));
+                self.indent();
+                self.newline();
+                self.add_code("const stateData = this._stateStack_.pop();");
+                self.newline();
+                self.add_code("if (stateData === undefined) {");
+                self.indent();
+                self.newline();
+                self.add_code("throw new Error(\"State stack is empty.\");");
+                self.outdent();
+                self.newline();
+                self.add_code("}");
+                self.newline();
+                self.add_code("return stateData;");
+                self.outdent();
+                self.newline();
+                self.add_code("}");
+            }
+            if self.generate_change_state {
+                self.newline();
+                self.newline();
+                if self.generate_state_context {
+                    self.add_code(&format!(
+                        "private _changeState_(stateContext: {}): void {{",
+                        self.format_state_context_type()
+                    ));
+                    self.indent();
+                    self.newline();
+                    self.add_code("this._state_ = stateContext.state;");
+                    self.newline();
+                    self.add_code("this._stateContext_ = stateContext;");
+                } else {
+                    self.add_code(&format!(
+                        "private _changeState_(newState: {}): void {{",
+                        self.format_state_type()
+                    ));
+                    self.indent();
+                    self.newline();
+                    self.add_code("this._state_ = newState;");
+                }
+                self.outdent();
+                self.newline();
+                self.add_code("}");
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_comment(&mut self, line: usize) -> bool {
+        // can't use self.newline() or self.add_code() due to double borrow.
+        let mut generated_comment = false;
+        while self.current_comment_idx < self.comments.len()
+            && line >= self.comments[self.current_comment_idx].line
+        {
+            let comment = &self.comments[self.current_comment_idx];
+            if comment.token_type == TokenType::SingleLineComment {
+                self.code
+                    .push_str(&format!("  // {}", &comment.lexeme[3..]));
+                self.code.push_str(&format!(
+                    "\n{}",
+                    (0..self.dent).map(|_| "    ").collect::<String>()
+                ));
+            } else {
+                let len = &comment.lexeme.len() - 3;
+                self.code
+                    .push_str(&format!("/* {}", &comment.lexeme[3..len]));
+                self.code.push_str("*/");
+            }
+
+            self.current_comment_idx += 1;
+            generated_comment = true;
+        }
+
+        generated_comment
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Declares `stateContext` for the target state with its state arguments,
+    // state variables and enter arguments.
+
+    fn generate_new_state_context(
+        &mut self,
+        target_state_name: &str,
+        state_args_opt: &Option<ExprListNode>,
+        enter_args_opt: Option<&[(String, String)]>,
+    ) {
+        // -- State Arguments --
+
+        let mut state_args = Vec::new();
+        if let Some(state_args_node) = state_args_opt {
+            if let Some(state_sym) = self.arcanium.get_state(target_state_name) {
+                match &state_sym.borrow().params_opt {
+                    Some(event_params) => {
+                        let mut param_symbols_it = event_params.iter();
+                        // Loop through the ARGUMENTS...
+                        for expr_t in &state_args_node.exprs_t {
+                            // ...and validate w/ the PARAMETERS
+                            match param_symbols_it.next() {
+                                Some(param_symbol_rcref) => {
+                                    let param_symbol = param_symbol_rcref.borrow();
+                                    let mut expr = String::new();
+                                    expr_t.accept_to_string(self, &mut expr);
+                                    state_args.push((param_symbol.name.clone(), expr));
+                                }
+                                None => panic!(
+                                    "Invalid number of arguments for \"{}\" state parameters.",
+                                    target_state_name
+                                ),
+                            }
+                        }
+                    }
+                    None => {}
+                }
+            } else {
+                panic!("TODO");
+            }
+        }
+
+        // -- State Variables --
+
+        let state_vars = self.generate_state_vars(target_state_name);
+
+        let state_context = self.format_state_context(
+            &self.format_target_state_name(target_state_name),
+            &state_args,
+            &state_vars,
+            enter_args_opt,
+        );
+        self.add_code(&format!(
+            "const stateContext: {} = {};",
+            self.format_state_context_member_type(target_state_name),
+            state_context
+        ));
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_exit_args(&mut self, exit_args_opt: &Option<ExprListNode>) -> bool {
+        let exit_args = match exit_args_opt {
+            Some(exit_args) if !exit_args.exprs_t.is_empty() => exit_args,
+            _ => return false,
+        };
+
+        // Note - searching for event keyed with "State:<"
+        // e.g. "S1:<"
+
+        let mut msg: String = String::new();
+        if let Some(state_name) = &self.current_state_name_opt {
+            msg = state_name.clone();
+        }
+        msg.push(':');
+        msg.push_str(&self.symbol_config.exit_msg_symbol);
+
+        if let Some(event_sym) = self.arcanium.get_event(&msg, &self.current_state_name_opt) {
+            match &event_sym.borrow().params_opt {
+                Some(event_params) => {
+                    if exit_args.exprs_t.len() != event_params.len() {
+                        panic!("Fatal error: misaligned parameters to arguments.")
+                    }
+                    let mut param_symbols_it = event_params.iter();
+                    self.add_code("const exitArgs: Record<string, unknown> = {};");
+                    self.newline();
+                    // Loop through the ARGUMENTS...
+                    for expr_t in &exit_args.exprs_t {
+                        // ...and validate w/ the PARAMETERS
+                        match param_symbols_it.next() {
+                            Some(p) => {
+                                let mut expr = String::new();
+                                expr_t.accept_to_string(self, &mut expr);
+                                self.add_code(&format!("exitArgs[\"{}\"] = {};", p.name, expr));
+                                self.newline();
+                            }
+                            None => {
+                                panic!("Invalid number of arguments for \"{}\" event handler.", msg)
+                            }
+                        }
+                    }
+                }
+                None => panic!("Fatal error: misaligned parameters to arguments."),
+            }
+        } else {
+            panic!("TODO");
+        }
+        true
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_state_ref_change_state(
+        &mut self,
+        change_state_stmt_node: &ChangeStateStatementNode,
+    ) {
+        let (target_state_name, state_args_opt) = match &change_state_stmt_node.state_context_t {
+            StateContextType::StateRef { state_context_node } => (
+                &state_context_node.state_ref_node.name,
+                &state_context_node.state_ref_args_opt,
+            ),
+            _ => panic!("TODO"),
+        };
+
+        self.newline();
+        match &change_state_stmt_node.label_opt {
+            Some(label) => {
+                self.add_code(&format!("// {}", label));
+                self.newline();
+            }
+            None => {}
+        }
+        if self.generate_state_context {
+            self.generate_new_state_context(target_state_name, state_args_opt, None);
+            self.add_code("this._changeState_(stateContext);");
+        } else {
+            self.add_code(&format!(
+                "this._changeState_({});",
+                self.format_target_state_name(target_state_name)
+            ));
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn generate_state_ref_transition(&mut self, transition_statement: &TransitionStatementNode) {
+        let (target_state_name, state_args_opt, enter_args_opt) =
+            match &transition_statement.target_state_context_t {
+                StateContextType::StateRef { state_context_node } => (
+                    &state_context_node.state_ref_node.name,
+                    &state_context_node.state_ref_args_opt,
+                    &state_context_node.enter_args_opt,
+                ),
+                StateContextType::StateStackPop {} => {
+                    self.errors.push("Unknown error.".to_string());
+                    return;
+                }
+            };
+
+        self.newline();
+        match &transition_statement.label_opt {
+            Some(label) => {
+                self.add_code(&format!("// {}", label));
+                self.newline();
+            }
+            None => {}
+        }
+
+        let has_exit_args = self.generate_exit_args(&transition_statement.exit_args_opt);
+
+        // -- Enter Arguments --
+
+        let mut enter_arg_values_opt = None;
+        if let Some(enter_args) = enter_args_opt {
+            // Note - searching for event keyed with "State:>"
+            // e.g. "S1:>"
+
+            let mut msg: String = String::from(target_state_name);
+            msg.push(':');
+            msg.push_str(&self.symbol_config.enter_msg_symbol);
+
+            if let Some(event_sym) = self.arcanium.get_event(&msg, &self.current_state_name_opt) {
+                match &event_sym.borrow().params_opt {
+                    Some(event_params) => {
+                        if enter_args.exprs_t.len() != event_params.len() {
+                            panic!("Fatal error: misaligned parameters to arguments.")
+                        }
+                        let mut enter_arg_values = Vec::new();
+                        let mut param_symbols_it = event_params.iter();
+                        for expr_t in &enter_args.exprs_t {
+                            match param_symbols_it.next() {
+                                Some(p) => {
+                                    let mut expr = String::new();
+                                    expr_t.accept_to_string(self, &mut expr);
+                                    enter_arg_values.push((p.name.clone(), expr));
+                                }
+                                None => panic!(
+                                    "Invalid number of arguments for \"{}\" event handler.",
+                                    msg
+                                ),
+                            }
+                        }
+                        enter_arg_values_opt = Some(enter_arg_values);
+                    }
+                    None => panic!("Invalid number of arguments for \"{}\" event handler.", msg),
+                }
+            } else {
+                self.warnings.push(format!("State {} does not have an enter event handler but is being passed parameters in a transition", target_state_name));
+            }
+        }
+
+        if self.generate_state_context {
+            self.generate_new_state_context(
+                target_state_name,
+                state_args_opt,
+                enter_arg_values_opt.as_deref(),
+            );
+        }
+
+        let mut args = vec![self.format_target_state_name(target_state_name)];
+        if self.generate_exit_args {
+            let exit_args = if has_exit_args { "exitArgs" } else { "null" };
+            args.push(exit_args.to_string());
+        }
+        if self.generate_state_context {
+            args.push("stateContext".to_string());
+        }
+        self.add_code(&format!("this._transition_({});", args.join(", ")));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn format_target_state_name(&self, state_name: &str) -> String {
+        format!("this._s{}_", state_name)
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // NOTE!!: it is *currently* disallowed to send state or event arguments to a state stack pop target.
+    // So currently this method just sets any exitArgs and pops the context from the state stack.
+
+    fn generate_state_stack_pop_transition(
+        &mut self,
+        transition_statement: &TransitionStatementNode,
+    ) {
+        self.newline();
+        match &transition_statement.label_opt {
+            Some(label) => {
+                self.add_code(&format!("// {}", label));
+                self.newline();
+            }
+            None => {}
+        }
+
+        let has_exit_args = self.generate_exit_args(&transition_statement.exit_args_opt);
+
+        let mut args = Vec::new();
+        if self.generate_state_context {
+            self.add_code("const stateContext = this._stateStack_pop_();");
+            args.push("stateContext.state".to_string());
+        } else {
+            self.add_code("const state = this._stateStack_pop_();");
+            args.push("state".to_string());
+        }
+        self.newline();
+        if self.generate_exit_args {
+            let exit_args = if has_exit_args { "exitArgs" } else { "null" };
+            args.push(exit_args.to_string());
+        }
+        if self.generate_state_context {
+            args.push("stateContext".to_string());
+        }
+        self.add_code(&format!("this._transition_({});", args.join(", ")));
+    }
+}
+
+//* --------------------------------------------------------------------- *//
+
+impl AstVisitor for TypeScriptVisitor {
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_system_node(&mut self, system_node: &SystemNode) {
+        self.system_name = system_node.name.clone();
+        if system_node.has_deferred_events() {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the typescript target.",
+            ));
+        }
+        self.add_code(&format!("// {}", self.compiler_version));
+        self.newline();
+        self.newline();
+        // domain enums
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            for enum_decl_node_rcref in &domain_block_node.enums {
+                enum_decl_node_rcref.borrow().accept(self);
+            }
+        }
+
+        // First state name needed for machinery.
+        // Don't generate if there isn't at least one state.
+        match system_node.get_first_state() {
+            Some(x) => {
+                self.first_state_name = x.borrow().name.clone();
+                self.has_states = true;
+            }
+            None => {}
+        }
+
+        self.generate_frame_event_type(system_node);
+
+        self.add_code(&format!("export interface {}Actions {{", system_node.name));
+        self.indent();
+        if let Some(actions_block_node) = &system_node.actions_block_node_opt {
+            actions_block_node.accept(self);
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+        self.newline();
+
+        if self.has_states {
+            self.generate_state_context_class(system_node);
+        }
+
+        self.add_code(&format!("export class {} {{", system_node.name));
+        self.indent();
+
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            domain_block_node.accept(self);
+        }
+
+        self.newline();
+        self.add_code(&format!(
+            "private readonly actions: {}Actions;",
+            system_node.name
+        ));
+        if self.has_states {
+            self.generate_machinery_fields();
+        }
+        self.generate_constructor();
+
+        if let Some(interface_block_node) = &system_node.interface_block_node_opt {
+            interface_block_node.accept(self);
+        }
+
+        if let Some(machine_block_node) = &system_node.machine_block_node_opt {
+            machine_block_node.accept(self);
+        }
+
+        if self.has_states {
+            self.generate_machinery(system_node);
+        }
+
+        if self.generate_comment(system_node.line) {
+            self.newline();
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_frame_messages_enum(&mut self, _interface_block_node: &InterfaceBlockNode) {
+        panic!("Error - visit_frame_messages_enum() only used in Rust.");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_interface_parameters(&mut self, _interface_block_node: &InterfaceBlockNode) {
+        panic!("visit_interface_parameters() not valid for target language.");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_interface_method_call_expression_node(
+        &mut self,
+        interface_method_call_expr_node: &InterfaceMethodCallExprNode,
+    ) {
+        self.add_code(&format!(
+            "this.{}",
+            interface_method_call_expr_node.identifier.name.lexeme
+        ));
+        interface_method_call_expr_node.call_expr_list.accept(self);
+
+        // TODO: review this return as I think it is a nop.
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_interface_method_call_expression_node_to_string(
+        &mut self,
+        interface_method_call_expr_node: &InterfaceMethodCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "this.{}",
+            interface_method_call_expr_node.identifier.name.lexeme
+        ));
+        interface_method_call_expr_node
+            .call_expr_list
+            .accept_to_string(self, output);
+
+        // TODO: review this return as I think it is a nop.
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_interface_block_node(&mut self, interface_block_node: &InterfaceBlockNode) {
+        self.newline();
+        self.newline();
+        self.add_code("//===================== Interface Block ===================//");
+        self.newline();
+
+        for interface_method_node_rcref in &interface_block_node.interface_methods {
+            let interface_method_node = interface_method_node_rcref.borrow();
+            interface_method_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_interface_method_node(&mut self, interface_method_node: &InterfaceMethodNode) {
+        let mut send_params = String::new();
+
+        match &interface_method_node.params {
+            Some(params) => {
+                let mut separator = "";
+                for param in params {
+                    let pname = &param.param_name;
+                    send_params.push_str(&format!("{}{}: {}", separator, pname, pname));
+                    separator = ", ";
+                }
+            }
+            None => {}
+        }
+
+        // see if an alias exists.
+        let method_name_or_alias = match &interface_method_node.alias {
+            Some(alias_message_node) => &alias_message_node.name,
+            None => &interface_method_node.name,
+        };
+
+        let return_type = match &interface_method_node.return_type_opt {
+            Some(return_type) => return_type.get_type_str(),
+            None => String::from("void"),
+        };
+
+        self.newline();
+        self.add_code(&format!(
+            "{}({}): {} {{",
+            interface_method_node.name,
+            TypeScriptVisitor::format_params(&interface_method_node.params),
+            return_type
+        ));
+        self.indent();
+        self.newline();
+        if interface_method_node.params.is_some() {
+            self.add_code(&format!(
+                "const e: {} = {{ _message: {:?}, _parameters: {{ {} }} }};",
+                self.format_frame_event_type(),
+                method_name_or_alias,
+                send_params
+            ));
+        } else {
+            self.add_code(&format!(
+                "const e: {} = {{ _message: {:?}, _parameters: null }};",
+                self.format_frame_event_type(),
+                method_name_or_alias
+            ));
+        }
+        if self.has_states {
+            self.newline();
+            self.add_code("this._state_(e);");
+        }
+
+        match &interface_method_node.return_type_opt {
+            Some(return_type) => {
+                self.newline();
+                self.add_code(&format!(
+                    "return e._return as {};",
+                    return_type.get_type_str()
+                ));
+            }
+            None => {}
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_machine_block_node(&mut self, machine_block_node: &MachineBlockNode) {
+        self.newline();
+        self.add_code("//===================== Machine Block ===================//");
+
+        for state_node_rcref in &machine_block_node.states {
+            state_node_rcref.borrow().accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_actions_block_node(&mut self, actions_block_node: &ActionsBlockNode) {
+        for action_decl_node_rcref in &actions_block_node.actions {
+            let action_decl_node = action_decl_node_rcref.borrow();
+            action_decl_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_action_node_rust_trait(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_action_node_rust_trait() not implemented.");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_actions_node_rust_impl(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_actions_node_rust_impl() not implemented.");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_decl_node(&mut self, enum_decl_node: &EnumDeclNode) {
+        let enumerators: Vec<String> = enum_decl_node
+            .enumerators
+            .iter()
+            .map(|enumerator| format!("{} = \"{}\"", enumerator, enumerator))
+            .collect();
+        self.add_code(&format!(
+            "export enum {} {{ {} }}",
+            enum_decl_node.name,
+            enumerators.join(", ")
+        ));
+        self.newline();
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_domain_block_node(&mut self, domain_block_node: &DomainBlockNode) {
+        for variable_decl_node_rcref in &domain_block_node.member_variables {
+            let variable_decl_node = variable_decl_node_rcref.borrow();
+            variable_decl_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_state_node(&mut self, state_node: &StateNode) {
+        self.generate_comment(state_node.line);
+        self.current_state_name_opt = Some(state_node.name.clone());
+        self.newline();
+        self.newline();
+        self.add_code(&format!(
+            "private _s{}_(e: {}): void {{",
+            state_node.name,
+            self.format_frame_event_type()
+        ));
+        self.indent();
+        self.generate_this_state_context(state_node);
+
+        if let Some(calls) = &state_node.calls_opt {
+            for call in calls {
+                self.newline();
+                call.accept(self);
+                self.add_code(";");
+            }
+        }
+
+        self.first_event_handler = true; // context for formatting
+
+        if !state_node.evt_handlers_rcref.is_empty() {
+            for evt_handler_node in &state_node.evt_handlers_rcref {
+                evt_handler_node.as_ref().borrow().accept(self);
+            }
+        }
+
+        match &state_node.dispatch_opt {
+            Some(dispatch) => {
+                dispatch.accept(self);
+            }
+            None => {}
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+
+        self.current_state_name_opt = None;
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_event_handler_node(&mut self, evt_handler_node: &EventHandlerNode) {
+        self.newline();
+        self.generate_comment(evt_handler_node.line);
+        if let MessageType::CustomMessage { message_node } = &evt_handler_node.msg_t {
+            // Comparing on the message narrows `e` to its FrameEvent member.
+            if self.first_event_handler {
+                self.add_code(&format!("if (e._message === {:?}) {{", message_node.name));
+            } else {
+                self.add_code(&format!(
+                    "else if (e._message === {:?}) {{",
+                    message_node.name
+                ));
+            }
+        } else {
+            // AnyMessage ( ||* )
+            if self.first_event_handler {
+                // This logic is for when there is only the catch all event handler ||*
+                self.add_code("if (true) {");
+            } else {
+                // other event handlers preceded ||*
+                self.add_code("else {");
+            }
+        }
+        self.generate_comment(evt_handler_node.line);
+
+        {
+            let event_symbol = evt_handler_node.event_symbol_rcref.borrow();
+            self.current_event_is_enter_or_exit =
+                event_symbol.is_enter_msg || event_symbol.is_exit_msg;
+            self.current_event_is_enter = event_symbol.is_enter_msg;
+            self.current_event_param_types.clear();
+            if let Some(params) = &event_symbol.params_opt {
+                for param in params {
+                    self.current_event_param_types.insert(
+                        param.name.clone(),
+                        TypeScriptVisitor::format_type(&param.param_type_opt),
+                    );
+                }
+            }
+        }
+
+        self.indent();
+
+        // Generate statements
+        self.visit_decl_stmts(&evt_handler_node.statements);
+
+        let terminator_node = &evt_handler_node.terminator_node;
+        terminator_node.accept(self);
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+
+        // this controls formatting here
+        self.first_event_handler = false;
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_event_handler_terminator_node(
+        &mut self,
+        evt_handler_terminator_node: &TerminatorExpr,
+    ) {
+        self.newline();
+        match &evt_handler_terminator_node.terminator_type {
+            TerminatorType::Return => match &evt_handler_terminator_node.return_expr_t_opt {
+                Some(expr_t) => {
+                    self.add_code("e._return = ");
+                    expr_t.accept(self);
+                    self.add_code(";");
+                    self.newline();
+                    self.add_code("return;");
+                    self.newline();
+                }
+                None => self.add_code("return;"),
+            },
+            TerminatorType::Continue => {
+                // self.add_code("break;")
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_call_statement_node(&mut self, method_call_statement: &CallStmtNode) {
+        self.newline();
+        method_call_statement.call_expr_node.accept(self);
+        self.add_code(";");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_call_expression_node(&mut self, method_call: &CallExprNode) {
+        if let Some(call_chain) = &method_call.call_chain {
+            for callable in call_chain {
+                callable.callable_accept(self);
+                self.add_code(".");
+            }
+        }
+
+        self.add_code(&method_call.identifier.name.lexeme.to_string());
+
+        method_call.call_expr_list.accept(self);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_call_expression_node_to_string(
+        &mut self,
+        method_call: &CallExprNode,
+        output: &mut String,
+    ) {
+        if let Some(call_chain) = &method_call.call_chain {
+            for callable in call_chain {
+                callable.callable_accept(self);
+                output.push('.');
+            }
+        }
+
+        output.push_str(&method_call.identifier.name.lexeme.to_string());
+
+        method_call.call_expr_list.accept_to_string(self, output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_call_expr_list_node(&mut self, call_expr_list: &CallExprListNode) {
+        let mut separator = "";
+        self.add_code("(");
+
+        for expr in &call_expr_list.exprs_t {
+            self.add_code(separator);
+            expr.accept(self);
+            separator = ",";
+        }
+
+        self.add_code(")");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_call_expr_list_node_to_string(
+        &mut self,
+        call_expr_list: &CallExprListNode,
+        output: &mut String,
+    ) {
+        let mut separator = "";
+        output.push('(');
+
+        for expr in &call_expr_list.exprs_t {
+            output.push_str(separator);
+            expr.accept_to_string(self, output);
+            separator = ",";
+        }
+
+        output.push(')');
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_action_call_expression_node(&mut self, action_call: &ActionCallExprNode) {
+        let action_name = self.format_action_name(&action_call.identifier.name.lexeme);
+        self.add_code(&action_name);
+        action_call.call_expr_list.accept(self);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_action_call_expression_node_to_string(
+        &mut self,
+        action_call: &ActionCallExprNode,
+        output: &mut String,
+    ) {
+        let action_name = self.format_action_name(&action_call.identifier.name.lexeme);
+        output.push_str(&action_name);
+        action_call.call_expr_list.accept_to_string(self, output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_action_call_statement_node(&mut self, action_call_stmt_node: &ActionCallStmtNode) {
+        self.newline();
+        action_call_stmt_node.action_call_expr_node.accept(self);
+        self.add_code(";");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_transition_statement_node(&mut self, transition_statement: &TransitionStatementNode) {
+        match &transition_statement.target_state_context_t {
+            StateContextType::StateRef { .. } => {
+                self.generate_state_ref_transition(transition_statement)
+            }
+            StateContextType::StateStackPop {} => {
+                self.generate_state_stack_pop_transition(transition_statement)
+            }
+        };
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_state_ref_node(&mut self, state_ref: &StateRefNode) {
+        self.add_code(&state_ref.name.to_string());
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_change_state_statement_node(
+        &mut self,
+        change_state_stmt_node: &ChangeStateStatementNode,
+    ) {
+        match &change_state_stmt_node.state_context_t {
+            StateContextType::StateRef { .. } => {
+                self.generate_state_ref_change_state(change_state_stmt_node)
+            }
+            StateContextType::StateStackPop {} => {
+                self.newline();
+                self.add_code("this._changeState_(this._stateStack_pop_());");
+            }
+        };
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // TODO: ??
+    fn visit_parameter_node(&mut self, _parameter_node: &ParameterNode) {
+        // self.add_code(&format!("{}",parameter_node.name));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_dispatch_node(&mut self, dispatch_node: &DispatchNode) {
+        self.newline();
+        self.add_code(&format!(
+            "{}(e);",
+            self.format_target_state_name(&dispatch_node.target_state_ref.name)
+        ));
+        self.generate_comment(dispatch_node.line);
+        self.newline();
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_test_statement_node(&mut self, test_stmt_node: &TestStatementNode) {
+        match &test_stmt_node.test_t {
+            TestType::BoolTest { bool_test_node } => {
+                bool_test_node.accept(self);
+            }
+            TestType::StringMatchTest {
+                string_match_test_node,
+            } => {
+                string_match_test_node.accept(self);
+            }
+            TestType::NumberMatchTest {
+                number_match_test_node,
+            } => {
+                number_match_test_node.accept(self);
+            }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                regex_match_test_node.accept(self);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_bool_test_node(&mut self, bool_test_node: &BoolTestNode) {
+        let mut if_or_else_if = "if ";
+
+        self.newline();
+        for branch_node in &bool_test_node.conditional_branch_nodes {
+            if branch_node.is_negated {
+                self.add_code(&format!("{}(!(", if_or_else_if));
+            } else {
+                self.add_code(&format!("{}(", if_or_else_if));
+            }
+
+            branch_node.expr_t.accept(self);
+
+            if branch_node.is_negated {
+                self.add_code(")");
+            }
+            self.add_code(") {");
+            self.indent();
+
+            branch_node.accept(self);
+
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+
+            if_or_else_if = " else if ";
+        }
+
+        // (':' bool_test_else_branch)?
+        if let Some(bool_test_else_branch_node) = &bool_test_node.else_branch_node_opt {
+            bool_test_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_call_chain_literal_statement_node(
+        &mut self,
+        method_call_chain_literal_stmt_node: &CallChainLiteralStmtNode,
+    ) {
+        self.newline();
+        method_call_chain_literal_stmt_node
+            .call_chain_literal_expr_node
+            .accept(self);
+        self.add_code(";");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_call_chain_literal_expr_node(
+        &mut self,
+        method_call_chain_expression_node: &CallChainLiteralExprNode,
+    ) {
+        // TODO: maybe put this in an AST node
+
+        let mut separator = "";
+
+        for node in &method_call_chain_expression_node.call_chain {
+            self.add_code(separator);
+            match &node {
+                CallChainLiteralNodeType::IdentifierNodeT { id_node } => {
+                    id_node.accept(self);
+                }
+                CallChainLiteralNodeType::CallT { call } => {
+                    call.accept(self);
+                }
+                CallChainLiteralNodeType::InterfaceMethodCallT {
+                    interface_method_call_expr_node,
+                } => {
+                    interface_method_call_expr_node.accept(self);
+                }
+                CallChainLiteralNodeType::ActionCallT {
+                    action_call_expr_node,
+                } => {
+                    action_call_expr_node.accept(self);
+                }
+                CallChainLiteralNodeType::VariableNodeT { var_node } => {
+                    var_node.accept(self);
+                }
+            }
+            separator = ".";
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_call_chain_literal_expr_node_to_string(
+        &mut self,
+        method_call_chain_expression_node: &CallChainLiteralExprNode,
+        output: &mut String,
+    ) {
+        let mut separator = "";
+
+        for node in &method_call_chain_expression_node.call_chain {
+            output.push_str(separator);
+            match &node {
+                CallChainLiteralNodeType::IdentifierNodeT { id_node } => {
+                    id_node.accept_to_string(self, output);
+                }
+                CallChainLiteralNodeType::CallT { call } => {
+                    call.accept_to_string(self, output);
+                }
+                CallChainLiteralNodeType::InterfaceMethodCallT {
+                    interface_method_call_expr_node,
+                } => {
+                    interface_method_call_expr_node.accept_to_string(self, output);
+                }
+                CallChainLiteralNodeType::ActionCallT {
+                    action_call_expr_node,
+                } => {
+                    action_call_expr_node.accept_to_string(self, output);
+                }
+                CallChainLiteralNodeType::VariableNodeT { var_node } => {
+                    var_node.accept_to_string(self, output);
+                }
+            }
+            separator = ".";
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_bool_test_conditional_branch_node(
+        &mut self,
+        bool_test_true_branch_node: &BoolTestConditionalBranchNode,
+    ) {
+        self.visit_decl_stmts(&bool_test_true_branch_node.statements);
+
+        match &bool_test_true_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_bool_test_else_branch_node(
+        &mut self,
+        bool_test_else_branch_node: &BoolTestElseBranchNode,
+    ) {
+        self.add_code(" else {");
+        self.indent();
+
+        self.visit_decl_stmts(&bool_test_else_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &bool_test_else_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_string_match_test_node(&mut self, string_match_test_node: &StringMatchTestNode) {
+        let mut if_or_else_if = "if";
+
+        self.newline();
+
+        for match_branch_node in &string_match_test_node.match_branch_nodes {
+            self.add_code(&format!("{} ((", if_or_else_if));
+            // TODO: use string_match_test_node.expr_t.accept(self) ?
+            match &string_match_test_node.expr_t {
+                ExprType::CallExprT {
+                    call_expr_node: method_call_expr_node,
+                } => method_call_expr_node.accept(self),
+                ExprType::ActionCallExprT {
+                    action_call_expr_node,
+                } => action_call_expr_node.accept(self),
+                ExprType::CallChainLiteralExprT {
+                    call_chain_expr_node,
+                } => call_chain_expr_node.accept(self),
+                ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+
+                _ => panic!("TODO"),
+            }
+
+            // TODO: use accept
+            // self.add_code(&format!(" == \""));
+            // match_branch_node.string_match_pattern_node.accept(self);
+            // self.add_code(&format!("\") {{"));
+
+            let mut first_match = true;
+            for match_string in &match_branch_node
+                .string_match_pattern_node
+                .match_pattern_strings
+            {
+                if first_match {
+                    self.add_code(&format!(" == \"{}\")", match_string));
+                    first_match = false;
+                } else {
+                    self.add_code(" || (");
+                    match &string_match_test_node.expr_t {
+                        ExprType::CallExprT {
+                            call_expr_node: method_call_expr_node,
+                        } => method_call_expr_node.accept(self),
+                        ExprType::ActionCallExprT {
+                            action_call_expr_node,
+                        } => action_call_expr_node.accept(self),
+                        ExprType::CallChainLiteralExprT {
+                            call_chain_expr_node,
+                        } => call_chain_expr_node.accept(self),
+                        ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                        _ => panic!("TODO"),
+                    }
+                    self.add_code(&format!(" == \"{}\")", match_string));
+                }
+            }
+            self.add_code(") {");
+            self.indent();
+
+            match_branch_node.accept(self);
+
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+
+            if_or_else_if = " else if";
+        }
+
+        // (':' string_test_else_branch)?
+        if let Some(string_match_else_branch_node) = &string_match_test_node.else_branch_node_opt {
+            string_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_string_match_test_match_branch_node(
+        &mut self,
+        string_match_test_match_branch_node: &StringMatchTestMatchBranchNode,
+    ) {
+        //        self.indent();
+        self.visit_decl_stmts(&string_match_test_match_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &string_match_test_match_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_string_match_test_else_branch_node(
+        &mut self,
+        string_match_test_else_branch_node: &StringMatchTestElseBranchNode,
+    ) {
+        self.add_code(" else {");
+        self.indent();
+
+        self.visit_decl_stmts(&string_match_test_else_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &string_match_test_else_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        let mut if_or_else_if = "if";
+
+        self.newline();
+
+        for match_branch_node in &enum_match_test_node.match_branch_nodes {
+            self.add_code(&format!("{} ((", if_or_else_if));
+            match &enum_match_test_node.expr_t {
+                ExprType::CallExprT {
+                    call_expr_node: method_call_expr_node,
+                } => method_call_expr_node.accept(self),
+                ExprType::ActionCallExprT {
+                    action_call_expr_node,
+                } => action_call_expr_node.accept(self),
+                ExprType::CallChainLiteralExprT {
+                    call_chain_expr_node,
+                } => call_chain_expr_node.accept(self),
+                ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+
+                _ => panic!("TODO"),
+            }
+
+            let mut first_match = true;
+            for enumerator in &match_branch_node.enumerators {
+                if first_match {
+                    self.add_code(&format!(
+                        " == {}.{})",
+                        enum_match_test_node.enum_type_name, enumerator
+                    ));
+                    first_match = false;
+                } else {
+                    self.add_code(" || (");
+                    match &enum_match_test_node.expr_t {
+                        ExprType::CallExprT {
+                            call_expr_node: method_call_expr_node,
+                        } => method_call_expr_node.accept(self),
+                        ExprType::ActionCallExprT {
+                            action_call_expr_node,
+                        } => action_call_expr_node.accept(self),
+                        ExprType::CallChainLiteralExprT {
+                            call_chain_expr_node,
+                        } => call_chain_expr_node.accept(self),
+                        ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                        _ => panic!("TODO"),
+                    }
+                    self.add_code(&format!(
+                        " == {}.{})",
+                        enum_match_test_node.enum_type_name, enumerator
+                    ));
+                }
+            }
+            self.add_code(") {");
+            self.indent();
+
+            match_branch_node.accept(self);
+
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+
+            if_or_else_if = " else if";
+        }
+
+        // (':' enum_test_else_branch)?
+        if let Some(enum_match_else_branch_node) = &enum_match_test_node.else_branch_node_opt {
+            enum_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_match_branch_node(
+        &mut self,
+        enum_match_test_match_branch_node: &EnumMatchTestMatchBranchNode,
+    ) {
+        //        self.indent();
+        self.visit_decl_stmts(&enum_match_test_match_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &enum_match_test_match_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enum_match_test_else_branch_node(
+        &mut self,
+        enum_match_test_else_branch_node: &EnumMatchTestElseBranchNode,
+    ) {
+        self.add_code(" else {");
+        self.indent();
+
+        self.visit_decl_stmts(&enum_match_test_else_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &enum_match_test_else_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // The match result is scoped to a block so that several regex tests
+    // can appear in the same event handler.
+
+    fn visit_regex_match_test_node(&mut self, regex_match_test_node: &RegexMatchTestNode) {
+        let mut if_or_else_if = "if";
+
+        self.newline();
+        self.add_code("{");
+        self.indent();
+        self.newline();
+        self.add_code("let regex_match: RegExpExecArray | null;");
+        self.newline();
+        for match_branch_node in &regex_match_test_node.match_branch_nodes {
+            let pattern_node = &match_branch_node.regex_match_pattern_node;
+            self.add_code(&format!(
+                "{} ((regex_match = new RegExp({:?}).exec(",
+                if_or_else_if,
+                pattern_node.pattern_with_group_prefix("?<")
+            ));
+            match &regex_match_test_node.expr_t {
+                ExprType::CallExprT {
+                    call_expr_node: method_call_expr_node,
+                } => method_call_expr_node.accept(self),
+                ExprType::ActionCallExprT {
+                    action_call_expr_node,
+                } => action_call_expr_node.accept(self),
+                ExprType::CallChainLiteralExprT {
+                    call_chain_expr_node,
+                } => call_chain_expr_node.accept(self),
+                ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                ExprType::ExprListT { expr_list_node } => {
+                    // must be only 1 expression in the list
+                    if expr_list_node.exprs_t.len() != 1 {
+                        self.errors
+                            .push("Error - expression list is not testable.".to_string());
+                    }
+                    let x = expr_list_node.exprs_t.first().unwrap();
+                    x.accept(self);
+                }
+                _ => self.errors.push("TODO".to_string()),
+            }
+            self.add_code(")) !== null) {");
+            self.indent();
+
+            // bind named capture groups to locals
+            for capture_name in &pattern_node.capture_names {
+                self.newline();
+                self.add_code(&format!(
+                    "let {} = regex_match.groups?.{} ?? \"\";",
+                    capture_name, capture_name
+                ));
+            }
+
+            match_branch_node.accept(self);
+
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+
+            if_or_else_if = " else if";
+        }
+
+        // (':' regex_test_else_branch)?
+        if let Some(regex_match_else_branch_node) = &regex_match_test_node.else_branch_node_opt {
+            regex_match_else_branch_node.accept(self);
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_regex_match_test_match_branch_node(
+        &mut self,
+        regex_match_test_match_branch_node: &RegexMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&regex_match_test_match_branch_node.statements);
+
+        if let Some(branch_terminator_expr) =
+            &regex_match_test_match_branch_node.branch_terminator_expr_opt
+        {
+            self.newline();
+            match &branch_terminator_expr.terminator_type {
+                TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                    Some(expr_t) => {
+                        self.add_code("e._return = ");
+                        expr_t.accept(self);
+                        self.add_code(";");
+                        self.newline();
+                        self.add_code("return;");
+                    }
+                    None => self.add_code("return;"),
+                },
+                TerminatorType::Continue => {
+                    self.add_code("break;");
+                }
+            }
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_regex_match_test_else_branch_node(
+        &mut self,
+        regex_match_test_else_branch_node: &RegexMatchTestElseBranchNode,
+    ) {
+        self.add_code(" else {");
+        self.indent();
+
+        self.visit_decl_stmts(&regex_match_test_else_branch_node.statements);
+
+        if let Some(branch_terminator_expr) =
+            &regex_match_test_else_branch_node.branch_terminator_expr_opt
+        {
+            self.newline();
+            match &branch_terminator_expr.terminator_type {
+                TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                    Some(expr_t) => {
+                        self.add_code("e._return = ");
+                        expr_t.accept(self);
+                        self.add_code(";");
+                        self.newline();
+                        self.add_code("return;");
+                    }
+                    None => self.add_code("return;"),
+                },
+                TerminatorType::Continue => {
+                    self.add_code("break;");
+                }
+            }
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_string_match_test_pattern_node(
+        &mut self,
+        _string_match_test_else_branch_node: &StringMatchTestPatternNode,
+    ) {
+        // TODO
+        panic!("todo");
+    }
+
+    //-----------------------------------------------------//
+
+    fn visit_number_match_test_node(&mut self, number_match_test_node: &NumberMatchTestNode) {
+        let mut if_or_else_if = "if";
+
+        self.newline();
+        for match_branch_node in &number_match_test_node.match_branch_nodes {
+            self.add_code(&format!("{} ((", if_or_else_if));
+            match &number_match_test_node.expr_t {
+                ExprType::CallExprT {
+                    call_expr_node: method_call_expr_node,
+                } => method_call_expr_node.accept(self),
+                ExprType::ActionCallExprT {
+                    action_call_expr_node,
+                } => action_call_expr_node.accept(self),
+                ExprType::CallChainLiteralExprT {
+                    call_chain_expr_node,
+                } => call_chain_expr_node.accept(self),
+                ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                _ => panic!("TODO"),
+            }
+
+            let mut first_match = true;
+            for match_number in &match_branch_node.number_match_pattern_nodes {
+                if first_match {
+                    self.add_code(&format!(" == {})", match_number.match_pattern_number));
+                    first_match = false;
+                } else {
+                    self.add_code(" || (");
+                    match &number_match_test_node.expr_t {
+                        ExprType::CallExprT {
+                            call_expr_node: method_call_expr_node,
+                        } => method_call_expr_node.accept(self),
+                        ExprType::ActionCallExprT {
+                            action_call_expr_node,
+                        } => action_call_expr_node.accept(self),
+                        ExprType::CallChainLiteralExprT {
+                            call_chain_expr_node,
+                        } => call_chain_expr_node.accept(self),
+                        ExprType::VariableExprT { var_node: id_node } => id_node.accept(self),
+                        _ => panic!("TODO"),
+                    }
+                    self.add_code(&format!(" == {})", match_number.match_pattern_number));
+                }
+            }
+
+            self.add_code(") {");
+            self.indent();
+
+            match_branch_node.accept(self);
+
+            self.outdent();
+            self.newline();
+            self.add_code("}");
+
+            //           self.indent();
+
+            if_or_else_if = " else if";
+        }
+
+        // (':' number_test_else_branch)?
+        if let Some(number_match_else_branch_node) = &number_match_test_node.else_branch_node_opt {
+            number_match_else_branch_node.accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_number_match_test_match_branch_node(
+        &mut self,
+        number_match_test_match_branch_node: &NumberMatchTestMatchBranchNode,
+    ) {
+        self.visit_decl_stmts(&number_match_test_match_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &number_match_test_match_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_number_match_test_else_branch_node(
+        &mut self,
+        number_match_test_else_branch_node: &NumberMatchTestElseBranchNode,
+    ) {
+        self.add_code(" else {");
+        self.indent();
+
+        self.visit_decl_stmts(&number_match_test_else_branch_node.statements);
+
+        // TODO - factor this out to work w/ other terminator code.
+        match &number_match_test_else_branch_node.branch_terminator_expr_opt {
+            Some(branch_terminator_expr) => {
+                self.newline();
+                match &branch_terminator_expr.terminator_type {
+                    TerminatorType::Return => match &branch_terminator_expr.return_expr_t_opt {
+                        Some(expr_t) => {
+                            self.add_code("e._return = ");
+                            expr_t.accept(self);
+                            self.add_code(";");
+                            self.newline();
+                            self.add_code("return;");
+                        }
+                        None => self.add_code("return;"),
+                    },
+                    TerminatorType::Continue => {
+                        self.add_code("break;");
+                    }
+                }
+            }
+            None => {}
+        }
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_number_match_test_pattern_node(
+        &mut self,
+        match_pattern_node: &NumberMatchTestPatternNode,
+    ) {
+        self.add_code(&match_pattern_node.match_pattern_number.to_string());
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_expression_list_node(&mut self, expr_list: &ExprListNode) {
+        let mut separator = "";
+        self.add_code("(");
+        for expr in &expr_list.exprs_t {
+            self.add_code(separator);
+            expr.accept(self);
+            separator = ",";
+        }
+        self.add_code(")");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_expression_list_node_to_string(
+        &mut self,
+        expr_list: &ExprListNode,
+        output: &mut String,
+    ) {
+        //        self.add_code(&format!("{}(e);\n",dispatch_node.target_state_ref.name));
+
+        let mut separator = "";
+        output.push('(');
+        for expr in &expr_list.exprs_t {
+            output.push_str(separator);
+            expr.accept_to_string(self, output);
+            separator = ",";
+        }
+        output.push(')');
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_literal_expression_node(&mut self, literal_expression_node: &LiteralExprNode) {
+        match &literal_expression_node.token_t {
+            TokenType::Number => self.add_code(&literal_expression_node.value.to_string()),
+            TokenType::SuperString => self.add_code(&literal_expression_node.value.to_string()),
+            TokenType::String => self.add_code(&format!("\"{}\"", literal_expression_node.value)),
+            TokenType::True => self.add_code("true"),
+            TokenType::False => self.add_code("false"),
+            TokenType::Null => self.add_code("null"),
+            TokenType::Nil => self.add_code("null"),
+            _ => panic!("TODO"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_literal_expression_node_to_string(
+        &mut self,
+        literal_expression_node: &LiteralExprNode,
+        output: &mut String,
+    ) {
+        // TODO: make a focused enum or the literals
+        match &literal_expression_node.token_t {
+            TokenType::Number => output.push_str(&literal_expression_node.value.to_string()),
+            TokenType::SuperString => output.push_str(&literal_expression_node.value.to_string()),
+            TokenType::String => {
+                output.push_str(&format!("\"{}\"", literal_expression_node.value));
+            }
+            TokenType::True => {
+                output.push_str("true");
+            }
+            TokenType::False => {
+                output.push_str("false");
+            }
+            TokenType::Nil => {
+                output.push_str("null");
+            }
+            TokenType::Null => {
+                output.push_str("null");
+            }
+            _ => panic!("TODO"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_identifier_node(&mut self, identifier_node: &IdentifierNode) {
+        self.add_code(&identifier_node.name.lexeme.to_string());
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_identifier_node_to_string(
+        &mut self,
+        identifier_node: &IdentifierNode,
+        output: &mut String,
+    ) {
+        output.push_str(&identifier_node.name.lexeme.to_string());
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_state_stack_operation_node(
+        &mut self,
+        _state_stack_operation_node: &StateStackOperationNode,
+    ) {
+        //        self.add_code(&format!("{}",identifier_node.name.lexeme));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_state_stack_operation_node_to_string(
+        &mut self,
+        _state_stack_operation_node: &StateStackOperationNode,
+        _output: &mut String,
+    ) {
+        //        self.add_code(&format!("{}",identifier_node.name.lexeme));
+    }
+
+    //* --------------------------------------------------------------------- *//
+    //
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        self.newline();
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(expr_t) => {
+                self.add_code("while (");
+                expr_t.accept(self);
+                self.add_code(") {");
+            }
+            None => self.add_code("while (true) {"),
+        }
+        self.indent();
+
+        self.visit_decl_stmts(&loop_stmt_node.statements);
+
+        self.outdent();
+        self.newline();
+        self.add_code("}");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_loop_control_stmt_node(&mut self, loop_control_stmt_node: &LoopControlStmtNode) {
+        self.newline();
+        match loop_control_stmt_node.loop_control_t {
+            LoopControlType::Break => self.add_code("break;"),
+            LoopControlType::Continue => self.add_code("continue;"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_state_stack_operation_statement_node(
+        &mut self,
+        state_stack_op_statement_node: &StateStackOperationStatementNode,
+    ) {
+        //        self.add_code(&format!("{}",identifier_node.name.lexeme));
+
+        //       panic!("TODO: how is this used?");
+
+        match state_stack_op_statement_node
+            .state_stack_operation_node
+            .operation_t
+        {
+            StateStackOperationType::Push => {
+                self.newline();
+                if self.generate_state_context {
+                    self.add_code("this._stateStack_push_(this._stateContext_);");
+                } else {
+                    self.add_code("this._stateStack_push_(this._state_);");
+                }
+            }
+            StateStackOperationType::Pop => {
+                self.newline();
+                self.add_code("this._stateStack_pop_();");
+            }
+        }
+    }
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_state_context_node(&mut self, _state_context_node: &StateContextNode) {
+        // TODO
+        //        self.add_code(&format!("{}",identifier_node.name.lexeme));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_frame_event_part(&mut self, frame_event_part: &FrameEventPart) {
+        // TODO: make this code generate from settings
+        match frame_event_part {
+            FrameEventPart::Event {
+                is_reference: _is_reference,
+            } => self.add_code("e"),
+            FrameEventPart::Message {
+                is_reference: _is_reference,
+            } => self.add_code("e._message"),
+            FrameEventPart::Param {
+                param_tok,
+                is_reference: _is_reference,
+            } => {
+                let param = self.format_event_param(&param_tok.lexeme);
+                self.add_code(&param)
+            }
+            FrameEventPart::Return {
+                is_reference: _is_reference,
+            } => self.add_code("e._return"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_frame_event_part_to_string(
+        &mut self,
+        frame_event_part: &FrameEventPart,
+        output: &mut String,
+    ) {
+        // TODO: make this code generate from settings
+        match frame_event_part {
+            FrameEventPart::Event {
+                is_reference: _is_reference,
+            } => output.push('e'),
+            FrameEventPart::Message {
+                is_reference: _is_reference,
+            } => output.push_str("e._message"),
+            FrameEventPart::Param {
+                param_tok,
+                is_reference: _is_reference,
+            } => output.push_str(&self.format_event_param(&param_tok.lexeme)),
+            FrameEventPart::Return {
+                is_reference: _is_reference,
+            } => output.push_str("e._return"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_action_decl_node(&mut self, action_decl_node: &ActionNode) {
+        let return_type = match &action_decl_node.type_opt {
+            Some(type_node) => type_node.get_type_str(),
+            None => String::from("void"),
+        };
+        self.newline();
+        self.add_code(&format!(
+            "{}({}): {};",
+            action_decl_node.name,
+            TypeScriptVisitor::format_params(&action_decl_node.params),
+            return_type
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_action_impl_node(&mut self, _action_decl_node: &ActionNode) {
+        panic!("visit_action_impl_node() not implemented.");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_domain_variable_decl_node(&mut self, variable_decl_node: &VariableDeclNode) {
+        self.visit_variable_decl_node(variable_decl_node);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_variable_decl_node(&mut self, variable_decl_node: &VariableDeclNode) {
+        let var_name = &variable_decl_node.name;
+        let var_init_expr = &variable_decl_node.initializer_expr_t_opt.as_ref().unwrap();
+        self.newline();
+        let mut code = String::new();
+        var_init_expr.accept_to_string(self, &mut code);
+        // Without a declared type the initializer's type is inferred.
+        let var_type = match &variable_decl_node.type_opt {
+            Some(type_node) => format!(": {}", type_node.get_type_str()),
+            None => String::new(),
+        };
+        match &variable_decl_node.identifier_decl_scope {
+            IdentifierDeclScope::DomainBlock => {
+                let modifier = if variable_decl_node.is_constant {
+                    "readonly "
+                } else {
+                    ""
+                };
+                self.add_code(&format!("{}{}{} = {};", modifier, var_name, var_type, code));
+            }
+            IdentifierDeclScope::EventHandlerVar => {
+                let keyword = if variable_decl_node.is_constant {
+                    "const"
+                } else {
+                    "let"
+                };
+                self.add_code(&format!("{} {}{} = {};", keyword, var_name, var_type, code));
+            }
+            _ => panic!("Error - unexpected scope for variable declaration"),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_variable_expr_node(&mut self, variable_node: &VariableNode) {
+        let code = self.format_variable_expr(variable_node);
+        self.add_code(&code);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_variable_expr_node_to_string(
+        &mut self,
+        variable_node: &VariableNode,
+        output: &mut String,
+    ) {
+        let code = self.format_variable_expr(variable_node);
+        output.push_str(&code);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_variable_stmt_node(&mut self, variable_stmt_node: &VariableStmtNode) {
+        // TODO: what is this line about?
+        self.generate_comment(variable_stmt_node.get_line());
+        self.newline();
+        self.add_code(&format!(
+            "{};",
+            variable_stmt_node.var_node.id_node.name.lexeme
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_assignment_expr_node(&mut self, assignment_expr_node: &AssignmentExprNode) {
+        self.generate_comment(assignment_expr_node.line);
+        self.newline();
+        self.visiting_l_value = true;
+        assignment_expr_node.l_value_box.accept(self);
+        self.visiting_l_value = false;
+        self.add_code(" = ");
+        assignment_expr_node.r_value_box.accept(self);
+        self.add_code(";");
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_assignment_expr_node_to_string(
+        &mut self,
+        assignment_expr_node: &AssignmentExprNode,
+        output: &mut String,
+    ) {
+        self.generate_comment(assignment_expr_node.line);
+        self.newline();
+        self.newline_to_string(output);
+        self.visiting_l_value = true;
+        assignment_expr_node
+            .l_value_box
+            .accept_to_string(self, output);
+        self.visiting_l_value = false;
+        output.push_str(" = ");
+        assignment_expr_node
+            .r_value_box
+            .accept_to_string(self, output);
+        output.push(';');
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_assignment_statement_node(&mut self, assignment_stmt_node: &AssignmentStmtNode) {
+        self.generate_comment(assignment_stmt_node.get_line());
+        assignment_stmt_node.assignment_expr_node.accept(self);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_unary_expr_node(&mut self, unary_expr_node: &UnaryExprNode) {
+        // TODO
+        //       self.generate_comment(assignment_expr_node.line);
+        unary_expr_node.operator.accept(self);
+        unary_expr_node.right_rcref.borrow().accept(self);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_unary_expr_node_to_string(
+        &mut self,
+        unary_expr_node: &UnaryExprNode,
+        output: &mut String,
+    ) {
+        // TODO
+        //       self.generate_comment(assignment_expr_node.line);
+        unary_expr_node.operator.accept_to_string(self, output);
+        unary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_binary_expr_node(&mut self, binary_expr_node: &BinaryExprNode) {
+        // TODO
+        //       self.generate_comment(assignment_expr_node.line);
+        if binary_expr_node.operator == OperatorType::LogicalXor {
+            self.add_code("((");
+            binary_expr_node.left_rcref.borrow().accept(self);
+            self.add_code(") && !(");
+            binary_expr_node.right_rcref.borrow().accept(self);
+            self.add_code(")) || (!(");
+            binary_expr_node.left_rcref.borrow().accept(self);
+            self.add_code(") && (");
+            binary_expr_node.right_rcref.borrow().accept(self);
+            self.add_code("))");
+        } else {
+            binary_expr_node.left_rcref.borrow().accept(self);
+            binary_expr_node.operator.accept(self);
+            binary_expr_node.right_rcref.borrow().accept(self);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enumerator_expr_node(&mut self, enumerator_expr_node: &EnumeratorExprNode) {
+        let mut output = String::new();
+        self.visit_enumerator_expr_node_to_string(enumerator_expr_node, &mut output);
+        self.add_code(&output);
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}.{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_binary_expr_node_to_string(
+        &mut self,
+        binary_expr_node: &BinaryExprNode,
+        output: &mut String,
+    ) {
+        if binary_expr_node.operator == OperatorType::LogicalXor {
+            output.push_str("((");
+            binary_expr_node
+                .left_rcref
+                .borrow()
+                .accept_to_string(self, output);
+            output.push_str(") && !(");
+            binary_expr_node
+                .right_rcref
+                .borrow()
+                .accept_to_string(self, output);
+            output.push_str(")) || (!(");
+            binary_expr_node
+                .left_rcref
+                .borrow()
+                .accept_to_string(self, output);
+            output.push_str(") && (");
+            binary_expr_node
+                .right_rcref
+                .borrow()
+                .accept_to_string(self, output);
+            output.push_str("))");
+        } else {
+            binary_expr_node
+                .left_rcref
+                .borrow()
+                .accept_to_string(self, output);
+            binary_expr_node.operator.accept_to_string(self, output);
+            binary_expr_node
+                .right_rcref
+                .borrow()
+                .accept_to_string(self, output);
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_operator_type(&mut self, operator_type: &OperatorType) {
+        match operator_type {
+            OperatorType::Plus => self.add_code(" + "),
+            OperatorType::Minus => self.add_code(" - "),
+            OperatorType::Negated => self.add_code("-"),
+            OperatorType::Multiply => self.add_code(" * "),
+            OperatorType::Divide => self.add_code(" / "),
+            OperatorType::Greater => self.add_code(" > "),
+            OperatorType::GreaterEqual => self.add_code(" >= "),
+            OperatorType::Less => self.add_code(" < "),
+            OperatorType::LessEqual => self.add_code(" <= "),
+            OperatorType::Not => self.add_code("!"),
+            OperatorType::EqualEqual => self.add_code(" == "),
+            OperatorType::NotEqual => self.add_code(" != "),
+            OperatorType::LogicalAnd => self.add_code(" && "),
+            OperatorType::LogicalOr => self.add_code(" || "),
+            OperatorType::LogicalXor => self.add_code(""),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    fn visit_operator_type_to_string(&mut self, operator_type: &OperatorType, output: &mut String) {
+        match operator_type {
+            OperatorType::Plus => output.push_str(" + "),
+            OperatorType::Minus => output.push_str(" - "),
+            OperatorType::Negated => output.push('-'),
+            OperatorType::Multiply => output.push_str(" * "),
+            OperatorType::Divide => output.push_str(" / "),
+            OperatorType::Greater => output.push_str(" > "),
+            OperatorType::GreaterEqual => output.push_str(" >= "),
+            OperatorType::Less => output.push_str(" < "),
+            OperatorType::LessEqual => output.push_str(" <= "),
+            OperatorType::Not => output.push('!'),
+            OperatorType::EqualEqual => output.push_str(" == "),
+            OperatorType::NotEqual => output.push_str(" != "),
+            OperatorType::LogicalAnd => output.push_str(" && "),
+            OperatorType::LogicalOr => output.push_str(" || "),
+            OperatorType::LogicalXor => output.push_str(""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frame_c::compiler::Exe;
+
+    fn generate(spec: &str) -> String {
+        Exe::new()
+            .run(&None, spec.to_string(), String::from("typescript"))
+            .unwrap_or_else(|err| panic!("{}", err.error))
+    }
+
+    const STATE_CONTEXT: &str = include_str!("../../../../framec_tests/src/state_context.frm");
+
+    #[test]
+    fn typed_state_contexts() {
+        let code = generate(STATE_CONTEXT);
+        assert!(code.contains(
+            "interface StateContextSmBarContext {
+    readonly state: StateContextSmState;
+    readonly stateArgs: { y: i32 };
+    readonly stateVars: { z: i32 };
+    readonly enterArgs: { a: i32 } | null;
+}"
+        ));
+        assert!(code.contains(
+            "type StateContextSmStateContext = StateContextSmInitContext | StateContextSmFooContext | StateContextSmBarContext;"
+        ));
+        assert!(code.contains(
+            "const stateContext: StateContextSmBarContext = {
+                state: this._sBar_,
+                stateArgs: { y: thisStateContext.stateVars.x },
+                stateVars: { z: 0 },
+                enterArgs: { a: tmp },
+            };"
+        ));
+    }
+
+    #[test]
+    fn state_context_accesses_are_not_cast() {
+        let code = generate(STATE_CONTEXT);
+        assert!(code
+            .contains("const thisStateContext = this._stateContext_ as StateContextSmBarContext;"));
+        assert!(code.contains(
+            "thisStateContext.stateVars.z = thisStateContext.enterArgs!.a + thisStateContext.stateArgs.y;"
+        ));
+        assert!(!code.contains("stateVars[\""));
+        assert!(!code.contains("stateArgs[\""));
+        assert!(!code.contains("enterArgs[\""));
+    }
+}
@@ -1,2344 +1,1153 @@
-#![allow(non_snake_case)]
-
 use crate::frame_c::ast::*;
-use crate::frame_c::symbol_table::*;
+use crate::frame_c::config::{FrameConfig, XStateConfig};
+use crate::frame_c::scanner::TokenType;
+use crate::frame_c::utils::SystemHierarchy;
 use crate::frame_c::visitors::*;
-use crate::frame_c::scanner::{Token,TokenType};
-
-struct Config {
-    state_var_name:String,
-    start_system_msg:String,
-    stop_system_msg:String,
-    enter_msg:String,
-    exit_msg:String,
-    frame_event:String,
-    frame_message:String,
-    frame_event_return:String,
-    frame_event_variable_name:String,
-    frame_event_message_attribute_name:String,
-    frame_event_parameters_attribute_name:String,
-    frame_event_return_attribute_name:String,
-
+use std::collections::HashMap;
+
+/// A path through an event handler that has not yet been ended by a
+/// transition or a return, with the conditions of the tests it passed and
+/// the actions called along the way.
+#[derive(Clone, Default)]
+struct XStatePath {
+    guards: Vec<String>,
+    actions: Vec<String>,
 }
 
-impl Config {
-    fn new() -> Config {
-        Config {
-            state_var_name:String::from("state"),
-            start_system_msg:String::from("start"),
-            stop_system_msg:String::from("stop"),
-            enter_msg:String::from("entry"),
-            exit_msg:String::from("exit"),
-            frame_event:String::from("FrameEvent"),
-            frame_message:String::from("FrameMessage"),
-            frame_event_return:String::from("FrameEventReturn"),
-            frame_event_variable_name:String::from("e"),
-            frame_event_message_attribute_name:String::from("message"),
-            frame_event_parameters_attribute_name:String::from("parameters"),
-            frame_event_return_attribute_name:String::from("ret"),
+/// A path through an event handler that ends in a transition, or without
+/// a target if the handler returns.
+#[derive(Clone)]
+struct XStateTransition {
+    target_opt: Option<String>,
+    guards: Vec<String>,
+    // Frame re-enters the source state on a self transition, but not on a
+    // change-state.
+    is_change_state: bool,
+    actions: Vec<String>,
+}
 
-        }
-    }
+/// The paths through one event handler.
+struct XStateHandler {
+    event: String,
+    transitions: Vec<XStateTransition>,
+    // Paths reaching the end of a handler terminated by `:>`, which
+    // continue in the parent state's handler.
+    continuations: Vec<XStatePath>,
 }
 
+/// Generates an XState machine config (`createMachine()` argument) from a
+/// Frame system. Action calls become named actions, tests around a
+/// transition become its guard, and domain variables with literal
+/// initializers become the machine context.
+///
+/// Frame's hierarchy only dispatches events from a state to its parent, and
+/// a parent state can be the target of a transition itself, so it doesn't
+/// map onto XState compound states. Every Frame state becomes a top-level
+/// XState state whose handlers include those inherited from its ancestors.
 pub struct XStateVisitor {
-    config:Config,
-    compiler_version:String,
-    pub code:String,
-    pub dent:usize,
-    pub current_state_name_opt:Option<String>,
-    arcanium:Arcanum,
-    symbol_config:SymbolConfig,
-    comments:Vec<Token>,
-    current_comment_idx:usize,
-    first_event_handler:bool,
-    system_name:String,
-    first_state_name:String,
-    serialize:Vec<String>,
-    deserialize:Vec<String>,
-    subclass_code:Vec<String>,
-    warnings:Vec<String>,
-    has_states:bool,
-    errors:Vec<String>,
-    visiting_call_chain_literal_variable:bool,
-    generate_exit_args:bool,
-    generate_state_context:bool,
-    generate_state_stack:bool,
-    generate_change_state:bool,
-    generate_transition_state:bool,
+    compiler_version: &'static str,
+    config: XStateConfig,
+    system_hierarchy: SystemHierarchy,
+    state_names: Vec<String>,
+    handlers: HashMap<String, Vec<XStateHandler>>,
+    code: String,
+    dent: usize,
+    errors: Vec<String>,
+    // Paths through the handler being visited that reach the current
+    // statement.
+    paths: Vec<XStatePath>,
+    transitions: Vec<XStateTransition>,
+    uses_history: bool,
 }
 
 impl XStateVisitor {
-
-    //* --------------------------------------------------------------------- *//
-
-    pub fn new(  arcanium:Arcanum
-                 , generate_exit_args:bool
-                 , generate_state_context:bool
-                 , generate_state_stack:bool
-                 , generate_change_state:bool
-                 , generate_transition_state:bool
-                 , compiler_version:&str
-                 , comments:Vec<Token>) -> XStateVisitor {
-
-        // These closures are needed to do the same actions as add_code() and newline()
-        // when inside a borrowed self reference as they modify self.
-        // let mut add_code_cl = |target:&mut String, s:&String| target.push_str(s);
-        // let mut newline_cl = |target:&mut String, s:&String, d:usize| {
-        //     target.push_str(&*format!("\n{}",(0..d).map(|_| "\t").collect::<String>()));
-        // };
-
+    pub fn new(
+        compiler_version: &'static str,
+        config: FrameConfig,
+        system_hierarchy: SystemHierarchy,
+    ) -> XStateVisitor {
         XStateVisitor {
-            config:Config::new(),
-            compiler_version:compiler_version.to_string(),
-            code:String::from(""),
-            dent:0,
-            current_state_name_opt:None,
-            arcanium,
-            symbol_config:SymbolConfig::new(),
-            comments,
-            current_comment_idx:0,
-            first_event_handler:true,
-            system_name:String::new(),
-            first_state_name:String::new(),
-            serialize:Vec::new(),
-            deserialize:Vec::new(),
-            has_states:false,
-            warnings:Vec::new(),
-            visiting_call_chain_literal_variable:false,
-            errors:Vec::new(),
-            subclass_code:Vec::new(),
-            generate_exit_args,
-            generate_state_context,
-            generate_state_stack,
-            generate_change_state,
-            generate_transition_state,
-        }
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    pub fn is_enter_or_exit_event(&self, msg:&str) -> bool {
-        if msg == self.config.enter_msg {
-            return true;
-        } else if msg == self.config.exit_msg {
-            return true;
+            compiler_version,
+            config: config.codegen.xstate,
+            system_hierarchy,
+            state_names: Vec::new(),
+            handlers: HashMap::new(),
+            code: String::new(),
+            dent: 0,
+            errors: Vec::new(),
+            paths: Vec::new(),
+            transitions: Vec::new(),
+            uses_history: false,
         }
-        return false;
     }
 
-
-    //* --------------------------------------------------------------------- *//
-
-    pub fn get_msg_enum(&self, msg:&str) -> String {
-        match msg {
-            ">>" => self.config.start_system_msg.clone(),
-            "<<" => self.config.stop_system_msg.clone(),
-            ">" => self.config.enter_msg.clone(),
-            "<" => self.config.exit_msg.clone(),
-            _ => msg.to_string(),
-        }
+    pub fn run(&mut self, system_node: &SystemNode) {
+        system_node.accept(self);
     }
 
-    //* --------------------------------------------------------------------- *//
-
     pub fn get_code(&self) -> String {
-        if self.errors.len() > 0 {
+        if !self.errors.is_empty() {
             let mut error_list = String::new();
             for error in &self.errors {
-                error_list.push_str(&error.clone());
+                error_list.push_str(error);
+                error_list.push('\n');
             }
             error_list
-        } else  {
+        } else {
             self.code.clone()
         }
     }
 
     //* --------------------------------------------------------------------- *//
 
-    fn get_variable_type(&self,symbol_type:&SymbolType) -> String {
-        let var_type = match &*symbol_type {
-            SymbolType::DomainVariable { domain_variable_symbol_rcref } => {
-                match &domain_variable_symbol_rcref.borrow().var_type {
-                    Some(x) => String::from(""),
-                    None => String::from(""),
-                }
-            },
-            SymbolType::StateParam { state_param_symbol_rcref } => {
-                match &state_param_symbol_rcref.borrow().param_type {
-                    Some(x) => String::from(""),
-                    None => String::from(""),
-                }
-            },
-            SymbolType::StateVariable { state_variable_symbol_rcref } => {
-                match &state_variable_symbol_rcref.borrow().var_type {
-                    Some(x) => String::from(""),
-                    None => String::from(""),
-                }                    },
-            SymbolType::EventHandlerParam { event_handler_param_symbol_rcref } => {
-                match &event_handler_param_symbol_rcref.borrow().param_type {
-                    Some(x) => String::from(""),
-                    None => String::from(""),
-                }
-            },
-            SymbolType::EventHandlerVariable { event_handler_variable_symbol_rcref } => {
-                match &event_handler_variable_symbol_rcref.borrow().var_type {
-                    Some(x) => String::from(""),
-                    None => String::from(""),
-                }
-            },
-
-            _ => panic!("TODO"),
-        };
-
-        return var_type;
+    fn add_code(&mut self, s: &str) {
+        self.code.push_str(s);
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn format_variable_expr(&self, variable_node:&VariableNode) -> String {
-        let mut code = String::new();
-
-        match variable_node.scope {
-            IdentifierDeclScope::DomainBlock => {
-                code.push_str(&format!("that.{}",variable_node.id_node.name.lexeme));
-            },
-            IdentifierDeclScope::StateParam => {
-                let var_node = variable_node;
-                let var_symbol_rcref_opt = &var_node.symbol_type_rcref_opt;
-                let var_symbol_rcref = var_symbol_rcref_opt.as_ref().unwrap();
-                let var_symbol = var_symbol_rcref.borrow();
-                let var_type = self.get_variable_type(&*var_symbol);
-
-                if self.visiting_call_chain_literal_variable {
-                    code.push_str("(");
-                }
-                code.push_str(&format!("_stateContext_.getStateArg(\"{}\")"
-                                       ,variable_node.id_node.name.lexeme));
-                if self.visiting_call_chain_literal_variable {
-                    code.push_str(")");
-                }
-            },
-            IdentifierDeclScope::StateVar => {
-                let var_node = variable_node;
-                let var_symbol_rcref_opt = &var_node.symbol_type_rcref_opt;
-                let var_symbol_rcref = var_symbol_rcref_opt.as_ref().unwrap();
-                let var_symbol = var_symbol_rcref.borrow();
-                let var_type = self.get_variable_type(&*var_symbol);
-
-                if self.visiting_call_chain_literal_variable {
-                    code.push_str("(");
-                }
-                code.push_str(&format!(" _stateContext_.getStateVar(\"{}\")"
-                                       ,variable_node.id_node.name.lexeme));
-                if self.visiting_call_chain_literal_variable {
-                    code.push_str(")");
-                }
-            }
-            IdentifierDeclScope::EventHandlerParam => {
-                let var_node = variable_node;
-                let var_symbol_rcref_opt = &var_node.symbol_type_rcref_opt;
-                let var_symbol_rcref = var_symbol_rcref_opt.as_ref().unwrap();
-                let var_symbol = var_symbol_rcref.borrow();
-                let var_type = self.get_variable_type(&*var_symbol);
-
-                if self.visiting_call_chain_literal_variable {
-                    code.push_str("(");
-                }
-                code.push_str(&format!("e._parameters[\"{}\"]"
-                                       ,variable_node.id_node.name.lexeme));
-                if self.visiting_call_chain_literal_variable {
-                    code.push_str(")");
-                }
-
-            },
-            IdentifierDeclScope::EventHandlerVar => {
-                code.push_str(&format!("{}",variable_node.id_node.name.lexeme));
-            }
-            IdentifierDeclScope::None => {
-                // TODO: Explore labeling Variables as "extern" scope
-                code.push_str(&format!("{}",variable_node.id_node.name.lexeme));
-            },            // Actions?
-            _ => panic!("Illegal scope."),
-        }
-
-        code
+    fn newline(&mut self) {
+        self.code.push('\n');
+        self.code.push_str(&"  ".repeat(self.dent));
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn format_parameter_list(&mut self,params:&Vec<ParameterNode>) {
-        let mut separator = "";
-        for param in params {
-            self.add_code(&format!("{}", separator));
-            self.add_code(&format!("{}", param.param_name));
-            separator = ",";
-        }
+    fn indent(&mut self) {
+        self.dent += 1;
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn format_action_name(&mut self,action_name:&String) -> String {
-        return format!("that.{}_do",action_name)
+    fn outdent(&mut self) {
+        self.dent -= 1;
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    pub fn run(&mut self,system_node:&SystemNode) {
-        system_node.accept( self);
+    fn enter_block(&mut self, key: &str) {
+        self.newline();
+        self.add_code(&format!("{}: {{", key));
+        self.indent();
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn add_code(&mut self, s:&str)  {
-        self.code.push_str(&*format!("{}",s));
+    fn exit_block(&mut self) {
+        self.outdent();
+        self.newline();
+        self.add_code("},");
     }
 
     //* --------------------------------------------------------------------- *//
 
-    fn newline(&mut self)  {
-        self.code.push_str(&*format!("\n{}",self.dent()));
+    fn format_string_list(items: &[String]) -> String {
+        let quoted: Vec<String> = items.iter().map(|item| format!("{:?}", item)).collect();
+        format!("[{}]", quoted.join(", "))
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn newline_to_string(&mut self, output:&mut String)  {
-        output.push_str(&*format!("\n{}",self.dent()));
+    fn format_expr(&mut self, expr_t: &ExprType) -> String {
+        let mut output = String::new();
+        expr_t.accept_to_string(self, &mut output);
+        output
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn dent(&self) -> String {
-        return (0..self.dent).map(|_| "    ").collect::<String>()
+    fn format_guard(guards: &[String]) -> Option<String> {
+        if guards.is_empty() {
+            return None;
+        }
+        let guards: Vec<String> = guards
+            .iter()
+            .map(|guard| {
+                if guards.len() > 1 && guard.contains("||") {
+                    format!("({})", guard)
+                } else {
+                    guard.clone()
+                }
+            })
+            .collect();
+        Some(guards.join(" && "))
     }
 
-    //* --------------------------------------------------------------------- *//
+    fn negate(condition: &str) -> String {
+        format!("!({})", condition)
+    }
 
-    fn indent(&mut self) {
-        self.dent += 1;
+    // Absolute state reference, e.g. `#System.State`.
+    fn format_target(&self, state_name: &str) -> String {
+        format!("#{}.{}", self.system_hierarchy.system_name, state_name)
     }
 
-    //* --------------------------------------------------------------------- *//
+    fn parent_name(&self, state_name: &str) -> Option<String> {
+        match self.system_hierarchy.get_node(state_name) {
+            Some(node)
+                if !node.parent_name.is_empty()
+                    && node.parent_name != self.system_hierarchy.system_name =>
+            {
+                Some(node.parent_name.clone())
+            }
+            _ => None,
+        }
+    }
 
-    fn outdent(&mut self) {
-        self.dent -= 1;
+    // Domain variables become context entries. Only literal and
+    // enumerator initializers have a target-independent value, anything
+    // else starts out as null.
+    fn format_context_value(&mut self, expr_t_opt: &Option<ExprType>) -> String {
+        match expr_t_opt {
+            Some(ExprType::LiteralExprT { literal_expr_node }) => match literal_expr_node.token_t {
+                TokenType::Number | TokenType::String | TokenType::True | TokenType::False => {
+                    let mut output = String::new();
+                    self.visit_literal_expression_node_to_string(literal_expr_node, &mut output);
+                    output
+                }
+                _ => String::from("null"),
+            },
+            Some(ExprType::EnumeratorExprT {
+                enumerator_expr_node,
+            }) => format!("{:?}", enumerator_expr_node.enumerator),
+            _ => String::from("null"),
+        }
     }
 
     //* --------------------------------------------------------------------- *//
 
-    fn visit_decl_stmts(&mut self, decl_stmt_types:&Vec<DeclOrStmtType>) {
+    fn visit_decl_stmts(&mut self, decl_stmt_types: &[DeclOrStmtType]) {
         for decl_stmt_t in decl_stmt_types.iter() {
             match decl_stmt_t {
-                DeclOrStmtType::VarDeclT {var_decl_t_rc_ref}
-                => {
-                    let variable_decl_node = var_decl_t_rc_ref.borrow();
-                    variable_decl_node.accept(self);
-                },
-                DeclOrStmtType::StmtT {stmt_t} => {
-                    match stmt_t {
-                        StatementType::ExpressionStmt { expr_stmt_t } => {
-                            match expr_stmt_t {
-                                ExprStmtType::ActionCallStmtT { action_call_stmt_node }
-                                => action_call_stmt_node.accept(self),                        // // TODO
-                                ExprStmtType::CallStmtT { call_stmt_node }
-                                => call_stmt_node.accept(self),
-                                ExprStmtType::CallChainLiteralStmtT { call_chain_literal_stmt_node }
-                                => call_chain_literal_stmt_node.accept(self),
-                                ExprStmtType::AssignmentStmtT { assignment_stmt_node }
-                                => assignment_stmt_node.accept(self),
-                                ExprStmtType::VariableStmtT { variable_stmt_node }
-                                => variable_stmt_node.accept(self),
-                            }
-                        },
-                        StatementType::TransitionStmt { transition_statement } => {
-                            transition_statement.accept(self);
-                        }
-                        StatementType::TestStmt { test_stmt_node } => {
-                            test_stmt_node.accept(self);
-                        },
-                        StatementType::StateStackStmt {state_stack_operation_statement_node} => {
-                            state_stack_operation_statement_node.accept(self);
-                        },
-                        StatementType::ChangeStateStmt {change_state_stmt} => {
-                            change_state_stmt.accept(self);
-                        },
-                        StatementType::NoStmt => {
-                            // TODO
-                            panic!("todo");
-                        }
+                DeclOrStmtType::VarDeclT { .. } => {}
+                DeclOrStmtType::StmtT { stmt_t } => match stmt_t {
+                    StatementType::ExpressionStmt { expr_stmt_t } => {
+                        self.collect_action(expr_stmt_t);
                     }
-                }
+                    StatementType::TransitionStmt {
+                        transition_statement,
+                    } => {
+                        transition_statement.accept(self);
+                    }
+                    StatementType::TestStmt { test_stmt_node } => {
+                        test_stmt_node.accept(self);
+                    }
+                    StatementType::StateStackStmt { .. } => {}
+                    StatementType::ChangeStateStmt { change_state_stmt } => {
+                        change_state_stmt.accept(self);
+                    }
+                    StatementType::LoopStmt { loop_stmt_node } => {
+                        loop_stmt_node.accept(self);
+                    }
+                    StatementType::LoopControlStmt { .. } => {}
+                    StatementType::NoStmt => {}
+                },
             }
         }
     }
 
-
-    //* --------------------------------------------------------------------- *//
-    //
-    // fn generate_machinery(&mut self, system_node: &SystemNode) {
-    //     self.newline();
-    //     self.newline();
-    //     self.add_code("//=============== Machinery and Mechanisms ==============//");
-    //     self.newline();
-    //     if let Some(_) = system_node.get_first_state() {
-    //         self.newline();
-    //         self.add_code(&format!("let _state_ = _s{}_;",self.first_state_name));
-    //         if self.generate_state_context {
-    //             self.newline();
-    //             self.add_code(&format!("let _stateContext_ = StateContext(_state_);"));
-    //             self.newline();
-    //             if self.has_states {
-    //                 if let Some(state_symbol_rcref) = self.arcanium.get_state(&self.first_state_name) {
-    //                     self.newline();
-    //                     let state_symbol = state_symbol_rcref.borrow();
-    //                     let state_node = &state_symbol.state_node.as_ref().unwrap().borrow();
-    //                     // generate local state variables
-    //                     if state_node.vars.is_some() {
-    //                         for var_rcref in state_node.vars.as_ref().unwrap() {
-    //                             let var = var_rcref.borrow();
-    //                             let var_type = match &var.type_opt {
-    //                                 Some(var_type) => var_type.get_type_str(),
-    //                                 None => String::from("<?>"),
-    //                             };
-    //                             let expr_t = var.initializer_expr_t_opt.as_ref().unwrap();
-    //                             let mut expr_code = String::new();
-    //                             expr_t.accept_to_string(self, &mut expr_code);
-    //                             self.newline();
-    //                             self.add_code(&format!("_stateContext_.addStateVar(\"{}\",{});", var.name, expr_code));
-    //                             self.newline();
-    //                         }
-    //                     }
-    //                 }
-    //             }
-    //         }
-    //         if self.generate_transition_state {
-    //             self.newline();
-    //             self.newline();
-    //             if self.generate_state_context {
-    //                 if self.generate_exit_args {
-    //                     self.add_code(&format!("let _transition_ = function(newState,exitArgs, stateContext) {{"));
-    //                 } else {
-    //                     self.add_code(&format!("let _transition_ = function(newState, stateContext) {{"));
-    //                 }
-    //             } else {
-    //                 if self.generate_exit_args {
-    //                     self.add_code(&format!("let _transition_ = function(newState,exitArgs) {{"));
-    //                 } else {
-    //                     self.add_code(&format!("let _transition_ = function(newState) {{"));
-    //                 }
-    //             }
-    //             self.indent();
-    //             self.newline();
-    //             if self.generate_exit_args {
-    //                 self.add_code(&format!("let exitEvent = FrameEvent(\"<\",exitArgs);"));
-    //             } else {
-    //                 self.add_code(&format!("let exitEvent = FrameEvent(\"<\",null);"));
-    //             }
-    //             self.newline();
-    //             self.add_code(&format!("_state_(exitEvent);"));
-    //             self.newline();
-    //             self.add_code(&format!("_state_ = newState;"));
-    //             self.newline();
-    //             if self.generate_state_context {
-    //                 self.add_code(&format!("_stateContext_ = stateContext;"));
-    //                 self.newline();
-    //                 self.add_code(&format!("let enterEvent = FrameEvent(\">\",_stateContext_.getEnterArgs());"));
-    //                 self.newline();
-    //             } else {
-    //                 self.add_code(&format!("let enterEvent = FrameEvent(\">\",null);"));
-    //                 self.newline();
-    //             }
-    //             self.add_code(&format!("_state_(enterEvent);"));
-    //             self.outdent();
-    //             self.newline();
-    //             self.add_code(&format!("}}"));
-    //         }
-    //         if self.generate_state_stack {
-    //             self.newline();
-    //             self.newline();
-    //             self.add_code(&format!("let _stateStack_ = [];"));
-    //             self.newline();
-    //             self.newline();
-    //             self.add_code(&format!("let  _stateStack_push_ = function (stateData) {{"));
-    //             self.indent();
-    //             self.newline();
-    //             self.add_code(&format!("_stateStack_.push(stateData);"));
-    //             self.outdent();
-    //             self.newline();
-    //             self.add_code(&format!("}}"));
-    //             self.newline();
-    //             self.newline();
-    //             self.add_code(&format!("let _stateStack_pop_ = function() {{"));
-    //             self.indent();
-    //             self.newline();
-    //             self.add_code(&format!("return _stateStack_.pop();"));
-    //             self.outdent();
-    //             self.newline();
-    //             self.add_code(&format!("}}"));
-    //         }
-    //         if self.generate_change_state {
-    //             self.newline();
-    //             self.newline();
-    //             self.add_code(&format!("let _changeState_ = function(newState) {{"));
-    //             self.indent();
-    //             self.newline();
-    //             self.add_code(&format!("_state_ = newState;"));
-    //             self.outdent();
-    //             self.newline();
-    //             self.add_code(&format!("}}"));
-    //         }
-    //
-    //         if self.arcanium.is_serializable() {
-    //             self.newline();
-    //             for line in self.serialize.iter() {
-    //                 self.code.push_str(&*format!("{}",line));
-    //                 self.code.push_str(&*format!("\n{}",self.dent()));
-    //             }
-    //
-    //             for line in self.deserialize.iter() {
-    //                 self.code.push_str(&*format!("{}",line));
-    //                 self.code.push_str(&*format!("\n{}",self.dent()));
-    //             }
-    //         }
-    //     }
-    // }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn generate_subclass(&mut self) {
-        for line in self.subclass_code.iter() {
-            self.code.push_str(&*format!("{}",line));
-            self.code.push_str(&*format!("\n{}",self.dent()));
+    // Calls to actions are listed by name; XState expects the
+    // implementations to be provided with the machine options.
+    fn collect_action(&mut self, expr_stmt_t: &ExprStmtType) {
+        let action_name_opt = match expr_stmt_t {
+            ExprStmtType::ActionCallStmtT {
+                action_call_stmt_node,
+            } => Some(
+                action_call_stmt_node
+                    .action_call_expr_node
+                    .identifier
+                    .name
+                    .lexeme
+                    .clone(),
+            ),
+            ExprStmtType::CallStmtT { call_stmt_node } => {
+                Some(call_stmt_node.call_expr_node.identifier.name.lexeme.clone())
+            }
+            ExprStmtType::CallChainLiteralStmtT {
+                call_chain_literal_stmt_node,
+            } => {
+                let call_chain = &call_chain_literal_stmt_node
+                    .call_chain_literal_expr_node
+                    .call_chain;
+                match (call_chain.len(), call_chain.front()) {
+                    (1, Some(CallChainLiteralNodeType::CallT { call })) => {
+                        Some(call.identifier.name.lexeme.clone())
+                    }
+                    (
+                        1,
+                        Some(CallChainLiteralNodeType::ActionCallT {
+                            action_call_expr_node,
+                        }),
+                    ) => Some(action_call_expr_node.identifier.name.lexeme.clone()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(action_name) = action_name_opt {
+            for path in &mut self.paths {
+                path.actions.push(action_name.clone());
+            }
         }
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn generate_comment(&mut self,line:usize) -> bool {
-
-        // can't use self.newline() or self.add_code() due to double borrow.
-        let mut generated_comment = false;
-        while self.current_comment_idx < self.comments.len() &&
-            line >= self.comments[self.current_comment_idx].line {
-            let comment = &self.comments[self.current_comment_idx];
-            if comment.token_type == TokenType::SingleLineComment {
-                self.code.push_str(&*format!("  // {}",&comment.lexeme[3..]));
-                self.code.push_str(&*format!("\n{}",(0..self.dent).map(|_| "\t").collect::<String>()));
-
-            } else {
-                let len = &comment.lexeme.len() - 3;
-                self.code.push_str(&*format!("/* {}",&comment.lexeme[3..len]));
-                self.code.push_str(&*format!("*/"));
-
+    // A transition ends every path reaching it.
+    fn add_transition(&mut self, target: String, is_change_state: bool) {
+        for path in std::mem::take(&mut self.paths) {
+            self.transitions.push(XStateTransition {
+                target_opt: Some(target.clone()),
+                guards: path.guards,
+                is_change_state,
+                actions: path.actions,
+            });
+        }
+    }
+
+    // A return ends every path reaching it without a transition.
+    fn add_return(&mut self) {
+        for path in std::mem::take(&mut self.paths) {
+            self.transitions.push(XStateTransition {
+                target_opt: None,
+                guards: path.guards,
+                is_change_state: false,
+                actions: path.actions,
+            });
+        }
+    }
+
+    // Visit a test branch, which is reached by the given paths once they
+    // have passed the branch's conditions, and return the paths that
+    // continue after the test.
+    fn visit_branch(
+        &mut self,
+        paths: &[XStatePath],
+        conditions: Vec<String>,
+        statements: &[DeclOrStmtType],
+        terminator_opt: &Option<TerminatorExpr>,
+    ) -> Vec<XStatePath> {
+        self.paths = paths
+            .iter()
+            .map(|path| {
+                let mut path = path.clone();
+                path.guards.extend(conditions.iter().cloned());
+                path
+            })
+            .collect();
+        self.visit_decl_stmts(statements);
+        if let Some(terminator) = terminator_opt {
+            if let TerminatorType::Return = terminator.terminator_type {
+                self.add_return();
             }
-
-            self.current_comment_idx += 1;
-            generated_comment = true;
         }
-        if generated_comment {
-//            self.code.push_str(&*format!("\n{}",(0..self.dent).map(|_| "\t").collect::<String>()));
+        std::mem::take(&mut self.paths)
+    }
+
+    // Visit the branches of a test in order. Each branch is only taken if
+    // the conditions of the branches before it failed, and if there is no
+    // else branch, the test is passed over when they all fail.
+    fn visit_test_branches(
+        &mut self,
+        branches: Vec<(String, &[DeclOrStmtType], &Option<TerminatorExpr>)>,
+        else_branch_opt: Option<(&[DeclOrStmtType], &Option<TerminatorExpr>)>,
+    ) {
+        let paths = std::mem::take(&mut self.paths);
+        let mut continuing = Vec::new();
+        let mut failed = Vec::new();
+        for (condition, statements, terminator_opt) in branches {
+            let mut conditions = failed.clone();
+            conditions.push(condition.clone());
+            continuing.extend(self.visit_branch(&paths, conditions, statements, terminator_opt));
+            failed.push(XStateVisitor::negate(&condition));
+        }
+        match else_branch_opt {
+            Some((statements, terminator_opt)) => {
+                continuing.extend(self.visit_branch(&paths, failed, statements, terminator_opt));
+            }
+            None => {
+                continuing.extend(self.visit_branch(&paths, failed, &[], &None));
+            }
         }
-
-        generated_comment
+        self.paths = continuing;
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    // @TODO: see diff w/ c++
-    fn generate_state_ref_change_state(&mut self, change_state_stmt_node: &ChangeStateStatementNode) {
-
-        let target_state_name = match &change_state_stmt_node.state_context_t {
-            StateContextType::StateRef {state_context_node}
-            => &state_context_node.state_ref_node.name,
-            _ => panic!("TODO"),
+    fn collect_handler(&mut self, evt_handler_node: &EventHandlerNode) -> XStateHandler {
+        let event = match &evt_handler_node.msg_t {
+            MessageType::CustomMessage { message_node } => message_node.name.clone(),
+            MessageType::AnyMessage { .. } => String::from("*"),
         };
-
-        self.newline();
-        self.add_code(&format!("_changeState_({});", self.format_target_state_name(target_state_name)));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn generate_state_ref_transition(&mut self, transition_statement: &TransitionStatementNode) {
-
-        let target_state_name = match &transition_statement.target_state_context_t {
-            StateContextType::StateRef {state_context_node} => {
-                &state_context_node.state_ref_node.name
-            },
-            _ => {
-                self.errors.push("Unknown error.".to_string());
-                ""
-            },
+        self.paths = vec![XStatePath::default()];
+        self.transitions.clear();
+        self.visit_decl_stmts(&evt_handler_node.statements);
+        let mut continuations = Vec::new();
+        match evt_handler_node.terminator_node.terminator_type {
+            TerminatorType::Return => self.add_return(),
+            TerminatorType::Continue => continuations = std::mem::take(&mut self.paths),
+        }
+        XStateHandler {
+            event,
+            transitions: std::mem::take(&mut self.transitions),
+            continuations,
+        }
+    }
+
+    // The paths taken when a state receives an event, which include those
+    // of the ancestors handling the event if the state doesn't, or if its
+    // handler continues with `:>`. Returns `None` if the event is not
+    // handled at all.
+    fn resolve_handler(&self, state_name: &str, event: &str) -> Option<Vec<XStateTransition>> {
+        let handler_opt = self
+            .handlers
+            .get(state_name)
+            .and_then(|handlers| handlers.iter().find(|handler| handler.event == event));
+        let parent_transitions_opt = match self.parent_name(state_name) {
+            Some(parent_name) => self.resolve_handler(&parent_name, event),
+            None => None,
         };
-
-        let state_ref_code = format!("{}",self.format_target_state_name(target_state_name));
-
-        self.newline();
-        match &transition_statement.label_opt {
-            Some(label) => {
-                self.add_code(&format!("// {}", label));
-                self.newline();
-            },
-            None => {},
-        }
-
-        if self.generate_state_context {
-            self.add_code(&format!("stateContext = StateContext({});",state_ref_code));
-            self.newline();
-        }
-
-        // -- Exit Arguments --
-
-        let mut has_exit_args = false;
-        if let Some(exit_args) = &transition_statement.exit_args_opt {
-            if exit_args.exprs_t.len() > 0 {
-                has_exit_args = true;
-
-                // Note - searching for event keyed with "State:<"
-                // e.g. "S1:<"
-
-                let mut msg: String = String::new();
-                if let Some(state_name) = &self.current_state_name_opt {
-                    msg = state_name.clone();
-                }
-                msg.push_str(":");
-                msg.push_str(&self.symbol_config.exit_msg_symbol);
-
-                if let Some(event_sym) = self.arcanium.get_event(&msg, &self.current_state_name_opt) {
-                    match &event_sym.borrow().params_opt {
-                        Some(event_params) => {
-                            if exit_args.exprs_t.len() != event_params.len() {
-                                panic!("Fatal error: misaligned parameters to arguments.")
-                            }
-                            let mut param_symbols_it = event_params.iter();
-                            self.add_code("let exitArgs = {};");
-                            self.newline();
-                            // Loop through the ARGUMENTS...
-                            for expr_t in &exit_args.exprs_t {
-                                // ...and validate w/ the PARAMETERS
-                                match param_symbols_it.next() {
-                                    Some(p) => {
-                                        let param_type = match &p.param_type {
-                                            Some(param_type) => param_type.get_type_str(),
-                                            None => String::from("<?>"),
-                                        };
-                                        let mut expr = String::new();
-                                        expr_t.accept_to_string(self, &mut expr);
-                                        self.add_code(&format!("exitArgs[\"{}\"] = {};", p.name, expr));
-                                        self.newline();
-                                    },
-                                    None => panic!("Invalid number of arguments for \"{}\" event handler.", msg),
-                                }
-                            }
-                        },
-                        None => panic!("Fatal error: misaligned parameters to arguments."),
+        let handler = match handler_opt {
+            Some(handler) => handler,
+            None => return parent_transitions_opt,
+        };
+        let mut transitions = handler.transitions.clone();
+        for path in &handler.continuations {
+            match &parent_transitions_opt {
+                Some(parent_transitions) => {
+                    for parent_transition in parent_transitions {
+                        let mut transition = parent_transition.clone();
+                        transition.guards = path.guards.clone();
+                        transition
+                            .guards
+                            .extend(parent_transition.guards.iter().cloned());
+                        transition.actions = path.actions.clone();
+                        transition
+                            .actions
+                            .extend(parent_transition.actions.iter().cloned());
+                        transitions.push(transition);
                     }
-                } else {
-                    panic!("TODO");
                 }
+                None => transitions.push(XStateTransition {
+                    target_opt: None,
+                    guards: path.guards.clone(),
+                    is_change_state: false,
+                    actions: path.actions.clone(),
+                }),
             }
         }
+        Some(transitions)
+    }
 
-        // -- Enter Arguments --
-
-        let enter_args_opt = match &transition_statement.target_state_context_t {
-            StateContextType::StateRef { state_context_node}
-            => &state_context_node.enter_args_opt,
-            StateContextType::StateStackPop {}
-            => &None,
+    // The actions every path starts with, which are run unconditionally.
+    fn common_actions(transitions: &[XStateTransition]) -> Vec<String> {
+        let mut common = match transitions.first() {
+            Some(transition) => transition.actions.clone(),
+            None => return Vec::new(),
         };
-
-        if let Some(enter_args) = enter_args_opt {
-
-            // Note - searching for event keyed with "State:>"
-            // e.g. "S1:>"
-
-            let mut msg:String = String::from(target_state_name);
-            msg.push_str(":");
-            msg.push_str(&self.symbol_config.enter_msg_symbol);
-
-            if let Some(event_sym) = self.arcanium.get_event(&msg,&self.current_state_name_opt) {
-                match &event_sym.borrow().params_opt {
-                    Some(event_params) => {
-                        if enter_args.exprs_t.len() != event_params.len() {
-                            panic!("Fatal error: misaligned parameters to arguments.")
-                        }
-                        let mut param_symbols_it =  event_params.iter();
-                        for expr_t in &enter_args.exprs_t {
-                            match param_symbols_it.next() {
-                                Some(p) => {
-                                    let param_type = match &p.param_type {
-                                        Some(param_type) => param_type.get_type_str(),
-                                        None => String::from("<?>"),
-                                    };
-                                    let mut expr = String::new();
-                                    expr_t.accept_to_string(self,&mut expr);
-                                    self.add_code(&format!("stateContext.addEnterArg(\"{}\",{});", p.name, expr));
-                                    self.newline();
-                                },
-                                None => panic!("Invalid number of arguments for \"{}\" event handler.",msg),
-                            }
-                        }
-                    },
-                    None => panic!("Invalid number of arguments for \"{}\" event handler.",msg),
-                }
-            } else {
-                self.warnings.push(format!("State {} does not have an enter event handler but is being passed parameters in a transition", target_state_name));
-            }
+        for transition in &transitions[1..] {
+            let len = common
+                .iter()
+                .zip(&transition.actions)
+                .take_while(|(a, b)| a == b)
+                .count();
+            common.truncate(len);
         }
+        common
+    }
 
-        // -- State Arguments --
+    //* --------------------------------------------------------------------- *//
 
-        let target_state_args_opt = match &transition_statement.target_state_context_t {
-            StateContextType::StateRef { state_context_node}
-            => &state_context_node.state_ref_args_opt,
-            StateContextType::StateStackPop {}
-            => &Option::None,
-        };
-//
-        if let Some(state_args) = target_state_args_opt {
-//            let mut params_copy = Vec::new();
-            if let Some(state_sym) = self.arcanium.get_state(&target_state_name) {
-                match &state_sym.borrow().params_opt {
-                    Some(event_params) => {
-                        let mut param_symbols_it = event_params.iter();
-                        // Loop through the ARGUMENTS...
-                        for expr_t in &state_args.exprs_t {
-                            // ...and validate w/ the PARAMETERS
-                            match param_symbols_it.next() {
-
-                                Some(param_symbol_rcref) => {
-                                    let param_symbol = param_symbol_rcref.borrow();
-                                    let param_type = match &param_symbol.param_type {
-                                        Some(param_type) => param_type.get_type_str(),
-                                        None => String::from("<?>"),
-                                    };
-                                    let mut expr = String::new();
-                                    expr_t.accept_to_string(self, &mut expr);
-                                    self.add_code(&format!("stateContext.addStateArg(\"{}\",{});", param_symbol.name, expr));
-                                    self.newline();
-                                },
-                                None => panic!("Invalid number of arguments for \"{}\" state parameters.", target_state_name),
-                            }
-//
-                        }
-                    },
-                    None => {}
-                }
-            } else {
-                panic!("TODO");
-            }
-        } // -- State Arguments --
-
-        // -- State Variables --
-
-        let target_state_rcref_opt = self.arcanium.get_state(&target_state_name);
-
-        match target_state_rcref_opt {
-            Some(q) => {
-//                target_state_vars = "stateVars".to_string();
-                if let Some(state_symbol_rcref) = self.arcanium.get_state(&q.borrow().name) {
-                    let state_symbol = state_symbol_rcref.borrow();
-                    let state_node = &state_symbol.state_node.as_ref().unwrap().borrow();
-                    // generate local state variables
-                    if state_node.vars.is_some() {
-//                        let mut separator = "";
-                        for var_rcref in state_node.vars.as_ref().unwrap() {
-                            let var = var_rcref.borrow();
-                            let var_type = match &var.type_opt {
-                                Some(var_type) => var_type.get_type_str(),
-                                None => String::from("<?>"),
-                            };
-                            let expr_t = var.initializer_expr_t_opt.as_ref().unwrap();
-                            let mut expr_code = String::new();
-                            expr_t.accept_to_string(self,&mut expr_code);
-                            self.add_code(&format!("stateContext.addStateVar(\"{}\",{});", var.name, expr_code));
-                            self.newline();
-                        }
-                    }
-                }
-            },
-            None => {
-//                code = target_state_vars.clone();
-            },
-        }
-        let exit_args = if has_exit_args {
-            "exitArgs"
+    fn generate_transition(&mut self, state_name: &str, transition: &XStateTransition) {
+        let guard_key = if self.config.code.xstate_version < 5 {
+            "cond"
         } else {
-            "null"
+            "guard"
         };
-        if self.generate_state_context {
-            if self.generate_exit_args {
-                self.add_code(&format!("_transition_({},{},stateContext);",self.format_target_state_name(target_state_name),exit_args));
-            } else {
-                self.add_code(&format!("_transition_({},stateContext);",self.format_target_state_name(target_state_name)));
-            }
-        } else {
-            if self.generate_exit_args {
-                self.add_code(&format!("_transition_({},{});",self.format_target_state_name(target_state_name),exit_args));
-            } else {
-                self.add_code(&format!("_transition_({});",self.format_target_state_name(target_state_name)));
-            }
+        let mut fields = Vec::new();
+        if let Some(target) = &transition.target_opt {
+            fields.push(format!("target: {:?}", target));
         }
+        if let Some(guard) = XStateVisitor::format_guard(&transition.guards) {
+            fields.push(format!("{}: {:?}", guard_key, guard));
+        }
+        let is_self_transition =
+            transition.target_opt.as_deref() == Some(self.format_target(state_name).as_str());
+        if is_self_transition && !transition.is_change_state && self.config.code.xstate_version >= 5
+        {
+            fields.push(String::from("reenter: true"));
+        }
+        if !transition.actions.is_empty() || fields.is_empty() {
+            fields.push(format!(
+                "actions: {}",
+                XStateVisitor::format_string_list(&transition.actions)
+            ));
+        }
+        self.newline();
+        self.add_code(&format!("{{ {} }},", fields.join(", ")));
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn format_target_state_name(&self,state_name:&str) -> String {
-        format!("_s{}_",state_name)
+    fn generate_transitions(
+        &mut self,
+        state_name: &str,
+        key: &str,
+        transitions: &[XStateTransition],
+    ) {
+        self.newline();
+        self.add_code(&format!("{}: [", key));
+        self.indent();
+        for transition in transitions {
+            self.generate_transition(state_name, transition);
+        }
+        self.outdent();
+        self.newline();
+        self.add_code("],");
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    // NOTE!!: it is *currently* disallowed to send state or event arguments to a state stack pop target.
-    // So currently this method just sets any exitArgs and pops the context from the state stack.
-
-    fn generate_state_stack_pop_transition(&mut self, transition_statement: &TransitionStatementNode) {
-        let mut exit_args_val = String::from("null");
+    fn generate_state(&mut self, state_name: &str) {
+        self.enter_block(state_name);
 
-        self.newline();
-        match &transition_statement.label_opt {
-            Some(label) => {
-                self.add_code(&format!("// {}", label));
+        if let Some(enter_transitions) = self.resolve_handler(state_name, ">") {
+            let entry_actions = XStateVisitor::common_actions(&enter_transitions);
+            if !entry_actions.is_empty() {
                 self.newline();
-            },
-            None => {},
+                self.add_code(&format!(
+                    "entry: {},",
+                    XStateVisitor::format_string_list(&entry_actions)
+                ));
+            }
+            // transitions out of an enter handler are taken immediately
+            // after the entry actions
+            let always: Vec<XStateTransition> = enter_transitions
+                .into_iter()
+                .filter(|transition| transition.target_opt.is_some())
+                .map(|mut transition| {
+                    transition.actions.drain(..entry_actions.len());
+                    transition
+                })
+                .collect();
+            if !always.is_empty() {
+                self.generate_transitions(state_name, "always", &always);
+            }
         }
 
-        // -- Exit Arguments --
-
-        if let Some(exit_args) = &transition_statement.exit_args_opt {
-            if exit_args.exprs_t.len() > 0 {
-
-                // Note - searching for event keyed with "State:<"
-                // e.g. "S1:<"
+        if let Some(exit_transitions) = self.resolve_handler(state_name, "<") {
+            let exit_actions = XStateVisitor::common_actions(&exit_transitions);
+            if !exit_actions.is_empty() {
+                self.newline();
+                self.add_code(&format!(
+                    "exit: {},",
+                    XStateVisitor::format_string_list(&exit_actions)
+                ));
+            }
+        }
 
-                let mut msg: String = String::new();
-                if let Some(state_name) = &self.current_state_name_opt {
-                    msg = state_name.clone();
-                }
-                msg.push_str(":");
-                msg.push_str(&self.symbol_config.exit_msg_symbol);
-
-                if let Some(event_sym) = self.arcanium.get_event(&msg, &self.current_state_name_opt) {
-                    match &event_sym.borrow().params_opt {
-                        Some(event_params) => {
-                            if exit_args.exprs_t.len() != event_params.len() {
-                                panic!("Fatal error: misaligned parameters to arguments.")
-                            }
-                            let mut param_symbols_it = event_params.iter();
-                            self.add_code("FrameEventParams exitArgs = new FrameEventParams();");
-                            self.newline();
-                            // Loop through the ARGUMENTS...
-                            for expr_t in &exit_args.exprs_t {
-                                // ...and validate w/ the PARAMETERS
-                                match param_symbols_it.next() {
-                                    Some(p) => {
-                                        let param_type = match &p.param_type {
-                                            Some(param_type) => param_type.get_type_str(),
-                                            None => String::from("<?>"),
-                                        };
-                                        let mut expr = String::new();
-                                        expr_t.accept_to_string(self, &mut expr);
-                                        self.add_code(&format!("exitArgs[\"{}\"] = {};", p.name, expr));
-                                        self.newline();
-                                    },
-                                    None => panic!("Invalid number of arguments for \"{}\" event handler.", msg),
-                                }
-                            }
-                        },
-                        None => panic!("Fatal error: misaligned parameters to arguments."),
+        // events handled by the state come first, then those it inherits
+        let mut events: Vec<String> = Vec::new();
+        let mut state_name_opt = Some(state_name.to_string());
+        while let Some(name) = state_name_opt {
+            if let Some(handlers) = self.handlers.get(&name) {
+                for handler in handlers {
+                    if handler.event != ">"
+                        && handler.event != "<"
+                        && !events.contains(&handler.event)
+                    {
+                        events.push(handler.event.clone());
                     }
-                } else {
-                    panic!("TODO");
                 }
             }
-        }
-        if self.generate_state_context {
-            self.add_code(&format!("let stateContext = _stateStack_pop();"));
-        } else {
-            self.add_code(&format!("let state = _stateStack_pop_();"));
-        }
-        self.newline();
-        if self.generate_exit_args {
-            if self.generate_state_context {
-                self.add_code(&format!("_transition_(stateContext.state,exitArgs,stateContext);"));
-            } else {
-                self.add_code(&format!("_transition_(state,exitArgs);"));
-            }
-        } else {
-            if self.generate_state_context {
-                self.add_code(&format!("_transition_(stateContext.state,stateContext);"));
-            } else {
-                self.add_code(&format!("_transition_(state);"));
+            state_name_opt = self.parent_name(&name);
+        }
+
+        if !events.is_empty() {
+            self.enter_block("on");
+            for event in &events {
+                let transitions = self.resolve_handler(state_name, event).unwrap_or_default();
+                let key = format!("{:?}", event);
+                match transitions.as_slice() {
+                    [transition]
+                        if transition.target_opt.is_none() && transition.guards.is_empty() =>
+                    {
+                        self.newline();
+                        self.add_code(&format!(
+                            "{}: {{ actions: {} }},",
+                            key,
+                            XStateVisitor::format_string_list(&transition.actions)
+                        ));
+                    }
+                    _ => self.generate_transitions(state_name, &key, &transitions),
+                }
             }
+            self.exit_block();
         }
+
+        self.exit_block();
     }
 }
 
 //* --------------------------------------------------------------------- *//
 
 impl AstVisitor for XStateVisitor {
-
-    //* --------------------------------------------------------------------- *//
-
     fn visit_system_node(&mut self, system_node: &SystemNode) {
-        self.system_name = system_node.name.clone();
-        self.add_code(&format!("// {}",self.compiler_version));
-        self.newline();
-        self.add_code("// get include files at https://github.com/frame-lang/frame-ancillary-files");
+        if system_node.has_deferred_events() {
+            self.errors.push(String::from(
+                "Deferred events are not supported by the xstate target.",
+            ));
+        }
+        self.add_code(&format!("// {}", self.compiler_version));
         self.newline();
         self.newline();
-        self.add_code(&format!("const {}Machine = Machine({{", system_node.name));
+        self.add_code(&format!(
+            "export const {}MachineConfig = {{",
+            system_node.name
+        ));
         self.indent();
         self.newline();
-        self.add_code(&format!("id: '{}',", system_node.name));
+        self.add_code(&format!("id: {:?},", system_node.name));
 
-
-
-        // First state name needed for machinery.
-        // Don't generate if there isn't at least one state.
-        match (&system_node).get_first_state() {
-            Some(x) => {
-                self.first_state_name = x.borrow().name.clone();
-                self.has_states = true;
-                self.newline();
-                self.add_code(&format!("initial: '{}',",self.first_state_name));
-            },
-            None => {},
+        if let Some(first_state) = system_node.get_first_state() {
+            self.newline();
+            self.add_code(&format!("initial: {:?},", first_state.borrow().name));
         }
 
-
-
-        // if let Some(interface_block_node) = &system_node.interface_block_node_opt {
-        //     interface_block_node.accept(self);
-        // }
+        if let Some(domain_block_node) = &system_node.domain_block_node_opt {
+            domain_block_node.accept(self);
+        }
 
         if let Some(machine_block_node) = &system_node.machine_block_node_opt {
             machine_block_node.accept(self);
         }
 
-        // if let Some(actions_block_node) = &system_node.actions_block_node_opt {
-        //     actions_block_node.accept(self);
-        // }
-        //
-        // if let Some(domain_block_node) = &system_node.domain_block_node_opt {
-        //     domain_block_node.accept(self);
-        // }
-
-        // self.subclass_code.push(format!("\treturn that;"));
-        // self.subclass_code.push(format!("}};"));
-        // self.subclass_code.push("********************/".to_string());
-        //
-        // self.serialize.push("".to_string());
-        // self.serialize.push("\treturn JSON.stringify(bag);".to_string());
-        // self.serialize.push("}".to_string());
-        // self.serialize.push("".to_string());
-        //
-        // self.deserialize.push("".to_string());
-        // self.deserialize.push("}".to_string());
-
-        // if self.has_states {
-        //     self.generate_machinery(system_node);
-        // }
-
-        // self.newline();
-        // self.newline();
-        // self.add_code("return that; ");
-        // if self.generate_comment(system_node.line) {
-        //     self.newline();
-        // }
-        // self.outdent();
-        // if self.generate_comment(system_node.line) {
-        //     self.newline();
-        // }
+        self.outdent();
         self.newline();
         self.add_code("};");
         self.newline();
-
-        self.generate_subclass();
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_frame_messages_enum(&mut self, interface_block_node: &InterfaceBlockNode) {
-        panic!("Error - visit_frame_messages_enum() only used in Rust.");
-
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_interface_parameters(&mut self, interface_block_node: &InterfaceBlockNode) {
-        panic!("visit_interface_parameters() not valid for target language.");
-
+    fn visit_machine_block_node(&mut self, machine_block_node: &MachineBlockNode) {
+        for state_node_rcref in &machine_block_node.states {
+            let state_node = state_node_rcref.borrow();
+            let handlers: Vec<XStateHandler> = state_node
+                .evt_handlers_rcref
+                .iter()
+                .map(|evt_handler_rcref| self.collect_handler(&evt_handler_rcref.borrow()))
+                .collect();
+            self.state_names.push(state_node.name.clone());
+            self.handlers.insert(state_node.name.clone(), handlers);
+        }
+
+        self.enter_block("states");
+        for state_name in self.state_names.clone() {
+            self.generate_state(&state_name);
+        }
+        // `-> $$[-]` returns to the most recently active state
+        if self.uses_history {
+            self.newline();
+            self.add_code("history: { type: \"history\", history: \"deep\" },");
+        }
+        self.exit_block();
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_interface_block_node(&mut self, interface_block_node: &InterfaceBlockNode) {
-        self.newline();
-        self.add_code("//===================== Interface Block ===================//");
-        self.newline();
-
-        for interface_method_node in &interface_block_node.interface_methods {
-            interface_method_node.accept(self);
+    fn visit_domain_block_node(&mut self, domain_block_node: &DomainBlockNode) {
+        if domain_block_node.member_variables.is_empty() {
+            return;
+        }
+        self.enter_block("context");
+        for variable_decl_node_rcref in &domain_block_node.member_variables {
+            let variable_decl_node = variable_decl_node_rcref.borrow();
+            let value = self.format_context_value(&variable_decl_node.initializer_expr_t_opt);
+            self.newline();
+            self.add_code(&format!("{}: {},", variable_decl_node.name, value));
         }
+        self.exit_block();
+    }
 
+    fn visit_action_node_rust_trait(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_action_node_rust_trait() not implemented.");
     }
 
-    //* --------------------------------------------------------------------- *//
+    fn visit_actions_node_rust_impl(&mut self, _: &ActionsBlockNode) {
+        panic!("Error - visit_actions_node_rust_impl() not implemented.");
+    }
 
-    fn visit_interface_method_node(&mut self, interface_method_node: &InterfaceMethodNode) {
-        let mut call_params = String::new();
-        let mut send_params = String::new();
-
-        call_params.push_str(&format!("\n{}that.{} = function (", self.dent(), interface_method_node.name));
-
-        let has_params;
-        match &interface_method_node.params {
-            Some(params) => {
-                has_params = true;
-                let mut separator = "";
-                for param in params {
-                    let pname = &param.param_name;
-                    call_params.push_str(&format!("{}{}", separator, pname));
-                    send_params.push_str(&format!("{}\"{}\":{}", separator, pname, pname));
-                    separator = ",";
-                }
-            },
-            None => { has_params = false }
+    fn visit_transition_statement_node(&mut self, transition_statement: &TransitionStatementNode) {
+        match &transition_statement.target_state_context_t {
+            StateContextType::StateRef { state_context_node } => {
+                let target = self.format_target(&state_context_node.state_ref_node.name);
+                self.add_transition(target, false);
+            }
+            StateContextType::StateStackPop {} => {
+                self.uses_history = true;
+                let target = format!("#{}.history", self.system_hierarchy.system_name);
+                self.add_transition(target, false);
+            }
         }
+    }
 
-        // see if an alias exists.
-        let method_name_or_alias: &String;
-
-        match &interface_method_node.alias {
-            Some(alias_message_node) => {
-                method_name_or_alias = &alias_message_node.name;
-            },
-            None => {
-                method_name_or_alias = &interface_method_node.name;
+    fn visit_change_state_statement_node(
+        &mut self,
+        change_state_stmt_node: &ChangeStateStatementNode,
+    ) {
+        match &change_state_stmt_node.state_context_t {
+            StateContextType::StateRef { state_context_node } => {
+                let target = self.format_target(&state_context_node.state_ref_node.name);
+                self.add_transition(target, true);
+            }
+            StateContextType::StateStackPop {} => {
+                self.uses_history = true;
+                let target = format!("#{}.history", self.system_hierarchy.system_name);
+                self.add_transition(target, true);
             }
         }
-
-        self.add_code(&format!("{}) {{", call_params));
-        self.indent();
-        self.newline();
-        if has_params {
-            self.add_code(&format!("let e = FrameEvent(\"{}\",{{{}}});", method_name_or_alias, send_params));
-        } else {
-            self.add_code(&format!("let e = FrameEvent(\"{}\",null);", method_name_or_alias));
-        }
-        self.newline();
-        self.add_code(&format!("_state_(e);"));
-
-        match &interface_method_node.return_type_opt {
-            Some(return_type) => {
-                self.newline();
-                self.add_code(&format!("return e._return;"));
-            },
-            None => {}
-        }
-
-        self.outdent(); self.newline();
-        self.add_code(&format!("}}"));
-        self.newline();
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_machine_block_node(&mut self, machine_block_node: &MachineBlockNode) {
-        self.newline();
-        self.add_code("states: {");
-        self.indent();
-        self.newline();
-        // self.add_code("//===================== Machine Block ===================//");
-
-        // self.serialize.push("".to_string());
-        // self.serialize.push("\tlet stateName = null".to_string());
-        //
-        // self.deserialize.push("".to_string());
-        // self.deserialize.push("\tconst bag = JSON.parse(data);".to_string());
-        // self.deserialize.push("".to_string());
-        // self.deserialize.push("\tswitch (bag.state) {".to_string());
-
-        for state_node_rcref in &machine_block_node.states {
-            state_node_rcref.borrow().accept(self);
-        }
-
-        // self.serialize.push("".to_string());
-        // self.serialize.push("\tvar bag = {".to_string());
-        // self.serialize.push("\t\tstate : stateName,".to_string());
-        // self.serialize.push("\t\tdomain : {}".to_string());
-        // self.serialize.push("\t};".to_string());
-        // self.serialize.push("".to_string());
-        //
-        // self.deserialize.push("\t}".to_string());
-        // self.deserialize.push("".to_string());
-
-        self.indent();
-        self.newline();
-        self.add_code("},");
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_actions_block_node(&mut self, actions_block_node: &ActionsBlockNode) {
-        self.newline();
-        self.newline();
-        self.add_code("//===================== Actions Block ===================//");
-        self.newline();
-
-        for action_decl_node_rcref in &actions_block_node.actions {
-            let action_decl_node = action_decl_node_rcref.borrow();
-            action_decl_node.accept(self);
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_action_node_rust_trait(&mut self, _: &ActionsBlockNode) {
-        panic!("Error - visit_action_node_rust_trait() not implemented.");
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_actions_node_rust_impl(&mut self, _: &ActionsBlockNode) {
-        panic!("Error - visit_actions_node_rust_impl() not implemented.");
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_domain_block_node(&mut self, domain_block_node: &DomainBlockNode) {
-        self.newline();
-        self.newline();
-        self.add_code("//===================== Domain Block ===================//");
-        self.newline();
-
-        let mut newline = false;
-        for variable_decl_node_rcref in &domain_block_node.member_variables {
-            let variable_decl_node = variable_decl_node_rcref.borrow();
-            variable_decl_node.accept(self);
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_state_node(&mut self, state_node: &StateNode) {
-
-        self.generate_comment(state_node.line);
-        self.current_state_name_opt = Some(state_node.name.clone());
-        self.newline();
-        self.add_code(&format!("{}: {{", state_node.name));
-        self.indent();
-
-        // self.serialize.push(format!("\tif (_state_ == _s{}_) stateName = \"{}\"",state_node.name,state_node.name));
-        //
-        // self.deserialize.push(format!("\t\tcase \"{}\": _state_ = _s{}_; break;",state_node.name,state_node.name));
-
-        // @TODO: remove if not needed
-        // let state_symbol = match self.arcanium.get_state(&state_node.name) {
-        //     Some(state_symbol) => state_symbol,
-        //     None => panic!("TODO"),
-        // };
-
-        // if let Some(calls) = &state_node.calls {
-        //     for call in calls {
-        //         self.newline();
-        //         call.accept(self);
-        //         self.add_code(";");
-        //     }
-        // }
-
-        self.first_event_handler = true; // context for formatting
-
-        if state_node.evt_handlers_rcref.len() > 0 {
-            for evt_handler_node in &state_node.evt_handlers_rcref {
-                evt_handler_node.as_ref().borrow().accept(self);
-            }
-        }
-
-        match &state_node.dispatch_opt {
-            Some(dispatch) => {
-                dispatch.accept(self);
-            },
-            None => {},
-        }
-
-        self.outdent();
-        self.newline();
-        self.add_code("}");
-
-        self.current_state_name_opt = None;
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_event_handler_node(&mut self, evt_handler_node: &EventHandlerNode) {
-        self.newline();
-        self.generate_comment(evt_handler_node.line);
-//        let mut generate_final_close_paren = true;
-        if let MessageType::CustomMessage {message_node} = &evt_handler_node.msg_t {
-            // if self.first_event_handler {
-            //     self.add_code(&format!("if (e._message == \"{}\") {{", message_node.name));
-            // } else {
-            self.add_code(&format!("{}:", self.get_msg_enum(&message_node.name)));
-            // }
-
-            if self.is_enter_or_exit_event(&*self.get_msg_enum(&message_node.name)) {
-                self.add_code(&format!("["));
-            } else {
-                self.add_code(&format!("{{"));
-            }
-
-            self.indent();
-            self.newline();
-            // Generate statements
-            self.visit_decl_stmts(&evt_handler_node.statements);
-
-            // let terminator_node = &evt_handler_node.terminator_node;
-            // terminator_node.accept(self);
-            self.outdent();
-            self.newline();
-            if self.is_enter_or_exit_event(&*self.get_msg_enum(&message_node.name)) {
-                self.add_code(&format!("],"));
-            } else {
-                self.add_code(&format!("}}"));
-            }
-
-            // this controls formatting here
-            self.first_event_handler = false;
-        } else {
-            panic!("AnyMessage (||*) not supported for XState.")
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_event_handler_terminator_node(&mut self, evt_handler_terminator_node: &TerminatorExpr) {
-        self.newline();
-        match &evt_handler_terminator_node.terminator_type {
-            TerminatorType::Return => {
-                match &evt_handler_terminator_node.return_expr_t_opt {
-                    Some(expr_t) => {
-                        self.add_code(&format!("e._return = "));
-                        expr_t.accept(self);
-                        self.newline();
-                        self.add_code("return;");
-                        self.newline();
-                    },
-                    None => self.add_code("return;"),
-                }
-
-            },
-            TerminatorType::Continue => {
-                // self.add_code("break;")
-            },
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_call_statement_node(&mut self, method_call_statement: &CallStmtNode) {
-        self.newline();
-        method_call_statement.call_expr_node.accept(self);
-        self.add_code(&format!(";"));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_call_expression_node(&mut self, method_call: &CallExprNode) {
-
-        if let Some(call_chain) = &method_call.call_chain {
-
-            for callable in call_chain {
-                callable.callable_accept(self);
-                self.add_code(&format!("."));
-            }
-        }
-
-        self.add_code(&format!("{}", method_call.identifier.name.lexeme));
-
-        method_call.call_expr_list.accept(self);
-
-        self.add_code(&format!(""));
-
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_call_expression_node_to_string(&mut self, method_call: &CallExprNode, output:&mut String) {
-
-        if let Some(call_chain) = &method_call.call_chain {
-
-            for callable in call_chain {
-                callable.callable_accept(self);
-                output.push_str(&format!("."));
-            }
-        }
-
-        output.push_str(&format!("{}", method_call.identifier.name.lexeme));
-
-        method_call.call_expr_list.accept_to_string(self, output);
-
-        output.push_str(&format!(""));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_call_expr_list_node(&mut self, call_expr_list: &CallExprListNode) {
-
-        let mut separator = "";
-        self.add_code(&format!("("));
-
-        for expr in &call_expr_list.exprs_t {
-
-            self.add_code(&format!("{}",separator));
-            expr.accept(self);
-            separator = ",";
-        }
-
-        self.add_code(&format!(")"));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_call_expr_list_node_to_string(&mut self, call_expr_list: &CallExprListNode, output:&mut String) {
-
-        let mut separator = "";
-        output.push_str(&format!("("));
-
-        for expr in &call_expr_list.exprs_t {
-
-            output.push_str(&format!("{}",separator));
-            expr.accept_to_string(self, output);
-            separator = ",";
-        }
-
-        output.push_str(&format!(")"));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_action_call_expression_node(&mut self, action_call: &ActionCallExprNode) {
-
-        let action_name = self.format_action_name(&action_call.identifier.name.lexeme);
-        self.add_code(&format!("{}", action_name));
-
-        action_call.call_expr_list.accept(self);
-
-        self.add_code(&format!(""));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_action_call_expression_node_to_string(&mut self, action_call: &ActionCallExprNode, output:&mut String) {
-
-        let action_name = self.format_action_name(&action_call.identifier.name.lexeme);
-        output.push_str(&format!("{}",action_name));
-
-        action_call.call_expr_list.accept_to_string(self, output);
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_action_call_statement_node(&mut self, action_call_stmt_node: &ActionCallStmtNode) {
-        self.newline();
-        action_call_stmt_node.action_call_expr_node.accept(self);
-        self.add_code(&format!(";"));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_transition_statement_node(&mut self, transition_statement: &TransitionStatementNode) {
-
-        match &transition_statement.target_state_context_t {
-            StateContextType::StateRef { state_context_node}
-            => self.generate_state_ref_transition(transition_statement),
-            StateContextType::StateStackPop {}
-            => self.generate_state_stack_pop_transition(transition_statement),
-        };
-
-    }
-
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_state_ref_node(&mut self, state_ref: &StateRefNode) {
-        self.add_code(&format!("{}", state_ref.name));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_change_state_statement_node(&mut self, change_state_stmt_node:&ChangeStateStatementNode) {
-
-        match &change_state_stmt_node.state_context_t {
-            StateContextType::StateRef { state_context_node}
-            => self.generate_state_ref_change_state(change_state_stmt_node),
-            StateContextType::StateStackPop {}
-            => panic!("TODO - not implemented"),
-        };
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    // TODO: ??
-    fn visit_parameter_node(&mut self, parameter_node: &ParameterNode) {
-
-        // self.add_code(&format!("{}",parameter_node.name));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_dispatch_node(&mut self, dispatch_node: &DispatchNode) {
-        self.newline();
-        self.add_code(&format!("_s{}_(e);", dispatch_node.target_state_ref.name));
-        self.generate_comment(dispatch_node.line);
-        self.newline();
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_test_statement_node(&mut self, test_stmt_node: &TestStatementNode) {
-
-        match &test_stmt_node.test_t {
-            TestType::BoolTest {bool_test_node}  => {
-                bool_test_node.accept(self);
-            },
-            TestType::StringMatchTest {string_match_test_node} => {
-                string_match_test_node.accept(self);
-            },
-            TestType::NumberMatchTest {number_match_test_node} => {
-                number_match_test_node.accept(self);
-            },
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_bool_test_node(&mut self, bool_test_node:&BoolTestNode) {
-
-        let mut if_or_else_if = "if ";
-
-        self.newline();
-        for branch_node in &bool_test_node.conditional_branch_nodes {
-            if branch_node.is_negated {
-                self.add_code(&format!("{}(!(",if_or_else_if));
-            } else {
-                self.add_code(&format!("{}(",if_or_else_if));
-            }
-
-            branch_node.expr_t.accept(self);
-
-            if branch_node.is_negated {
-                self.add_code(&format!(")"));
-            }
-            self.add_code(&format!(") {{"));
-            self.indent();
-
-            branch_node.accept(self);
-
-            self.outdent(); self.newline();
-            self.add_code(&format!("}}"));
-
-            if_or_else_if = " else if ";
-        }
-
-        // (':' bool_test_else_branch)?
-        if let Some(bool_test_else_branch_node) = &bool_test_node.else_branch_node_opt {
-            bool_test_else_branch_node.accept(self);
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_call_chain_literal_statement_node(&mut self, method_call_chain_literal_stmt_node:&CallChainLiteralStmtNode) {
-
-        self.newline();
-        method_call_chain_literal_stmt_node.call_chain_literal_expr_node.accept(self);
-        self.add_code(&format!(";"));
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_call_chain_literal_expr_node(&mut self, method_call_chain_expression_node: &CallChainLiteralExprNode) {
-        // TODO: maybe put this in an AST node
-
-        let mut separator = "";
-
-        for node in &method_call_chain_expression_node.call_chain {
-            self.add_code(&format!("{}",separator));
-            match &node {
-                CallChainLiteralNodeType::IdentifierNodeT { id_node }=> {
-                    id_node.accept(self);
-                },
-                CallChainLiteralNodeType::CallT {call}=> {
-                    call.accept(self);
-                },
-                CallChainLiteralNodeType::ActionCallT {action_call_expr_node}=> {
-                    action_call_expr_node.accept(self);
-                },
-                CallChainLiteralNodeType::VariableNodeT {var_node}=> {
-                    self.visiting_call_chain_literal_variable = true;
-                    var_node.accept(self);
-                    self.visiting_call_chain_literal_variable = false;
-                },
-            }
-            separator = ".";
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_call_chain_literal_expr_node_to_string(&mut self, method_call_chain_expression_node:&CallChainLiteralExprNode, output:&mut String) {
-        let mut separator = "";
-
-        for node in &method_call_chain_expression_node.call_chain {
-            output.push_str(&format!("{}",separator));
-            match &node {
-                CallChainLiteralNodeType::IdentifierNodeT { id_node }=> {
-                    id_node.accept_to_string(self,output);
-                },
-                CallChainLiteralNodeType::CallT {call}=> {
-                    call.accept_to_string(self, output);
-                },
-                CallChainLiteralNodeType::ActionCallT {action_call_expr_node}=> {
-                    action_call_expr_node.accept_to_string(self, output);
-                },
-                CallChainLiteralNodeType::VariableNodeT {var_node}=> {
-                    var_node.accept_to_string(self, output);
-                },
-            }
-            separator = ".";
-        }
-    }
-
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_bool_test_conditional_branch_node(&mut self, bool_test_true_branch_node:&BoolTestConditionalBranchNode) {
-
-        self.visit_decl_stmts(&bool_test_true_branch_node.statements);
-
-        match &bool_test_true_branch_node.branch_terminator_expr_opt {
-            Some(branch_terminator_expr) => {
-                self.newline();
-                match &branch_terminator_expr.terminator_type {
-                    Return => {
-                        match &branch_terminator_expr.return_expr_t_opt {
-                            Some(expr_t) => {
-                                self.add_code(&format!("e._return = "));
-                                expr_t.accept(self);
-                                self.add_code(";");
-                                self.newline();
-                                self.add_code("return;");
-                            },
-                            None => self.add_code("return;"),
-                        }
-                    }
-                    Continue => {
-                        self.add_code("break;");
-                    }
-                }
-            }
-            None => {}
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_bool_test_else_branch_node(&mut self, bool_test_else_branch_node:&BoolTestElseBranchNode) {
-
-        self.add_code(&format!(" else {{"));
-        self.indent();
-
-        self.visit_decl_stmts(&bool_test_else_branch_node.statements);
-
-        // TODO - factor this out to work w/ other terminator code.
-        match &bool_test_else_branch_node.branch_terminator_expr_opt {
-            Some(branch_terminator_expr) => {
-                self.newline();
-                match &bool_test_else_branch_node {
-                    Return => {
-                        match &branch_terminator_expr.return_expr_t_opt {
-                            Some(expr_t) => {
-                                self.add_code(&format!("e._return = ",));
-                                expr_t.accept(self);
-                                self.add_code(";");
-                                self.newline();
-                                self.add_code("return;");
-                            },
-                            None => self.add_code("return;"),
-                        }
-                    }
-                    Continue => {
-                        self.add_code("break;");
-                    }
-                }
-            }
-            None => {}
-        }
-
-        self.outdent();
-        self.newline();
-        self.add_code(&format!("}}"));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_string_match_test_node(&mut self, string_match_test_node:&StringMatchTestNode) {
-
-        let mut if_or_else_if = "if";
-
-        self.newline();
-        for match_branch_node in &string_match_test_node.match_branch_nodes {
-            self.add_code(&format!("{} (", if_or_else_if));
-            // TODO: use string_match_test_node.expr_t.accept(self) ?
-            match &string_match_test_node.expr_t {
-                ExprType::CallExprT { call_expr_node: method_call_expr_node }
-                => method_call_expr_node.accept(self),
-                ExprType::ActionCallExprT { action_call_expr_node }
-                => action_call_expr_node.accept(self),
-                ExprType::CallChainLiteralExprT { call_chain_expr_node }
-                => call_chain_expr_node.accept(self),
-                ExprType::VariableExprT { var_node: id_node }
-                => id_node.accept(self),
-
-                _ => panic!("TODO"),
-            }
-
-            // TODO: use accept
-            // self.add_code(&format!(" == \""));
-            // match_branch_node.string_match_pattern_node.accept(self);
-            // self.add_code(&format!("\") {{"));
-
-            let mut first_match = true;
-            for match_string in &match_branch_node.string_match_pattern_node.match_pattern_strings {
-                if first_match {
-                    self.add_code(&format!(" == \"{}\")",match_string));
-                    first_match = false;
-                } else {
-                    self.add_code(&format!(" || ("));
-                    match &string_match_test_node.expr_t {
-                        ExprType::CallExprT { call_expr_node: method_call_expr_node }
-                        => method_call_expr_node.accept(self),
-                        ExprType::ActionCallExprT { action_call_expr_node }
-                        => action_call_expr_node.accept(self),
-                        ExprType::CallChainLiteralExprT { call_chain_expr_node }
-                        => call_chain_expr_node.accept(self),
-                        ExprType::VariableExprT { var_node: id_node }
-                        => id_node.accept(self),
-                        _ => panic!("TODO"),
-                    }
-                    self.add_code(&format!(" == \"{}\")",match_string));
-                }
-            }
-            self.add_code(&format!(" {{"));
-            self.indent();
-
-            match_branch_node.accept(self);
-
-            self.outdent(); self.newline();
-            self.add_code(&format!("}}"));
-
-            if_or_else_if = " else if";
-        }
-
-        // (':' string_test_else_branch)?
-        if let Some(string_match_else_branch_node) = &string_match_test_node.else_branch_node_opt {
-            string_match_else_branch_node.accept(self);
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_string_match_test_match_branch_node(&mut self, string_match_test_match_branch_node:&StringMatchTestMatchBranchNode) {
-
-//        self.indent();
-        self.visit_decl_stmts(&string_match_test_match_branch_node.statements);
-
-        // TODO - factor this out to work w/ other terminator code.
-        match &string_match_test_match_branch_node.branch_terminator_expr_opt {
-            Some(branch_terminator_expr) => {
-                self.newline();
-                match &branch_terminator_expr.terminator_type {
-                    Return => {
-                        match &branch_terminator_expr.return_expr_t_opt {
-                            Some(expr_t) => {
-                                self.add_code(&format!("e._return = "));
-                                expr_t.accept(self);
-                                self.add_code(";");
-                                self.newline();
-                                self.add_code("return;");
-                            },
-                            None => self.add_code("return;"),
-                        }
-                    }
-                    Continue => {
-                        self.add_code("break;");
-
-                    }
-                }
-            }
-            None => {}
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_string_match_test_else_branch_node(&mut self, string_match_test_else_branch_node:&StringMatchTestElseBranchNode) {
-
-        self.add_code(&format!(" else {{"));
-        self.indent();
-
-        self.visit_decl_stmts(&string_match_test_else_branch_node.statements);
-
-        // TODO - factor this out to work w/ other terminator code.
-        match &string_match_test_else_branch_node.branch_terminator_expr_opt {
-            Some(branch_terminator_expr) => {
-                self.newline();
-                match &string_match_test_else_branch_node {
-                    Return => {
-                        match &branch_terminator_expr.return_expr_t_opt {
-                            Some(expr_t) => {
-                                self.add_code(&format!("e._return = ",));
-                                expr_t.accept(self);
-                                self.add_code(";");
-                                self.newline();
-                                self.add_code("return;");
-                            },
-                            None => self.add_code("return;"),
-                        }
-                    }
-                    Continue => {
-                        self.add_code("break;");
-
-                    }
-                }
-            }
-            None => {}
-        }
-
-        self.outdent();
-        self.newline();
-        self.add_code(&format!("}}"));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_string_match_test_pattern_node(&mut self, string_match_test_else_branch_node:&StringMatchTestPatternNode) {
-
-        // TODO
-        panic!("todo");
-    }
-
-    //-----------------------------------------------------//
-
-    fn visit_number_match_test_node(&mut self, number_match_test_node:&NumberMatchTestNode) {
-
-        let mut if_or_else_if = "if";
-
-        self.newline();
-        for match_branch_node in &number_match_test_node.match_branch_nodes {
-            self.add_code(&format!("{} (", if_or_else_if));
-            match &number_match_test_node.expr_t {
-                ExprType::CallExprT { call_expr_node: method_call_expr_node }
-                => method_call_expr_node.accept(self),
-                ExprType::ActionCallExprT { action_call_expr_node }
-                => action_call_expr_node.accept(self),
-                ExprType::CallChainLiteralExprT { call_chain_expr_node }
-                => call_chain_expr_node.accept(self),
-                ExprType::VariableExprT { var_node: id_node }
-                => id_node.accept(self),
-                _ => panic!("TODO"),
-            }
-
-            let mut first_match = true;
-            for match_number in &match_branch_node.number_match_pattern_nodes {
-                if first_match {
-                    self.add_code(&format!(" == {})",match_number.match_pattern_number));
-                    first_match = false;
-                } else {
-                    self.add_code(&format!(" || ("));
-                    match &number_match_test_node.expr_t {
-                        ExprType::CallExprT { call_expr_node: method_call_expr_node }
-                        => method_call_expr_node.accept(self),
-                        ExprType::ActionCallExprT { action_call_expr_node }
-                        => action_call_expr_node.accept(self),
-                        ExprType::CallChainLiteralExprT { call_chain_expr_node }
-                        => call_chain_expr_node.accept(self),
-                        ExprType::VariableExprT { var_node: id_node }
-                        => id_node.accept(self),
-                        _ => panic!("TODO"),
-                    }
-                    self.add_code(&format!(" == {})",match_number.match_pattern_number));
-                }
-            }
-
-            self.add_code(&format!(") {{"));
-            self.indent();
-
-            match_branch_node.accept(self);
-
-            self.outdent(); self.newline();
-            self.add_code(&format!("}}"));
-
-            //           self.indent();
-
-            if_or_else_if = " else if";
-        }
-
-        // (':' number_test_else_branch)?
-        if let Some(number_match_else_branch_node) = &number_match_test_node.else_branch_node_opt {
-            number_match_else_branch_node.accept(self);
-        }
-
-
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_number_match_test_match_branch_node(&mut self, number_match_test_match_branch_node:&NumberMatchTestMatchBranchNode) {
-
-        self.visit_decl_stmts(&number_match_test_match_branch_node.statements);
-
-        // TODO - factor this out to work w/ other terminator code.
-        match &number_match_test_match_branch_node.branch_terminator_expr_opt {
-            Some(branch_terminator_expr) => {
-                self.newline();
-                match number_match_test_match_branch_node {
-                    Return => {
-                        match &branch_terminator_expr.return_expr_t_opt {
-                            Some(expr_t) => {
-                                self.add_code(&format!("e._return = "));
-                                expr_t.accept(self);
-                                self.add_code(";");
-                                self.newline();
-                                self.add_code("return;");
-                            },
-                            None => self.add_code("return;"),
-                        }
-                    }
-                    Continue => {
-                        self.add_code("break;");
-
-                    }
-                }
+    fn visit_test_statement_node(&mut self, test_stmt_node: &TestStatementNode) {
+        match &test_stmt_node.test_t {
+            TestType::BoolTest { bool_test_node } => {
+                bool_test_node.accept(self);
             }
-            None => {}
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_number_match_test_else_branch_node(&mut self, number_match_test_else_branch_node:&NumberMatchTestElseBranchNode) {
-
-        self.add_code(&format!(" else {{"));
-        self.indent();
-
-        self.visit_decl_stmts(&number_match_test_else_branch_node.statements);
-
-        // TODO - factor this out to work w/ other terminator code.
-        match &number_match_test_else_branch_node.branch_terminator_expr_opt {
-            Some(branch_terminator_expr) => {
-                self.newline();
-                match number_match_test_else_branch_node {
-                    Return => {
-                        match &branch_terminator_expr.return_expr_t_opt {
-                            Some(expr_t) => {
-                                self.add_code(&format!("e._return = "));
-                                expr_t.accept(self);
-                                self.add_code(";");
-                                self.newline();
-                                self.add_code("return;");
-                            },
-                            None => self.add_code("return;"),
-                        }
-                    }
-                    Continue => {
-                        self.add_code("break;");
-
-                    }
-                }
+            TestType::StringMatchTest {
+                string_match_test_node,
+            } => {
+                string_match_test_node.accept(self);
+            }
+            TestType::NumberMatchTest {
+                number_match_test_node,
+            } => {
+                number_match_test_node.accept(self);
+            }
+            TestType::RegexMatchTest {
+                regex_match_test_node,
+            } => {
+                regex_match_test_node.accept(self);
+            }
+            TestType::EnumMatchTest {
+                enum_match_test_node,
+            } => {
+                enum_match_test_node.accept(self);
             }
-            None => {}
-        }
-
-        self.outdent();
-        self.newline();
-        self.add_code(&format!("}}"));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_number_match_test_pattern_node(&mut self, match_pattern_node:&NumberMatchTestPatternNode) {
-        self.add_code(&format!("{}", match_pattern_node.match_pattern_number));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_expression_list_node(&mut self, expr_list: &ExprListNode) {
-
-        let mut separator = "";
-        self.add_code(&format!("("));
-        for expr in &expr_list.exprs_t {
-
-            self.add_code(&format!("{}",separator));
-            expr.accept(self);
-            separator = ",";
         }
-        self.add_code(&format!(")"));
-
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_expression_list_node_to_string(&mut self, expr_list: &ExprListNode, output:&mut String) {
-
-//        self.add_code(&format!("{}(e);\n",dispatch_node.target_state_ref.name));
-
+    fn visit_bool_test_node(&mut self, bool_test_node: &BoolTestNode) {
+        let mut branches = Vec::new();
+        for branch_node in &bool_test_node.conditional_branch_nodes {
+            let mut condition = self.format_expr(&branch_node.expr_t);
+            if branch_node.is_negated {
+                condition = XStateVisitor::negate(&condition);
+            }
+            branches.push((
+                condition,
+                branch_node.statements.as_slice(),
+                &branch_node.branch_terminator_expr_opt,
+            ));
+        }
+        let else_branch_opt = bool_test_node
+            .else_branch_node_opt
+            .as_ref()
+            .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+        self.visit_test_branches(branches, else_branch_opt);
+    }
+
+    fn visit_string_match_test_node(&mut self, string_match_test_node: &StringMatchTestNode) {
+        let subject = self.format_expr(&string_match_test_node.expr_t);
+        let mut branches = Vec::new();
+        for branch_node in &string_match_test_node.match_branch_nodes {
+            let patterns: Vec<String> = branch_node
+                .string_match_pattern_node
+                .match_pattern_strings
+                .iter()
+                .map(|pattern| format!("{} == {:?}", subject, pattern))
+                .collect();
+            branches.push((
+                patterns.join(" || "),
+                branch_node.statements.as_slice(),
+                &branch_node.branch_terminator_expr_opt,
+            ));
+        }
+        let else_branch_opt = string_match_test_node
+            .else_branch_node_opt
+            .as_ref()
+            .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+        self.visit_test_branches(branches, else_branch_opt);
+    }
+
+    fn visit_number_match_test_node(&mut self, number_match_test_node: &NumberMatchTestNode) {
+        let subject = self.format_expr(&number_match_test_node.expr_t);
+        let mut branches = Vec::new();
+        for branch_node in &number_match_test_node.match_branch_nodes {
+            let patterns: Vec<String> = branch_node
+                .number_match_pattern_nodes
+                .iter()
+                .map(|pattern| format!("{} == {}", subject, pattern.match_pattern_number))
+                .collect();
+            branches.push((
+                patterns.join(" || "),
+                branch_node.statements.as_slice(),
+                &branch_node.branch_terminator_expr_opt,
+            ));
+        }
+        let else_branch_opt = number_match_test_node
+            .else_branch_node_opt
+            .as_ref()
+            .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+        self.visit_test_branches(branches, else_branch_opt);
+    }
+
+    fn visit_regex_match_test_node(&mut self, regex_match_test_node: &RegexMatchTestNode) {
+        let subject = self.format_expr(&regex_match_test_node.expr_t);
+        let mut branches = Vec::new();
+        for branch_node in &regex_match_test_node.match_branch_nodes {
+            branches.push((
+                format!(
+                    "/{}/.test({})",
+                    branch_node.regex_match_pattern_node.pattern, subject
+                ),
+                branch_node.statements.as_slice(),
+                &branch_node.branch_terminator_expr_opt,
+            ));
+        }
+        let else_branch_opt = regex_match_test_node
+            .else_branch_node_opt
+            .as_ref()
+            .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+        self.visit_test_branches(branches, else_branch_opt);
+    }
+
+    fn visit_enum_match_test_node(&mut self, enum_match_test_node: &EnumMatchTestNode) {
+        let subject = self.format_expr(&enum_match_test_node.expr_t);
+        let mut branches = Vec::new();
+        for branch_node in &enum_match_test_node.match_branch_nodes {
+            let patterns: Vec<String> = branch_node
+                .enumerators
+                .iter()
+                .map(|enumerator| {
+                    format!(
+                        "{} == {}.{}",
+                        subject, enum_match_test_node.enum_type_name, enumerator
+                    )
+                })
+                .collect();
+            branches.push((
+                patterns.join(" || "),
+                branch_node.statements.as_slice(),
+                &branch_node.branch_terminator_expr_opt,
+            ));
+        }
+        let else_branch_opt = enum_match_test_node
+            .else_branch_node_opt
+            .as_ref()
+            .map(|node| (node.statements.as_slice(), &node.branch_terminator_expr_opt));
+        self.visit_test_branches(branches, else_branch_opt);
+    }
+
+    // A loop is approximated by a single pass through its body.
+    fn visit_loop_stmt_node(&mut self, loop_stmt_node: &LoopStmtNode) {
+        match &loop_stmt_node.condition_expr_t_opt {
+            Some(condition_expr_t) => {
+                let condition = self.format_expr(condition_expr_t);
+                self.visit_test_branches(
+                    vec![(condition, loop_stmt_node.statements.as_slice(), &None)],
+                    None,
+                );
+            }
+            None => self.visit_decl_stmts(&loop_stmt_node.statements),
+        }
+    }
+
+    //* --------------------------------------------------------------------- *//
+
+    // Expressions are only rendered as guard names, so they are written in
+    // a neutral, JavaScript-like form.
+
+    fn visit_expression_list_node_to_string(
+        &mut self,
+        expr_list: &ExprListNode,
+        output: &mut String,
+    ) {
+        let exprs: Vec<String> = expr_list
+            .exprs_t
+            .iter()
+            .map(|expr_t| self.format_expr(expr_t))
+            .collect();
+        output.push_str(&format!("({})", exprs.join(", ")));
+    }
+
+    fn visit_call_expr_list_node_to_string(
+        &mut self,
+        call_expr_list: &CallExprListNode,
+        output: &mut String,
+    ) {
+        let exprs: Vec<String> = call_expr_list
+            .exprs_t
+            .iter()
+            .map(|expr_t| self.format_expr(expr_t))
+            .collect();
+        output.push_str(&format!("({})", exprs.join(", ")));
+    }
+
+    fn visit_call_expression_node_to_string(
+        &mut self,
+        method_call: &CallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&method_call.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(&method_call.call_expr_list, output);
+    }
+
+    fn visit_action_call_expression_node_to_string(
+        &mut self,
+        action_call: &ActionCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&action_call.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(&action_call.call_expr_list, output);
+    }
+
+    fn visit_interface_method_call_expression_node_to_string(
+        &mut self,
+        interface_method_call_expr_node: &InterfaceMethodCallExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&interface_method_call_expr_node.identifier.name.lexeme);
+        self.visit_call_expr_list_node_to_string(
+            &interface_method_call_expr_node.call_expr_list,
+            output,
+        );
+    }
+
+    fn visit_call_chain_literal_expr_node_to_string(
+        &mut self,
+        method_call_chain_expression_node: &CallChainLiteralExprNode,
+        output: &mut String,
+    ) {
         let mut separator = "";
-        output.push_str(&format!("("));
-        for expr in &expr_list.exprs_t {
-
-            output.push_str(&format!("{}",separator));
-            expr.accept_to_string(self, output);
-            separator = ",";
-        }
-        output.push_str(&format!(")"));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_literal_expression_node(&mut self, literal_expression_node: &LiteralExprNode) {
-
-        match &literal_expression_node.token_t {
-            TokenType::Number
-            => self.add_code(&format!("{}", literal_expression_node.value)),
-            TokenType::SuperString
-            => self.add_code(&format!("{}", literal_expression_node.value)),
-            TokenType::String
-            => self.add_code(&format!("\"{}\"", literal_expression_node.value)),
-            TokenType::True
-            => self.add_code("true"),
-            TokenType::False
-            => self.add_code("false"),
-            TokenType::Null
-            => self.add_code("null"),
-            TokenType::Nil
-            => self.add_code("null"),
-            _ => panic!("TODO"),
+        for node in &method_call_chain_expression_node.call_chain {
+            output.push_str(separator);
+            match node {
+                CallChainLiteralNodeType::VariableNodeT { var_node } => {
+                    output.push_str(&var_node.id_node.name.lexeme);
+                }
+                CallChainLiteralNodeType::IdentifierNodeT { id_node } => {
+                    output.push_str(&id_node.name.lexeme);
+                }
+                CallChainLiteralNodeType::CallT { call } => {
+                    self.visit_call_expression_node_to_string(call, output);
+                }
+                CallChainLiteralNodeType::InterfaceMethodCallT {
+                    interface_method_call_expr_node,
+                } => {
+                    self.visit_interface_method_call_expression_node_to_string(
+                        interface_method_call_expr_node,
+                        output,
+                    );
+                }
+                CallChainLiteralNodeType::ActionCallT {
+                    action_call_expr_node,
+                } => {
+                    self.visit_action_call_expression_node_to_string(action_call_expr_node, output);
+                }
+            }
+            separator = ".";
         }
-
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_literal_expression_node_to_string(&mut self, literal_expression_node: &LiteralExprNode, output:&mut String) {
-
-        // TODO: make a focused enum or the literals
+    fn visit_literal_expression_node_to_string(
+        &mut self,
+        literal_expression_node: &LiteralExprNode,
+        output: &mut String,
+    ) {
         match &literal_expression_node.token_t {
-            TokenType::Number => {
-                output.push_str(&format!("{}", literal_expression_node.value))
-            },
             TokenType::String => {
                 output.push_str(&format!("\"{}\"", literal_expression_node.value));
-            },
-            TokenType::True => {
-                output.push_str("true");
-            },
-            TokenType::False => {
-                output.push_str("false");
-            },
-            TokenType::Nil => {
-                output.push_str("null");
-            },
-            TokenType::Null => {
-                output.push_str("null");
-            },
-            _ => panic!("TODO"),
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_identifier_node(&mut self, identifier_node: &IdentifierNode) {
-
-        self.add_code(&format!("{}",identifier_node.name.lexeme));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_identifier_node_to_string(&mut self, identifier_node: &IdentifierNode, output:&mut String) {
-
-        output.push_str(&format!("{}",identifier_node.name.lexeme));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_state_stack_operation_node(&mut self, state_stack_operation_node:&StateStackOperationNode) {
-
-//        self.add_code(&format!("{}",identifier_node.name.lexeme));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_state_stack_operation_node_to_string(&mut self, state_stack_operation_node:&StateStackOperationNode, output:&mut String) {
-
-//        self.add_code(&format!("{}",identifier_node.name.lexeme));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-//
-    fn visit_state_stack_operation_statement_node(&mut self, state_stack_op_statement_node:&StateStackOperationStatementNode) {
-
-//        self.add_code(&format!("{}",identifier_node.name.lexeme));
-
-        //       panic!("TODO: how is this used?");
-
-        match state_stack_op_statement_node.state_stack_operation_node.operation_t {
-            StateStackOperationType::Push => {
-                self.newline();
-                if self.generate_state_context {
-                    self.add_code(&format!("_stateStack_push_(_stateContext_);"));
-                } else {
-                    self.add_code(&format!("_stateStack_push_(_state_);"));
-                }
-            },
-            StateStackOperationType::Pop => {
-                if self.generate_state_context {
-                    self.add_code(&format!("let stateContext = _stateStack_pop_()"));
-                } else {
-                    self.add_code(&format!("FrameState state = _stateStack_pop_()"));
-                }
             }
+            TokenType::True => output.push_str("true"),
+            TokenType::False => output.push_str("false"),
+            TokenType::Nil | TokenType::Null => output.push_str("null"),
+            _ => output.push_str(&literal_expression_node.value),
         }
     }
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_state_context_node(&mut self, state_context_node:&StateContextNode) {
-
-        // TODO
-//        self.add_code(&format!("{}",identifier_node.name.lexeme));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_frame_event_part(&mut self, frame_event_part:&FrameEventPart) {
-
-        // TODO: make this code generate from settings
-        match frame_event_part {
-            FrameEventPart::Event => self.add_code(&format!("e")),
-            FrameEventPart::Message => self.add_code(&format!("e._message")),
-            FrameEventPart::Param {param_tok} => self.add_code(&format!("e._parameters[\"{}\"]",param_tok.lexeme)),
-            FrameEventPart::Return => self.add_code(&format!("e._return")),
-        }
 
+    fn visit_variable_expr_node_to_string(
+        &mut self,
+        variable_node: &VariableNode,
+        output: &mut String,
+    ) {
+        output.push_str(&variable_node.id_node.name.lexeme);
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_frame_event_part_to_string(&mut self, frame_event_part:&FrameEventPart, output:&mut String) {
-
-        // TODO: make this code generate from settings
+    fn visit_frame_event_part_to_string(
+        &mut self,
+        frame_event_part: &FrameEventPart,
+        output: &mut String,
+    ) {
         match frame_event_part {
-            FrameEventPart::Event => output.push_str("e"),
-            FrameEventPart::Message => output.push_str("e._message"),
-            FrameEventPart::Param {param_tok} => output.push_str(&format!("e._parameters[\"{}\"]",param_tok.lexeme)),
-            FrameEventPart::Return => output.push_str("e._return"),
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_action_decl_node(&mut self, action_decl_node: &ActionNode) {
-
-        let mut subclass_code = String::new();
-
-        self.newline();
-        let action_name = self.format_action_name(&action_decl_node.name);
-        // self.add_code(&format!("let {} = function (",action_name));
-        self.add_code(&format!("{} = function (",action_name));
-        subclass_code.push_str(&format!("\t{} = function (",action_name));
-        let mut separator = "";
-        if let Some(params) = &action_decl_node.params {
-
-            for param in params {
-                self.add_code(&format!("{}",separator));
-                subclass_code.push_str(&format!("{}",separator));
-                separator = ",";
-                self.add_code(&format!("{}",param.param_name));
-                subclass_code.push_str(&format!("{}",param.param_name));
+            FrameEventPart::Event { .. } => output.push('@'),
+            FrameEventPart::Message { .. } => output.push_str("@||"),
+            FrameEventPart::Param { param_tok, .. } => {
+                output.push_str(&format!("@[{}]", param_tok.lexeme))
             }
+            FrameEventPart::Return { .. } => output.push_str("@^"),
         }
-        subclass_code.push_str(&format!(") {{}}"));
-        self.subclass_code.push(subclass_code);
-
-        self.add_code(&format!(") {{ throw new Error('Action not implemented.'); }}"));
-
-    }
-
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_action_impl_node(&mut self, action_decl_node: &ActionNode) {
-        panic!("visit_action_impl_node() not implemented.");
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_domain_variable_decl_node(&mut self, variable_decl_node: &VariableDeclNode) {
-
-        self.visit_variable_decl_node(variable_decl_node);
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_variable_decl_node(&mut self, variable_decl_node: &VariableDeclNode) {
-
-        let var_name =  &variable_decl_node.name;
-        let var_init_expr = &variable_decl_node.initializer_expr_t_opt.as_ref().unwrap();
-        self.newline();
-        let mut code = String::new();
-        var_init_expr.accept_to_string(self, &mut code);
-        match &variable_decl_node.identifier_decl_scope {
-            IdentifierDeclScope::DomainBlock => {
-                self.add_code( &format!("that.{} = {};",var_name, code));
-            },
-            IdentifierDeclScope::EventHandlerVar => {
-                self.add_code( &format!("let {} = {};",var_name, code));
-            },
-            _ => panic!("Error - unexpected scope for variable declaration"),
-        }
-
-        self.serialize.push(format!("\tbag.domain[\"{}\"] = {};",var_name,var_name));
-        self.deserialize.push(format!("\t{} = bag.domain[\"{}\"];",var_name,var_name));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_variable_expr_node(&mut self, variable_node: &VariableNode) {
-        let code = self.format_variable_expr(variable_node);
-        self.add_code(&code);
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_variable_expr_node_to_string(&mut self, variable_node: &VariableNode, output:&mut String) {
-        let code = self.format_variable_expr(variable_node);
-        output.push_str(&code);
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_variable_stmt_node(&mut self, variable_stmt_node: &VariableStmtNode) {
-        // TODO: what is this line about?
-        self.generate_comment(variable_stmt_node.get_line());
-        self.newline();
-        self.add_code(&format!("{};",variable_stmt_node.var_node.id_node.name.lexeme));
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_assignment_expr_node(&mut self, assignment_expr_node: &AssignmentExprNode) {
-
-        self.generate_comment(assignment_expr_node.line);
-        self.newline();
-        assignment_expr_node.l_value_box.accept(self);
-        self.add_code(" = ");
-        assignment_expr_node.r_value_box.accept(self);
-        self.add_code(";");
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_assignment_expr_node_to_string(&mut self, assignment_expr_node: &AssignmentExprNode, output:&mut String) {
-
-        self.generate_comment(assignment_expr_node.line);
-        self.newline();
-        self.newline_to_string(output);
-        assignment_expr_node.l_value_box.accept_to_string(self, output);
-        output.push_str(" = ");
-        assignment_expr_node.r_value_box.accept_to_string(self, output);
-        output.push_str(";");
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_assignment_statement_node(&mut self, assignment_stmt_node: &AssignmentStmtNode) {
-
-        self.generate_comment(assignment_stmt_node.get_line());
-        assignment_stmt_node.assignment_expr_node.accept(self);
-
-    }
-
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_unary_expr_node(&mut self, unary_expr_node: &UnaryExprNode) {
-
-        // TODO
-        //       self.generate_comment(assignment_expr_node.line);
-        unary_expr_node.operator.accept(self);
-        unary_expr_node.right_rcref.borrow().accept(self);
-
     }
 
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_unary_expr_node_to_string(&mut self, unary_expr_node: &UnaryExprNode, output:&mut String) {
-
-        // TODO
-        //       self.generate_comment(assignment_expr_node.line);
+    fn visit_unary_expr_node_to_string(
+        &mut self,
+        unary_expr_node: &UnaryExprNode,
+        output: &mut String,
+    ) {
         unary_expr_node.operator.accept_to_string(self, output);
-        unary_expr_node.right_rcref.borrow().accept_to_string(self,output);
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_binary_expr_node(&mut self, binary_expr_node: &BinaryExprNode) {
-
-        // TODO
-        //       self.generate_comment(assignment_expr_node.line);
-        if binary_expr_node.operator == OperatorType::LogicalXor {
-            self.add_code("((");
-            binary_expr_node.left_rcref.borrow().accept(self);
-            self.add_code(") && !(");
-            binary_expr_node.right_rcref.borrow().accept(self);
-            self.add_code(")) || (!(");
-            binary_expr_node.left_rcref.borrow().accept(self);
-            self.add_code(") && (");
-            binary_expr_node.right_rcref.borrow().accept(self);
-            self.add_code("))");
-        } else {
-            binary_expr_node.left_rcref.borrow().accept(self);
-            binary_expr_node.operator.accept(self);
-            binary_expr_node.right_rcref.borrow().accept(self);
-        }
-
-    }
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_binary_expr_node_to_string(&mut self, binary_expr_node: &BinaryExprNode, output:&mut String) {
-
-        if binary_expr_node.operator == OperatorType::LogicalXor {
-            output.push_str("((");
-            binary_expr_node.left_rcref.borrow().accept_to_string(self,output);
-            output.push_str(") && !(");
-            binary_expr_node.right_rcref.borrow().accept_to_string(self,output);
-            output.push_str(")) || (!(");
-            binary_expr_node.left_rcref.borrow().accept_to_string(self,output);
-            output.push_str(") && (");
-            binary_expr_node.right_rcref.borrow().accept_to_string(self,output);
-            output.push_str("))");
-
-        } else {
-            binary_expr_node.left_rcref.borrow().accept_to_string(self,output);
-            binary_expr_node.operator.accept_to_string(self, output);
-            binary_expr_node.right_rcref.borrow().accept_to_string(self,output);
-        }
-
-    }
-
-
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_operator_type(&mut self, operator_type: &OperatorType) {
-
-        match operator_type {
-            OperatorType::Plus => self.add_code(" + "),
-            OperatorType::Minus => self.add_code(" - "),
-            OperatorType::Negated => self.add_code("-"),
-            OperatorType::Multiply => self.add_code(" * "),
-            OperatorType::Divide => self.add_code(" / "),
-            OperatorType::Greater => self.add_code(" > "),
-            OperatorType::GreaterEqual => self.add_code(" >= "),
-            OperatorType::Less => self.add_code(" < "),
-            OperatorType::LessEqual => self.add_code(" <= "),
-            OperatorType::Not => self.add_code("!"),
-            OperatorType::EqualEqual => self.add_code(" == "),
-            OperatorType::NotEqual => self.add_code(" != "),
-            OperatorType::LogicalAnd => self.add_code(" && "),
-            OperatorType::LogicalOr => self.add_code(" || "),
-            OperatorType::LogicalXor => self.add_code(""),
-        }
-
+        unary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_binary_expr_node_to_string(
+        &mut self,
+        binary_expr_node: &BinaryExprNode,
+        output: &mut String,
+    ) {
+        binary_expr_node
+            .left_rcref
+            .borrow()
+            .accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node.operator.accept_to_string(self, output);
+        output.push(' ');
+        binary_expr_node
+            .right_rcref
+            .borrow()
+            .accept_to_string(self, output);
+    }
+
+    fn visit_enumerator_expr_node_to_string(
+        &mut self,
+        enumerator_expr_node: &EnumeratorExprNode,
+        output: &mut String,
+    ) {
+        output.push_str(&format!(
+            "{}.{}",
+            enumerator_expr_node.enum_type, enumerator_expr_node.enumerator
+        ));
+    }
+
+    fn visit_operator_type_to_string(&mut self, operator_type: &OperatorType, output: &mut String) {
+        let operator = match operator_type {
+            OperatorType::Plus => "+",
+            OperatorType::Minus | OperatorType::Negated => "-",
+            OperatorType::Multiply => "*",
+            OperatorType::Divide => "/",
+            OperatorType::Greater => ">",
+            OperatorType::GreaterEqual => ">=",
+            OperatorType::Less => "<",
+            OperatorType::LessEqual => "<=",
+            OperatorType::EqualEqual => "==",
+            OperatorType::NotEqual => "!=",
+            OperatorType::Not => "!",
+            OperatorType::LogicalAnd => "&&",
+            OperatorType::LogicalOr => "||",
+            OperatorType::LogicalXor => "^",
+        };
+        output.push_str(operator);
     }
+}
 
-    //* --------------------------------------------------------------------- *//
-
-    fn visit_operator_type_to_string(&mut self, operator_type: &OperatorType, output:&mut String) {
-
-        match operator_type {
-            OperatorType::Plus => output.push_str(" + "),
-            OperatorType::Minus => output.push_str(" - "),
-            OperatorType::Negated => output.push_str("-"),
-            OperatorType::Multiply => output.push_str(" * "),
-            OperatorType::Divide => output.push_str(" / "),
-            OperatorType::Greater => output.push_str(" > "),
-            OperatorType::GreaterEqual => output.push_str(" >= "),
-            OperatorType::Less => output.push_str(" < "),
-            OperatorType::LessEqual => output.push_str(" <= "),
-            OperatorType::Not => output.push_str("!"),
-            OperatorType::EqualEqual => output.push_str(" == "),
-            OperatorType::NotEqual => output.push_str(" != "),
-            OperatorType::LogicalAnd => output.push_str(" && "),
-            OperatorType::LogicalOr => output.push_str(" || "),
-            OperatorType::LogicalXor => output.push_str(""),
-        }
-
+#[cfg(test)]
+mod tests {
+    use crate::frame_c::compiler::Exe;
+
+    fn generate(spec: &str) -> String {
+        Exe::new()
+            .run(&None, spec.to_string(), String::from("xstate"))
+            .unwrap_or_else(|err| panic!("{}", err.error))
+    }
+
+    // The config of one state, which is a top-level entry of `states`.
+    fn state_config<'a>(code: &'a str, state_name: &str) -> &'a str {
+        let start = code
+            .find(&format!("\n    {}: {{", state_name))
+            .unwrap_or_else(|| panic!("no top-level state {}", state_name));
+        let len = code[start..].find("\n    },").unwrap();
+        &code[start..start + len]
+    }
+
+    const BRANCHING: &str = "
+#Branching
+    -interface-
+    e [x:i32]
+    -machine-
+    $A
+        |e| [x:i32]
+            start()
+            x > 3 ? big() -> $B : small() -> $C ::
+            ^
+    $B
+        |e| [x:i32]
+            x == 0 ? zero() ^ ::
+            log() ^
+    $C
+    -actions-
+    start
+    big
+    small
+    zero
+    log
+##
+";
+
+    #[test]
+    fn branch_actions() {
+        let code = generate(BRANCHING);
+        let a = state_config(&code, "A");
+        assert!(a.contains(
+            r##"{ target: "#Branching.B", guard: "x > 3", actions: ["start", "big"] },"##
+        ));
+        assert!(a.contains(
+            r##"{ target: "#Branching.C", guard: "!(x > 3)", actions: ["start", "small"] },"##
+        ));
+    }
+
+    #[test]
+    fn branch_return() {
+        let code = generate(BRANCHING);
+        let b = state_config(&code, "B");
+        assert!(b.contains(r##"{ guard: "x == 0", actions: ["zero"] },"##));
+        assert!(b.contains(r##"{ guard: "!(x == 0)", actions: ["log"] },"##));
+    }
+
+    #[test]
+    fn parent_states_are_flat() {
+        let code = generate(include_str!(
+            "../../../../framec_tests/src/hierarchical.frm"
+        ));
+        assert!(!code.contains("initial: \"S0\""));
+        for state_name in ["I", "S", "S0", "S1", "S2", "S3", "T"] {
+            state_config(&code, state_name);
+        }
+        // a transition to a parent state ends in the parent state
+        let t = state_config(&code, "T");
+        assert!(t.contains(r##"{ target: "#Hierarchical.S", actions: ["log"] },"##));
+        let s = state_config(&code, "S");
+        assert!(s.contains(r##"entry: ["enter"],"##));
+    }
+
+    #[test]
+    fn inherited_handlers() {
+        let code = generate(include_str!(
+            "../../../../framec_tests/src/hierarchical.frm"
+        ));
+        // S3 defers A to its grandparent
+        let s3 = state_config(&code, "S3");
+        assert!(s3.contains(r##""A": ["##));
+        assert!(s3.contains(r##"{ target: "#Hierarchical.S0", actions: ["log"] },"##));
+        // S0 overrides the parent handler for A
+        let s0 = state_config(&code, "S0");
+        assert!(s0.contains(r##"{ target: "#Hierarchical.T", actions: ["log"] },"##));
+        assert!(!s0.contains(r##"target: "#Hierarchical.S0""##));
+    }
+
+    #[test]
+    fn continue_handlers() {
+        let code = generate(include_str!(
+            "../../../../framec_tests/src/hierarchical.frm"
+        ));
+        // S0 logs, then takes the parent's transition
+        let s0 = state_config(&code, "S0");
+        assert!(s0.contains(r##"{ target: "#Hierarchical.S1", actions: ["log", "log"] },"##));
+        assert!(s0.contains(r##"entry: ["enter", "enter"],"##));
+        // S2 continues through S0 to S
+        let s2 = state_config(&code, "S2");
+        assert!(s2.contains(r##"{ target: "#Hierarchical.S1", actions: ["log", "log", "log"] },"##));
+        // the transition in S2's C handler ends it before the parent is reached
+        assert!(s2.contains(r##"{ target: "#Hierarchical.T", actions: ["log"] },"##));
+        // S1 continues to S, which doesn't handle C
+        let s1 = state_config(&code, "S1");
+        assert!(s1.contains(r##""C": { actions: ["log"] },"##));
+        assert!(s1.contains(
+            r##"{ target: "#Hierarchical.S1", reenter: true, actions: ["log", "log"] },"##
+        ));
     }
 }
-
-
-
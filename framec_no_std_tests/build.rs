@@ -0,0 +1,42 @@
+use anyhow::{bail, Result};
+use framec::frame_c::compiler::{Exe, GeneratedCode};
+use std::env;
+use std::fs;
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::path::PathBuf;
+use walkdir::WalkDir;
+
+fn process_frame(input_path: &Path, output_path: &Path) -> Result<()> {
+    let exe = Exe::new();
+    match exe.generate_file(&None, input_path, "rust".to_string())? {
+        GeneratedCode::File(output_code) => fs::write(output_path, output_code)?,
+        GeneratedCode::ModuleTree(_) => {
+            bail!("{:?} must be generated as a single file", input_path)
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let input_dir = PathBuf::from("src");
+    if !input_dir.is_dir() {
+        bail!("{:?} isn't a directory", input_dir);
+    }
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+    create_dir_all(&out)
+        .unwrap_or_else(|_| panic!("Failed to create output directory: {:?}", &out));
+
+    for entry in WalkDir::new(&input_dir) {
+        let entry = entry?;
+        let input_path = entry.path();
+        if input_path.extension().unwrap_or_default() == "frm" {
+            println!("cargo:rerun-if-changed={:?}", &input_path);
+            let stripped_path = input_path.strip_prefix(&input_dir)?;
+            let mut rust_output_path = out.join(stripped_path);
+            rust_output_path.set_extension("rs");
+            process_frame(input_path, &rust_output_path)?;
+        }
+    }
+    Ok(())
+}
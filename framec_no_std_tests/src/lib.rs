@@ -0,0 +1,43 @@
+//! Tests for code generated with the `no_std` feature. Unlike `framec_tests`, this crate is
+//! `#![no_std]` itself, so the generated code only compiles if it depends on nothing but `core`.
+
+#![no_std]
+
+mod no_std;
+mod no_std_fallible;
+mod no_std_info;
+
+/// Names of events and states, which the `no_std` feature generates as static strings.
+pub type Name = &'static str;
+
+/// A fixed-capacity log of names, standing in for a `Vec` in the test specs.
+#[derive(Clone, Copy, Debug)]
+pub struct Log {
+    names: [Name; 8],
+    len: usize,
+}
+
+impl Log {
+    pub const fn new() -> Log {
+        Log {
+            names: [""; 8],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, name: Name) {
+        assert!(self.len < self.names.len(), "log is full");
+        self.names[self.len] = name;
+        self.len += 1;
+    }
+
+    pub fn as_slice(&self) -> &[Name] {
+        &self.names[..self.len]
+    }
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Log::new()
+    }
+}
@@ -0,0 +1,78 @@
+//! Tests the `no_std` feature, which generates code that only depends on `core` and never
+//! allocates. The state stack capacity is set to 2 in the spec.
+
+use crate::{Log, Name};
+
+include!(concat!(env!("OUT_DIR"), "/", "no_std.rs"));
+
+impl NoStd {
+    pub fn note(&mut self, msg: Name) {
+        self.tape.push(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that event names and string literals are static strings.
+    #[test]
+    fn static_names() {
+        let mut sm = NoStd::new();
+        sm.start(1);
+        sm.tick();
+        assert_eq!(sm.tape.as_slice(), ["Off:>", "start", "On:<", "Off:>"]);
+    }
+
+    /// Test that state parameters and variables are stored in the inline state context.
+    #[test]
+    fn inline_state_context() {
+        let mut sm = NoStd::new();
+        sm.start(3);
+        sm.tick();
+        sm.tick();
+        assert_eq!(sm.state, NoStdState::On);
+        assert_eq!(sm.count(), 2);
+        sm.tick();
+        assert_eq!(sm.state, NoStdState::Off);
+        assert_eq!(sm.count(), 0);
+    }
+
+    /// Test that popping the fixed-capacity state stack restores a pushed state context.
+    #[test]
+    fn push_pop() {
+        let mut sm = NoStd::new();
+        sm.start(5);
+        sm.tick();
+        sm.push();
+        sm.tick();
+        sm.tick();
+        assert_eq!(sm.count(), 3);
+        sm.pop();
+        assert_eq!(sm.count(), 1);
+        sm.push();
+        sm.push();
+        sm.pop();
+        sm.pop();
+        assert_eq!(sm.count(), 1);
+    }
+
+    /// Test that pushing beyond the configured capacity panics.
+    #[test]
+    #[should_panic(expected = "history stack is full")]
+    fn push_full() {
+        let mut sm = NoStd::new();
+        sm.start(5);
+        sm.push();
+        sm.push();
+        sm.push();
+    }
+
+    /// Test that popping an empty stack panics.
+    #[test]
+    #[should_panic(expected = "history stack is empty")]
+    fn pop_empty() {
+        let mut sm = NoStd::new();
+        sm.pop();
+    }
+}
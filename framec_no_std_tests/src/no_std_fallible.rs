@@ -0,0 +1,52 @@
+//! Tests the `no_std` feature combined with the `fallible_actions` feature, which requires an
+//! error type that does not depend on `std`.
+
+use crate::{Log, Name};
+
+include!(concat!(env!("OUT_DIR"), "/", "no_std_fallible.rs"));
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SensorError {
+    Disconnected,
+}
+
+impl NoStdFallible {
+    fn read_level(&mut self) -> Result<u8, SensorError> {
+        if self.readings == 0 {
+            return Err(SensorError::Disconnected);
+        }
+        self.readings -= 1;
+        Ok(self.readings)
+    }
+
+    fn note(&mut self, msg: Name) {
+        self.tape.push(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that interface methods wrap the return value in `Ok` when no action fails.
+    #[test]
+    fn success() {
+        let mut sm = NoStdFallible::new();
+        sm.readings = 2;
+        assert_eq!(sm.poll(), Ok(1));
+        assert_eq!(sm.poll(), Ok(0));
+        assert_eq!(sm.state, NoStdFallibleState::Reading);
+        assert!(sm.tape.as_slice().is_empty());
+    }
+
+    /// Test that a failing action is reported to the `|!|` handler and returned.
+    #[test]
+    fn error_handled() {
+        let mut sm = NoStdFallible::new();
+        assert_eq!(sm.poll(), Err(SensorError::Disconnected));
+        assert_eq!(sm.state, NoStdFallibleState::Fault);
+        assert_eq!(sm.tape.as_slice(), ["fault"]);
+        assert_eq!(sm.reset(), Ok(()));
+        assert_eq!(sm.state, NoStdFallibleState::Reading);
+    }
+}
@@ -0,0 +1,48 @@
+//! Tests the `runtime_info` feature combined with the `no_std` feature, which generates the
+//! static info of the runtime interface without the rest of the runtime support.
+
+include!(concat!(env!("OUT_DIR"), "/", "no_std_info.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frame_runtime::info::*;
+
+    /// Test that the static info can be inspected in a const context.
+    #[test]
+    fn const_machine_info() {
+        const NUM_STATES: usize = NoStdInfo::machine_info().states.len();
+        const BUSY: Option<&StateInfo> = NoStdInfo::machine_info().get_state("Busy");
+        const HAS_COUNT: bool = NoStdInfo::machine_info().get_variable("count").is_some();
+        assert_eq!(NUM_STATES, 3);
+        assert_eq!(BUSY.unwrap().parent.unwrap().name, "Parent");
+        assert_eq!(BUSY.unwrap().machine().name, "NoStdInfo");
+        assert!(HAS_COUNT);
+    }
+
+    /// Test that the transitions are listed in the info.
+    #[test]
+    fn transitions() {
+        let transitions = NoStdInfo::machine_info().transitions;
+        assert_eq!(transitions.len(), 2);
+        assert!(transitions[0].is_transition());
+        assert_eq!(transitions[0].label, "wake");
+        assert_eq!(transitions[0].source.name, "Idle");
+        assert_eq!(transitions[0].target.name, "Busy");
+        assert!(transitions[1].is_change_state());
+        assert_eq!(transitions[1].target.name, "Idle");
+    }
+
+    /// Test that the info describes the running state machine.
+    #[test]
+    fn current_state() {
+        let mut sm = NoStdInfo::new();
+        sm.next();
+        assert_eq!(sm.state, NoStdInfoState::Busy);
+        assert!(NoStdInfo::machine_info()
+            .get_state("Busy")
+            .unwrap()
+            .get_handler("next")
+            .is_some());
+    }
+}
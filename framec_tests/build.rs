@@ -1,5 +1,5 @@
 use anyhow::{bail, Result};
-use framec::frame_c::compiler::Exe;
+use framec::frame_c::compiler::{Exe, GeneratedCode};
 use std::env;
 use std::fs;
 use std::fs::create_dir_all;
@@ -9,8 +9,37 @@ use walkdir::WalkDir;
 
 fn process_frame(input_path: &Path, output_path: &Path, lang: &str) -> Result<()> {
     let exe = Exe::new();
-    let output_code = exe.run_file(&None, input_path, lang.to_string())?;
-    fs::write(output_path, output_code)?;
+    match exe.generate_file(&None, input_path, lang.to_string())? {
+        GeneratedCode::File(output_code) => fs::write(output_path, output_code)?,
+        GeneratedCode::ModuleTree(files) => {
+            // the output file only declares the module, whose files go in a directory beside it
+            let module_dir = output_path.with_extension("");
+            create_dir_all(&module_dir)?;
+            for (file_name, output_code) in files {
+                fs::write(module_dir.join(file_name), output_code)?;
+            }
+            let module_name = module_dir.file_name().unwrap().to_string_lossy();
+            let module_decl = format!(
+                "#[path = {:?}]\nmod {};\n",
+                module_dir.join("mod.rs"),
+                module_name
+            );
+            fs::write(output_path, module_decl)?;
+        }
+    }
+    Ok(())
+}
+
+fn process_scxml(
+    input_path: &Path,
+    frame_output_path: &Path,
+    rust_output_path: &Path,
+) -> Result<()> {
+    let exe = Exe::new();
+    let frame_code = exe.import_scxml_file(input_path)?;
+    fs::write(frame_output_path, &frame_code)?;
+    let rust_code = exe.run(&None, frame_code, "rust".to_string())?;
+    fs::write(rust_output_path, rust_code)?;
     Ok(())
 }
 
@@ -26,6 +55,16 @@ fn main() -> Result<()> {
     for entry in WalkDir::new(&input_dir) {
         let entry = entry?;
         let input_path = entry.path();
+        if input_path.extension().unwrap_or_default() == "scxml" {
+            // imported state charts are compiled through their Frame skeleton
+            println!("cargo:rerun-if-changed={:?}", &input_path);
+            let stripped_path = input_path.strip_prefix(&input_dir)?;
+            let mut frame_output_path = out.join(stripped_path);
+            let mut rust_output_path = frame_output_path.clone();
+            frame_output_path.set_extension("frm");
+            rust_output_path.set_extension("rs");
+            process_scxml(input_path, &frame_output_path, &rust_output_path)?;
+        }
         if input_path.extension().unwrap_or_default() == "frm" {
             println!("cargo:rerun-if-changed={:?}", &input_path);
             let stripped_path = input_path.strip_prefix(&input_dir)?;
@@ -0,0 +1,142 @@
+//! Tests the `async_support` feature, which generates `async` interface methods and actions.
+//!
+//! The generated code does not depend on an executor, so these tests drive the machine with a
+//! minimal `block_on`. Actions yield to the executor once before logging so that every action
+//! call crosses an await point.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+type Log = Vec<String>;
+include!(concat!(env!("OUT_DIR"), "/", "async_actions.rs"));
+
+/// A future that is pending the first time it is polled.
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+impl AsyncActions {
+    pub async fn open(&mut self, host: String) {
+        yield_now().await;
+        self.tape.push(format!("open {}", host));
+    }
+    pub async fn close(&mut self) {
+        yield_now().await;
+        self.tape.push(String::from("close"));
+    }
+    pub async fn write(&mut self, msg: String) {
+        yield_now().await;
+        self.tape.push(format!("write {}", msg));
+    }
+    pub async fn transition_hook(
+        &mut self,
+        old_state: AsyncActionsState,
+        new_state: AsyncActionsState,
+    ) {
+        yield_now().await;
+        self.tape.push(format!("{:?}->{:?}", old_state, new_state));
+    }
+    pub async fn change_state_hook(
+        &mut self,
+        old_state: AsyncActionsState,
+        new_state: AsyncActionsState,
+    ) {
+        yield_now().await;
+        self.tape.push(format!("{:?}->>{:?}", old_state, new_state));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Waker;
+
+    /// Run a future to completion on the current thread.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Test that awaited actions in handlers run and that the handler continues afterward.
+    #[test]
+    fn awaited_actions() {
+        block_on(async {
+            let mut sm = AsyncActions::new().await;
+            sm.connect(String::from("example.com")).await;
+            sm.send(String::from("a")).await;
+            sm.send(String::from("b")).await;
+            assert_eq!(sm.sent_count().await, 2);
+            assert_eq!(
+                sm.tape,
+                vec![
+                    "open example.com",
+                    "Idle->Online",
+                    "write hello",
+                    "write a",
+                    "write b"
+                ]
+            );
+        });
+    }
+
+    /// Test that exit handlers, the state update, and enter handlers run in order even though
+    /// each of them awaits.
+    #[test]
+    fn transition_order() {
+        block_on(async {
+            let mut sm = AsyncActions::new().await;
+            sm.connect(String::from("a.com")).await;
+            sm.tape.clear();
+            sm.disconnect().await;
+            assert_eq!(sm.state, AsyncActionsState::Idle);
+            assert_eq!(sm.tape, vec!["close", "Online->Idle"]);
+        });
+    }
+
+    /// Test that change-state awaits the hook but does not send exit or enter events.
+    #[test]
+    fn change_state() {
+        block_on(async {
+            let mut sm = AsyncActions::new().await;
+            sm.connect(String::from("a.com")).await;
+            sm.tape.clear();
+            sm.reconnect().await;
+            assert_eq!(sm.state, AsyncActionsState::Retrying);
+            sm.connect(String::from("b.com")).await;
+            assert_eq!(sm.state, AsyncActionsState::Online);
+            assert_eq!(
+                sm.tape,
+                vec![
+                    "Online->>Retrying",
+                    "open b.com",
+                    "close",
+                    "Retrying->Online",
+                    "write hello"
+                ]
+            );
+        });
+    }
+}
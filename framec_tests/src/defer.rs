@@ -0,0 +1,83 @@
+//! Test event deferral. A state may declare events that it defers with a `|defer|` clause.
+//! Deferred events are queued and replayed after the machine moves to a state that does not
+//! defer them.
+
+type Log = Vec<String>;
+include!(concat!(env!("OUT_DIR"), "/", "defer.rs"));
+
+impl Defer {
+    pub fn log(&mut self, msg: String) {
+        self.tape.push(msg);
+    }
+    pub fn sent(&mut self, data: u32) {
+        self.tape.push(format!("sent {}", data));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a deferred event is not handled by the deferring state.
+    #[test]
+    fn deferred_event_not_handled() {
+        let mut sm = Defer::new();
+        sm.send(1);
+        assert_eq!(sm.state, DeferState::Disconnected);
+        assert!(sm.tape.is_empty());
+    }
+
+    /// Test that deferred events are held until the machine reaches a state that does not defer
+    /// them, and are then replayed in the order they were sent.
+    #[test]
+    fn replay_after_transition() {
+        let mut sm = Defer::new();
+        sm.send(1);
+        sm.connect();
+        sm.send(2);
+        assert_eq!(sm.state, DeferState::Handshaking);
+        assert_eq!(sm.tape, vec!["handshaking"]);
+        sm.handshake_done();
+        assert_eq!(sm.state, DeferState::Connected);
+        assert_eq!(sm.tape, vec!["handshaking", "connected", "sent 1", "sent 2"]);
+    }
+
+    /// Test that deferred events of different kinds are replayed in order, and that an event
+    /// that is still deferred by the next state stays queued.
+    #[test]
+    fn replay_preserves_order() {
+        let mut sm = Defer::new();
+        sm.connect();
+        sm.send(1);
+        sm.connect();
+        sm.send(2);
+        sm.handshake_done();
+        assert_eq!(
+            sm.tape,
+            vec![
+                "handshaking",
+                "connected",
+                "sent 1",
+                "already connected",
+                "sent 2"
+            ]
+        );
+    }
+
+    /// Test that deferred events are also replayed after a change-state.
+    #[test]
+    fn replay_after_change_state() {
+        let mut sm = Defer::new();
+        sm.connect();
+        sm.handshake_done();
+        sm.hold();
+        sm.tape.clear();
+        sm.send(1);
+        sm.send(2);
+        assert_eq!(sm.state, DeferState::Held);
+        assert!(sm.tape.is_empty());
+        sm.connect();
+        assert_eq!(sm.state, DeferState::Connected);
+        assert_eq!(sm.tape, vec!["sent 1", "sent 2"]);
+    }
+}
@@ -0,0 +1,74 @@
+//! Test domain enums, enumerator expressions and exhaustive enum match tests.
+
+type Log = Vec<String>;
+include!(concat!(env!("OUT_DIR"), "/", "enum_match.rs"));
+
+impl EnumMatch {
+    pub fn log(&mut self, msg: String) {
+        self.tape.push(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a domain enum variable is initialized from an enumerator.
+    #[test]
+    fn domain_enum_initializer() {
+        let sm = EnumMatch::new();
+        assert_eq!(sm.color, Color::Red);
+    }
+
+    /// Test that enum parameters can be assigned to enum domain variables.
+    #[test]
+    fn enum_parameter() {
+        let mut sm = EnumMatch::new();
+        sm.set_color(Color::Blue);
+        assert_eq!(sm.color, Color::Blue);
+    }
+
+    /// Test an enum return type.
+    #[test]
+    fn enum_return() {
+        let mut sm = EnumMatch::new();
+        assert_eq!(sm.get_color(), Color::Red);
+        sm.next();
+        assert_eq!(sm.get_color(), Color::Green);
+    }
+
+    /// Test match branches with single and multiple enumerators.
+    #[test]
+    fn match_branches() {
+        let mut sm = EnumMatch::new();
+        sm.describe();
+        sm.set_color(Color::Green);
+        sm.describe();
+        sm.set_color(Color::Blue);
+        sm.describe();
+        assert_eq!(sm.tape, vec!["red", "cool", "cool"]);
+    }
+
+    /// Test returning a value from an enum match branch.
+    #[test]
+    fn match_return() {
+        let mut sm = EnumMatch::new();
+        assert!(sm.is_warm(Color::Red));
+        assert!(!sm.is_warm(Color::Green));
+        assert!(!sm.is_warm(Color::Blue));
+    }
+
+    /// Test enumerator assignments and a transition from an enum match.
+    #[test]
+    fn match_assign_and_transition() {
+        let mut sm = EnumMatch::new();
+        sm.next();
+        assert_eq!(sm.color, Color::Green);
+        sm.next();
+        assert_eq!(sm.color, Color::Blue);
+        sm.next();
+        assert_eq!(sm.state, EnumMatchState::Done);
+        sm.describe();
+        assert_eq!(sm.tape, vec!["done"]);
+    }
+}
@@ -0,0 +1,176 @@
+//! Tests the `fallible_actions` feature, in which actions may return errors that are reported to
+//! `|!|` handlers and returned from interface methods.
+
+include!(concat!(env!("OUT_DIR"), "/", "fallible_actions.rs"));
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SensorError {
+    Disconnected,
+    OutOfRange(i32),
+    LogFull,
+    SelfTestFailed,
+    EnterFailed,
+    ExitFailed,
+}
+
+/// A scripted sensor that records the actions of the state machine. It is disconnected once it
+/// runs out of readings.
+#[derive(Default)]
+struct Sensor {
+    readings: Vec<Result<i32, SensorError>>,
+    recorded: Vec<i32>,
+    logged: Vec<SensorError>,
+    log_full: bool,
+    broken: bool,
+    enter_broken: bool,
+    exit_broken: bool,
+    enters: usize,
+}
+
+impl FallibleActions {
+    fn read_temp(&mut self) -> Result<i32, SensorError> {
+        if self.sensor.readings.is_empty() {
+            return Err(SensorError::Disconnected);
+        }
+        self.sensor.readings.remove(0)
+    }
+
+    fn record(&mut self, t: i32) -> Result<(), SensorError> {
+        if t > 100 {
+            return Err(SensorError::OutOfRange(t));
+        }
+        self.sensor.recorded.push(t);
+        Ok(())
+    }
+
+    fn log_error(&mut self, error: &SensorError) -> Result<(), SensorError> {
+        if self.sensor.log_full {
+            return Err(SensorError::LogFull);
+        }
+        self.sensor.logged.push(error.clone());
+        Ok(())
+    }
+
+    fn self_test(&mut self) -> Result<(), SensorError> {
+        if self.sensor.broken {
+            Err(SensorError::SelfTestFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn enter_reading(&mut self) -> Result<(), SensorError> {
+        self.sensor.enters += 1;
+        if self.sensor.enter_broken {
+            Err(SensorError::EnterFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn leave_fault(&mut self) -> Result<(), SensorError> {
+        if self.sensor.exit_broken {
+            Err(SensorError::ExitFailed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine(readings: Vec<Result<i32, SensorError>>) -> FallibleActions {
+        let mut sm = FallibleActions::new();
+        sm.sensor.readings = readings;
+        sm
+    }
+
+    /// Test that interface methods wrap the return value in `Ok` when no action fails.
+    #[test]
+    fn success() {
+        let mut sm = machine(vec![Ok(20), Ok(25)]);
+        assert_eq!(sm.poll(), Ok(20));
+        assert_eq!(sm.poll(), Ok(25));
+        assert_eq!(sm.sensor.recorded, vec![20, 25]);
+        assert_eq!(sm.state, FallibleActionsState::Reading);
+    }
+
+    /// Test that a failing action ends its handler, and that the error is passed to the `|!|`
+    /// handler before it is returned.
+    #[test]
+    fn error_handled() {
+        let mut sm = machine(vec![]);
+        assert_eq!(sm.poll(), Err(SensorError::Disconnected));
+        assert!(sm.sensor.recorded.is_empty());
+        assert_eq!(sm.sensor.logged, vec![SensorError::Disconnected]);
+        assert_eq!(sm.state, FallibleActionsState::Fault);
+    }
+
+    /// Test that actions without a return value can fail.
+    #[test]
+    fn unit_action_error() {
+        let mut sm = machine(vec![Ok(120)]);
+        assert_eq!(sm.poll(), Err(SensorError::OutOfRange(120)));
+        assert_eq!(sm.sensor.logged, vec![SensorError::OutOfRange(120)]);
+        assert_eq!(sm.state, FallibleActionsState::Fault);
+    }
+
+    /// Test that an error is returned even if the current state has no `|!|` handler.
+    #[test]
+    fn error_unhandled() {
+        let mut sm = machine(vec![]);
+        sm.poll().unwrap_err();
+        sm.sensor.broken = true;
+        assert_eq!(sm.reset(), Err(SensorError::SelfTestFailed));
+        assert_eq!(sm.sensor.logged, vec![SensorError::Disconnected]);
+        assert_eq!(sm.state, FallibleActionsState::Fault);
+        sm.sensor.broken = false;
+        assert_eq!(sm.reset(), Ok(()));
+        assert_eq!(sm.state, FallibleActionsState::Reading);
+    }
+
+    /// Test that an error raised by the `|!|` handler is returned instead of the original one.
+    #[test]
+    fn error_handler_error() {
+        let mut sm = machine(vec![]);
+        sm.sensor.log_full = true;
+        assert_eq!(sm.poll(), Err(SensorError::LogFull));
+        assert!(sm.sensor.logged.is_empty());
+        assert_eq!(sm.state, FallibleActionsState::Reading);
+    }
+
+    /// Test that a failing enter action still completes the transition, and that its error is
+    /// handled by the new state.
+    #[test]
+    fn enter_error() {
+        let mut sm = machine(vec![]);
+        sm.poll().unwrap_err();
+        sm.sensor.enter_broken = true;
+        assert_eq!(sm.reset(), Err(SensorError::EnterFailed));
+        assert_eq!(sm.sensor.enters, 2);
+        assert_eq!(
+            sm.sensor.logged,
+            vec![SensorError::Disconnected, SensorError::EnterFailed]
+        );
+        assert_eq!(sm.state, FallibleActionsState::Fault);
+    }
+
+    /// Test that a failing exit action abandons the transition, so that the enter action is not
+    /// called and cannot replace the error of the exit action.
+    #[test]
+    fn exit_and_enter_error() {
+        let mut sm = machine(vec![]);
+        sm.poll().unwrap_err();
+        sm.sensor.exit_broken = true;
+        sm.sensor.enter_broken = true;
+        assert_eq!(sm.reset(), Err(SensorError::ExitFailed));
+        assert_eq!(sm.sensor.enters, 1);
+        assert_eq!(sm.state, FallibleActionsState::Fault);
+        sm.sensor.exit_broken = false;
+        sm.sensor.enter_broken = false;
+        assert_eq!(sm.reset(), Ok(()));
+        assert_eq!(sm.state, FallibleActionsState::Reading);
+    }
+}
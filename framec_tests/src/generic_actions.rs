@@ -0,0 +1,95 @@
+//! Tests the `generic_actions` feature, which generates a state machine that is generic over an
+//! injected actions object and any type parameters declared in `system_type_params`.
+
+include!(concat!(env!("OUT_DIR"), "/", "generic_actions.rs"));
+
+/// An actions object that records the heater commands and transitions of the machine, and
+/// replays a scripted sequence of temperature readings.
+#[derive(Default)]
+struct MockHeater {
+    heater_log: Vec<bool>,
+    transitions: Vec<(GenericActionsState, GenericActionsState)>,
+    temperatures: Vec<i32>,
+}
+
+impl GenericActionsActions for MockHeater {
+    fn set_heater(&mut self, on: bool) {
+        self.heater_log.push(on);
+    }
+    fn temperature(&mut self) -> i32 {
+        self.temperatures.remove(0)
+    }
+    fn transition_hook(&mut self, old_state: GenericActionsState, new_state: GenericActionsState) {
+        self.transitions.push((old_state, new_state));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A domain type that is only known to the user of the generated code.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Joules(i64);
+
+    impl std::ops::Add<i32> for Joules {
+        type Output = Joules;
+        fn add(self, rhs: i32) -> Joules {
+            Joules(self.0 + rhs as i64)
+        }
+    }
+
+    fn mock(temperatures: &[i32]) -> MockHeater {
+        MockHeater {
+            temperatures: temperatures.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    /// Test that actions are invoked on the object passed to the constructor.
+    #[test]
+    fn injected_actions() {
+        let mut sm: GenericActions<MockHeater, Joules> = GenericActions::new(mock(&[20, 60]));
+        sm.heat(50);
+        sm.tick();
+        assert_eq!(sm.state, GenericActionsState::Heating);
+        sm.tick();
+        assert_eq!(sm.state, GenericActionsState::Idle);
+        assert_eq!(sm.actions.heater_log, vec![true, false]);
+    }
+
+    /// Test that hook methods are invoked on the actions object.
+    #[test]
+    fn hook_methods() {
+        let mut sm: GenericActions<MockHeater, Joules> = GenericActions::new(mock(&[60]));
+        sm.heat(50);
+        sm.tick();
+        assert_eq!(
+            sm.actions.transitions,
+            vec![
+                (GenericActionsState::Idle, GenericActionsState::Heating),
+                (GenericActionsState::Heating, GenericActionsState::Idle),
+            ]
+        );
+    }
+
+    /// Test that domain variables may use the declared type parameters.
+    #[test]
+    fn domain_type_params() {
+        let mut sm: GenericActions<MockHeater, Joules> = GenericActions::new(mock(&[0, 0, 90]));
+        sm.heat(30);
+        sm.tick();
+        sm.tick();
+        sm.tick();
+        assert_eq!(sm.energy, Joules(90));
+    }
+
+    /// Test that `Default` is implemented when the actions type implements it.
+    #[test]
+    fn default_actions() {
+        let sm: GenericActions<MockHeater, i32> = GenericActions::default();
+        assert_eq!(sm.state, GenericActionsState::Idle);
+        assert_eq!(sm.energy, 0);
+        assert!(sm.actions.heater_log.is_empty());
+    }
+}
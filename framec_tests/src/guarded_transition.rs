@@ -0,0 +1,77 @@
+//! Test guarded transitions. A guard clause `[expr]` before a transition or change-state only
+//! lets the transition happen when the expression is true. A guard may directly follow the
+//! handler's message or parameters, e.g. `|go| [armed == true] ->> $Busy`, or begin a statement.
+//! The guard text is recorded in the runtime `TransitionInfo`.
+
+type Log = Vec<String>;
+include!(concat!(env!("OUT_DIR"), "/", "guarded_transition.rs"));
+
+impl GuardedTransition {
+    pub fn log(&mut self, msg: String) {
+        self.tape.push(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frame_runtime::*;
+
+    /// Test that a transition is taken when its guard is true.
+    #[test]
+    fn guard_passes() {
+        let mut sm = GuardedTransition::new();
+        sm.inc(4);
+        assert_eq!(sm.state, GuardedTransitionState::Busy);
+        assert!(sm.tape.is_empty());
+    }
+
+    /// Test that the handler continues past a transition whose guard is false.
+    #[test]
+    fn guard_fails() {
+        let mut sm = GuardedTransition::new();
+        sm.inc(3);
+        assert_eq!(sm.state, GuardedTransitionState::Idle);
+        assert_eq!(sm.tape, vec!["small"]);
+        sm.inc(5);
+        sm.inc(7);
+        assert_eq!(sm.state, GuardedTransitionState::Busy);
+        assert_eq!(sm.tape, vec!["small", "stay"]);
+        sm.inc(11);
+        assert_eq!(sm.state, GuardedTransitionState::Idle);
+    }
+
+    /// Test a guard on the handler of an event without parameters.
+    #[test]
+    fn handler_guard() {
+        let mut sm = GuardedTransition::new();
+        sm.go();
+        assert_eq!(sm.state, GuardedTransitionState::Idle);
+        sm.arm();
+        sm.go();
+        assert_eq!(sm.state, GuardedTransitionState::Busy);
+    }
+
+    /// Test that the guard text is recorded in the runtime transition info.
+    #[test]
+    fn guard_info() {
+        let info = GuardedTransition::machine_info();
+        let idle = info.get_state("Idle").unwrap();
+        let busy = info.get_state("Busy").unwrap();
+
+        let idle_out = idle.outgoing_transitions();
+        assert_eq!(idle_out.len(), 2);
+        assert!(idle_out[0].is_transition());
+        assert_eq!(idle_out[0].label, "big");
+        assert_eq!(idle_out[0].guard, Some("x > 3"));
+        assert!(idle_out[1].is_change_state());
+        assert_eq!(idle_out[1].guard, Some("armed == true"));
+
+        let busy_out = busy.outgoing_transitions();
+        assert_eq!(busy_out.len(), 2);
+        assert!(busy_out[0].is_guarded());
+        assert_eq!(busy_out[0].guard, Some("x == 0 || x > 10"));
+        assert!(!busy_out[1].is_guarded());
+        assert_eq!(busy_out[1].guard, None);
+    }
+}
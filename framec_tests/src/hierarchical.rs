@@ -279,6 +279,24 @@ mod tests {
         assert_eq!(s3.parent.unwrap().name, "S1");
     }
 
+    /// Test that the generated machine info can be inspected at compile time.
+    #[test]
+    fn const_state_parents() {
+        const S2: Option<&StateInfo> = runtime_info::machine_info().get_state("S2");
+        const S2_GRANDPARENT: &str = match S2 {
+            Some(StateInfo {
+                parent: Some(StateInfo {
+                    parent: Some(grandparent),
+                    ..
+                }),
+                ..
+            }) => grandparent.name,
+            _ => "",
+        };
+        assert_eq!(S2_GRANDPARENT, "S");
+        assert_eq!(S2.unwrap().machine().name, "Hierarchical");
+    }
+
     /// Test that states have the right ancestors via the runtime interface.
     #[test]
     fn state_ancestors() {
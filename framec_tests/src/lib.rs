@@ -1,21 +1,35 @@
 // Tests with runtime_support disabled.
+mod async_actions;
 mod branch;
 mod config;
+mod defer;
 mod empty;
+mod enum_match;
 mod event_handler;
+mod fallible_actions;
+mod generic_actions;
 mod handler_calls;
 mod hierarchical_guard;
+mod module_tree;
+mod r#loop;
 mod r#match;
+mod persist;
+mod regex_match;
 mod rust_naming_off;
 mod rust_naming_on;
+mod scxml_import;
 mod simple_handler_calls;
 mod state_context;
+mod test_harness;
+mod tracing;
+mod typed_events;
 mod var_scope;
 
 // Tests with runtime_support enabled.
 mod basic;
 mod basic_sync;
 mod event_monitor;
+mod guarded_transition;
 mod hierarchical;
 mod state_context_runtime;
 mod state_context_runtime_sync;
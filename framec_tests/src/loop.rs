@@ -0,0 +1,71 @@
+//! Test `loop` and `while` statements with `break` and `continue`, and
+//! else-if chains in boolean tests.
+
+type Log = Vec<String>;
+include!(concat!(env!("OUT_DIR"), "/", "loop.rs"));
+
+impl Loop {
+    pub fn log(&mut self, msg: String) {
+        self.tape.push(msg);
+    }
+    pub fn log_num(&mut self, i: i32) {
+        self.tape.push(i.to_string());
+    }
+    pub fn is_odd(&mut self, i: i32) -> bool {
+        i % 2 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test a `while` loop that runs until its condition fails.
+    #[test]
+    fn while_loop() {
+        let mut sm = Loop::new();
+        sm.count_to(3);
+        assert_eq!(sm.tape, vec!["0", "1", "2"]);
+        sm.tape.clear();
+        sm.count_to(0);
+        assert!(sm.tape.is_empty());
+    }
+
+    /// Test that `continue` skips the rest of the loop body.
+    #[test]
+    fn continue_statement() {
+        let mut sm = Loop::new();
+        sm.skip_odd(6);
+        assert_eq!(sm.tape, vec!["2", "4", "6"]);
+    }
+
+    /// Test that `break` exits the loop and execution resumes after it.
+    #[test]
+    fn break_statement() {
+        let mut sm = Loop::new();
+        sm.first_over(10);
+        assert_eq!(sm.tape, vec!["16"]);
+    }
+
+    /// Test that a transition inside a loop leaves the handler rather than
+    /// continuing to loop.
+    #[test]
+    fn transition_in_loop() {
+        let mut sm = Loop::new();
+        sm.poll();
+        assert_eq!(sm.tape, vec!["waiting", "waiting"]);
+        assert_eq!(sm.ticks, 3);
+        assert_eq!(sm.state, LoopState::Ready);
+    }
+
+    /// Test that only the first matching branch of an else-if chain runs.
+    #[test]
+    fn else_if_chain() {
+        let mut sm = Loop::new();
+        sm.classify(-4);
+        sm.classify(0);
+        sm.classify(7);
+        sm.classify(12);
+        assert_eq!(sm.tape, vec!["negative", "zero", "small", "large"]);
+    }
+}
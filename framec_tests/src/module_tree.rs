@@ -0,0 +1,63 @@
+//! Tests the `generate_module_tree` feature, which splits the generated code into a module
+//! directory whose shared definitions are visible within the crate.
+
+type Log = Vec<String>;
+include!(concat!(env!("OUT_DIR"), "/", "module_tree.rs"));
+
+use self::module_tree::ModuleTree;
+
+impl ModuleTree {
+    pub fn log(&mut self, msg: String) {
+        self.tape.push(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::module_tree::{FrameMessage, ModuleTreeState};
+    use super::*;
+    use frame_runtime::*;
+    use std::path::Path;
+
+    #[test]
+    fn module_files() {
+        let module_dir = Path::new(env!("OUT_DIR")).join("module_tree");
+        for file_name in &["mod.rs", "events.rs", "states.rs", "info.rs", "machine.rs"] {
+            assert!(
+                module_dir.join(file_name).is_file(),
+                "missing {}",
+                file_name
+            );
+        }
+    }
+
+    #[test]
+    fn handle_events() {
+        let mut sm = ModuleTree::new();
+        sm.start();
+        sm.add(2);
+        sm.add(3);
+        assert_eq!(sm.total(), 5);
+        assert_eq!(sm.tape, vec!["counting"]);
+        assert_eq!(sm.state().info().name, "Counting");
+        let count = sm.state().variables().lookup("count").unwrap();
+        assert_eq!(*count.downcast_ref::<i32>().unwrap(), 5);
+    }
+
+    #[test]
+    fn shared_definitions() {
+        assert_eq!(FrameMessage::Add.to_string(), "add");
+        assert_eq!(
+            FrameMessage::Enter(ModuleTreeState::Counting).to_string(),
+            "Counting:>"
+        );
+    }
+
+    #[test]
+    fn runtime_info() {
+        let info = ModuleTree::machine_info();
+        assert_eq!(info.name, "ModuleTree");
+        assert_eq!(info.states.len(), 2);
+        assert!(info.get_state("Idle").is_some());
+    }
+}
@@ -0,0 +1,84 @@
+//! Tests snapshotting and restoring a state machine with the `serde` feature.
+
+type Log = Vec<String>;
+include!(concat!(env!("OUT_DIR"), "/", "persist.rs"));
+
+impl Persist {
+    pub fn log(&mut self, msg: String) {
+        self.tape.push(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a snapshot survives a round trip through JSON and restores the current state,
+    /// its arguments and variables, and the domain variables.
+    #[test]
+    fn json_round_trip() {
+        let mut sm = Persist::new();
+        sm.start(10);
+        sm.work(3);
+        sm.set_mood(Mood::Busy);
+        let json = serde_json::to_string(&sm.snapshot()).unwrap();
+
+        let mut restored = Persist::new();
+        restored.restore(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.state, PersistState::Working);
+        assert_eq!(restored.get_budget(), 10);
+        assert_eq!(restored.get_spent(), 3);
+        assert_eq!(restored.mood, Mood::Busy);
+        assert_eq!(restored.tape, vec!["Idle:>", "Working:>"]);
+    }
+
+    /// Test that restoring a snapshot does not send an enter event to the restored state.
+    #[test]
+    fn restore_skips_enter() {
+        let mut sm = Persist::new();
+        sm.start(10);
+        let snapshot = sm.snapshot();
+        let snapshot_tape = snapshot.tape.clone();
+        assert_eq!(snapshot_tape, vec!["Idle:>", "Working:>"]);
+
+        let mut restored = Persist::new();
+        restored.restore(snapshot);
+        assert_eq!(restored.tape, snapshot_tape);
+        restored.work(2);
+        assert_eq!(restored.tape, snapshot_tape);
+        assert_eq!(restored.get_spent(), 2);
+    }
+
+    /// Test that a snapshot is not affected by events handled after it was taken.
+    #[test]
+    fn snapshot_is_independent() {
+        let mut sm = Persist::new();
+        sm.start(10);
+        sm.work(1);
+        let snapshot = sm.snapshot();
+        sm.work(4);
+        assert_eq!(sm.get_spent(), 5);
+        sm.restore(snapshot);
+        assert_eq!(sm.get_spent(), 1);
+    }
+
+    /// Test that the state stack is persisted, including the contexts of pushed states.
+    #[test]
+    fn state_stack() {
+        let mut sm = Persist::new();
+        sm.start(10);
+        sm.work(3);
+        sm.push();
+        sm.done();
+        assert_eq!(sm.state, PersistState::Idle);
+        let json = serde_json::to_string(&sm.snapshot()).unwrap();
+
+        let mut restored = Persist::new();
+        restored.restore(serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.state, PersistState::Idle);
+        restored.pop();
+        assert_eq!(restored.state, PersistState::Working);
+        assert_eq!(restored.get_budget(), 10);
+        assert_eq!(restored.get_spent(), 3);
+    }
+}
@@ -0,0 +1,88 @@
+//! Test regular expression matching, including binding named capture groups
+//! to local variables within a match branch.
+
+type Log = Vec<String>;
+include!(concat!(env!("OUT_DIR"), "/", "regex_match.rs"));
+
+impl RegexMatch {
+    pub fn log(&mut self, msg: String) {
+        self.tape.push(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that the first matching pattern is selected, and that patterns
+    /// search the whole string unless explicitly anchored.
+    #[test]
+    fn first_match_wins() {
+        let mut sm = RegexMatch::new();
+        sm.simple();
+        sm.on_string("hello".to_string());
+        assert_eq!(sm.tape, vec!["hello"]);
+        sm.tape.clear();
+        sm.on_string("hello world".to_string());
+        assert_eq!(sm.tape, vec!["starts with h"]);
+        sm.tape.clear();
+        sm.on_string("h42".to_string());
+        assert_eq!(sm.tape, vec!["starts with h"]);
+        sm.tape.clear();
+        sm.on_string("abc 123".to_string());
+        assert_eq!(sm.tape, vec!["has digits"]);
+    }
+
+    /// Test that an escaped `/` in a pattern matches a literal slash.
+    #[test]
+    fn escaped_slash() {
+        let mut sm = RegexMatch::new();
+        sm.simple();
+        sm.on_string("path/a/b".to_string());
+        assert_eq!(sm.tape, vec!["slash"]);
+    }
+
+    /// Test that the else branch runs when no pattern matches.
+    #[test]
+    fn no_match() {
+        let mut sm = RegexMatch::new();
+        sm.simple();
+        sm.on_string("goodbye".to_string());
+        assert_eq!(sm.tape, vec!["?"]);
+    }
+
+    /// Test that named capture groups are bound to locals in the branch.
+    #[test]
+    fn named_captures() {
+        let mut sm = RegexMatch::new();
+        sm.captures();
+        sm.on_string("answer=42".to_string());
+        assert_eq!(sm.tape, vec!["answer", "42"]);
+        sm.tape.clear();
+        sm.on_string("go:north".to_string());
+        assert_eq!(sm.tape, vec!["go", "north"]);
+    }
+
+    /// Test that a named group that does not participate in the match is
+    /// bound to the empty string.
+    #[test]
+    fn unmatched_capture_is_empty() {
+        let mut sm = RegexMatch::new();
+        sm.captures();
+        sm.on_string("look".to_string());
+        assert_eq!(sm.tape, vec!["look", ""]);
+        sm.tape.clear();
+        sm.on_string("answer=".to_string());
+        assert_eq!(sm.tape, vec!["answer", ""]);
+    }
+
+    /// Test a transition from within a regex match branch.
+    #[test]
+    fn transition_in_branch() {
+        let mut sm = RegexMatch::new();
+        sm.captures();
+        sm.on_string("!quit".to_string());
+        assert_eq!(sm.state, RegexMatchState::Final);
+        assert!(sm.tape.is_empty());
+    }
+}
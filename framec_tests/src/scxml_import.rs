@@ -0,0 +1,107 @@
+//! Test a state machine imported from SCXML. The Frame skeleton for
+//! `scxml_import.scxml` is generated by `framec --import-scxml` in the build
+//! script and then compiled like any other Frame spec.
+
+include!(concat!(env!("OUT_DIR"), "/", "scxml_import.rs"));
+
+// The imported skeleton has no typed domain variables to log into.
+thread_local! {
+    static TAPE: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+fn record(entry: &str) {
+    TAPE.with(|tape| tape.borrow_mut().push(entry.to_string()));
+}
+
+impl ScxmlImport {
+    pub fn send_report_broken(&mut self) {
+        record("report.broken");
+    }
+    pub fn send_report_off(&mut self) {
+        record("report.off");
+    }
+    pub fn send_report_on(&mut self) {
+        record("report.on");
+    }
+    pub fn send_report_bright(&mut self) {
+        record("report.bright");
+    }
+    pub fn raise_leaving(&mut self) {
+        record("leaving");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take_tape() -> Vec<String> {
+        TAPE.with(|tape| tape.borrow_mut().drain(..).collect())
+    }
+
+    /// Test that the `initial` attribute selects the start state, even when
+    /// it is not the first state in the document.
+    #[test]
+    fn initial_state() {
+        take_tape();
+        let sm = ScxmlImport::new();
+        assert_eq!(sm.state, ScxmlImportState::Off);
+        assert_eq!(take_tape(), vec!["report.off"]);
+    }
+
+    /// Test that `<onentry>` and `<onexit>` content runs on transitions.
+    #[test]
+    fn entry_and_exit_actions() {
+        take_tape();
+        let mut sm = ScxmlImport::new();
+        sm.toggle();
+        assert_eq!(sm.state, ScxmlImportState::On);
+        sm.toggle();
+        assert_eq!(sm.state, ScxmlImportState::Off);
+        assert_eq!(
+            take_tape(),
+            vec!["report.off", "report.on", "leaving", "report.off"]
+        );
+    }
+
+    /// Test that a transition whose `cond` is false is skipped in favor of
+    /// the next transition for the same event.
+    #[test]
+    fn conditional_transition() {
+        let mut sm = ScxmlImport::new();
+        sm.toggle();
+        sm.toggle();
+        assert_eq!(sm.state, ScxmlImportState::Off);
+    }
+
+    /// Test that the first transition whose `cond` holds is taken, and that
+    /// later transitions for the same event are not.
+    #[test]
+    fn first_enabled_transition() {
+        let mut sm = ScxmlImport::new();
+        sm.kick();
+        assert_eq!(sm.state, ScxmlImportState::Dim);
+        sm.fail_hard();
+        sm.repair();
+        assert_eq!(sm.state, ScxmlImportState::Off);
+    }
+
+    /// Test that a nested state handles its own events and defers the rest
+    /// to its parent state.
+    #[test]
+    fn nested_state() {
+        let mut sm = ScxmlImport::new();
+        sm.toggle();
+        sm.dim();
+        assert_eq!(sm.state, ScxmlImportState::Dim);
+        take_tape();
+        sm.dim();
+        assert_eq!(sm.state, ScxmlImportState::On);
+        assert_eq!(take_tape(), vec!["report.bright", "leaving", "report.on"]);
+        sm.dim();
+        sm.fail_hard();
+        assert_eq!(sm.state, ScxmlImportState::Broken);
+        sm.repair();
+        assert_eq!(sm.state, ScxmlImportState::Off);
+    }
+}
@@ -0,0 +1,87 @@
+//! Tests the `generate_test_harness` feature, which generates a property-based test that sends
+//! random sequences of interface calls to the state machine. The generated test runs alongside
+//! the tests below.
+
+include!(concat!(env!("OUT_DIR"), "/", "test_harness.rs"));
+
+#[cfg(test)]
+use std::cell::Cell;
+
+#[cfg(test)]
+thread_local! {
+    static INVARIANT_CHECKS: Cell<usize> = Cell::new(0);
+}
+
+/// A user-defined invariant: the depth tracked by the handlers matches the state stack.
+#[cfg(test)]
+fn check_depth(sm: &TestHarness) {
+    INVARIANT_CHECKS.with(|checks| checks.set(checks.get() + 1));
+    assert_eq!(sm.depth as usize, sm.state_stack.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_harness_harness::{apply, check_invariants, interface_call, InterfaceCall};
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::test_runner::{TestError, TestRunner};
+
+    /// Test that generated calls are sent to the corresponding interface methods.
+    #[test]
+    fn apply_calls() {
+        let mut sm = TestHarness::new();
+        apply(&mut sm, InterfaceCall::Heat { target: 30 });
+        apply(&mut sm, InterfaceCall::Mix { a: 40, b: false });
+        assert_eq!(sm.temperature, 30);
+        apply(&mut sm, InterfaceCall::Mix { a: 40, b: true });
+        assert_eq!(sm.temperature, 40);
+        apply(&mut sm, InterfaceCall::Push);
+        apply(&mut sm, InterfaceCall::Push);
+        assert_eq!(sm.level(), 2);
+        apply(&mut sm, InterfaceCall::Pop);
+        assert_eq!(sm.level(), 1);
+        assert_eq!(sm.state, TestHarnessState::Busy);
+    }
+
+    /// Test that the invariants listed in `test_invariants` are checked.
+    #[test]
+    fn user_invariants() {
+        let mut sm = TestHarness::new();
+        apply(&mut sm, InterfaceCall::Push);
+        let before = INVARIANT_CHECKS.with(Cell::get);
+        check_invariants(&sm);
+        assert_eq!(INVARIANT_CHECKS.with(Cell::get), before + 1);
+    }
+
+    /// Test that a violated invariant is caught by the user-defined invariants.
+    #[test]
+    #[should_panic]
+    fn invariant_violation() {
+        let mut sm = TestHarness::new();
+        sm.depth = 1;
+        check_invariants(&sm);
+    }
+
+    /// Test that failing call sequences found with the generated strategy shrink to a minimal
+    /// sequence.
+    #[test]
+    fn shrinking() {
+        let mut runner = TestRunner::deterministic();
+        let result = runner.run(&vec(interface_call(), 0..=16), |calls| {
+            let mut sm = TestHarness::new();
+            for call in calls {
+                apply(&mut sm, call);
+                prop_assert!(sm.level() < 2);
+            }
+            Ok(())
+        });
+        match result {
+            Err(TestError::Fail(_, calls)) => {
+                assert_eq!(calls.len(), 2);
+                assert!(calls.iter().all(|call| matches!(call, InterfaceCall::Push)));
+            }
+            _ => panic!("expected a failing call sequence"),
+        }
+    }
+}
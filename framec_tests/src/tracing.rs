@@ -0,0 +1,153 @@
+//! Tests the `tracing` feature, which reports event dispatch, transitions, and state stack
+//! operations through the `tracing` crate.
+
+include!(concat!(env!("OUT_DIR"), "/", "tracing.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::{self, Write};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Formats the fields of a span or event, with the message first.
+    #[derive(Default)]
+    struct Fields {
+        message: String,
+        others: String,
+    }
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                write!(self.message, "{:?}", value).unwrap();
+            } else {
+                write!(self.others, " {}={:?}", field.name(), value).unwrap();
+            }
+        }
+    }
+
+    /// A subscriber that records every span and event as a line of text.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        lines: Arc<Mutex<Vec<String>>>,
+        next_id: Arc<AtomicU64>,
+    }
+
+    impl Recorder {
+        fn lines(&self) -> Vec<String> {
+            self.lines.lock().unwrap().clone()
+        }
+
+        /// Get the recorded events, omitting spans.
+        fn events(&self) -> Vec<String> {
+            self.lines()
+                .into_iter()
+                .filter(|line| !line.starts_with("span"))
+                .collect()
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields::default();
+            span.record(&mut fields);
+            self.lines.lock().unwrap().push(format!(
+                "span {}{} message={}",
+                span.metadata().name(),
+                fields.others,
+                fields.message
+            ));
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.lines.lock().unwrap().push(format!(
+                "{} {}{}",
+                event.metadata().level(),
+                fields.message,
+                fields.others
+            ));
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    /// Run `f` with a fresh recorder as the default subscriber.
+    fn record(f: impl FnOnce()) -> Recorder {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), f);
+        recorder
+    }
+
+    /// Test that each event is handled in a span identifying the system, state, and message.
+    #[test]
+    fn handle_event_spans() {
+        let recorder = record(|| {
+            let mut sm = Tracing::new();
+            sm.next();
+        });
+        assert_eq!(
+            recorder.lines()[..3],
+            [
+                "DEBUG enter state=Idle",
+                "span handle_event system=\"Tracing\" state=Idle message=Idle:>",
+                "span handle_event system=\"Tracing\" state=Idle message=next",
+            ]
+        );
+    }
+
+    /// Test that a transition reports the exit, the transition itself, and the enter.
+    #[test]
+    fn transition_events() {
+        let mut sm = Tracing::new();
+        let recorder = record(|| sm.next());
+        assert_eq!(
+            recorder.events(),
+            [
+                "DEBUG exit state=Idle",
+                "INFO transition id=0 label=\"wake\" source=Idle target=Busy",
+                "DEBUG enter state=Busy",
+            ]
+        );
+    }
+
+    /// Test that a change-state is reported without exit or enter events.
+    #[test]
+    fn change_state_events() {
+        let mut sm = Tracing::new();
+        let recorder = record(|| sm.hop());
+        assert_eq!(
+            recorder.events(),
+            ["INFO change_state id=1 label=\"skip\" source=Idle target=Busy"]
+        );
+    }
+
+    /// Test that pushing to and popping from the state stack are reported.
+    #[test]
+    fn state_stack_events() {
+        let mut sm = Tracing::new();
+        sm.next();
+        let recorder = record(|| {
+            sm.push();
+            sm.next();
+            sm.pop();
+        });
+        let events = recorder.events();
+        assert_eq!(events[0], "DEBUG push state=Busy");
+        assert_eq!(events[4], "DEBUG pop state=Busy");
+        assert_eq!(
+            events[6],
+            "INFO transition id=2 label=\"\" source=Idle target=Busy"
+        );
+    }
+}
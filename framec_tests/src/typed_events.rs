@@ -0,0 +1,63 @@
+//! Tests the `typed_events` feature, which generates a single event enum whose variants carry
+//! the event arguments and are dispatched by value.
+
+type Name = String;
+type Log = Vec<Name>;
+include!(concat!(env!("OUT_DIR"), "/", "typed_events.rs"));
+
+impl TypedEvents {
+    pub fn log(&mut self, msg: Name) {
+        self.tape.push(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that events are not allocated or shared through reference-counted pointers.
+    #[test]
+    fn no_shared_events() {
+        let code = include_str!(concat!(env!("OUT_DIR"), "/", "typed_events.rs"));
+        assert!(!code.contains("Rc<FrameEvent>"));
+        assert!(!code.contains("FrameEvent::new"));
+        assert!(!code.contains("FrameEventArgs"));
+    }
+
+    /// Test that return values are delivered by the handler that matched the event.
+    #[test]
+    fn return_values() {
+        let mut sm = TypedEvents::new();
+        assert_eq!(sm.mult(3, 4), 0);
+        sm.start();
+        assert_eq!(sm.mult(3, 4), 12);
+    }
+
+    /// Test that events not handled by a state are passed by value to its parent, and that the
+    /// parent's return value is delivered.
+    #[test]
+    fn parent_dispatch() {
+        let mut sm = TypedEvents::new();
+        sm.start();
+        assert_eq!(sm.add(2, 3), 5);
+        assert_eq!(sm.tape.last().unwrap(), "add");
+    }
+
+    /// Test that calling an interface method that is not handled in the current state panics
+    /// when a return value is expected.
+    #[test]
+    #[should_panic(expected = "Bad return value for add")]
+    fn missing_return_value() {
+        let mut sm = TypedEvents::new();
+        sm.add(2, 3);
+    }
+
+    /// Test that exit arguments are carried by the exit event variant of the state.
+    #[test]
+    fn exit_args() {
+        let mut sm = TypedEvents::new();
+        sm.start();
+        sm.stop();
+        assert_eq!(sm.tape, vec!["Idle:>", "stopped", "Idle:>"]);
+    }
+}